@@ -1,9 +1,12 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use crate::ast::UniversalPou;
 use crate::ports::backend::PouCodec;
 
 use super::config::HollysysConfig;
+use super::envelope::{ClipboardDecode, decode_clipboard, decode_first};
+use super::lossless::{RoundtripReport, variant_from_codec_tag, verify_roundtrip};
+use super::parser::{ParseOptions, PartialDecode, detect, read_pou_recovering_with_options};
 use super::protocol::PlcVariant;
 use super::serializer::PouSerializer;
 
@@ -11,12 +14,26 @@ use super::serializer::PouSerializer;
 #[derive(Debug, Clone)]
 pub struct HollysysCodec {
     config: HollysysConfig,
+    /// 自动模式：解码前按数据探测 variant/serialize_version
+    auto_detect: bool,
 }
 
 impl HollysysCodec {
     /// 使用指定配置创建编解码器
     pub fn new(config: HollysysConfig) -> Self {
-        Self { config }
+        Self { config, auto_detect: false }
+    }
+
+    /// 自动识别版本的编解码器
+    /// - 解码：按 `detect` 的结果选择 Normal/Safety 与序列化版本
+    /// - 编码：POU 携带无损布局时沿用布局的版本，否则使用默认 Normal 配置
+    pub fn auto() -> Self {
+        Self::auto_with_config(HollysysConfig::normal())
+    }
+
+    /// 自动识别版本，其余参数（功能块库、分配器等）取自 config
+    pub fn auto_with_config(config: HollysysConfig) -> Self {
+        Self { config, auto_detect: true }
     }
 
     /// 快捷构建：Normal 版本
@@ -33,29 +50,84 @@ impl HollysysCodec {
     pub fn config(&self) -> &HollysysConfig {
         &self.config
     }
+
+    /// 往返校验：无损解码后再编码，逐字节比较并定位首个差异
+    /// 与 config.lossless 无关，总是使用无损模式
+    pub fn verify_roundtrip(&self, data: &[u8]) -> Result<RoundtripReport> {
+        verify_roundtrip(data, &self.resolve_config(data))
+    }
+
+    /// 尽力解码：解析失败的网络/元件/变量被跳过，返回能读出的部分与跳过的区间
+    /// 用于抢救新版本或损坏的剪贴板数据；结果不携带无损布局
+    pub fn decode_partial(&self, data: &[u8]) -> Result<PartialDecode> {
+        read_pou_recovering_with_options(data, &ParseOptions::from(&self.resolve_config(data)))
+    }
+
+    /// 解码剪贴板缓冲区中的全部 POU（多选复制），并报告各容器内容之后的非零字节
+    pub fn decode_clipboard(&self, data: &[u8]) -> Result<ClipboardDecode> {
+        decode_clipboard(data, self.config.pou_total_len, |container| self.resolve_config(container))
+    }
+
+    /// 把多个 POU 依次编码为各自的容器并拼接成一个剪贴板缓冲区
+    pub fn encode_clipboard(&self, pous: &[UniversalPou]) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        for pou in pous {
+            data.extend(self.encode(pou).with_context(|| format!("编码 POU {} 失败", pou.name))?);
+        }
+        Ok(data)
+    }
+
+    /// 解码该数据时实际使用的配置（自动模式下按数据探测）
+    pub fn resolve_config(&self, data: &[u8]) -> HollysysConfig {
+        if !self.auto_detect {
+            return self.config.clone();
+        }
+        let format = detect(data);
+        HollysysConfig {
+            variant: format.variant,
+            serialize_version: format.serialize_version,
+            ..self.config.clone()
+        }
+    }
+
+    /// 编码时实际使用的配置
+    fn encode_config(&self, pou: &UniversalPou) -> HollysysConfig {
+        let layout = pou.preserved.as_ref().filter(|_| self.auto_detect);
+        match layout.and_then(|layout| Some((variant_from_codec_tag(&layout.codec)?, layout))) {
+            Some((variant, layout)) => HollysysConfig {
+                variant,
+                serialize_version: layout.serialize_version,
+                ..self.config.clone()
+            },
+            None => self.config.clone(),
+        }
+    }
 }
 
 impl PouCodec for HollysysCodec {
     /// 解码入口：从剪贴板二进制流解析为 POU
+    /// 解析失败时返回的错误可 downcast 为 `ParseError`（含偏移、对象路径与附近字节）
+    /// 缓冲区含多个 POU 时只返回第一个；容器内非零尾部字节记录警告
     fn decode(&self, data: &[u8]) -> Result<UniversalPou> {
-        super::parser::read_pou_with_config(
-            data,
-            self.config.variant,
-            self.config.serialize_version,
-        )
+        decode_first(data, self.config.pou_total_len, |container| self.resolve_config(container))
     }
 
     /// 编码入口：生成剪贴板二进制流
     fn encode(&self, pou: &UniversalPou) -> Result<Vec<u8>> {
-        let mut serializer = PouSerializer::from_config(self.config.clone());
+        let mut serializer = PouSerializer::from_config(self.encode_config(pou));
         serializer.serialize(pou)
     }
 
-    /// 剪贴板格式名称（根据版本分流）
+    /// 剪贴板格式名称（根据版本分流；自动模式按默认配置）
     fn format_name(&self) -> &'static str {
         match self.config.variant {
             PlcVariant::Normal => "POU_TREE_Clipboard_PLC",
             PlcVariant::Safety => "POU_TREE_Clipboard_ITCC",
         }
     }
+
+    /// 自动模式按 POU 的无损布局决定版本，编码前不确定
+    fn variant(&self) -> Option<PlcVariant> {
+        (!self.auto_detect).then_some(self.config.variant)
+    }
 }
@@ -1,4 +1,8 @@
+use std::sync::Arc;
+
 use super::protocol::PlcVariant;
+use super::var_ids::{SequentialVarIds, VarIdAllocator};
+use crate::symbols_config::SymbolConfig;
 
 /// Hollysys 序列化配置
 /// 说明：该结构用于集中管理“规则参数”，避免散落在序列化逻辑中。
@@ -6,12 +10,24 @@ use super::protocol::PlcVariant;
 pub struct HollysysConfig {
     /// 版本标识（Normal/Safety）
     pub variant: PlcVariant,
-    /// POU 片段固定长度（样本固定为 0x2000）
+    /// POU 容器块长度（样本为 0x2000）：内容不足时零填充到一块，更长时按块整数倍增长
+    /// 解码时剪贴板缓冲区按该块长度切分出多个 POU（多选复制）
     pub pou_total_len: usize,
     /// 序列化版本号（用于控制可选字段）
     /// - Normal: 影响 CLDBox/CLDOutput 的可选字段
     /// - Safety: 当前仅保留占位，便于未来扩展
     pub serialize_version: u32,
+    /// 无损模式：解码时保留原始字节布局，编码时必须逐段回写
+    /// - 开启后若 POU 结构变化（增删网络/元件）导致无法无损回写，编码直接报错
+    /// - 关闭时若 POU 携带可用布局仍会优先使用，否则走常规序列化
+    pub lossless: bool,
+    /// 未显式提供 var_id/addr_id 的变量由该分配器补齐（默认顺序分配）
+    pub var_id_allocator: Arc<dyn VarIdAllocator>,
+    /// 目标工程中已在用的 var_id，分配时跳过
+    pub reserved_var_ids: Vec<u16>,
+    /// 功能块库：解码时用于变量分组，编码时用于实例标志与版本检查
+    /// 为空时解码使用内置默认库（SymbolConfig::builtin）
+    pub symbols: Arc<SymbolConfig>,
 }
 
 impl HollysysConfig {
@@ -21,6 +37,10 @@ impl HollysysConfig {
             variant: PlcVariant::Normal,
             pou_total_len: 0x2000,
             serialize_version: 13,
+            lossless: false,
+            var_id_allocator: Arc::new(SequentialVarIds::default()),
+            reserved_var_ids: Vec::new(),
+            symbols: Arc::new(SymbolConfig::default()),
         }
     }
 
@@ -30,6 +50,10 @@ impl HollysysConfig {
             variant: PlcVariant::Safety,
             pou_total_len: 0x2000,
             serialize_version: 13,
+            lossless: false,
+            var_id_allocator: Arc::new(SequentialVarIds::default()),
+            reserved_var_ids: Vec::new(),
+            symbols: Arc::new(SymbolConfig::default()),
         }
     }
 
@@ -40,4 +64,28 @@ impl HollysysConfig {
             PlcVariant::Safety => Self::safety(),
         }
     }
+
+    /// 设置无损模式
+    pub fn with_lossless(mut self, lossless: bool) -> Self {
+        self.lossless = lossless;
+        self
+    }
+
+    /// 设置 var_id/addr_id 分配策略
+    pub fn with_var_id_allocator(mut self, allocator: impl VarIdAllocator + 'static) -> Self {
+        self.var_id_allocator = Arc::new(allocator);
+        self
+    }
+
+    /// 设置目标工程中已在用的 var_id
+    pub fn with_reserved_var_ids(mut self, ids: impl IntoIterator<Item = u16>) -> Self {
+        self.reserved_var_ids = ids.into_iter().collect();
+        self
+    }
+
+    /// 设置功能块库
+    pub fn with_symbols(mut self, symbols: SymbolConfig) -> Self {
+        self.symbols = Arc::new(symbols);
+        self
+    }
 }
@@ -0,0 +1,126 @@
+/*
+剪贴板容器：一个剪贴板缓冲区由若干 POU 容器首尾相接组成（多选复制时多于一个）
+- 每个容器长度为块长度（HollysysConfig::pou_total_len，样本为 0x2000）的整数倍：
+  POU 内容不足时零填充到一块，超过 8 KiB 时按块增长
+- 容器边界只落在块边界上：从容器起点逐块向后查找，第一个之后是 POU 头部（名称串后紧跟同名串）
+  或之后全为零的块边界即容器终点；最后一个容器延伸到缓冲区末尾（含其后的零填充）
+- 容器内 POU 内容之后本应全为零，出现非零字节时记为 TrailingBytes 报告，不再静默忽略
+*/
+use std::ops::Range;
+
+use anyhow::{Context, Result, bail};
+use log::warn;
+use serde::Serialize;
+
+use crate::ast::UniversalPou;
+
+use super::config::HollysysConfig;
+use super::parser::{ParseOptions, looks_like_pou_header, read_pou_until};
+
+/// 容器内 POU 内容之后的非零字节（缓冲区内的绝对偏移）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrailingBytes {
+    /// 所在 POU 在缓冲区中的序号
+    pub pou_index: usize,
+    /// 第一个到最后一个非零字节，[start, end)
+    pub range: Range<usize>,
+}
+
+/// 剪贴板缓冲区的解码结果
+#[derive(Debug, Clone)]
+pub struct ClipboardDecode {
+    pub pous: Vec<UniversalPou>,
+    /// 各容器在缓冲区中的区间，与 pous 一一对应
+    pub containers: Vec<Range<usize>>,
+    pub trailing: Vec<TrailingBytes>,
+}
+
+/// 内容长度对应的容器长度：块长度的整数倍，至少一块；块长度为 0 时不填充
+pub(crate) fn container_len(content_len: usize, block_len: usize) -> usize {
+    if block_len == 0 {
+        return content_len;
+    }
+    content_len.div_ceil(block_len).max(1) * block_len
+}
+
+/// 把缓冲区切分为 POU 容器区间；全零缓冲区没有容器
+pub fn split_containers(data: &[u8], block_len: usize) -> Vec<Range<usize>> {
+    let end = data.iter().rposition(|b| *b != 0).map_or(0, |pos| pos + 1);
+    // 块长度为 0 时整个缓冲区是一个容器
+    let block_len = if block_len == 0 { data.len().max(1) } else { block_len };
+    let mut containers = Vec::new();
+    let mut start = 0;
+    while start < end {
+        let mut stop = start + block_len;
+        while stop < end && !looks_like_pou_header(&data[stop..]) {
+            stop += block_len;
+        }
+        let stop = stop.min(data.len());
+        containers.push(start..stop);
+        start = stop;
+    }
+    if let Some(last) = containers.last_mut() {
+        last.end = data.len();
+    }
+    containers
+}
+
+/// 逐个容器解码；config_for 为每个容器给出解码配置（自动模式下按容器数据探测）
+pub(crate) fn decode_clipboard(
+    data: &[u8],
+    block_len: usize,
+    config_for: impl Fn(&[u8]) -> HollysysConfig,
+) -> Result<ClipboardDecode> {
+    let containers = split_containers(data, block_len);
+    if containers.is_empty() {
+        bail!("剪贴板数据为空（{} 字节全为零）", data.len());
+    }
+    let mut decoded = ClipboardDecode { pous: Vec::new(), containers: Vec::new(), trailing: Vec::new() };
+    for (index, range) in containers.into_iter().enumerate() {
+        let (pou, trailing) = decode_container(data, index, range.clone(), &config_for)
+            .with_context(|| format!("解析第 {} 个 POU 失败（偏移 {:#x}）", index + 1, range.start))?;
+        decoded.pous.push(pou);
+        decoded.containers.push(range);
+        decoded.trailing.extend(trailing);
+    }
+    Ok(decoded)
+}
+
+/// 只解码第一个容器（单 POU 接口）；多余的 POU 与非零尾部字节记录警告
+pub(crate) fn decode_first(
+    data: &[u8],
+    block_len: usize,
+    config_for: impl Fn(&[u8]) -> HollysysConfig,
+) -> Result<UniversalPou> {
+    let containers = split_containers(data, block_len);
+    let first = containers.first().cloned().unwrap_or(0..data.len());
+    let (pou, trailing) = decode_container(data, 0, first, &config_for)?;
+    if let Some(trailing) = trailing {
+        warn!("POU {} 之后有非零字节未解析: {:#x}..{:#x}", pou.name, trailing.range.start, trailing.range.end);
+    }
+    if containers.len() > 1 {
+        warn!("剪贴板数据包含 {} 个 POU，仅解码第一个（多选复制请使用 decode_clipboard）", containers.len());
+    }
+    Ok(pou)
+}
+
+fn decode_container(
+    data: &[u8],
+    index: usize,
+    range: Range<usize>,
+    config_for: &impl Fn(&[u8]) -> HollysysConfig,
+) -> Result<(UniversalPou, Option<TrailingBytes>)> {
+    let slice = &data[range.clone()];
+    let (pou, content_end) = read_pou_until(slice, &ParseOptions::from(&config_for(slice)))?;
+    let content_end = content_end.min(slice.len());
+    let base = range.start + content_end;
+    let trailing = trailing_range(&slice[content_end..])
+        .map(|rest| TrailingBytes { pou_index: index, range: base + rest.start..base + rest.end });
+    Ok((pou, trailing))
+}
+
+fn trailing_range(rest: &[u8]) -> Option<Range<usize>> {
+    let first = rest.iter().position(|b| *b != 0)?;
+    let last = rest.iter().rposition(|b| *b != 0)?;
+    Some(first..last + 1)
+}
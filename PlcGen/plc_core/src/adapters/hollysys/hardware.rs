@@ -0,0 +1,406 @@
+/*
+硬件组态对象编解码（dllDPLogic.dll 各类 Serialize，序列化版本 0x26）
+- 对象流不含 CRuntimeClass 头，从该类 Serialize 读取的第一个字段开始；全局小端，CString 为 GBK
+- Modbus 从站（CModbusSlave，TCP/RTU 共用）：按 empty_slave.bin 还原的 V026 布局
+  8 个基类链字符串 + 6 个参数字符串（启用/IP/备用 IP/站号/端口/param13）+ 17 个 u32 + 1 个 u8 尾块
+  尾块语义尚未对齐，原样保留；新建对象使用 empty_slave.bin 的取值
+  目前只有 TCP 样本：RTU 从站的串口参数在流中的位置未知，由通用从站生成时报错
+  通用从站的名称/描述/超时/重试在该布局中没有已确认的位置，生成时不写入，原值随 SlaveObjects 返回
+- 读写命令（CModbusOrder）/ 通道（CModbusChannel）：CDevice 头 + 自身字段 + 列表（见 Docs/硬件组态相关规则.md）
+- 读写命令可由通用从站生成；通道的数据类型码尚无样本，通道列表不生成，原值随 SlaveObjects 返回
+- IO 模块（CModule 派生类）的流布局文档尚未还原，暂不支持
+*/
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::parser::MfcReader;
+use super::serializer::MfcWriter;
+use crate::domain::hardware::{CommSlave, SlaveLink};
+
+/// 运行环境固定的硬件序列化版本（CAppGlobalFunc::SetSerilizeVersion）
+pub const HARDWARE_SERIALIZE_VERSION: u32 = 0x26;
+
+/// 从站基类链字符串个数
+const SLAVE_BASE_STRINGS: usize = 8;
+
+/// CDevice 头：所有硬件对象先写基类数据
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceHeader {
+    pub name: String,
+    pub id: u32,
+    pub flag1: u8,
+    pub flag2: u8,
+    pub description: String,
+}
+
+/// 映射项（count 位宽由所在对象决定）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingItem {
+    pub p1: u32,
+    pub p2: u16,
+    pub p3: u8,
+    pub p4: u16,
+    pub blob: Vec<u8>,
+}
+
+/// Modbus 从站尾块（17 个 u32 + 1 个 u8），语义未对齐
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlaveTrailer {
+    pub dwords: [u32; 17],
+    pub end: u8,
+}
+
+impl Default for SlaveTrailer {
+    /// empty_slave.bin 的取值
+    fn default() -> Self {
+        Self {
+            dwords: [
+                0x0000_0000,
+                0x0000_0001,
+                0xFFFF_FFFF,
+                0x0000_0000,
+                0xFF00_0000,
+                0x0000_0000,
+                0x0000_0000,
+                0xFFFF_FFFF,
+                0x0000_0000,
+                0xFFFF_FFFF,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ],
+            end: 0,
+        }
+    }
+}
+
+/// Modbus 从站（CModbusSlave，V026）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModbusSlaveRecord {
+    /// 基类链字符串，新建对象全为空
+    pub base_strings: Vec<String>,
+    pub enabled: bool,
+    pub ip: String,
+    /// 冗余 IP，未使用时为 0.0.0.0
+    pub backup_ip: String,
+    pub unit_id: u8,
+    pub port: u16,
+    pub param13: String,
+    #[serde(default)]
+    pub trailer: SlaveTrailer,
+}
+
+impl Default for ModbusSlaveRecord {
+    fn default() -> Self {
+        Self {
+            base_strings: vec![String::new(); SLAVE_BASE_STRINGS],
+            enabled: true,
+            ip: "0.0.0.0".to_string(),
+            backup_ip: "0.0.0.0".to_string(),
+            unit_id: 1,
+            port: 502,
+            param13: "0".to_string(),
+            trailer: SlaveTrailer::default(),
+        }
+    }
+}
+
+/// 由通用硬件树的从站生成的硬件对象
+/// 布局中没有已确认位置的字段不写入记录，原值保留在 unencoded 中，由调用方补填或提示用户
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlaveObjects {
+    pub slave: ModbusSlaveRecord,
+    /// 读写命令，按通用从站的命令顺序；通道列表为空（见 unencoded）
+    pub orders: Vec<ModbusOrderRecord>,
+    pub unencoded: Vec<UnencodedField>,
+}
+
+/// 未能编码的字段：路径（如 "timeout_ms"、"orders[0].channels"）与原值
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnencodedField {
+    pub path: String,
+    pub value: serde_json::Value,
+}
+
+impl SlaveObjects {
+    /// RTU 从站的串口参数布局未经样本确认，暂不支持（避免静默丢弃串口参数）
+    pub fn from_comm_slave(slave: &CommSlave) -> Result<Self> {
+        let (ip, port, unit_id) = match &slave.link {
+            SlaveLink::ModbusTcp { ip, port, unit_id } => (ip, *port, *unit_id),
+            SlaveLink::ModbusRtu { port, .. } => bail!(
+                "从站 {} 为 Modbus RTU（{}）：串口参数在从站对象中的布局尚无样本确认，暂不支持生成",
+                slave.name,
+                port
+            ),
+        };
+        let record = ModbusSlaveRecord { enabled: slave.enabled, ip: ip.clone(), port, unit_id, ..Default::default() };
+
+        let mut unencoded = vec![
+            UnencodedField::new("name", &slave.name),
+            UnencodedField::new("timeout_ms", slave.timeout_ms),
+            UnencodedField::new("retry_count", slave.retry_count),
+        ];
+        if !slave.description.is_empty() {
+            unencoded.push(UnencodedField::new("description", &slave.description));
+        }
+        let mut orders = Vec::with_capacity(slave.orders.len());
+        for (index, order) in slave.orders.iter().enumerate() {
+            orders.push(ModbusOrderRecord {
+                header: DeviceHeader { name: order.name.clone(), ..Default::default() },
+                function_index: order.function_code,
+                start_address: order.start_address.into(),
+                length: order.length.into(),
+                scan_period: order.scan_period_ms,
+                ..Default::default()
+            });
+            if !order.channels.is_empty() {
+                unencoded.push(UnencodedField::new(&format!("orders[{}].channels", index), &order.channels));
+            }
+        }
+        Ok(Self { slave: record, orders, unencoded })
+    }
+}
+
+impl UnencodedField {
+    fn new(path: &str, value: impl Serialize) -> Self {
+        Self { path: path.to_string(), value: serde_json::to_value(value).unwrap_or_default() }
+    }
+}
+
+/// Modbus 读写命令（CModbusOrder）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModbusOrderRecord {
+    pub header: DeviceHeader,
+    /// 功能码索引（03 = 读保持寄存器）
+    pub function_index: u8,
+    pub start_address: u32,
+    pub length: u32,
+    pub param3: u16,
+    pub param4: u16,
+    pub param5: u8,
+    pub scan_period: u32,
+    pub param7: u32,
+    #[serde(default)]
+    pub mappings: Vec<MappingItem>,
+    #[serde(default)]
+    pub channels: Vec<ModbusChannelRecord>,
+}
+
+/// Modbus 通道（CModbusChannel）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModbusChannelRecord {
+    pub header: DeviceHeader,
+    pub offset: u32,
+    pub data_type: u8,
+    pub bit_offset: u8,
+}
+
+/// 硬件对象编解码器（仅 V026）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HardwareCodec;
+
+impl HardwareCodec {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn decode_slave(&self, data: &[u8]) -> Result<ModbusSlaveRecord> {
+        let mut r = MfcReader::new(data);
+        let base_strings =
+            (0..SLAVE_BASE_STRINGS).map(|_| r.read_mfc_string()).collect::<Result<Vec<_>>>().context("从站基类链")?;
+        let enabled = r.read_mfc_string()?;
+        let ip = r.read_mfc_string()?;
+        let backup_ip = r.read_mfc_string()?;
+        let unit_id = r.read_mfc_string()?;
+        let port = r.read_mfc_string()?;
+        let param13 = r.read_mfc_string()?;
+        let mut dwords = [0u32; 17];
+        for dword in &mut dwords {
+            *dword = r.read_u32().context("从站尾块")?;
+        }
+        let end = r.read_u8().context("从站尾块")?;
+        ensure_consumed(&r, "从站")?;
+        Ok(ModbusSlaveRecord {
+            base_strings,
+            enabled: parse_field::<u8>("启用", &enabled)? != 0,
+            ip,
+            backup_ip,
+            unit_id: parse_field("站号", &unit_id)?,
+            port: parse_field("端口", &port)?,
+            param13,
+            trailer: SlaveTrailer { dwords, end },
+        })
+    }
+
+    pub fn encode_slave(&self, slave: &ModbusSlaveRecord) -> Result<Vec<u8>> {
+        if slave.base_strings.len() != SLAVE_BASE_STRINGS {
+            bail!("从站基类链字符串应为 {} 个，实际 {}", SLAVE_BASE_STRINGS, slave.base_strings.len());
+        }
+        let mut w = MfcWriter::new(Vec::new());
+        for text in &slave.base_strings {
+            w.write_mfc_string(text)?;
+        }
+        w.write_mfc_string(if slave.enabled { "1" } else { "0" })?;
+        w.write_mfc_string(&slave.ip)?;
+        w.write_mfc_string(&slave.backup_ip)?;
+        w.write_mfc_string(&slave.unit_id.to_string())?;
+        w.write_mfc_string(&slave.port.to_string())?;
+        w.write_mfc_string(&slave.param13)?;
+        for dword in slave.trailer.dwords {
+            w.write_u32(dword)?;
+        }
+        w.write_u8(slave.trailer.end)?;
+        Ok(w.into_inner())
+    }
+
+    pub fn decode_order(&self, data: &[u8]) -> Result<ModbusOrderRecord> {
+        let mut r = MfcReader::new(data);
+        let order = read_order(&mut r)?;
+        ensure_consumed(&r, "读写命令")?;
+        Ok(order)
+    }
+
+    pub fn encode_order(&self, order: &ModbusOrderRecord) -> Result<Vec<u8>> {
+        let mut w = MfcWriter::new(Vec::new());
+        write_order(&mut w, order)?;
+        Ok(w.into_inner())
+    }
+
+    pub fn decode_channel(&self, data: &[u8]) -> Result<ModbusChannelRecord> {
+        let mut r = MfcReader::new(data);
+        let channel = read_channel(&mut r)?;
+        ensure_consumed(&r, "通道")?;
+        Ok(channel)
+    }
+
+    pub fn encode_channel(&self, channel: &ModbusChannelRecord) -> Result<Vec<u8>> {
+        let mut w = MfcWriter::new(Vec::new());
+        write_channel(&mut w, channel)?;
+        Ok(w.into_inner())
+    }
+}
+
+/// 剪贴板数据可能带零填充，其余多出的字节视为布局不符
+fn ensure_consumed(r: &MfcReader, object: &str) -> Result<()> {
+    if !r.remaining_all_zero() {
+        bail!("{}解析结束后仍有 {} 字节未识别（偏移 {}）", object, r.remaining_len(), r.position());
+    }
+    Ok(())
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str, text: &str) -> Result<T> {
+    text.trim().parse().ok().with_context(|| format!("从站{}不是有效数值: {:?}", field, text))
+}
+
+fn read_header(r: &mut MfcReader) -> Result<DeviceHeader> {
+    Ok(DeviceHeader {
+        name: r.read_mfc_string()?,
+        id: r.read_u32()?,
+        flag1: r.read_u8()?,
+        flag2: r.read_u8()?,
+        description: r.read_mfc_string()?,
+    })
+}
+
+fn write_header(w: &mut MfcWriter<Vec<u8>>, header: &DeviceHeader) -> Result<()> {
+    w.write_mfc_string(&header.name)?;
+    w.write_u32(header.id)?;
+    w.write_u8(header.flag1)?;
+    w.write_u8(header.flag2)?;
+    w.write_mfc_string(&header.description)
+}
+
+fn read_mapping(r: &mut MfcReader) -> Result<MappingItem> {
+    let (p1, p2, p3, p4) = (r.read_u32()?, r.read_u16()?, r.read_u8()?, r.read_u16()?);
+    let len = r.read_u32()? as usize;
+    if len > r.remaining_len() {
+        bail!("映射项数据长度超出剩余字节: len={} pos={}", len, r.position());
+    }
+    Ok(MappingItem { p1, p2, p3, p4, blob: r.read_bytes(len)? })
+}
+
+fn write_mapping(w: &mut MfcWriter<Vec<u8>>, item: &MappingItem) -> Result<()> {
+    w.write_u32(item.p1)?;
+    w.write_u16(item.p2)?;
+    w.write_u8(item.p3)?;
+    w.write_u16(item.p4)?;
+    w.write_u32(item.blob.len() as u32)?;
+    w.write_bytes(&item.blob)
+}
+
+/// 读取 u32 计数并检查不超过剩余字节（每项至少 min_item 字节）
+fn read_count(r: &mut MfcReader, what: &str, min_item: usize) -> Result<usize> {
+    let count = r.read_u32()? as usize;
+    if count.saturating_mul(min_item) > r.remaining_len() {
+        bail!("{}数量异常: {}（偏移 {}）", what, count, r.position());
+    }
+    Ok(count)
+}
+
+fn read_order(r: &mut MfcReader) -> Result<ModbusOrderRecord> {
+    let header = read_header(r)?;
+    let function_index = r.read_u8()?;
+    let start_address = r.read_u32()?;
+    let length = r.read_u32()?;
+    let param3 = r.read_u16()?;
+    let param4 = r.read_u16()?;
+    let param5 = r.read_u8()?;
+    let scan_period = r.read_u32()?;
+    let param7 = r.read_u32()?;
+    let mappings = (0..read_count(r, "映射项", 13)?).map(|_| read_mapping(r)).collect::<Result<_>>()?;
+    let channels = (0..read_count(r, "通道", 14)?).map(|_| read_channel(r)).collect::<Result<_>>()?;
+    Ok(ModbusOrderRecord {
+        header,
+        function_index,
+        start_address,
+        length,
+        param3,
+        param4,
+        param5,
+        scan_period,
+        param7,
+        mappings,
+        channels,
+    })
+}
+
+fn write_order(w: &mut MfcWriter<Vec<u8>>, order: &ModbusOrderRecord) -> Result<()> {
+    write_header(w, &order.header)?;
+    w.write_u8(order.function_index)?;
+    w.write_u32(order.start_address)?;
+    w.write_u32(order.length)?;
+    w.write_u16(order.param3)?;
+    w.write_u16(order.param4)?;
+    w.write_u8(order.param5)?;
+    w.write_u32(order.scan_period)?;
+    w.write_u32(order.param7)?;
+    w.write_u32(order.mappings.len() as u32)?;
+    for item in &order.mappings {
+        write_mapping(w, item)?;
+    }
+    w.write_u32(order.channels.len() as u32)?;
+    for channel in &order.channels {
+        write_channel(w, channel)?;
+    }
+    Ok(())
+}
+
+fn read_channel(r: &mut MfcReader) -> Result<ModbusChannelRecord> {
+    Ok(ModbusChannelRecord {
+        header: read_header(r)?,
+        offset: r.read_u32()?,
+        data_type: r.read_u8()?,
+        bit_offset: r.read_u8()?,
+    })
+}
+
+fn write_channel(w: &mut MfcWriter<Vec<u8>>, channel: &ModbusChannelRecord) -> Result<()> {
+    write_header(w, &channel.header)?;
+    w.write_u32(channel.offset)?;
+    w.write_u8(channel.data_type)?;
+    w.write_u8(channel.bit_offset)
+}
@@ -0,0 +1,318 @@
+/*
+无损回写：解码时记录对象区间，编码时逐段回写
+- 解析器在无损模式下记录每个已识别对象的字节区间（名称、头部字符串、网络、元件、变量表）
+- 区间之间未识别的字节作为 Raw 段原样保留，末尾零填充记为 Padding
+- 序列化器按段回写：指纹未变的段输出原始字节，变化的段按解析器布局重新编码
+*/
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::ast::{PreservedLayout, PreservedSegment, UniversalPou};
+
+use super::config::HollysysConfig;
+use super::parser::{ParseOptions, read_pou_with_options};
+use super::protocol::PlcVariant;
+use super::serializer::PouSerializer;
+
+/// 解析器记录的对象区间（绝对偏移，[start, end)）
+#[derive(Debug, Clone)]
+pub(crate) struct CapturedSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: SpanKind,
+}
+
+/// 区间类型；body/fields 为相对区间起点的偏移
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SpanKind {
+    Name { align4: bool },
+    HeaderStrings { count_width: u8 },
+    Network { id: i32, body: usize, fields: usize },
+    Element { id: i32, body: usize },
+    Variables,
+}
+
+/// 布局所属编码器标识
+pub(crate) fn codec_tag(variant: PlcVariant) -> &'static str {
+    match variant {
+        PlcVariant::Normal => "hollysys-normal",
+        PlcVariant::Safety => "hollysys-safety",
+    }
+}
+
+/// 由布局标识反查版本
+pub(crate) fn variant_from_codec_tag(tag: &str) -> Option<PlcVariant> {
+    [PlcVariant::Normal, PlcVariant::Safety]
+        .into_iter()
+        .find(|variant| codec_tag(*variant) == tag)
+}
+
+/// FNV-1a 64 位哈希（只用于检测修改，不要求抗碰撞）
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// 指纹以十六进制字符串保存，避免前端 JSON 数字精度丢失
+fn fingerprint_of<T: Serialize + ?Sized>(value: &T) -> String {
+    let json = serde_json::to_vec(value).unwrap_or_default();
+    format!("{:016x}", fnv1a(&json))
+}
+
+pub(crate) fn name_fingerprint(pou: &UniversalPou) -> String {
+    fingerprint_of(&pou.name)
+}
+
+pub(crate) fn header_strings_fingerprint(pou: &UniversalPou) -> String {
+    fingerprint_of(&pou.header_strings)
+}
+
+pub(crate) fn network_fingerprint(id: i32, label: &str, comment: &str) -> String {
+    fingerprint_of(&(id, label, comment))
+}
+
+/// 元件指纹包含 preserved 字段：修改未知字段同样触发重编码
+pub(crate) fn element_fingerprint(elem: &crate::ast::LdElement) -> String {
+    fingerprint_of(elem)
+}
+
+/// Safety 变量写入依赖头部字符串推断容器类型，因此一并纳入
+pub(crate) fn variables_fingerprint(pou: &UniversalPou) -> String {
+    fingerprint_of(&(&pou.variables, &pou.header_strings))
+}
+
+/// 由解析区间生成字节布局
+/// - 区间按起点排序，与前一区间重叠的区间丢弃（其字节已被覆盖）
+/// - 找不到对应 AST 对象（或 ID 重复）的区间降级为 Raw
+pub(crate) fn build_layout(
+    data: &[u8],
+    mut spans: Vec<CapturedSpan>,
+    pou: &UniversalPou,
+    variant: PlcVariant,
+    serialize_version: u32,
+) -> PreservedLayout {
+    spans.sort_by_key(|span| span.start);
+
+    let networks: HashMap<i32, _> = pou.networks.iter().map(|net| (net.id, net)).collect();
+    let mut elements = HashMap::new();
+    for net in &pou.networks {
+        for elem in &net.elements {
+            elements.entry(elem.id).or_insert((net.id, elem));
+        }
+    }
+
+    let mut seen_networks = HashSet::new();
+    let mut seen_elements = HashSet::new();
+    let mut segments = Vec::new();
+    let mut cursor = 0usize;
+    for span in spans {
+        if span.start < cursor || span.end > data.len() {
+            continue;
+        }
+        if span.start > cursor {
+            segments.push(PreservedSegment::Raw {
+                bytes: data[cursor..span.start].to_vec(),
+            });
+        }
+        let bytes = data[span.start..span.end].to_vec();
+        let segment = match span.kind {
+            SpanKind::Name { align4 } => PreservedSegment::Name {
+                fingerprint: name_fingerprint(pou),
+                align4,
+                bytes,
+            },
+            SpanKind::HeaderStrings { count_width } => PreservedSegment::HeaderStrings {
+                fingerprint: header_strings_fingerprint(pou),
+                count_width,
+                bytes,
+            },
+            SpanKind::Network { id, body, fields } => match networks.get(&id) {
+                Some(net) if seen_networks.insert(id) => PreservedSegment::Network {
+                    id,
+                    fingerprint: network_fingerprint(net.id, &net.label, &net.comment),
+                    body,
+                    fields,
+                    bytes,
+                },
+                _ => PreservedSegment::Raw { bytes },
+            },
+            SpanKind::Element { id, body } => match elements.get(&id) {
+                Some((network, elem)) if seen_elements.insert(id) => PreservedSegment::Element {
+                    id,
+                    network: *network,
+                    fingerprint: element_fingerprint(elem),
+                    body,
+                    bytes,
+                },
+                _ => PreservedSegment::Raw { bytes },
+            },
+            SpanKind::Variables => PreservedSegment::Variables {
+                fingerprint: variables_fingerprint(pou),
+                bytes,
+            },
+        };
+        segments.push(segment);
+        cursor = span.end;
+    }
+    if cursor < data.len() {
+        segments.push(PreservedSegment::Raw {
+            bytes: data[cursor..].to_vec(),
+        });
+    }
+    split_trailing_padding(&mut segments, data.len());
+
+    PreservedLayout {
+        codec: codec_tag(variant).to_string(),
+        serialize_version,
+        segments,
+    }
+}
+
+/// 末尾的零字节拆为 Padding：重编码导致长度变化时仍能补齐到原总长度
+fn split_trailing_padding(segments: &mut Vec<PreservedSegment>, total_len: usize) {
+    let bytes = match segments.last_mut() {
+        Some(PreservedSegment::Raw { bytes }) | Some(PreservedSegment::Variables { bytes, .. }) => {
+            bytes
+        }
+        _ => return,
+    };
+    let zeros = bytes.iter().rev().take_while(|b| **b == 0).count();
+    if zeros == 0 {
+        return;
+    }
+    bytes.truncate(bytes.len() - zeros);
+    if bytes.is_empty() && matches!(segments.last(), Some(PreservedSegment::Raw { .. })) {
+        segments.pop();
+    }
+    segments.push(PreservedSegment::Padding { total_len });
+}
+
+/// 清除元件/引脚上的 preserved 字段（非无损模式输出干净的 AST）
+pub(crate) fn strip_preserved(pou: &mut UniversalPou) {
+    pou.preserved = None;
+    for net in &mut pou.networks {
+        for elem in &mut net.elements {
+            elem.preserved = None;
+            for pin in &mut elem.pins {
+                pin.preserved = None;
+            }
+        }
+    }
+}
+
+/// 往返校验报告
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundtripReport {
+    pub original_len: usize,
+    pub encoded_len: usize,
+    /// 无损编码结果与原始字节的首个差异（None 表示逐字节一致）
+    pub first_diff: Option<RoundtripDiff>,
+    /// 忽略指纹、强制按 AST 重编码全部对象时的首个差异
+    /// 用于评估“修改后再编码”的可信度：None 表示编码器能完整复现该 POU
+    pub reencode_diff: Option<RoundtripDiff>,
+    /// 强制重编码失败的原因（如字符串无法转为 GBK）；失败时 reencode_diff 为 None
+    pub reencode_error: Option<String>,
+}
+
+impl RoundtripReport {
+    /// 无损编码是否与原始字节完全一致
+    pub fn is_exact(&self) -> bool {
+        self.first_diff.is_none()
+    }
+}
+
+/// 差异定位
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundtripDiff {
+    /// 首个不一致的字节偏移
+    pub offset: usize,
+    /// 偏移所在的布局段（如 "Element id=12 network=3"）
+    pub segment: String,
+    /// 偏移附近的原始字节 / 编码字节（十六进制）
+    pub expected: String,
+    pub actual: String,
+}
+
+const DIFF_CONTEXT: usize = 16;
+
+/// 执行 decode → encode 往返并比较字节
+pub(crate) fn verify_roundtrip(data: &[u8], config: &HollysysConfig) -> Result<RoundtripReport> {
+    let pou = read_pou_with_options(data, &ParseOptions::from(config).with_lossless(true))?;
+    let layout = pou.preserved.as_ref();
+    let serializer = PouSerializer::from_config(config.clone().with_lossless(true));
+
+    let encoded = serializer.serialize_preserved(&pou, false)?;
+    let (reencode_diff, reencode_error) = match serializer.serialize_preserved(&pou, true) {
+        Ok(reencoded) => (diff_bytes(data, &reencoded, layout), None),
+        Err(err) => (None, Some(format!("{:#}", err))),
+    };
+
+    Ok(RoundtripReport {
+        original_len: data.len(),
+        encoded_len: encoded.len(),
+        first_diff: diff_bytes(data, &encoded, layout),
+        reencode_diff,
+        reencode_error,
+    })
+}
+
+fn diff_bytes(expected: &[u8], actual: &[u8], layout: Option<&PreservedLayout>) -> Option<RoundtripDiff> {
+    let offset = expected
+        .iter()
+        .zip(actual)
+        .position(|(a, b)| a != b)
+        .or_else(|| (expected.len() != actual.len()).then(|| expected.len().min(actual.len())))?;
+    Some(RoundtripDiff {
+        offset,
+        segment: layout
+            .map(|layout| describe_segment_at(layout, offset))
+            .unwrap_or_default(),
+        expected: hex_window(expected, offset),
+        actual: hex_window(actual, offset),
+    })
+}
+
+fn hex_window(bytes: &[u8], offset: usize) -> String {
+    let start = offset.saturating_sub(DIFF_CONTEXT).min(bytes.len());
+    let end = (offset + DIFF_CONTEXT).min(bytes.len());
+    bytes[start..end]
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 按原始字节长度定位偏移所在的段
+fn describe_segment_at(layout: &PreservedLayout, offset: usize) -> String {
+    let mut cursor = 0usize;
+    for segment in &layout.segments {
+        let (len, label) = match segment {
+            PreservedSegment::Raw { bytes } => (bytes.len(), "Raw".to_string()),
+            PreservedSegment::Padding { total_len } => {
+                (total_len.saturating_sub(cursor), "Padding".to_string())
+            }
+            PreservedSegment::Name { bytes, .. } => (bytes.len(), "Name".to_string()),
+            PreservedSegment::HeaderStrings { bytes, .. } => {
+                (bytes.len(), "HeaderStrings".to_string())
+            }
+            PreservedSegment::Network { id, bytes, .. } => {
+                (bytes.len(), format!("Network id={}", id))
+            }
+            PreservedSegment::Element { id, network, bytes, .. } => {
+                (bytes.len(), format!("Element id={} network={}", id, network))
+            }
+            PreservedSegment::Variables { bytes, .. } => (bytes.len(), "Variables".to_string()),
+        };
+        if offset < cursor + len {
+            return format!("{} @{}+{}", label, cursor, offset - cursor);
+        }
+        cursor += len;
+    }
+    format!("<EOF> @{}", cursor)
+}
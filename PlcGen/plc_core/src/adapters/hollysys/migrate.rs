@@ -0,0 +1,302 @@
+/*
+跨版本迁移：Normal ↔ Safety
+- 拓扑：Normal 连接图 ↔ Safety Token 流（经 domain::topology 统一转换）
+- 变量尾部：Normal 的 addr_id(u64)/mode/id2 ↔ Safety 的 addr_id(u32)/area_code，mode/id2 交由目标版本按默认值生成
+  addr_id 截断到 32 位后若等于 Safety 的未绑定标记 0xFFFFFFFF，则解除绑定并报告 Unsupported
+- 头部：Safety 的 header_strings（依赖的功能块类型）由实例化 Box 推导；Normal 不写该数组
+- 引脚/元件：无损模式保留的版本相关字段一律丢弃，由目标版本序列化器填默认值
+SOE 的版本编码差异（Normal u16 标志 / Safety 0x0100·0x0001）由序列化器根据 soe_enable 生成，迁移时只保留标志本身
+*/
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::ast::{LdElement, Network, UniversalPou, Variable, VariableNode};
+use crate::domain::topology::{TopologyForm, convert_network, has_topology, network_elements};
+
+use super::protocol::PlcVariant;
+
+/// Normal 未绑定地址
+const NORMAL_UNBOUND_ADDR: u64 = 0xFFFF_FFFF_FFFF_FFFF;
+/// Safety 未绑定地址（仅 32 位）
+const SAFETY_UNBOUND_ADDR: u64 = 0xFFFF_FFFF;
+/// Safety area_code 默认值
+const DEFAULT_AREA_CODE: u8 = 0x04;
+
+/// 迁移问题级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MigrationSeverity {
+    /// 已自动改写，无信息损失
+    Info,
+    /// 目标版本无对应字段，信息被丢弃或截断
+    Lossy,
+    /// 目标版本无法表达，结果需要人工处理
+    Unsupported,
+}
+
+/// 迁移问题
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationIssue {
+    pub severity: MigrationSeverity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for MigrationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:?}]", self.severity)?;
+        if let Some(id) = self.network_id {
+            write!(f, " network#{}", id)?;
+        }
+        if let Some(id) = self.element_id {
+            write!(f, " element#{}", id)?;
+        }
+        if let Some(name) = &self.variable {
+            write!(f, " var {}", name)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// 迁移报告
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    #[serde(skip)]
+    pub from: PlcVariant,
+    #[serde(skip)]
+    pub to: PlcVariant,
+    pub issues: Vec<MigrationIssue>,
+}
+
+impl MigrationReport {
+    /// 没有信息损失且无需人工处理
+    pub fn is_lossless(&self) -> bool {
+        self.issues.iter().all(|issue| issue.severity == MigrationSeverity::Info)
+    }
+
+    /// 目标版本无法表达的构造
+    pub fn unsupported(&self) -> impl Iterator<Item = &MigrationIssue> {
+        self.issues.iter().filter(|issue| issue.severity == MigrationSeverity::Unsupported)
+    }
+
+    fn push(&mut self, severity: MigrationSeverity, message: impl Into<String>) -> &mut MigrationIssue {
+        self.issues.push(MigrationIssue {
+            severity,
+            network_id: None,
+            element_id: None,
+            variable: None,
+            message: message.into(),
+        });
+        self.issues.last_mut().expect("issue just pushed")
+    }
+}
+
+/// 将 POU 从 from 版本迁移到 to 版本
+/// 同版本迁移原样返回；跨版本时无损布局作废，结果只能走常规序列化
+pub fn migrate_pou(pou: &UniversalPou, from: PlcVariant, to: PlcVariant) -> (UniversalPou, MigrationReport) {
+    let mut report = MigrationReport { from, to, issues: Vec::new() };
+    if from == to {
+        return (pou.clone(), report);
+    }
+
+    let mut out = pou.clone();
+    if out.preserved.take().is_some() {
+        report.push(MigrationSeverity::Info, "丢弃源版本的无损字节布局");
+    }
+
+    let form = match to {
+        PlcVariant::Normal => TopologyForm::Connections,
+        PlcVariant::Safety => TopologyForm::Tokens,
+    };
+    out.networks = pou
+        .networks
+        .iter()
+        .map(|net| migrate_network(net, to, form, &mut report))
+        .collect();
+
+    out.header_strings = migrate_header_strings(pou, to, &mut report);
+    for node in &mut out.variables {
+        migrate_variable_node(node, &pou.header_strings, to, &mut report);
+    }
+    (out, report)
+}
+
+fn migrate_network(
+    net: &Network,
+    to: PlcVariant,
+    form: TopologyForm,
+    report: &mut MigrationReport,
+) -> Network {
+    let mut out = match convert_network(net, form) {
+        Ok(converted) => {
+            if !has_topology(net) && net.elements.len() > 1 {
+                report
+                    .push(MigrationSeverity::Lossy, "源网络缺少拓扑描述，按元件声明顺序串联")
+                    .network_id = Some(net.id);
+            }
+            converted
+        }
+        Err(err) => {
+            report
+                .push(MigrationSeverity::Unsupported, format!("拓扑无法转换（{}），按元件声明顺序串联", err))
+                .network_id = Some(net.id);
+            let mut fallback = net.clone();
+            fallback.elements = network_elements(net);
+            fallback.safety_topology.clear();
+            for elem in &mut fallback.elements {
+                elem.connections.clear();
+            }
+            // 无拓扑描述时按声明顺序串联，必然可转换
+            convert_network(&fallback, form).unwrap_or(fallback)
+        }
+    };
+
+    for elem in &mut out.elements {
+        migrate_element(elem, net.id, to, report);
+    }
+    out
+}
+
+fn migrate_element(elem: &mut LdElement, network_id: i32, to: PlcVariant, report: &mut MigrationReport) {
+    elem.preserved = None;
+    let mut lossy_binding = false;
+    for pin in &mut elem.pins {
+        let binding = pin.preserved.take().and_then(|extras| extras.binding_id);
+        lossy_binding |= to == PlcVariant::Safety && binding.is_some_and(|id| id != 0xFFFF_FFFF);
+    }
+
+    let mut note = |severity, message: String| {
+        let issue = report.push(severity, message);
+        issue.network_id = Some(network_id);
+        issue.element_id = Some(elem.id);
+    };
+    match to {
+        PlcVariant::Safety => {
+            if !elem.comment.is_empty() || !elem.desc.is_empty() {
+                note(MigrationSeverity::Lossy, "Safety 元件不保存注释/描述".to_string());
+            }
+            if lossy_binding {
+                note(MigrationSeverity::Lossy, "Safety 引脚没有 binding_id 字段".to_string());
+            }
+        }
+        PlcVariant::Normal => {
+            let unnamed = elem.pins.iter().filter(|pin| pin.name.is_empty()).count();
+            if unnamed > 0 {
+                note(
+                    MigrationSeverity::Unsupported,
+                    format!("{} 有 {} 个未命名引脚，Normal 引脚必须显式命名", elem.name, unnamed),
+                );
+            }
+        }
+    }
+}
+
+/// Safety：保留原有依赖项并补上实例化 Box 的功能块类型；Normal：头部不含该数组
+fn migrate_header_strings(pou: &UniversalPou, to: PlcVariant, report: &mut MigrationReport) -> Vec<String> {
+    match to {
+        PlcVariant::Normal => {
+            if !pou.header_strings.is_empty() {
+                report.push(
+                    MigrationSeverity::Info,
+                    format!("Normal 头部不含依赖项数组，{} 项依赖改由变量容器类型记录", pou.header_strings.len()),
+                );
+            }
+            Vec::new()
+        }
+        PlcVariant::Safety => {
+            let mut headers = pou.header_strings.clone();
+            for elem in pou.networks.iter().flat_map(|net| &net.elements) {
+                if !elem.instance.is_empty() && !elem.name.is_empty() && !headers.contains(&elem.name) {
+                    headers.push(elem.name.clone());
+                }
+            }
+            if headers.len() > pou.header_strings.len() {
+                report.push(
+                    MigrationSeverity::Info,
+                    format!("由功能块实例推导依赖项: {}", headers[pou.header_strings.len()..].join(", ")),
+                );
+            }
+            headers
+        }
+    }
+}
+
+fn migrate_variable_node(
+    node: &mut VariableNode,
+    header_strings: &[String],
+    to: PlcVariant,
+    report: &mut MigrationReport,
+) {
+    match node {
+        VariableNode::Leaf(var) => migrate_variable(var, to, report),
+        VariableNode::Group { name, type_name, children } => {
+            if to == PlcVariant::Normal && type_name.is_none() {
+                // Safety 按 header_strings 推断功能块容器；Normal 没有该数组，类型需显式记录
+                *type_name = header_strings
+                    .iter()
+                    .find(|header| *name == **header || name.starts_with(&format!("{}_", header)))
+                    .cloned();
+            }
+            for child in children {
+                migrate_variable_node(child, header_strings, to, report);
+            }
+        }
+    }
+}
+
+fn migrate_variable(var: &mut Variable, to: PlcVariant, report: &mut MigrationReport) {
+    let mut note = |severity, message: String| {
+        report.push(severity, message).variable = Some(var.name.clone());
+    };
+    // mode/id2 的取值与版本绑定，交由目标序列化器生成默认值
+    var.mode = None;
+    var.id2 = None;
+    match to {
+        PlcVariant::Safety => {
+            var.addr_id = match var.addr_id {
+                None | Some(NORMAL_UNBOUND_ADDR) => None,
+                // 截断后与 Safety 未绑定标记相同的地址不能保留，否则变量会被当作未绑定
+                Some(addr) if addr & SAFETY_UNBOUND_ADDR == SAFETY_UNBOUND_ADDR => {
+                    note(
+                        MigrationSeverity::Unsupported,
+                        format!(
+                            "addr_id 0x{:X} 在 Safety 中等同未绑定标记 0xFFFFFFFF，已解除绑定，需重新分配地址",
+                            addr
+                        ),
+                    );
+                    None
+                }
+                Some(addr) if addr > SAFETY_UNBOUND_ADDR => {
+                    note(
+                        MigrationSeverity::Lossy,
+                        format!("addr_id 0x{:X} 超出 Safety 32 位地址，已截断", addr),
+                    );
+                    Some(addr & SAFETY_UNBOUND_ADDR)
+                }
+                Some(addr) => Some(addr),
+            };
+            if var.power_down_keep {
+                note(MigrationSeverity::Lossy, "Safety 变量表没有掉电保持字段".to_string());
+            }
+        }
+        PlcVariant::Normal => {
+            var.addr_id = match var.addr_id {
+                None | Some(SAFETY_UNBOUND_ADDR) => None,
+                Some(addr) => Some(addr),
+            };
+            if let Some(area) = var.area_code.take()
+                && area != DEFAULT_AREA_CODE
+            {
+                note(
+                    MigrationSeverity::Lossy,
+                    format!("Normal 变量表没有区域字段，area_code 0x{:02X} 被丢弃", area),
+                );
+            }
+        }
+    }
+}
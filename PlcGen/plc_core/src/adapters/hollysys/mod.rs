@@ -4,11 +4,28 @@ mod factory;
 mod parser;
 mod config;
 mod backend;
+mod lossless;
+mod migrate;
+mod var_ids;
+mod hardware;
+mod envelope;
 
 // 导出解析器入口（仅保留必要的公共 API）。
-pub use parser::{read_pou, read_pou_with_config, DEFAULT_SERIALIZE_VERSION};
+pub use parser::{
+    detect, read_pou, read_pou_lossless, read_pou_recovering, read_pou_recovering_with_options, read_pou_with_config,
+    read_pou_with_options, DetectedFormat, ObjectPath, ParseError, ParseOptions, ParseSection, PartialDecode,
+    SkippedRange, DEFAULT_SERIALIZE_VERSION,
+};
 
 // 对外导出：版本标识 / 配置 / 编解码器
 pub use protocol::PlcVariant;
 pub use config::HollysysConfig;
 pub use backend::HollysysCodec;
+pub use lossless::{RoundtripDiff, RoundtripReport};
+pub use envelope::{ClipboardDecode, TrailingBytes, split_containers};
+pub use var_ids::{ReferenceVarIds, SequentialVarIds, StableHashVarIds, VarIdAllocator, VarIdPool};
+pub use migrate::{MigrationIssue, MigrationReport, MigrationSeverity, migrate_pou};
+pub use hardware::{
+    DeviceHeader, HARDWARE_SERIALIZE_VERSION, HardwareCodec, MappingItem, ModbusChannelRecord, ModbusOrderRecord,
+    ModbusSlaveRecord, SlaveObjects, SlaveTrailer, UnencodedField,
+};
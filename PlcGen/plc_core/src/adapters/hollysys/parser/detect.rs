@@ -0,0 +1,160 @@
+use crate::ast::{UniversalPou, VariableNode};
+
+use super::mfc::MfcReader;
+use super::variables::{find_normal_var_table_offset, find_safety_var_table_offset};
+use super::{DEFAULT_SERIALIZE_VERSION, Variant, read_pou_with_config};
+
+/// 格式探测结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectedFormat {
+    pub variant: Variant,
+    pub serialize_version: u32,
+    /// 置信度 0.0 ~ 1.0：各项启发式与试解析结果的一致程度
+    pub confidence: f32,
+}
+
+/// 试解析的序列化版本候选（覆盖解析器中各版本分支的阈值）
+/// 得分相同时靠前者优先，因此默认版本排在首位
+const NORMAL_VERSION_CANDIDATES: &[u32] = &[DEFAULT_SERIALIZE_VERSION, 6, 1, 0];
+const SAFETY_VERSION_CANDIDATES: &[u32] = &[DEFAULT_SERIALIZE_VERSION, 0x0F, 0x2D, 0x34, 0x38, 0x44];
+
+/// 仅 Safety 对象流中出现的类名
+const SAFETY_ONLY_CLASSES: &[&[u8]] = &[b"CLDOr", b"CLDAnd"];
+
+/// 两个名称串之间允许的最大间隙（对齐 + 时间戳）
+const HEADER_GAP_MAX: usize = 12;
+
+/// 头部投票权重：两次名称之间是否夹有时间戳是最稳定的区分特征
+const HEADER_WEIGHT: u32 = 2;
+
+/// 探测剪贴板数据的版本（Normal/Safety）与序列化版本
+/// 依据：头部名称布局、Safety 专有类名、变量表特征，以及按候选版本试解析的结果
+pub fn detect(data: &[u8]) -> DetectedFormat {
+    let mut normal_votes = 0u32;
+    let mut safety_votes = 0u32;
+
+    match header_vote(data) {
+        Some(Variant::Normal) => normal_votes += HEADER_WEIGHT,
+        Some(Variant::Safety) => safety_votes += HEADER_WEIGHT,
+        None => {}
+    }
+    if SAFETY_ONLY_CLASSES.iter().any(|name| contains(data, name)) {
+        safety_votes += 1;
+    }
+    let reader = MfcReader::new(data);
+    match (
+        find_normal_var_table_offset(&reader).is_some(),
+        find_safety_var_table_offset(&reader).is_some(),
+    ) {
+        (true, false) => normal_votes += 1,
+        (false, true) => safety_votes += 1,
+        _ => {}
+    }
+
+    let normal = best_version(data, Variant::Normal, NORMAL_VERSION_CANDIDATES);
+    let safety = best_version(data, Variant::Safety, SAFETY_VERSION_CANDIDATES);
+    match (normal, safety) {
+        (Some((_, a)), Some((_, b))) if a > b => normal_votes += 1,
+        (Some((_, a)), Some((_, b))) if b > a => safety_votes += 1,
+        (Some(_), None) => normal_votes += 1,
+        (None, Some(_)) => safety_votes += 1,
+        _ => {}
+    }
+
+    let variant = if safety_votes > normal_votes {
+        Variant::Safety
+    } else if normal_votes > safety_votes || safety.is_none() {
+        Variant::Normal
+    } else {
+        Variant::Safety
+    };
+    let (winner_votes, decoded) = match variant {
+        Variant::Normal => (normal_votes, normal),
+        Variant::Safety => (safety_votes, safety),
+    };
+    let total = normal_votes + safety_votes;
+    let mut confidence = if total == 0 { 0.0 } else { winner_votes as f32 / total as f32 };
+    if decoded.is_none() {
+        confidence *= 0.5;
+    }
+
+    DetectedFormat {
+        variant,
+        serialize_version: decoded.map_or(DEFAULT_SERIALIZE_VERSION, |(version, _)| version),
+        confidence,
+    }
+}
+
+/// 数据开头是否像 POU 头部（名称串后紧跟同名串）；用于在剪贴板缓冲区中定位下一个 POU
+pub(crate) fn looks_like_pou_header(data: &[u8]) -> bool {
+    header_vote(data).is_some()
+}
+
+/// 头部布局：名称串之后紧跟第二个名称串，两者之间的间隙
+/// - Normal：对齐填充 + 4 字节时间戳（含非零字节）
+/// - Safety：仅有少量零填充（< 4 字节）
+/// - Safety（新版头部）：4 字节种子 + 名称 + 名称（无间隙）
+fn header_vote(data: &[u8]) -> Option<Variant> {
+    if let Some(gap) = name_gap(data, 0) {
+        if gap.iter().any(|b| *b != 0) {
+            return Some(Variant::Normal);
+        }
+        if gap.len() < 4 {
+            return Some(Variant::Safety);
+        }
+    }
+    match name_gap(data, 4) {
+        Some([]) => Some(Variant::Safety),
+        _ => None,
+    }
+}
+
+/// 读取 start 处的名称串，并在其后 HEADER_GAP_MAX 字节内查找相同的名称串，返回两者之间的字节
+fn name_gap(data: &[u8], start: usize) -> Option<&[u8]> {
+    let mut reader = MfcReader::new(data);
+    reader.seek_to(start).ok()?;
+    let name = reader.read_mfc_string().ok()?;
+    if name.is_empty() {
+        return None;
+    }
+    let name_end = reader.position();
+    let encoded = &data[start..name_end];
+    let window = data.get(name_end..(name_end + HEADER_GAP_MAX + encoded.len()).min(data.len()))?;
+    let gap = window.windows(encoded.len()).position(|w| w == encoded)?;
+    Some(&data[name_end..name_end + gap])
+}
+
+/// 按候选版本试解析，返回得分最高的 (版本, 得分)
+/// 得分 = 元件数 + 变量数 + 1（解析成功本身计 1 分）
+fn best_version(data: &[u8], variant: Variant, candidates: &[u32]) -> Option<(u32, usize)> {
+    let mut best: Option<(u32, usize)> = None;
+    for &version in candidates {
+        let Ok(pou) = read_pou_with_config(data, variant, version) else {
+            continue;
+        };
+        let score = decode_score(&pou);
+        if best.is_none_or(|(_, top)| score > top) {
+            best = Some((version, score));
+        }
+    }
+    best
+}
+
+fn decode_score(pou: &UniversalPou) -> usize {
+    let elements: usize = pou.networks.iter().map(|net| net.elements.len()).sum();
+    1 + elements + count_leaves(&pou.variables)
+}
+
+fn count_leaves(nodes: &[VariableNode]) -> usize {
+    nodes
+        .iter()
+        .map(|node| match node {
+            VariableNode::Leaf(_) => 1,
+            VariableNode::Group { children, .. } => count_leaves(children),
+        })
+        .sum()
+}
+
+fn contains(data: &[u8], needle: &[u8]) -> bool {
+    data.windows(needle.len()).any(|window| window == needle)
+}
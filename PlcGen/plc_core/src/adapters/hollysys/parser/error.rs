@@ -0,0 +1,133 @@
+use std::fmt;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use super::mfc::MfcReader;
+
+/// 十六进制窗口：失败偏移前后各取的字节数
+const WINDOW_CONTEXT: usize = 16;
+
+/// 解析失败诊断
+/// 由 `PouCodec::decode` 以 anyhow::Error 返回，可通过 `err.downcast_ref::<ParseError>()` 取出
+#[derive(Debug, Clone, Error, Serialize)]
+#[error("{message}（偏移 0x{offset:X}，位置 {path}，类 {}）", .class_name.as_deref().unwrap_or("-"))]
+pub struct ParseError {
+    /// 原始错误信息（含上下文链）
+    pub message: String,
+    /// 出错时读取器所在的字节偏移
+    pub offset: usize,
+    /// 出错时正在解析的对象
+    pub path: ObjectPath,
+    /// 当前对象在 ClassTable 中解析出的类名（未知类为 `#<class_id>`）
+    pub class_name: Option<String>,
+    /// 窗口首字节偏移
+    pub window_start: usize,
+    /// 偏移附近的字节，出错字节以方括号标出，如 `00 01 [FF] 02`
+    pub window: String,
+}
+
+impl ParseError {
+    /// 按读取器当前状态包装错误
+    pub(crate) fn at(reader: &MfcReader, err: anyhow::Error) -> Self {
+        let data = reader.inner.get_ref();
+        let offset = reader.position();
+        let window_start = offset.saturating_sub(WINDOW_CONTEXT).min(data.len());
+        let window_end = (offset + WINDOW_CONTEXT).min(data.len());
+        let window = (window_start..window_end)
+            .map(|idx| {
+                if idx == offset {
+                    format!("[{:02X}]", data[idx])
+                } else {
+                    format!("{:02X}", data[idx])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self {
+            message: format!("{:#}", err),
+            offset,
+            path: reader.trail.path,
+            class_name: reader.trail.class_name.clone(),
+            window_start,
+            window,
+        }
+    }
+}
+
+/// 解析阶段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum ParseSection {
+    #[default]
+    Header,
+    HeaderStrings,
+    Networks,
+    Variables,
+}
+
+/// 对象路径：阶段 / 网络序号 / 对象 ID / 引脚序号
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ObjectPath {
+    pub section: ParseSection,
+    /// 网络在 POU 中的序号（从 0 开始）
+    pub network_index: Option<usize>,
+    /// 当前对象的 ID（元件 ID；解析网络对象本身时为网络 ID）
+    pub element_id: Option<i32>,
+    /// 引脚在元件 pins 中的序号（输入在前、输出在后）
+    pub pin_index: Option<usize>,
+}
+
+impl fmt::Display for ObjectPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.section)?;
+        if let Some(index) = self.network_index {
+            write!(f, "/network[{}]", index)?;
+        }
+        if let Some(id) = self.element_id {
+            write!(f, "/element#{}", id)?;
+        }
+        if let Some(index) = self.pin_index {
+            write!(f, "/pin[{}]", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// 读取器的解析轨迹：各读取函数在进入对象时更新，失败时据此生成 ParseError
+/// 回退（restore）不会撤销轨迹，保留最后一次尝试的位置
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ParseTrail {
+    pub path: ObjectPath,
+    pub class_name: Option<String>,
+}
+
+impl ParseTrail {
+    pub(crate) fn enter_section(&mut self, section: ParseSection) {
+        self.path = ObjectPath { section, ..Default::default() };
+        self.class_name = None;
+    }
+
+    /// 读到对象标签：切换类名，清空对象 ID 与引脚
+    pub(crate) fn enter_object(&mut self, class_name: Option<String>) {
+        self.class_name = class_name;
+        self.path.element_id = None;
+        self.path.pin_index = None;
+    }
+
+    /// 当前对象是网络：网络序号递增
+    pub(crate) fn enter_network(&mut self) {
+        self.path.network_index = Some(self.path.network_index.map_or(0, |index| index + 1));
+    }
+
+    pub(crate) fn set_element_id(&mut self, id: i32) {
+        self.path.element_id = Some(id);
+    }
+
+    pub(crate) fn enter_pin(&mut self, index: usize) {
+        self.path.pin_index = Some(index);
+    }
+
+    pub(crate) fn leave_pin(&mut self) {
+        self.path.pin_index = None;
+    }
+}
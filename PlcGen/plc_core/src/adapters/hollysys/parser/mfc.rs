@@ -4,6 +4,10 @@ use anyhow::{Result, bail};
 use binrw::{BinRead, BinResult, Endian};
 use encoding_rs::GBK;
 
+use super::super::lossless::{CapturedSpan, SpanKind};
+use super::error::ParseTrail;
+use super::recover::SkippedRange;
+
 /// MFC CString: AfxReadStringLength + raw bytes (ANSI or UTF-16LE).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MfcString(pub String);
@@ -89,11 +93,90 @@ fn read_mfc_string_length<R: Read + Seek>(reader: &mut R, endian: Endian) -> Bin
 /// MFC 二进制读取器（用于解析 Hollysys 的剪贴板数据）
 pub struct MfcReader<'a> {
     pub(crate) inner: Cursor<&'a [u8]>,
+    /// 无损模式下记录的对象区间；None 表示不记录
+    spans: Option<Vec<CapturedSpan>>,
+    /// 当前解析位置（用于失败诊断）
+    pub(crate) trail: ParseTrail,
+    /// 恢复模式下跳过的区间；None 表示失败即返回错误
+    skipped: Option<Vec<SkippedRange>>,
+}
+
+/// 回退点：位置 + 已记录的对象区间、跳过区间数量
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Checkpoint {
+    pos: usize,
+    spans: usize,
+    skipped: usize,
 }
 
 impl<'a> MfcReader<'a> {
     pub fn new(data: &'a [u8]) -> Self {
-        Self { inner: Cursor::new(data) }
+        Self { inner: Cursor::new(data), spans: None, trail: ParseTrail::default(), skipped: None }
+    }
+
+    /// 开启区间记录（无损解码）
+    pub(crate) fn with_capture(data: &'a [u8]) -> Self {
+        Self { spans: Some(Vec::new()), ..Self::new(data) }
+    }
+
+    /// 开启恢复模式（尽力解码）
+    pub(crate) fn with_recovery(data: &'a [u8]) -> Self {
+        Self { skipped: Some(Vec::new()), ..Self::new(data) }
+    }
+
+    pub(crate) fn is_recovering(&self) -> bool {
+        self.skipped.is_some()
+    }
+
+    /// 记录跳过的区间；未开启恢复模式时忽略
+    pub(crate) fn skip(&mut self, range: SkippedRange) {
+        if let Some(skipped) = self.skipped.as_mut() {
+            skipped.push(range);
+        }
+    }
+
+    pub(crate) fn take_skipped(&mut self) -> Vec<SkippedRange> {
+        self.skipped.take().unwrap_or_default()
+    }
+
+    /// 记录 [start, 当前位置) 区间；未开启记录时忽略
+    pub(crate) fn record(&mut self, start: usize, kind: SpanKind) {
+        let end = self.position();
+        if let Some(spans) = self.spans.as_mut()
+            && end > start
+        {
+            spans.push(CapturedSpan { start, end, kind });
+        }
+    }
+
+    pub(crate) fn take_spans(&mut self) -> Vec<CapturedSpan> {
+        self.spans.take().unwrap_or_default()
+    }
+
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            pos: self.position(),
+            spans: self.spans.as_ref().map_or(0, Vec::len),
+            skipped: self.skipped.as_ref().map_or(0, Vec::len),
+        }
+    }
+
+    /// 回到回退点，并丢弃之后记录的区间
+    pub(crate) fn restore(&mut self, checkpoint: Checkpoint) -> Result<()> {
+        if let Some(spans) = self.spans.as_mut() {
+            spans.truncate(checkpoint.spans);
+        }
+        if let Some(skipped) = self.skipped.as_mut() {
+            skipped.truncate(checkpoint.skipped);
+        }
+        self.seek_to(checkpoint.pos)
+    }
+
+    /// 读取一个 MFC 字符串的原始字节（含长度前缀）
+    pub(crate) fn read_mfc_string_raw(&mut self) -> Result<Vec<u8>> {
+        let start = self.position();
+        let _ = self.read_mfc_string()?;
+        Ok(self.inner.get_ref()[start..self.position()].to_vec())
     }
 
     pub(crate) fn position(&self) -> usize {
@@ -1,24 +1,37 @@
 #![allow(dead_code)]
 
+mod detect;
+mod error;
 mod mfc;
 mod object_stream;
+mod recover;
 mod safety;
 mod variables;
 
 use std::collections::{HashMap, HashSet};
 use std::io::{Read, Seek};
-use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::{Context, Result, bail};
 use binrw::{binread, BinRead, BinResult, Endian};
-use log::warn;
 
 pub(crate) use super::protocol::PlcVariant as Variant;
-use crate::ast::{BoxPin, ElementType, LdElement, Network, PinDirection, UniversalPou, Variable, VariableNode};
+use crate::ast::{
+    BoxPin, ElementExtras, ElementType, LdElement, Network, PinDirection, PinExtras, UniversalPou, Variable,
+    VariableNode,
+};
 use crate::symbols_config::SymbolConfig;
 
-use mfc::{MfcReader, MfcString};
+use super::config::HollysysConfig;
+use super::lossless::{SpanKind, build_layout, strip_preserved};
+pub use detect::{DetectedFormat, detect};
+pub use error::{ObjectPath, ParseError, ParseSection};
+pub use recover::{PartialDecode, SkippedRange};
+pub(crate) use detect::looks_like_pou_header;
+pub(crate) use mfc::MfcReader;
+use mfc::MfcString;
 use object_stream::{ClassTable, ObjectKind, prefill_class_table, read_object_kind};
+use recover::{data_end, find_next_object, recover_section, skip_to_resync};
 use safety::read_networks_safety;
 use variables::{find_normal_var_table_offset, looks_like_safety_var_table, read_variables};
 
@@ -133,7 +146,6 @@ pub struct CLDBox {
 
 /// 默认序列化版本：与当前版本输出保持一致。
 pub const DEFAULT_SERIALIZE_VERSION: u32 = 13;
-const DEFAULT_SYMBOL_CONFIG_PATH: &str = "config/symbols_config.json";
 
 fn read_element_string(reader: &mut MfcReader, variant: Variant, max_len: usize) -> Result<String> {
     let _ = max_len;
@@ -172,28 +184,42 @@ fn read_element_fields(
 
 /// 读取 POU 头部，返回 POU 名称
 fn read_header(reader: &mut MfcReader, variant: Variant, serialize_version: u32) -> Result<String> {
-    let start = reader.position();
+    let start = reader.checkpoint();
     if variant == Variant::Safety {
         if let Ok(name) = read_header_variant_b(reader, serialize_version) {
             return Ok(name);
         }
     }
-    reader.seek_to(start)?;
+    reader.restore(start)?;
     read_header_legacy(reader, variant)
 }
 
 fn read_header_variant_b(reader: &mut MfcReader, serialize_version: u32) -> Result<String> {
-    let start = reader.position();
+    let start = reader.checkpoint();
     if serialize_version >= 0x0F {
         return read_header_variant_b_inner(reader, serialize_version, true);
     }
     if let Ok(name) = read_header_variant_b_inner(reader, serialize_version, false) {
         return Ok(name);
     }
-    reader.seek_to(start)?;
+    reader.restore(start)?;
     read_header_variant_b_inner(reader, serialize_version, true)
 }
 
+/// 读取头部中的名称串；无损模式下记录为 Name 段
+/// expected 非空时仅在内容一致时记录（第二个名称串）
+fn read_header_name(reader: &mut MfcReader, align4: bool, expected: Option<&str>) -> Result<String> {
+    let start = reader.position();
+    let name = reader.read_mfc_string()?;
+    if align4 {
+        reader.align_to_4bytes()?;
+    }
+    if expected.is_none_or(|expected| expected == name) {
+        reader.record(start, SpanKind::Name { align4 });
+    }
+    Ok(name)
+}
+
 fn read_header_variant_b_inner(
     reader: &mut MfcReader,
     serialize_version: u32,
@@ -202,8 +228,8 @@ fn read_header_variant_b_inner(
     if with_seed {
         let _ = reader.read_u32()?;
     }
-    let name = reader.read_mfc_string()?;
-    let _ = reader.read_mfc_string()?;
+    let name = read_header_name(reader, false, None)?;
+    let _ = read_header_name(reader, false, Some(&name))?;
     let _ = reader.read_u8()?;
     let _ = reader.read_u8()?;
     let _ = reader.read_u32()?;
@@ -229,15 +255,13 @@ fn read_header_variant_b_inner(
 }
 
 fn read_header_legacy(reader: &mut MfcReader, variant: Variant) -> Result<String> {
-    let name = reader.read_mfc_string()?;
-    reader.align_to_4bytes()?;
+    let name = read_header_name(reader, true, None)?;
 
     if variant == Variant::Normal {
         let _ = reader.read_u32()?;
     }
 
-    let _ = reader.read_mfc_string()?;
-    reader.align_to_4bytes()?;
+    let _ = read_header_name(reader, true, Some(&name))?;
 
     match variant {
         Variant::Normal => {
@@ -272,10 +296,21 @@ fn read_header_legacy(reader: &mut MfcReader, variant: Variant) -> Result<String
     Ok(name)
 }
 
-fn read_network(reader: &mut MfcReader, variant: Variant) -> Result<Network> {
+/// head_start 为对象标签起点，用于记录无损区间
+fn read_network(reader: &mut MfcReader, variant: Variant, head_start: usize) -> Result<Network> {
+    let body = reader.position();
     let (id, _type_id, _name, _comment, _desc, _connections) = read_element_base(reader, variant)?;
+    let fields = reader.position();
     let label = reader.read_mfc_string()?;
     let comment = reader.read_mfc_string()?;
+    reader.record(
+        head_start,
+        SpanKind::Network {
+            id,
+            body: body - head_start,
+            fields: fields - head_start,
+        },
+    );
 
     Ok(Network {
         id,
@@ -292,17 +327,16 @@ fn read_element_base(
 ) -> Result<(i32, u8, String, String, String, Vec<i32>)> {
     let id_u32 = reader.read_u32()?;
     let id = checked_i32(id_u32, "element.id")?;
+    reader.trail.set_element_id(id);
     let type_id = reader.read_u8()?;
     let (name, comment, desc, connections) = read_element_fields(reader, variant)?;
     Ok((id, type_id, name, comment, desc, connections))
 }
 
 fn read_contact(reader: &mut MfcReader, variant: Variant) -> Result<LdElement> {
-    let (id, _type_id, name, comment, desc, connections) = read_element_base(reader, variant)?;
-    let sub_type = reader.read_u8()?;
-    if variant == Variant::Normal {
-        let _ = reader.read_mfc_string()?;
-    }
+    let (id, type_id, name, comment, desc, connections) = read_element_base(reader, variant)?;
+    let mut extras = ElementExtras { type_id: Some(type_id), ..Default::default() };
+    let sub_type = read_contact_fields(reader, variant, &mut extras)?;
     Ok(LdElement {
         id,
         type_code: ElementType::Contact,
@@ -313,19 +347,14 @@ fn read_contact(reader: &mut MfcReader, variant: Variant) -> Result<LdElement> {
         pins: Vec::new(),
         connections,
         sub_type,
+        preserved: Some(extras),
     })
 }
 
 fn read_output(reader: &mut MfcReader, variant: Variant, serialize_version: u32) -> Result<LdElement> {
-    let (id, _type_id, name, comment, desc, connections) = read_element_base(reader, variant)?;
-    let sub_type = reader.read_u8()?;
-    let _ = reader.read_u8()?; // flag2
-    if variant == Variant::Normal && serialize_version > 0 {
-        let _ = reader.read_u8()?; // flag3
-    }
-    if variant == Variant::Normal {
-        let _ = reader.read_mfc_string()?; // geo/附加字段
-    }
+    let (id, type_id, name, comment, desc, connections) = read_element_base(reader, variant)?;
+    let mut extras = ElementExtras { type_id: Some(type_id), ..Default::default() };
+    let sub_type = read_output_fields(reader, variant, serialize_version, &mut extras)?;
     Ok(LdElement {
         id,
         type_code: ElementType::Coil,
@@ -336,9 +365,37 @@ fn read_output(reader: &mut MfcReader, variant: Variant, serialize_version: u32)
         pins: Vec::new(),
         connections,
         sub_type,
+        preserved: Some(extras),
     })
 }
 
+/// 触点派生字段：sub_type + (Normal) geo 串
+fn read_contact_fields(reader: &mut MfcReader, variant: Variant, extras: &mut ElementExtras) -> Result<u8> {
+    let sub_type = reader.read_u8()?;
+    if variant == Variant::Normal {
+        extras.geometry = Some(reader.read_mfc_string_raw()?);
+    }
+    Ok(sub_type)
+}
+
+/// 线圈派生字段：sub_type + flag2 + (Normal) flag3/geo 串
+fn read_output_fields(
+    reader: &mut MfcReader,
+    variant: Variant,
+    serialize_version: u32,
+    extras: &mut ElementExtras,
+) -> Result<u8> {
+    let sub_type = reader.read_u8()?;
+    extras.coil_flag2 = Some(reader.read_u8()?);
+    if variant == Variant::Normal && serialize_version > 0 {
+        extras.coil_flag3 = Some(reader.read_u8()?);
+    }
+    if variant == Variant::Normal {
+        extras.geometry = Some(reader.read_mfc_string_raw()?); // geo/附加字段
+    }
+    Ok(sub_type)
+}
+
 fn read_pin(
     reader: &mut MfcReader,
     variant: Variant,
@@ -349,52 +406,74 @@ fn read_pin(
         Variant::Safety => {
             let name = read_element_string(reader, variant, 80)?;
             let variable = read_element_string(reader, variant, 200)?;
-            Ok(BoxPin { name, variable, direction })
+            Ok(BoxPin { name, variable, direction, preserved: None })
         }
         Variant::Normal => {
-            let _ = reader.read_u8()?; // flag0
-            let _ = reader.read_u8()?; // flag1
+            let flag0 = reader.read_u8()?;
+            let flag1 = reader.read_u8()?;
             let name = reader.read_mfc_string()?;
             let variable = reader.read_mfc_string()?;
-            if serialize_version >= 13 && direction == PinDirection::Input {
-                let _ = reader.read_u32()?; // binding_id
-            }
-            Ok(BoxPin { name, variable, direction })
+            let binding_id = if serialize_version >= 13 && direction == PinDirection::Input {
+                Some(reader.read_u32()?)
+            } else {
+                None
+            };
+            Ok(BoxPin {
+                name,
+                variable,
+                direction,
+                preserved: Some(PinExtras { flag0, flag1, binding_id }),
+            })
         }
     }
 }
 
 fn read_box(reader: &mut MfcReader, variant: Variant, serialize_version: u32) -> Result<LdElement> {
-    let (id, _type_id, name, comment, desc, connections) = read_element_base(reader, variant)?;
+    let (id, type_id, name, comment, desc, connections) = read_element_base(reader, variant)?;
+    let mut extras = ElementExtras { type_id: Some(type_id), ..Default::default() };
+    let (instance, pins) = read_box_fields(reader, variant, serialize_version, &mut extras)?;
+    Ok(LdElement {
+        id,
+        type_code: ElementType::Box,
+        name,
+        comment,
+        desc,
+        instance,
+        pins,
+        connections,
+        sub_type: 0,
+        preserved: Some(extras),
+    })
+}
+
+/// 功能块派生字段：(Normal 版本字段) + flag + 实例名 + 输入/输出引脚
+fn read_box_fields(
+    reader: &mut MfcReader,
+    variant: Variant,
+    serialize_version: u32,
+    extras: &mut ElementExtras,
+) -> Result<(String, Vec<BoxPin>)> {
     if variant == Variant::Normal && serialize_version >= 6 {
-        let _ = reader.read_u32()?;
-        let _ = reader.read_u32()?;
+        extras.box_version = Some([reader.read_u32()?, reader.read_u32()?]);
     }
-    let _ = reader.read_u8()?; // flag
+    extras.box_flag = Some(reader.read_u8()?);
     let instance = read_element_string(reader, variant, 200)?;
 
     let input_count = reader.read_u32()? as usize;
     let mut pins = Vec::new();
     for _ in 0..input_count {
+        reader.trail.enter_pin(pins.len());
         pins.push(read_pin(reader, variant, serialize_version, PinDirection::Input)?);
     }
+    reader.trail.leave_pin();
 
     let output_count = reader.read_u32()? as usize;
     for _ in 0..output_count {
+        reader.trail.enter_pin(pins.len());
         pins.push(read_pin(reader, variant, serialize_version, PinDirection::Output)?);
     }
-
-    Ok(LdElement {
-        id,
-        type_code: ElementType::Box,
-        name,
-        comment,
-        desc,
-        instance,
-        pins,
-        connections,
-        sub_type: 0,
-    })
+    reader.trail.leave_pin();
+    Ok((instance, pins))
 }
 
 fn read_element_dynamic(
@@ -404,80 +483,36 @@ fn read_element_dynamic(
 ) -> Result<LdElement> {
     let (id, type_id, name, comment, desc, connections) = read_element_base(reader, variant)?;
     let type_code = element_type_from_id(variant, type_id)?;
-    match type_code {
+    let mut extras = ElementExtras { type_id: Some(type_id), ..Default::default() };
+    let (instance, pins, sub_type) = match type_code {
         ElementType::Box => {
-            if variant == Variant::Normal && serialize_version >= 6 {
-                let _ = reader.read_u32()?;
-                let _ = reader.read_u32()?;
-            }
-            let _ = reader.read_u8()?; // flag
-            let instance = read_element_string(reader, variant, 200)?;
-
-            let input_count = reader.read_u32()? as usize;
-            let mut pins = Vec::new();
-            for _ in 0..input_count {
-                pins.push(read_pin(reader, variant, serialize_version, PinDirection::Input)?);
-            }
-
-            let output_count = reader.read_u32()? as usize;
-            for _ in 0..output_count {
-                pins.push(read_pin(reader, variant, serialize_version, PinDirection::Output)?);
-            }
-
-            Ok(LdElement {
-                id,
-                type_code,
-                name,
-                comment,
-                desc,
-                instance,
-                pins,
-                connections,
-                sub_type: 0,
-            })
+            let (instance, pins) = read_box_fields(reader, variant, serialize_version, &mut extras)?;
+            (instance, pins, 0)
         }
         ElementType::Contact => {
-            let sub_type = reader.read_u8()?;
-            if variant == Variant::Normal {
-                let _ = reader.read_mfc_string()?;
-            }
-            Ok(LdElement {
-                id,
-                type_code,
-                name,
-                comment,
-                desc,
-                instance: String::new(),
-                pins: Vec::new(),
-                connections,
-                sub_type,
-            })
+            let sub_type = read_contact_fields(reader, variant, &mut extras)?;
+            (String::new(), Vec::new(), sub_type)
         }
         ElementType::Coil => {
-            let sub_type = reader.read_u8()?;
-            let _ = reader.read_u8()?; // flag2
-            if variant == Variant::Normal && serialize_version > 0 {
-                let _ = reader.read_u8()?; // flag3
-            }
-            if variant == Variant::Normal {
-                let _ = reader.read_mfc_string()?;
-            }
-            Ok(LdElement {
-                id,
-                type_code,
-                name,
-                comment,
-                desc,
-                instance: String::new(),
-                pins: Vec::new(),
-                connections,
-                sub_type,
-            })
+            let sub_type = read_output_fields(reader, variant, serialize_version, &mut extras)?;
+            (String::new(), Vec::new(), sub_type)
         }
         _ => {
             bail!("动态元素类型不支持: {:?}", type_code);
         }
-    }
+    };
+    Ok(LdElement {
+        id,
+        type_code,
+        name,
+        comment,
+        desc,
+        instance,
+        pins,
+        connections,
+        sub_type,
+        preserved: Some(extras),
+    })
 }
 
 fn read_networks(reader: &mut MfcReader, variant: Variant, serialize_version: u32) -> Result<Vec<Network>> {
@@ -507,88 +542,22 @@ fn read_networks(reader: &mut MfcReader, variant: Variant, serialize_version: u3
         }
 
         let pos = reader.position();
-        let object_kind = read_object_kind(reader, &mut class_table)?;
-        if let Some(rem) = remaining.as_mut() {
-            *rem = rem.saturating_sub(1);
-        }
-
-        match object_kind {
-            ObjectKind::Null => {}
-            ObjectKind::Reference(_) => {}
-            ObjectKind::New(class_name) => match class_name.as_str() {
-                "CLDNetwork" => {
-                    if let Some(net) = current.take() {
-                        networks.push(net);
-                    }
-                    let net = read_network(reader, variant)?;
-                    current = Some(net);
-                }
-                "CLDContact" => {
-                    let elem = read_contact(reader, variant)?;
-                    if let Some(net) = current.as_mut() {
-                        net.elements.push(elem);
-                    } else {
-                        bail!("元素出现在网络之前: {}", class_name);
-                    }
-                }
-                "CLDOutput" => {
-                    let elem = read_output(reader, variant, serialize_version)?;
-                    if let Some(net) = current.as_mut() {
-                        net.elements.push(elem);
-                    } else {
-                        bail!("元素出现在网络之前: {}", class_name);
-                    }
-                }
-                "CLDBox" => {
-                    let elem = read_box(reader, variant, serialize_version)?;
-                    if let Some(net) = current.as_mut() {
-                        net.elements.push(elem);
-                    } else {
-                        bail!("元素出现在网络之前: {}", class_name);
-                    }
-                }
-                "CLDAssign" => {
-                    let _ = read_element_base(reader, variant)?;
-                }
-                "CLDElement" | "CLDOr" | "CLDJump" | "CLDReturn" | "CLDBranches" => {
-                    let _ = read_element_base(reader, variant)?;
-                }
-                "CLDBracket" => {
-                    let _ = read_element_base(reader, variant)?;
-                    skip_network_tail(reader)?;
-                }
-                _ => {
-                    skip_network_tail(reader)?;
-                }
-            },
-            ObjectKind::UnknownClass(_) => {
-                let mut parsed_any = false;
-                loop {
-                    if let Some(type_id) = peek_element_type_id(reader) {
-                        if !is_element_type_id(variant, type_id) {
-                            break;
-                        }
-                        let elem = read_element_dynamic(reader, variant, serialize_version)?;
-                        parsed_any = true;
-                        if let Some(net) = current.as_mut() {
-                            net.elements.push(elem);
-                        } else {
-                            bail!("元素出现在网络之前: UnknownClass");
-                        }
-                    } else {
-                        break;
-                    }
-                    if looks_like_object_tag(reader) || looks_like_safety_var_table(reader) {
-                        break;
-                    }
-                    if !looks_like_element_object(reader, variant) {
-                        break;
-                    }
-                }
-                if !parsed_any {
-                    skip_network_tail(reader)?;
-                }
+        reader.trail.enter_object(None);
+        let result = read_object_kind(reader, &mut class_table).and_then(|object_kind| {
+            reader.trail.enter_object(object_kind.class_label());
+            if let Some(rem) = remaining.as_mut() {
+                *rem = rem.saturating_sub(1);
             }
+            read_normal_object(reader, object_kind, pos, variant, serialize_version, &mut current, &mut networks)
+        });
+        if let Err(err) = result {
+            let limit = stop_at.unwrap_or_else(|| data_end(reader.inner.get_ref()));
+            skip_to_resync(reader, pos, limit, err, |data, from| {
+                find_next_object(data, from, &class_table, variant)
+            })?;
+            // 跳过的对象数未知，之后以变量表起点/数据末尾为界
+            remaining = None;
+            continue;
         }
 
         if reader.position() == pos {
@@ -607,6 +576,110 @@ fn read_networks(reader: &mut MfcReader, variant: Variant, serialize_version: u3
     Ok(networks)
 }
 
+/// 读取对象标签之后的 Normal 对象主体；pos 为对象标签起点
+fn read_normal_object(
+    reader: &mut MfcReader,
+    object_kind: ObjectKind,
+    pos: usize,
+    variant: Variant,
+    serialize_version: u32,
+    current: &mut Option<Network>,
+    networks: &mut Vec<Network>,
+) -> Result<()> {
+    match object_kind {
+        ObjectKind::Null => {}
+        ObjectKind::Reference(_) => {}
+        ObjectKind::New(class_name) => match class_name.as_str() {
+            "CLDNetwork" => {
+                if let Some(net) = current.take() {
+                    networks.push(net);
+                }
+                reader.trail.enter_network();
+                let net = read_network(reader, variant, pos)?;
+                *current = Some(net);
+            }
+            "CLDContact" => {
+                let body = reader.position();
+                let elem = read_contact(reader, variant)?;
+                reader.record(pos, SpanKind::Element { id: elem.id, body: body - pos });
+                if let Some(net) = current.as_mut() {
+                    net.elements.push(elem);
+                } else {
+                    bail!("元素出现在网络之前: {}", class_name);
+                }
+            }
+            "CLDOutput" => {
+                let body = reader.position();
+                let elem = read_output(reader, variant, serialize_version)?;
+                reader.record(pos, SpanKind::Element { id: elem.id, body: body - pos });
+                if let Some(net) = current.as_mut() {
+                    net.elements.push(elem);
+                } else {
+                    bail!("元素出现在网络之前: {}", class_name);
+                }
+            }
+            "CLDBox" => {
+                let body = reader.position();
+                let elem = read_box(reader, variant, serialize_version)?;
+                reader.record(pos, SpanKind::Element { id: elem.id, body: body - pos });
+                if let Some(net) = current.as_mut() {
+                    net.elements.push(elem);
+                } else {
+                    bail!("元素出现在网络之前: {}", class_name);
+                }
+            }
+            "CLDAssign" => {
+                let _ = read_element_base(reader, variant)?;
+            }
+            "CLDElement" | "CLDOr" | "CLDJump" | "CLDReturn" | "CLDBranches" => {
+                let _ = read_element_base(reader, variant)?;
+            }
+            "CLDBracket" => {
+                let _ = read_element_base(reader, variant)?;
+                skip_network_tail(reader)?;
+            }
+            _ => {
+                skip_network_tail(reader)?;
+            }
+        },
+        ObjectKind::UnknownClass(class_id) => {
+            let mut parsed_any = false;
+            // 首个元件的区间包含对象标签，其后的元件从自身起点开始
+            let mut head = pos;
+            loop {
+                if let Some(type_id) = peek_element_type_id(reader) {
+                    if !is_element_type_id(variant, type_id) {
+                        break;
+                    }
+                    let body = reader.position();
+                    reader.trail.enter_object(Some(format!("#{}", class_id)));
+                    let elem = read_element_dynamic(reader, variant, serialize_version)?;
+                    reader.record(head, SpanKind::Element { id: elem.id, body: body - head });
+                    head = reader.position();
+                    parsed_any = true;
+                    if let Some(net) = current.as_mut() {
+                        net.elements.push(elem);
+                    } else {
+                        bail!("元素出现在网络之前: UnknownClass");
+                    }
+                } else {
+                    break;
+                }
+                if looks_like_object_tag(reader) || looks_like_safety_var_table(reader) {
+                    break;
+                }
+                if !looks_like_element_object(reader, variant) {
+                    break;
+                }
+            }
+            if !parsed_any {
+                skip_network_tail(reader)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 fn skip_network_tail(reader: &mut MfcReader) -> Result<()> {
     while reader.remaining_len() > 0 {
         if looks_like_object_tag(reader) || looks_like_safety_var_table(reader) {
@@ -795,104 +868,188 @@ fn checked_i32(value: u32, field: &str) -> Result<i32> {
     Ok(value as i32)
 }
 
+/// 解析选项：版本、序列化版本、无损模式与功能块库
+/// 功能块库由宿主注入（可为内置库叠加用户库），为空时使用内置默认库
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub variant: Variant,
+    pub serialize_version: u32,
+    /// 保留原始字节布局，供 PouSerializer 无损回写
+    pub lossless: bool,
+    pub symbols: Arc<SymbolConfig>,
+}
+
+impl ParseOptions {
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            variant,
+            serialize_version: DEFAULT_SERIALIZE_VERSION,
+            lossless: false,
+            symbols: Arc::new(SymbolConfig::default()),
+        }
+    }
+
+    pub fn with_serialize_version(mut self, serialize_version: u32) -> Self {
+        self.serialize_version = serialize_version;
+        self
+    }
+
+    pub fn with_lossless(mut self, lossless: bool) -> Self {
+        self.lossless = lossless;
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: impl Into<Arc<SymbolConfig>>) -> Self {
+        self.symbols = symbols.into();
+        self
+    }
+
+    fn symbols(&self) -> &SymbolConfig {
+        if self.symbols.is_empty() { SymbolConfig::builtin_ref() } else { &self.symbols }
+    }
+}
+
+impl From<&HollysysConfig> for ParseOptions {
+    fn from(config: &HollysysConfig) -> Self {
+        Self {
+            variant: config.variant,
+            serialize_version: config.serialize_version,
+            lossless: config.lossless,
+            symbols: config.symbols.clone(),
+        }
+    }
+}
+
 /// 解析入口：读取 Hollysys 剪贴板数据并输出通用 POU
 pub fn read_pou(data: &[u8], variant: Variant) -> Result<UniversalPou> {
-    read_pou_with_config(data, variant, DEFAULT_SERIALIZE_VERSION)
+    read_pou_with_options(data, &ParseOptions::new(variant))
 }
 
 /// 解析入口（带序列化版本配置）
 pub fn read_pou_with_config(data: &[u8], variant: Variant, serialize_version: u32) -> Result<UniversalPou> {
-    let mut reader = MfcReader::new(data);
-    let name = read_header(&mut reader, variant, serialize_version)?;
+    read_pou_with_options(data, &ParseOptions::new(variant).with_serialize_version(serialize_version))
+}
+
+/// 无损解析入口：AST 携带原始字节布局与未知字段，
+/// 交给 PouSerializer 时未修改的部分按原字节回写
+pub fn read_pou_lossless(data: &[u8], variant: Variant, serialize_version: u32) -> Result<UniversalPou> {
+    let options = ParseOptions::new(variant).with_serialize_version(serialize_version).with_lossless(true);
+    read_pou_with_options(data, &options)
+}
+
+/// 解析入口（完整选项）
+pub fn read_pou_with_options(data: &[u8], options: &ParseOptions) -> Result<UniversalPou> {
+    read_pou_until(data, options).map(|(pou, _)| pou)
+}
+
+/// 解析并返回 POU 内容的结束偏移（其后应为容器零填充）
+pub(crate) fn read_pou_until(data: &[u8], options: &ParseOptions) -> Result<(UniversalPou, usize)> {
+    let mut reader = if options.lossless { MfcReader::with_capture(data) } else { MfcReader::new(data) };
+    let mut pou = read_pou_body(&mut reader, options.variant, options.serialize_version, options.symbols())
+        .map_err(|err| ParseError::at(&reader, err))?;
+    if options.lossless {
+        let spans = reader.take_spans();
+        pou.preserved = Some(build_layout(data, spans, &pou, options.variant, options.serialize_version));
+    } else {
+        strip_preserved(&mut pou);
+    }
+    Ok((pou, reader.position()))
+}
+
+/// 尽力解析入口：网络/元件/变量解析失败时跳到下一个可识别的对象继续，
+/// 返回能读出的部分以及被跳过的字节区间
+pub fn read_pou_recovering(data: &[u8], variant: Variant, serialize_version: u32) -> Result<PartialDecode> {
+    read_pou_recovering_with_options(data, &ParseOptions::new(variant).with_serialize_version(serialize_version))
+}
+
+/// 尽力解析入口（完整选项）；结果不携带无损布局，options.lossless 被忽略
+pub fn read_pou_recovering_with_options(data: &[u8], options: &ParseOptions) -> Result<PartialDecode> {
+    let mut reader = MfcReader::with_recovery(data);
+    let mut pou = read_pou_body(&mut reader, options.variant, options.serialize_version, options.symbols())
+        .map_err(|err| ParseError::at(&reader, err))?;
+    strip_preserved(&mut pou);
+    Ok(PartialDecode { pou, skipped: reader.take_skipped() })
+}
+
+/// 各段在恢复模式下失败时返回空值（见 recover_section），否则直接返回错误
+fn read_pou_body(
+    reader: &mut MfcReader,
+    variant: Variant,
+    serialize_version: u32,
+    symbols: &SymbolConfig,
+) -> Result<UniversalPou> {
+    reader.trail.enter_section(ParseSection::Header);
+    let name = recover_section(reader, String::new(), |reader| read_header(reader, variant, serialize_version))?;
     let header_strings = if variant == Variant::Safety {
-        read_string_array(&mut reader)?
+        reader.trail.enter_section(ParseSection::HeaderStrings);
+        recover_section(reader, Vec::new(), read_string_array)?
     } else {
         Vec::new()
     };
     let (variables, networks) = if variant == Variant::Safety {
-        let nets = read_networks(&mut reader, variant, serialize_version)
-            .with_context(|| "读取 Safety networks 失败")?;
-        let vars = read_variables(&mut reader, variant, serialize_version)
-            .with_context(|| "读取 Safety variables 失败")?;
+        reader.trail.enter_section(ParseSection::Networks);
+        let nets = recover_section(reader, Vec::new(), |reader| {
+            read_networks(reader, variant, serialize_version).with_context(|| "读取 Safety networks 失败")
+        })?;
+        reader.trail.enter_section(ParseSection::Variables);
+        let vars = recover_section(reader, Vec::new(), |reader| {
+            read_variables(reader, variant, serialize_version).with_context(|| "读取 Safety variables 失败")
+        })?;
         (vars, nets)
     } else {
-        let nets = read_networks(&mut reader, variant, serialize_version)
-            .with_context(|| "读取 Normal networks 失败")?;
-        let vars = read_variables(&mut reader, variant, serialize_version)
-            .with_context(|| "读取 Normal variables 失败")?;
+        reader.trail.enter_section(ParseSection::Networks);
+        let nets = recover_section(reader, Vec::new(), |reader| {
+            read_networks(reader, variant, serialize_version).with_context(|| "读取 Normal networks 失败")
+        })?;
+        reader.trail.enter_section(ParseSection::Variables);
+        let vars = recover_section(reader, Vec::new(), |reader| {
+            read_variables(reader, variant, serialize_version).with_context(|| "读取 Normal variables 失败")
+        })?;
         (vars, nets)
     };
-    let symbol_lookup = load_symbol_lookup();
-    let variable_nodes = organize_variables(variables, &header_strings, &symbol_lookup);
+    let variable_nodes = organize_variables(variables, &header_strings, symbols);
     Ok(UniversalPou {
         name,
         header_strings,
         variables: variable_nodes,
         networks,
+        preserved: None,
     })
 }
 
-fn load_symbol_lookup() -> HashMap<String, HashSet<String>> {
-    let mut candidates = Vec::new();
-    candidates.push(Path::new(DEFAULT_SYMBOL_CONFIG_PATH).to_path_buf());
-    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
-    candidates.push(manifest_dir.join("..").join(DEFAULT_SYMBOL_CONFIG_PATH));
-
-    for path in candidates {
-        match SymbolConfig::load_from_file(&path) {
-            Ok(config) => {
-                let map = config.to_lookup_map();
-                if !map.is_empty() {
-                    return map;
-                }
-            }
-            Err(err) => {
-                warn!("符号表配置加载失败: {} ({})", path.display(), err);
-            }
-        }
-    }
-
-    HashMap::new()
-}
-
 fn organize_variables(
     flat_vars: Vec<Variable>,
     header_strings: &[String],
-    symbol_lookup: &HashMap<String, HashSet<String>>,
+    symbols: &SymbolConfig,
 ) -> Vec<VariableNode> {
     let mut root_nodes = Vec::new();
     let mut processed_indices = HashSet::new();
 
-    let mut matched_type = None;
-    for header in header_strings {
-        if symbol_lookup.contains_key(header) {
-            matched_type = Some(header.clone());
-            break;
-        }
-    }
+    let matched_type = header_strings
+        .iter()
+        .find_map(|header| symbols.get(header).map(|fb| (header.clone(), fb)));
 
-    if let Some(type_name) = matched_type {
-        if let Some(members) = symbol_lookup.get(&type_name) {
-            let mut group_children = Vec::new();
-            for (idx, var) in flat_vars.iter().enumerate() {
-                if var.name.contains('.') {
-                    continue;
-                }
-                let base = var.name.rsplit('.').next().unwrap_or(var.name.as_str());
-                if members.contains(base) {
-                    group_children.push(VariableNode::Leaf(var.clone()));
-                    processed_indices.insert(idx);
-                }
+    if let Some((type_name, fb)) = matched_type {
+        let members: HashSet<&str> = fb.member_names().collect();
+        let mut group_children = Vec::new();
+        for (idx, var) in flat_vars.iter().enumerate() {
+            if var.name.contains('.') {
+                continue;
             }
-
-            if !group_children.is_empty() {
-                root_nodes.push(VariableNode::Group {
-                    name: type_name.clone(),
-                    type_name: Some(type_name),
-                    children: group_children,
-                });
+            let base = var.name.rsplit('.').next().unwrap_or(var.name.as_str());
+            if members.contains(base) {
+                group_children.push(VariableNode::Leaf(var.clone()));
+                processed_indices.insert(idx);
             }
         }
+
+        if !group_children.is_empty() {
+            root_nodes.push(VariableNode::Group {
+                name: type_name.clone(),
+                type_name: Some(type_name),
+                children: group_children,
+            });
+        }
     }
 
     let mut dot_groups: HashMap<String, Vec<VariableNode>> = HashMap::new();
@@ -942,6 +1099,7 @@ fn read_string_array(reader: &mut MfcReader) -> Result<Vec<String>> {
     if reader.position() % 2 != 0 {
         let _ = reader.read_u8()?;
     }
+    let body = reader.position();
     let count = reader.read_u32()? as usize;
     if count > 5000 {
         reader.seek_to(start)?;
@@ -949,9 +1107,13 @@ fn read_string_array(reader: &mut MfcReader) -> Result<Vec<String>> {
             let _ = reader.read_u8()?;
         }
         let count = reader.read_u16()? as usize;
-        return read_string_array_items(reader, count);
+        let items = read_string_array_items(reader, count)?;
+        reader.record(body, SpanKind::HeaderStrings { count_width: 2 });
+        return Ok(items);
     }
-    read_string_array_items(reader, count)
+    let items = read_string_array_items(reader, count)?;
+    reader.record(body, SpanKind::HeaderStrings { count_width: 4 });
+    Ok(items)
 }
 
 fn read_string_array_items(reader: &mut MfcReader, count: usize) -> Result<Vec<String>> {
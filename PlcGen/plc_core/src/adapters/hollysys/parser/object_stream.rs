@@ -33,6 +33,17 @@ pub(crate) enum ObjectKind {
     UnknownClass(u32),
 }
 
+impl ObjectKind {
+    /// 诊断用类名：未知类记为 `#<class_id>`
+    pub(crate) fn class_label(&self) -> Option<String> {
+        match self {
+            ObjectKind::New(name) => Some(name.clone()),
+            ObjectKind::UnknownClass(id) => Some(format!("#{}", id)),
+            ObjectKind::Null | ObjectKind::Reference(_) => None,
+        }
+    }
+}
+
 pub(crate) fn read_object_kind(reader: &mut MfcReader, table: &mut ClassTable) -> Result<ObjectKind> {
     let tag = reader.read_u16()?;
     if tag == 0x0000 {
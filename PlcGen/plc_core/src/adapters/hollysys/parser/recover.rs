@@ -0,0 +1,145 @@
+/*
+恢复模式：尽力解码损坏或新版本的剪贴板数据
+- 网络/元件对象解析失败时，向后查找下一个类签名（FFFF + CLDxxx）或已知类引用，从该处继续
+- 变量解析失败时，向后查找下一个变量标记（0x15/0x18 + 名称串）继续
+- 整段（头部/网络列表/变量表）无法定位时，该段返回空值
+每次跳过都记录字节区间与原因，调用方据此判断结果的完整程度
+*/
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::ast::UniversalPou;
+
+use super::error::ParseError;
+use super::mfc::{MfcReader, scan_mfc_string_any, scan_mfc_string_ascii};
+use super::object_stream::ClassTable;
+use super::safety::find_class_sig_ahead;
+use super::{Variant, looks_like_element_object, peek_element_type_id};
+
+/// 恢复模式下跳过的字节区间 [start, end)
+/// start == end 表示该段未能定位（如找不到变量表起点）
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedRange {
+    pub start: usize,
+    pub end: usize,
+    /// 跳过原因（首个失败处的诊断信息）
+    pub reason: ParseError,
+}
+
+/// 尽力解码结果：能读出的网络与变量 + 被跳过的区间
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialDecode {
+    pub pou: UniversalPou,
+    pub skipped: Vec<SkippedRange>,
+}
+
+impl PartialDecode {
+    /// 是否完整解码（没有跳过任何字节）
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
+/// 对象级恢复：记录 [start, 恢复点) 并定位到恢复点
+/// find 从给定偏移向后查找恢复点；找不到（或超出 limit）时跳到 limit
+/// 非恢复模式下原样返回错误
+pub(crate) fn skip_to_resync(
+    reader: &mut MfcReader,
+    start: usize,
+    limit: usize,
+    err: anyhow::Error,
+    find: impl FnOnce(&[u8], usize) -> Option<usize>,
+) -> Result<()> {
+    if !reader.is_recovering() {
+        return Err(err);
+    }
+    let reason = ParseError::at(reader, err);
+    let data = reader.inner.get_ref();
+    // 至少前进一个字节，避免在同一位置反复失败
+    let limit = limit.clamp(start + 1, data.len().max(start + 1));
+    let resume = find(data, start + 1).filter(|offset| *offset < limit).unwrap_or(limit);
+    reader.skip(SkippedRange { start, end: resume, reason });
+    reader.seek_to(resume)
+}
+
+/// 段级恢复：读取失败时回到段起点，记录失败前读过的区间并返回 fallback
+pub(crate) fn recover_section<T>(
+    reader: &mut MfcReader,
+    fallback: T,
+    read: impl FnOnce(&mut MfcReader) -> Result<T>,
+) -> Result<T> {
+    let checkpoint = reader.checkpoint();
+    let start = reader.position();
+    match read(reader) {
+        Ok(value) => Ok(value),
+        Err(err) if reader.is_recovering() => {
+            let reason = ParseError::at(reader, err);
+            reader.restore(checkpoint)?;
+            reader.skip(SkippedRange { start, end: reason.offset.max(start), reason });
+            Ok(fallback)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// 查找下一个对象起点：类签名或指向已知 CLD 类的引用标签
+pub(crate) fn find_next_object(
+    data: &[u8],
+    from: usize,
+    table: &ClassTable,
+    variant: Variant,
+) -> Option<usize> {
+    let mut probe = MfcReader::new(data);
+    probe.seek_to(from.min(data.len())).ok()?;
+    let signature = find_class_sig_ahead(&probe, data.len()).map(|offset| from + offset);
+    let class_ref = (from..signature.unwrap_or(data.len()))
+        .find(|offset| looks_like_class_reference(&data[*offset..], table, variant));
+    class_ref.or(signature)
+}
+
+/// 类引用标签（0x8000 | class_id）+ 与类名相符的对象主体
+fn looks_like_class_reference(buf: &[u8], table: &ClassTable, variant: Variant) -> bool {
+    if buf.len() < 2 {
+        return false;
+    }
+    let tag = u16::from_le_bytes([buf[0], buf[1]]);
+    if tag & 0x8000 == 0 || tag == 0xFFFF {
+        return false;
+    }
+    let Ok(class_name) = table.get((tag & 0x7FFF) as u32) else {
+        return false;
+    };
+    let body = MfcReader::new(&buf[2..]);
+    match class_name.as_str() {
+        "CLDNetwork" => peek_element_type_id(&body) == Some(network_type_id(variant)),
+        name if name.starts_with("CLD") => looks_like_element_object(&body, variant),
+        _ => false,
+    }
+}
+
+fn network_type_id(variant: Variant) -> u8 {
+    match variant {
+        Variant::Normal => 0x0A,
+        Variant::Safety => 0x09,
+    }
+}
+
+/// 查找下一个 Normal 变量：类型标记 0x15/0x18 + 名称/名称2/注释/类型 四个字符串（名称非空，类型为 ASCII）
+pub(crate) fn find_next_normal_variable(data: &[u8], from: usize) -> Option<usize> {
+    (from..data.len()).find(|offset| {
+        if !matches!(data[*offset], 0x15 | 0x18) {
+            return false;
+        }
+        let mut idx = offset + 1;
+        scan_mfc_string_any(data, &mut idx, 80).unwrap_or(false)
+            && idx > offset + 2
+            && scan_mfc_string_any(data, &mut idx, 80).unwrap_or(false)
+            && scan_mfc_string_any(data, &mut idx, 200).unwrap_or(false)
+            && scan_mfc_string_ascii(data, &mut idx, 80).unwrap_or(false)
+    })
+}
+
+/// 数据末尾零填充之前的位置（恢复时的默认终点）
+pub(crate) fn data_end(data: &[u8]) -> usize {
+    data.iter().rposition(|b| *b != 0).map_or(0, |pos| pos + 1)
+}
@@ -4,13 +4,15 @@ use anyhow::{Result, bail};
 
 use super::mfc::MfcReader;
 use super::object_stream::{ClassTable, ObjectKind, prefill_class_table, read_object_kind};
+use super::recover::{data_end, find_next_object, skip_to_resync};
 use super::variables::{find_safety_var_table_offset, looks_like_safety_var_table, looks_like_safety_var_table_ahead, SAFETY_VAR_MAX};
 use super::{
     checked_i32, element_type_from_id, looks_like_object_tag, read_element_base,
     read_element_fields, read_element_string, read_pin, ElementType, LdElement, Network,
     PinDirection, Variant,
 };
-use crate::ast::SafetyTopologyToken;
+use super::super::lossless::SpanKind;
+use crate::ast::{ElementExtras, SafetyTopologyToken};
 
 #[derive(Debug, Clone)]
 struct SafetyNode {
@@ -48,6 +50,7 @@ fn read_safety_base(
 ) -> Result<(i32, u8, String, Vec<i32>)> {
     let id_u32 = reader.read_u32()?;
     let id = checked_i32(id_u32, "safety.element.id")?;
+    reader.trail.set_element_id(id);
     let type_id = reader.read_u8()?;
     if let Some(expect) = expected_type {
         if expect != type_id {
@@ -75,7 +78,7 @@ fn read_safety_base(
 fn read_safety_pin(reader: &mut MfcReader, direction: PinDirection) -> Result<super::BoxPin> {
     let name = read_safety_string(reader, 80)?;
     let variable = read_safety_string(reader, 200)?;
-    Ok(super::BoxPin { name, variable, direction })
+    Ok(super::BoxPin { name, variable, direction, preserved: None })
 }
 
 fn read_safety_node(
@@ -137,6 +140,7 @@ fn read_safety_node(
             pins,
             connections,
             sub_type,
+            preserved: None,
         },
         children,
         label,
@@ -255,10 +259,20 @@ fn safety_type_from_id(type_id: u8) -> Result<SafetyTypeId> {
     }
 }
 
-fn read_safety_object_body(reader: &mut MfcReader, serialize_version: u32) -> Result<SafetyParsedObject> {
+/// head_start 为对象标签起点：网络与元件对象在无损模式下记录区间
+fn read_safety_object_body(
+    reader: &mut MfcReader,
+    serialize_version: u32,
+    head_start: usize,
+) -> Result<SafetyParsedObject> {
+    let body = reader.position();
     let (id, type_id, name, _comment, _desc, connections) =
         read_element_base(reader, Variant::Safety)?;
     let kind = safety_type_from_id(type_id)?;
+    if kind == SafetyTypeId::Network {
+        reader.trail.enter_network();
+    }
+    let mut extras = ElementExtras { type_id: Some(type_id), ..Default::default() };
 
     let mut element = None;
     let mut label = None;
@@ -266,11 +280,20 @@ fn read_safety_object_body(reader: &mut MfcReader, serialize_version: u32) -> Re
 
     match kind {
         SafetyTypeId::Network => {
+            let fields = reader.position();
             label = Some(reader.read_mfc_string()?);
             comment = Some(reader.read_mfc_string()?);
+            reader.record(
+                head_start,
+                SpanKind::Network {
+                    id,
+                    body: body - head_start,
+                    fields: fields - head_start,
+                },
+            );
         }
         SafetyTypeId::Box => {
-            let _ = reader.read_u8()?; // box_flag
+            extras.box_flag = Some(reader.read_u8()?);
             let instance = read_element_string(reader, Variant::Safety, 200)?;
             let input_count = reader.read_u32()? as usize;
             if input_count > SAFETY_VAR_MAX {
@@ -278,15 +301,19 @@ fn read_safety_object_body(reader: &mut MfcReader, serialize_version: u32) -> Re
             }
             let mut pins = Vec::new();
             for _ in 0..input_count {
+                reader.trail.enter_pin(pins.len());
                 pins.push(read_pin(reader, Variant::Safety, serialize_version, PinDirection::Input)?);
             }
+            reader.trail.leave_pin();
             let output_count = reader.read_u32()? as usize;
             if output_count > SAFETY_VAR_MAX {
                 bail!("Safety Box 输出数量异常: {}", output_count);
             }
             for _ in 0..output_count {
+                reader.trail.enter_pin(pins.len());
                 pins.push(read_pin(reader, Variant::Safety, serialize_version, PinDirection::Output)?);
             }
+            reader.trail.leave_pin();
             element = Some(LdElement {
                 id,
                 type_code: ElementType::Box,
@@ -297,6 +324,7 @@ fn read_safety_object_body(reader: &mut MfcReader, serialize_version: u32) -> Re
                 pins,
                 connections: connections.clone(),
                 sub_type: 0,
+                preserved: Some(extras),
             });
         }
         SafetyTypeId::Contact => {
@@ -311,11 +339,12 @@ fn read_safety_object_body(reader: &mut MfcReader, serialize_version: u32) -> Re
                 pins: Vec::new(),
                 connections: connections.clone(),
                 sub_type,
+                preserved: Some(extras),
             });
         }
         SafetyTypeId::Output => {
             let sub_type = reader.read_u8()?;
-            let _ = reader.read_u8()?;
+            extras.coil_flag2 = Some(reader.read_u8()?);
             element = Some(LdElement {
                 id,
                 type_code: ElementType::Coil,
@@ -326,6 +355,7 @@ fn read_safety_object_body(reader: &mut MfcReader, serialize_version: u32) -> Re
                 pins: Vec::new(),
                 connections: connections.clone(),
                 sub_type,
+                preserved: Some(extras),
             });
         }
         SafetyTypeId::Assign => {
@@ -339,6 +369,7 @@ fn read_safety_object_body(reader: &mut MfcReader, serialize_version: u32) -> Re
                 pins: Vec::new(),
                 connections: connections.clone(),
                 sub_type: 0,
+                preserved: Some(extras),
             });
         }
         SafetyTypeId::Element
@@ -347,6 +378,9 @@ fn read_safety_object_body(reader: &mut MfcReader, serialize_version: u32) -> Re
         | SafetyTypeId::Return
         | SafetyTypeId::Jump => {}
     }
+    if element.is_some() {
+        reader.record(head_start, SpanKind::Element { id, body: body - head_start });
+    }
 
     Ok(SafetyParsedObject {
         id,
@@ -506,19 +540,34 @@ fn read_networks_safety_class(reader: &mut MfcReader, serialize_version: u32) ->
         }
 
         let pos = reader.position();
-        let object_kind = read_object_kind(reader, &mut class_table)?;
-        if let Some(rem) = remaining.as_mut() {
-            *rem = rem.saturating_sub(1);
-        }
-
-        match object_kind {
-            ObjectKind::Null => {}
-            ObjectKind::Reference(_) => {}
-            ObjectKind::New(_) | ObjectKind::UnknownClass(_) => {
-                let mut obj = read_safety_object_body(reader, serialize_version)?;
+        reader.trail.enter_object(None);
+        let result = read_object_kind(reader, &mut class_table).and_then(|object_kind| {
+            reader.trail.enter_object(object_kind.class_label());
+            if let Some(rem) = remaining.as_mut() {
+                *rem = rem.saturating_sub(1);
+            }
+            match object_kind {
+                ObjectKind::Null | ObjectKind::Reference(_) => Ok(None),
+                ObjectKind::New(_) | ObjectKind::UnknownClass(_) => {
+                    read_safety_object_body(reader, serialize_version, pos).map(Some)
+                }
+            }
+        });
+        match result {
+            Ok(Some(mut obj)) => {
                 obj.order = objects.len();
                 objects.push(obj);
             }
+            Ok(None) => {}
+            Err(err) => {
+                let limit = stop_at.unwrap_or_else(|| data_end(reader.inner.get_ref()));
+                skip_to_resync(reader, pos, limit, err, |data, from| {
+                    find_next_object(data, from, &class_table, Variant::Safety)
+                })?;
+                // 跳过的对象数未知，之后以变量表起点/数据末尾为界
+                remaining = None;
+                continue;
+            }
         }
 
         if reader.position() == pos {
@@ -620,7 +669,7 @@ fn read_safety_topology_raw(
     Ok((topology, inline_elements))
 }
 
-fn find_class_sig_ahead(reader: &MfcReader, window: usize) -> Option<usize> {
+pub(crate) fn find_class_sig_ahead(reader: &MfcReader, window: usize) -> Option<usize> {
     let buf = reader.remaining_slice();
     if buf.len() < 6 {
         return None;
@@ -674,6 +723,7 @@ fn read_safety_inline_element(
             pins: Vec::new(),
             connections: Vec::new(),
             sub_type: 0,
+            preserved: None,
         });
     }
 
@@ -704,6 +754,7 @@ fn read_safety_inline_element(
                 pins,
                 connections,
                 sub_type: 0,
+                preserved: None,
             })
         }
         ElementType::Contact => {
@@ -721,6 +772,7 @@ fn read_safety_inline_element(
                 pins: Vec::new(),
                 connections,
                 sub_type,
+                preserved: None,
             })
         }
         ElementType::Coil => {
@@ -742,6 +794,7 @@ fn read_safety_inline_element(
                 pins: Vec::new(),
                 connections,
                 sub_type,
+                preserved: None,
             })
         }
         ElementType::Assign => {
@@ -783,6 +836,7 @@ fn read_safety_assign(reader: &mut MfcReader) -> Result<LdElement> {
         pins: Vec::new(),
         connections,
         sub_type: 0,
+        preserved: None,
     })
 }
 
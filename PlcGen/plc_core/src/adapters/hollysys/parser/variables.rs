@@ -1,7 +1,9 @@
 use anyhow::{Context, Result, bail};
 
 use super::mfc::{MfcReader, scan_mfc_string_ascii};
+use super::recover::{data_end, find_next_normal_variable, skip_to_resync};
 use super::Variant;
+use super::super::lossless::SpanKind;
 use crate::ast::Variable;
 
 pub(crate) const SAFETY_VAR_MAX: usize = 2000;
@@ -18,17 +20,22 @@ pub(crate) fn read_variables(
 }
 
 fn read_variables_normal(reader: &mut MfcReader) -> Result<Vec<Variable>> {
-    let start = reader.position();
-    if let Ok(vars) = try_read_variables_normal(reader) {
+    let start = reader.checkpoint();
+    if let Ok((vars, body_start)) = try_read_variables_normal(reader) {
+        reader.record(body_start, SpanKind::Variables);
         return Ok(vars);
     }
-    reader.seek_to(start)?;
+    reader.restore(start)?;
     seek_to_normal_var_table(reader)?;
-    try_read_variables_normal(reader)
+    let (vars, body_start) = try_read_variables_normal(reader)?;
+    reader.record(body_start, SpanKind::Variables);
+    Ok(vars)
 }
 
-fn try_read_variables_normal(reader: &mut MfcReader) -> Result<Vec<Variable>> {
+/// 返回变量列表及变量主体（表头之后）的起始偏移
+fn try_read_variables_normal(reader: &mut MfcReader) -> Result<(Vec<Variable>, usize)> {
     let _ = try_read_normal_var_header(reader)?;
+    let body_start = reader.position();
     let mut vars = Vec::new();
     while reader.remaining_len() > 0 {
         skip_normal_zero_padding(reader)?;
@@ -42,9 +49,13 @@ fn try_read_variables_normal(reader: &mut MfcReader) -> Result<Vec<Variable>> {
             }
             break;
         }
+        let item_start = reader.position();
         let tag = reader.read_u8()?;
         if tag == 0x18 {
-            vars.extend(read_normal_group_variables(reader)?);
+            match read_normal_group_variables(reader) {
+                Ok(group) => vars.extend(group),
+                Err(err) => skip_normal_variable(reader, item_start, err)?,
+            }
             continue;
         }
         let var = read_variable_normal(reader);
@@ -57,34 +68,45 @@ fn try_read_variables_normal(reader: &mut MfcReader) -> Result<Vec<Variable>> {
                 if reader.remaining_len() == 0 || reader.remaining_all_zero() {
                     break;
                 }
-                return Err(err);
+                skip_normal_variable(reader, item_start, err)?;
             }
         }
     }
-    Ok(vars)
+    Ok((vars, body_start))
+}
+
+/// 恢复模式：跳到下一个变量标记；非恢复模式返回原错误
+fn skip_normal_variable(reader: &mut MfcReader, start: usize, err: anyhow::Error) -> Result<()> {
+    let limit = data_end(reader.inner.get_ref());
+    skip_to_resync(reader, start, limit, err, find_next_normal_variable)
 }
 
 fn read_variables_safety(reader: &mut MfcReader, serialize_version: u32) -> Result<Vec<Variable>> {
-    let start = reader.position();
+    let start = reader.checkpoint();
     let looks_like = looks_like_safety_var_table(reader);
-    if let Ok(vars) = try_read_variables_safety(reader, serialize_version) {
-        if !vars.is_empty() || looks_like {
-            return Ok(vars);
-        }
+    if let Ok((vars, body_start)) = try_read_variables_safety(reader, serialize_version)
+        && (!vars.is_empty() || looks_like)
+    {
+        reader.record(body_start, SpanKind::Variables);
+        return Ok(vars);
     }
-    reader.seek_to(start)?;
+    reader.restore(start)?;
     seek_to_safety_var_table(reader)?;
-    try_read_variables_safety(reader, serialize_version)
+    let (vars, body_start) = try_read_variables_safety(reader, serialize_version)?;
+    reader.record(body_start, SpanKind::Variables);
+    Ok(vars)
 }
 
-fn try_read_variables_safety(reader: &mut MfcReader, serialize_version: u32) -> Result<Vec<Variable>> {
+/// 返回变量列表及变量主体（数量字段）的起始偏移
+fn try_read_variables_safety(reader: &mut MfcReader, serialize_version: u32) -> Result<(Vec<Variable>, usize)> {
     skip_safety_var_header(reader)?;
+    let body_start = reader.position();
     if reader.remaining_len() < 4 {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), body_start));
     }
     let count = reader.read_u32()? as usize;
     if count == 0 {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), body_start));
     }
     if count > SAFETY_VAR_MAX {
         bail!("变量数量异常: {}", count);
@@ -93,18 +115,28 @@ fn try_read_variables_safety(reader: &mut MfcReader, serialize_version: u32) ->
     for idx in 0..count {
         skip_safety_zero_padding(reader)?;
         let offset = reader.position();
-        let type_id = reader
+        let entry = reader
             .read_u8()
-            .with_context(|| format!("safety var entry idx={} type_id read offset={}", idx, offset))?;
-        let mut entry = read_safety_db_object(reader, type_id, serialize_version, None).with_context(|| {
-            format!(
-                "safety var entry idx={} type_id=0x{:02X} offset={}",
-                idx, type_id, offset
-            )
-        })?;
-        vars.append(&mut entry);
+            .with_context(|| format!("safety var entry idx={} type_id read offset={}", idx, offset))
+            .and_then(|type_id| {
+                read_safety_db_object(reader, type_id, serialize_version, None).with_context(|| {
+                    format!(
+                        "safety var entry idx={} type_id=0x{:02X} offset={}",
+                        idx, type_id, offset
+                    )
+                })
+            });
+        match entry {
+            Ok(mut entry) => vars.append(&mut entry),
+            Err(err) => {
+                // DB 对象没有可靠的同步标记：恢复模式下保留已读变量，跳过表的剩余部分
+                let limit = data_end(reader.inner.get_ref());
+                skip_to_resync(reader, offset, limit, err, |_, _| None)?;
+                break;
+            }
+        }
     }
-    Ok(vars)
+    Ok((vars, body_start))
 }
 
 fn skip_safety_zero_padding(reader: &mut MfcReader) -> Result<()> {
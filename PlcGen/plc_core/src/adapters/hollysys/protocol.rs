@@ -1,9 +1,11 @@
 /*
 逆向出来的二进制结构
 */
+use serde::{Deserialize, Serialize};
+
 /// 定义和利时的两个版本
 #[allow(dead_code)]
-#[derive(Debug,Clone,Eq,PartialEq,Copy)]
+#[derive(Debug,Clone,Eq,PartialEq,Copy,Serialize,Deserialize)]
 pub enum PlcVariant{
     Normal,
     Safety
@@ -8,13 +8,23 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use anyhow::{Result, bail};
 use byteorder::{LittleEndian, WriteBytesExt};
 use encoding_rs::GBK;
-use log::debug;
+use log::{debug, warn};
 use crate::adapters::hollysys::protocol::PlcVariant;
 use super::config::HollysysConfig;
-use crate::ast::{ElementType, LdElement, Network, PinDirection, SafetyTopologyToken, UniversalPou, Variable, VariableNode};
+use super::envelope::container_len;
+use super::var_ids::{VarIdAllocator, VarIdPool};
+use crate::symbols_config::SymbolConfig;
+use super::lossless::{
+    codec_tag, element_fingerprint, header_strings_fingerprint, name_fingerprint, network_fingerprint,
+    variables_fingerprint,
+};
+use crate::ast::{
+    ElementType, LdElement, Network, PinDirection, PreservedLayout, PreservedSegment, SafetyTopologyToken,
+    UniversalPou, Variable, VariableNode,
+};
 
 /// 辅助类：处理 MFC 特有的二进制写入规则
-struct MfcWriter<W:Write>{
+pub(crate) struct MfcWriter<W:Write>{
     inner:W,
     pub offset:usize,
 }
@@ -74,11 +84,15 @@ impl<W:Write> MfcWriter<W>{
         }
         let len = encode_cow.len();
         //判断是否需要写入长度前缀
-        if encode_cow.len()<255{
+        if len<0xff{
             self.write_u8(len as u8)?;
-        }else{
+        }else if len<0xfffe{
             self.write_u8(0xff)?;
             self.write_u16(len as u16)?;
+        }else{
+            self.write_u8(0xff)?;
+            self.write_u16(0xffff)?;
+            self.write_u32(len as u32)?;
         }
 
         //写入实际的内容
@@ -146,6 +160,17 @@ impl PouSerializer{
 
     /// 主入口：将内存中的 UniversalPou 转换为二进制 Vec<u8>
     pub fn serialize(&mut self, pou: &UniversalPou) -> Result<Vec<u8>> {
+        // 携带原始布局（无损解码）时优先逐段回写
+        if let Some(layout) = pou.preserved.as_ref() {
+            match self.check_preserved(pou, layout) {
+                Ok(()) => return self.serialize_preserved(pou, false),
+                Err(err) if self.config.lossless => return Err(err),
+                Err(err) => warn!("原始布局不可用，改用常规序列化: {}", err),
+            }
+        } else if self.config.lossless {
+            bail!("无损模式要求 POU 携带原始布局，请使用无损解码得到的 POU");
+        }
+
         let mut writer = MfcWriter::new(Vec::new());
 
         debug!("Serializing POU: {}, Variant: {:?}", pou.name, self.config.variant);
@@ -165,11 +190,8 @@ impl PouSerializer{
         }
 
         // 阶段 4: 写入尾部填充 (Footer)
-        // 依据样本：POU 片段固定长度为 0x2000，不足部分用 0 填充。
-        let total_len = self.config.pou_total_len;
-        if writer.offset > total_len {
-            bail!("POU序列化长度超出固定上限: {}", writer.offset);
-        }
+        // 依据样本：POU 容器长度为 0x2000，不足部分用 0 填充；内容更长时容器按块整数倍增长
+        let total_len = container_len(writer.offset, self.config.pou_total_len);
         let padding = total_len - writer.offset;
         if padding > 0 {
             writer.write_bytes(&vec![0u8; padding])?;
@@ -378,7 +400,7 @@ impl PouSerializer{
 
         match elem.type_code {
             ElementType::Box => {
-                let has_instance = !elem.instance.is_empty();
+                let has_instance = self.box_has_instance(elem)?;
                 w.write_u8(if has_instance { 1 } else { 0 })?;
                 w.write_mfc_string(&elem.instance)?;
 
@@ -433,8 +455,12 @@ impl PouSerializer{
     /// 写入 CLDElement 基类字段。
     /// 顺序：id(u32) -> type_id(u8) -> name CString -> (Normal: comment/desc) -> conn_count(u32) -> conns...
     fn write_element_base(&self, w: &mut MfcWriter<Vec<u8>>, elem: &LdElement) -> Result<()> {
-        let id = checked_u32(elem.id, "element.id")?;
         let type_id = self.element_type_id(elem.type_code)?;
+        self.write_element_base_with_type(w, elem, type_id)
+    }
+
+    fn write_element_base_with_type(&self, w: &mut MfcWriter<Vec<u8>>, elem: &LdElement, type_id: u8) -> Result<()> {
+        let id = checked_u32(elem.id, "element.id")?;
 
         w.write_u32(id)?;
         w.write_u8(type_id)?;
@@ -491,8 +517,8 @@ impl PouSerializer{
             w.write_u32(0)?;
         }
 
-        // flag：样本中常与“是否有实例”相关，先按实例名是否为空推断
-        let has_instance = !elem.instance.is_empty();
+        // flag：样本中常与“是否有实例”相关，功能块库未登记时按实例名是否为空推断
+        let has_instance = self.box_has_instance(elem)?;
         w.write_u8(if has_instance { 1 } else { 0 })?;
 
         // 该 CString 在 Safety 样本中常为实例名，Normal 中也有同位置字段
@@ -512,6 +538,17 @@ impl PouSerializer{
         Ok(())
     }
 
+    /// Box 实例标志：功能块库登记的块按库中的实例类型，并拒绝当前版本不支持的块
+    fn box_has_instance(&self, elem: &LdElement) -> Result<bool> {
+        match self.config.symbols.get(&elem.name) {
+            Some(fb) if !fb.supports(self.config.variant) => {
+                bail!("功能块 {} 不支持 {:?} 版本: element.id={}", fb.name, self.config.variant, elem.id)
+            }
+            Some(fb) => Ok(fb.is_instance()),
+            None => Ok(!elem.instance.is_empty()),
+        }
+    }
+
     /// 写入单个引脚：Normal/Safety 的格式不同。
     fn write_pin(&self, w: &mut MfcWriter<Vec<u8>>, pin: &crate::ast::BoxPin, direction: PinDirection) -> Result<()> {
         match self.config.variant {
@@ -522,12 +559,15 @@ impl PouSerializer{
             }
             PlcVariant::Normal => {
                 // Normal 版：u8,u8 + name + var + binding_id
-                w.write_u8(1)?; // flag0 常见为 0x01
-                w.write_u8(0)?; // flag1 常见为 0x00
+                // 无损解码保留了原值时优先使用
+                let extras = pin.preserved.as_ref();
+                w.write_u8(extras.map_or(1, |e| e.flag0))?; // flag0 常见为 0x01
+                w.write_u8(extras.map_or(0, |e| e.flag1))?; // flag1 常见为 0x00
                 w.write_mfc_string(&pin.name)?;
                 w.write_mfc_string(&pin.variable)?;
                 if direction == PinDirection::Input && self.config.serialize_version >= 13 {
-                    w.write_u32(0xFFFF_FFFF)?; // binding_id，未绑定默认 -1
+                    // binding_id，未绑定默认 -1
+                    w.write_u32(extras.and_then(|e| e.binding_id).unwrap_or(0xFFFF_FFFF))?;
                 }
             }
         }
@@ -590,6 +630,232 @@ impl PouSerializer{
     }
 }
 
+// =========================================================
+// 无损回写：按解码时记录的布局逐段输出
+// =========================================================
+impl PouSerializer {
+    /// 检查布局能否用于当前 POU
+    /// 无损回写只支持“原位修改”：对象集合及其所属网络必须与解码时一致。
+    /// 增删对象会改变 MFC 对象流中的类/对象索引，无法在保留原字节的前提下安全插入。
+    pub(crate) fn check_preserved(&self, pou: &UniversalPou, layout: &PreservedLayout) -> Result<()> {
+        let expected_codec = codec_tag(self.config.variant);
+        if layout.codec != expected_codec || layout.serialize_version != self.config.serialize_version {
+            bail!(
+                "原始布局与当前配置不匹配: 布局={}/v{}, 配置={}/v{}",
+                layout.codec,
+                layout.serialize_version,
+                expected_codec,
+                self.config.serialize_version
+            );
+        }
+
+        let mut laid_networks = HashSet::new();
+        let mut laid_elements = HashMap::new();
+        for segment in &layout.segments {
+            match segment {
+                PreservedSegment::Network { id, .. } => {
+                    laid_networks.insert(*id);
+                }
+                PreservedSegment::Element { id, network, .. } => {
+                    laid_elements.insert(*id, *network);
+                }
+                _ => {}
+            }
+        }
+
+        let mut current_networks = HashSet::new();
+        let mut current_elements = HashMap::new();
+        for net in &pou.networks {
+            current_networks.insert(net.id);
+            for elem in &net.elements {
+                match laid_elements.get(&elem.id) {
+                    Some(network) if *network == net.id => {}
+                    Some(network) => bail!(
+                        "无损模式不支持移动元件: id={} 原网络={} 现网络={}",
+                        elem.id,
+                        network,
+                        net.id
+                    ),
+                    None => bail!("无损模式不支持新增元件: id={} (网络 {})", elem.id, net.id),
+                }
+                current_elements.insert(elem.id, net.id);
+            }
+            // 解码时合成的容器网络（如 Safety 孤立元件所在的 0 号网络）没有对应的网络对象
+            if !laid_networks.contains(&net.id) && net.elements.is_empty() {
+                bail!("无损模式不支持新增网络: id={}", net.id);
+            }
+        }
+        if let Some(id) = laid_networks.iter().find(|id| !current_networks.contains(id)) {
+            bail!("无损模式不支持删除网络: id={}", id);
+        }
+        if let Some(id) = laid_elements.keys().find(|id| !current_elements.contains_key(id)) {
+            bail!("无损模式不支持删除元件: id={}", id);
+        }
+        Ok(())
+    }
+
+    /// 按布局回写
+    /// - force=false：指纹未变的段原样输出，变化的段重新编码
+    /// - force=true：忽略指纹全部重新编码（用于评估编码器的还原能力）
+    pub(crate) fn serialize_preserved(&self, pou: &UniversalPou, force: bool) -> Result<Vec<u8>> {
+        let Some(layout) = pou.preserved.as_ref() else {
+            bail!("POU 未携带原始布局");
+        };
+        self.check_preserved(pou, layout)?;
+
+        let networks: HashMap<i32, &Network> = pou.networks.iter().map(|net| (net.id, net)).collect();
+        let elements: HashMap<i32, &LdElement> = pou
+            .networks
+            .iter()
+            .flat_map(|net| net.elements.iter())
+            .map(|elem| (elem.id, elem))
+            .collect();
+
+        let mut w = MfcWriter::new(Vec::new());
+        for segment in &layout.segments {
+            match segment {
+                PreservedSegment::Raw { bytes } => w.write_bytes(bytes)?,
+                PreservedSegment::Padding { total_len } => {
+                    // 修改后内容超出原容器时按块增长
+                    let total_len = if w.offset > *total_len {
+                        container_len(w.offset, self.config.pou_total_len)
+                    } else {
+                        *total_len
+                    };
+                    w.write_bytes(&vec![0u8; total_len - w.offset])?;
+                }
+                PreservedSegment::Name { fingerprint, align4, bytes } => {
+                    if !force && *fingerprint == name_fingerprint(pou) {
+                        w.write_bytes(bytes)?;
+                    } else {
+                        w.write_mfc_string(&pou.name)?;
+                        if *align4 {
+                            w.align_to_4bytes()?;
+                        }
+                    }
+                }
+                PreservedSegment::HeaderStrings { fingerprint, count_width, bytes } => {
+                    if !force && *fingerprint == header_strings_fingerprint(pou) {
+                        w.write_bytes(bytes)?;
+                    } else {
+                        self.write_header_strings_preserved(&mut w, pou, *count_width)?;
+                    }
+                }
+                PreservedSegment::Network { id, fingerprint, body, fields, bytes } => {
+                    let net = networks[id];
+                    if !force && *fingerprint == network_fingerprint(net.id, &net.label, &net.comment) {
+                        w.write_bytes(bytes)?;
+                    } else {
+                        // 对象头 + 基类字段保持原样，仅替换 id/label/comment
+                        w.write_bytes(&bytes[..*body])?;
+                        w.write_u32(checked_u32(net.id, "network.id")?)?;
+                        w.write_bytes(&bytes[*body + 4..*fields])?;
+                        w.write_mfc_string(&net.label)?;
+                        w.write_mfc_string(&net.comment)?;
+                    }
+                }
+                PreservedSegment::Element { id, fingerprint, body, bytes, .. } => {
+                    let elem = elements[id];
+                    if !force && *fingerprint == element_fingerprint(elem) {
+                        w.write_bytes(bytes)?;
+                    } else {
+                        w.write_bytes(&bytes[..*body])?;
+                        self.write_element_preserved(&mut w, elem)?;
+                    }
+                }
+                PreservedSegment::Variables { fingerprint, bytes } => {
+                    if !force && *fingerprint == variables_fingerprint(pou) {
+                        w.write_bytes(bytes)?;
+                    } else {
+                        self.write_variables(&mut w, pou)?;
+                    }
+                }
+            }
+        }
+        Ok(w.into_inner())
+    }
+
+    fn write_header_strings_preserved(
+        &self,
+        w: &mut MfcWriter<Vec<u8>>,
+        pou: &UniversalPou,
+        count_width: u8,
+    ) -> Result<()> {
+        let count = pou.header_strings.len();
+        if count_width == 2 {
+            w.write_u16(checked_u16(count as u32, "header_strings.count")?)?;
+        } else {
+            w.write_u32(count as u32)?;
+        }
+        for item in &pou.header_strings {
+            w.write_mfc_string(item)?;
+        }
+        Ok(())
+    }
+
+    /// 元件主体（对象标签之后）按解析器布局写入，未知字段取自 preserved
+    fn write_element_preserved(&self, w: &mut MfcWriter<Vec<u8>>, elem: &LdElement) -> Result<()> {
+        let extras = elem.preserved.clone().unwrap_or_default();
+        let type_id = match extras.type_id {
+            Some(type_id) => type_id,
+            None => self.element_type_id(elem.type_code)?,
+        };
+        self.write_element_base_with_type(w, elem, type_id)?;
+
+        let normal = self.config.variant == PlcVariant::Normal;
+        let version = self.config.serialize_version;
+        match elem.type_code {
+            ElementType::Contact => {
+                w.write_u8(elem.sub_type)?;
+                if normal {
+                    write_geometry(w, extras.geometry.as_deref())?;
+                }
+            }
+            ElementType::Coil => {
+                w.write_u8(elem.sub_type)?;
+                w.write_u8(extras.coil_flag2.unwrap_or(0))?;
+                if normal && version > 0 {
+                    w.write_u8(extras.coil_flag3.unwrap_or(0))?;
+                }
+                if normal {
+                    write_geometry(w, extras.geometry.as_deref())?;
+                }
+            }
+            ElementType::Box => {
+                if normal && version >= 6 {
+                    let [a, b] = extras.box_version.unwrap_or([0, 0]);
+                    w.write_u32(a)?;
+                    w.write_u32(b)?;
+                }
+                let has_instance = self.box_has_instance(elem)?;
+                w.write_u8(extras.box_flag.unwrap_or(if has_instance { 1 } else { 0 }))?;
+                w.write_mfc_string(&elem.instance)?;
+
+                let (input_pins, output_pins) = split_pins(&elem.pins);
+                w.write_u32(input_pins.len() as u32)?;
+                for pin in input_pins {
+                    self.write_pin(w, pin, PinDirection::Input)?;
+                }
+                w.write_u32(output_pins.len() as u32)?;
+                for pin in output_pins {
+                    self.write_pin(w, pin, PinDirection::Output)?;
+                }
+            }
+            ElementType::Assign => {}
+            ElementType::Network => bail!("ElementType::Network 不能作为元件回写"),
+        }
+        Ok(())
+    }
+}
+
+/// Normal 触点/线圈尾部的几何串：有原值写原值，否则写空串
+fn write_geometry(w: &mut MfcWriter<Vec<u8>>, raw: Option<&[u8]>) -> Result<()> {
+    match raw {
+        Some(raw) => w.write_bytes(raw),
+        None => w.write_mfc_string(""),
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SafetyDbKind {
     Base,
@@ -613,13 +879,12 @@ impl PouSerializer {
 
         // 变量 ID 分配策略：
         // 1) 如果上层显式提供 var_id，则直接使用；
-        // 2) 否则按变量表顺序分配递增 u16。
-        let mut next_var_id: u16 = 1;
-        let mut var_id_map: HashMap<String, u16> = HashMap::new();
+        // 2) 否则交给配置中的分配器（默认按变量表顺序分配递增 u16）。
+        let mut pool = VarIdPool::new(&self.config.reserved_var_ids, &flat_vars);
 
         for var in &flat_vars {
             w.write_u8(0x15)?; // TypeID: Local Variable
-            let var_id = assign_var_id(var, &mut var_id_map, &mut next_var_id)?;
+            let var_id = pool.assign(var, self.config.var_id_allocator.as_ref())?;
 
             // Normal: CBaseDB::Serialize 顺序
             w.write_mfc_string(&var.name)?;
@@ -633,7 +898,7 @@ impl PouSerializer {
 
             let retain_flag = if var.power_down_keep { 0x03 } else { 0x04 };
             w.write_u8(retain_flag)?;
-            let addr_id = resolve_addr_id(var);
+            let addr_id = resolve_addr_id(var, self.config.var_id_allocator.as_ref());
             w.write_u64(addr_id)?;
             w.write_mfc_string("")?;
             let mode = resolve_mode(var, addr_id);
@@ -649,7 +914,7 @@ impl PouSerializer {
 
     fn write_variables_safety(&self, w: &mut MfcWriter<Vec<u8>>, pou: &UniversalPou) -> Result<()> {
         let mut entries = Vec::new();
-        collect_safety_db_entries(&pou.variables, &pou.header_strings, &mut entries);
+        collect_safety_db_entries(&pou.variables, &pou.header_strings, &self.config.symbols, &mut entries);
         if entries.len() > u32::MAX as usize {
             bail!("变量数量超出 u32 上限: {}", entries.len());
         }
@@ -658,13 +923,13 @@ impl PouSerializer {
             return Ok(());
         }
 
-        let mut next_var_id: u16 = 1;
-        let mut var_id_map: HashMap<String, u16> = HashMap::new();
+        let flat_vars = Self::collect_variables(&pou.variables);
+        let mut pool = VarIdPool::new(&self.config.reserved_var_ids, &flat_vars);
 
         for entry in entries {
             let type_id = safety_db_type_id(entry.kind);
             w.write_u8(type_id)?;
-            self.write_safety_db_entry(w, &entry, &mut var_id_map, &mut next_var_id)?;
+            self.write_safety_db_entry(w, &entry, &mut pool)?;
         }
         Ok(())
     }
@@ -673,15 +938,14 @@ impl PouSerializer {
         &self,
         w: &mut MfcWriter<Vec<u8>>,
         entry: &SafetyDbEntry,
-        var_id_map: &mut HashMap<String, u16>,
-        next_var_id: &mut u16,
+        pool: &mut VarIdPool,
     ) -> Result<()> {
         match entry.kind {
             SafetyDbKind::Base => {
-                self.write_safety_base_db(w, &entry.base, var_id_map, next_var_id)?;
+                self.write_safety_base_db(w, &entry.base, pool)?;
             }
             SafetyDbKind::Struct => {
-                self.write_safety_base_db(w, &entry.base, var_id_map, next_var_id)?;
+                self.write_safety_base_db(w, &entry.base, pool)?;
                 let count = entry.members.len();
                 if count > u32::MAX as usize {
                     bail!("Struct 成员数量超出 u32 上限: {}", count);
@@ -690,11 +954,11 @@ impl PouSerializer {
                 for member in &entry.members {
                     let type_id = safety_db_type_id(member.kind);
                     w.write_u8(type_id)?;
-                    self.write_safety_db_entry(w, member, var_id_map, next_var_id)?;
+                    self.write_safety_db_entry(w, member, pool)?;
                 }
             }
             SafetyDbKind::FunctionBlock => {
-                self.write_safety_base_db(w, &entry.base, var_id_map, next_var_id)?;
+                self.write_safety_base_db(w, &entry.base, pool)?;
                 // 依据规则：FunctionBlock 包含 5 段 typed-list + KV 尾部
                 for list_index in 0..5 {
                     if list_index == 0 {
@@ -703,7 +967,7 @@ impl PouSerializer {
                         for member in &entry.members {
                             let type_id = safety_db_type_id(member.kind);
                             w.write_u8(type_id)?;
-                            self.write_safety_db_entry(w, member, var_id_map, next_var_id)?;
+                            self.write_safety_db_entry(w, member, pool)?;
                         }
                     } else {
                         w.write_u32(0)?;
@@ -719,10 +983,9 @@ impl PouSerializer {
         &self,
         w: &mut MfcWriter<Vec<u8>>,
         var: &Variable,
-        var_id_map: &mut HashMap<String, u16>,
-        next_var_id: &mut u16,
+        pool: &mut VarIdPool,
     ) -> Result<()> {
-        let var_id = assign_var_id(var, var_id_map, next_var_id)?;
+        let var_id = pool.assign(var, self.config.var_id_allocator.as_ref())?;
         if self.config.serialize_version >= 0x34 {
             self.write_safety_base_db_v34(w, var, var_id)
         } else {
@@ -754,7 +1017,7 @@ impl PouSerializer {
         let area_code = resolve_area_code(var);
         w.write_u8(area_code)?;
         w.write_u16(0xFFFF)?;
-        let addr_id = resolve_addr_id_u32(var);
+        let addr_id = resolve_addr_id_u32(var, self.config.var_id_allocator.as_ref());
         w.write_u32(addr_id)?;
         w.write_mfc_string("")?;
         let mode = resolve_mode_safety(var);
@@ -788,7 +1051,7 @@ impl PouSerializer {
         w.write_mfc_string(&var.init_value)?;
 
         w.write_u8(0)?;
-        let addr_id = resolve_addr_id_u32(var);
+        let addr_id = resolve_addr_id_u32(var, self.config.var_id_allocator.as_ref());
         w.write_u32(addr_id)?;
         let id2 = resolve_id2(var);
         w.write_u32(id2)?;
@@ -820,6 +1083,7 @@ fn safety_db_type_id(kind: SafetyDbKind) -> u8 {
 fn collect_safety_db_entries(
     nodes: &[VariableNode],
     header_strings: &[String],
+    symbols: &SymbolConfig,
     out: &mut Vec<SafetyDbEntry>,
 ) {
     for node in nodes {
@@ -833,13 +1097,13 @@ fn collect_safety_db_entries(
             }
             VariableNode::Group { name, type_name, children } => {
                 if is_virtual_group(name, type_name) {
-                    collect_safety_db_entries(children, header_strings, out);
+                    collect_safety_db_entries(children, header_strings, symbols, out);
                     continue;
                 }
-                let (kind, data_type) = infer_container_kind(name, type_name, header_strings);
-                let base_var = make_container_variable(name, &data_type);
+                let (kind, data_type) = infer_container_kind(name, type_name, header_strings, symbols);
+                let base_var = Variable::new(name, data_type);
                 let mut members = Vec::new();
-                collect_safety_db_entries(children, header_strings, &mut members);
+                collect_safety_db_entries(children, header_strings, symbols, &mut members);
                 out.push(SafetyDbEntry {
                     kind,
                     base: base_var,
@@ -864,10 +1128,14 @@ fn infer_container_kind(
     name: &str,
     type_name: &Option<String>,
     header_strings: &[String],
+    symbols: &SymbolConfig,
 ) -> (SafetyDbKind, String) {
     if let Some(t) = type_name {
         return (SafetyDbKind::FunctionBlock, t.clone());
     }
+    if let Some(fb) = symbols.get(name).filter(|fb| fb.is_instance()) {
+        return (SafetyDbKind::FunctionBlock, fb.name.clone());
+    }
     for header in header_strings {
         if name == header || name.starts_with(&format!("{}_", header)) {
             return (SafetyDbKind::FunctionBlock, header.clone());
@@ -876,22 +1144,6 @@ fn infer_container_kind(
     (SafetyDbKind::Struct, name.to_string())
 }
 
-fn make_container_variable(name: &str, data_type: &str) -> Variable {
-    Variable {
-        name: name.to_string(),
-        data_type: data_type.to_string(),
-        init_value: String::new(),
-        soe_enable: false,
-        power_down_keep: false,
-        comment: String::new(),
-        var_id: None,
-        addr_id: None,
-        mode: None,
-        id2: None,
-        area_code: None,
-    }
-}
-
 /// 收集 Safety 拓扑中内联元件的 ID（用于过滤 CObList 元素列表）。
 fn inline_element_ids(tokens: &[SafetyTopologyToken]) -> HashSet<i32> {
     let mut ids = HashSet::new();
@@ -943,30 +1195,11 @@ fn calc_init_flag(data_type: &str, init_value: &str) -> u8 {
     }
 }
 
-/// var_id 分配：优先使用上层提供的值；否则为新变量分配递增句柄。
-fn assign_var_id(
-    var: &Variable,
-    var_id_map: &mut HashMap<String, u16>,
-    next_var_id: &mut u16,
-) -> Result<u16> {
-    if let Some(value) = var.var_id {
-        return Ok(value);
-    }
-    if let Some(value) = var_id_map.get(&var.name) {
-        return Ok(*value);
-    }
-    let value = *next_var_id;
-    if value == u16::MAX {
-        bail!("var_id 超出 u16 上限，变量数量过多");
-    }
-    *next_var_id = next_var_id.wrapping_add(1);
-    var_id_map.insert(var.name.clone(), value);
-    Ok(value)
-}
-
-/// addr_id 解析：Normal tail 的 8 字节字段，默认视为未绑定。
-fn resolve_addr_id(var: &Variable) -> u64 {
-    var.addr_id.unwrap_or(0xFFFF_FFFF_FFFF_FFFF)
+/// addr_id 解析：Normal tail 的 8 字节字段；未提供时由分配器补齐，否则视为未绑定。
+fn resolve_addr_id(var: &Variable, allocator: &dyn VarIdAllocator) -> u64 {
+    var.addr_id
+        .or_else(|| allocator.addr_id(var))
+        .unwrap_or(0xFFFF_FFFF_FFFF_FFFF)
 }
 
 /// Normal 版 mode 解析：当 addr_id 为 FF..FF 时默认 0x06，否则默认 0x16。
@@ -984,8 +1217,8 @@ fn resolve_mode(var: &Variable, addr_id: u64) -> u8 {
 
 /// Safety 版 addr_id 解析：仅使用低 32 位。
 /// 说明：Safety 的 CBaseDB::Serialize 只写入 u32，未绑定时为 0xFFFFFFFF。
-fn resolve_addr_id_u32(var: &Variable) -> u32 {
-    let value = resolve_addr_id(var);
+fn resolve_addr_id_u32(var: &Variable, allocator: &dyn VarIdAllocator) -> u32 {
+    let value = resolve_addr_id(var, allocator);
     (value & 0xFFFF_FFFF) as u32
 }
 
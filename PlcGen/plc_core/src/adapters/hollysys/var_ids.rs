@@ -0,0 +1,191 @@
+/*
+变量 ID 分配策略
+- var_id(u16)：变量句柄，真实分配算法未知；序列化器只对未显式提供 var_id 的变量调用分配器
+- addr_id：变量未提供时可由分配器补齐（如沿用参考 POU），否则视为未绑定
+分配前先占用“配置中的保留 ID（目标工程已在用）+ 变量表中显式给出的 var_id”，保证结果不冲突
+同名变量共用同一个 ID；分配只依赖变量表内容与顺序，同一输入总是得到相同结果
+名称哈希分配的稳定性只在无冲突时成立：两个变量争用同一槽位时，先分配者（变量表中靠前）占用，
+后者按自身名称派生的探测序列另选槽位，因此增删/调整冲突变量的顺序可能改变后者的 ID
+*/
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::ast::{UniversalPou, Variable, VariableNode};
+
+/// 0xFFFF 不参与分配（与序列化器原有上限一致）
+const VAR_ID_LIMIT: u16 = u16::MAX;
+
+/// var_id / addr_id 分配器
+pub trait VarIdAllocator: fmt::Debug + Send + Sync {
+    /// 为变量分配 var_id，不得返回 pool 中已占用的 ID
+    fn allocate(&self, var: &Variable, pool: &VarIdPool) -> Result<u16>;
+
+    /// 变量未提供 addr_id 时的补齐值；None 表示未绑定
+    fn addr_id(&self, _var: &Variable) -> Option<u64> {
+        None
+    }
+}
+
+/// 单次序列化中的 ID 占用情况
+#[derive(Debug, Clone, Default)]
+pub struct VarIdPool {
+    used: HashSet<u16>,
+    by_name: HashMap<String, u16>,
+}
+
+impl VarIdPool {
+    /// 以保留 ID 与变量表中显式给出的 var_id 初始化
+    pub fn new<'a>(reserved: &[u16], vars: impl IntoIterator<Item = &'a Variable>) -> Self {
+        let mut pool = Self { used: reserved.iter().copied().collect(), ..Self::default() };
+        for var in vars {
+            if let Some(id) = var.var_id {
+                pool.used.insert(id);
+            }
+        }
+        pool
+    }
+
+    pub fn is_used(&self, id: u16) -> bool {
+        self.used.contains(&id)
+    }
+
+    /// 从 from 开始的第一个空闲 ID
+    pub fn next_free(&self, from: u16) -> Option<u16> {
+        (from..VAR_ID_LIMIT).find(|id| !self.is_used(*id))
+    }
+
+    /// 序列化器入口：显式 var_id 优先，其次复用同名变量的 ID，最后交给分配器
+    pub(crate) fn assign(&mut self, var: &Variable, allocator: &dyn VarIdAllocator) -> Result<u16> {
+        if let Some(id) = var.var_id {
+            self.by_name.entry(var.name.clone()).or_insert(id);
+            return Ok(id);
+        }
+        if let Some(id) = self.by_name.get(&var.name) {
+            return Ok(*id);
+        }
+        let id = allocator.allocate(var, self)?;
+        if id == VAR_ID_LIMIT || self.used.contains(&id) {
+            bail!("分配器返回的 var_id 0x{:04X} 已占用: {}", id, var.name);
+        }
+        self.used.insert(id);
+        self.by_name.insert(var.name.clone(), id);
+        Ok(id)
+    }
+}
+
+/// 顺序分配：从 start 开始取第一个空闲 ID（默认策略，start = 1）
+#[derive(Debug, Clone, Copy)]
+pub struct SequentialVarIds {
+    pub start: u16,
+}
+
+impl Default for SequentialVarIds {
+    fn default() -> Self {
+        Self { start: 1 }
+    }
+}
+
+impl VarIdAllocator for SequentialVarIds {
+    fn allocate(&self, _var: &Variable, pool: &VarIdPool) -> Result<u16> {
+        pool.next_free(self.start).ok_or_else(|| anyhow!("var_id 超出 u16 上限，变量数量过多"))
+    }
+}
+
+/// 名称哈希分配：ID 取决于变量名（FNV-1a）；首选槽位被占用时按“名称 + 探测次数”再哈希，
+/// 探测序列只由名称决定，与附近槽位的占用疏密无关
+/// 增删不冲突的变量不会改变已有变量的 ID；冲突时的结果见模块说明
+#[derive(Debug, Clone, Copy)]
+pub struct StableHashVarIds {
+    pub start: u16,
+}
+
+impl Default for StableHashVarIds {
+    fn default() -> Self {
+        Self { start: 1 }
+    }
+}
+
+/// 名称派生的探测次数；全部占用时（ID 池接近满）退回顺序查找
+const STABLE_HASH_PROBES: u32 = 64;
+
+impl VarIdAllocator for StableHashVarIds {
+    fn allocate(&self, var: &Variable, pool: &VarIdPool) -> Result<u16> {
+        let span = u32::from(VAR_ID_LIMIT.saturating_sub(self.start).max(1));
+        let name = var.name.as_bytes();
+        let first = fnv1a(name) % span;
+        (0..STABLE_HASH_PROBES)
+            .map(|probe| self.start + (probe_hash(name, probe) % span) as u16)
+            .chain((0..span).map(|step| self.start + ((first + step) % span) as u16))
+            .find(|id| !pool.is_used(*id))
+            .ok_or_else(|| anyhow!("var_id 超出 u16 上限，变量数量过多"))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811C_9DC5u32, fnv1a_step)
+}
+
+fn fnv1a_step(hash: u32, byte: &u8) -> u32 {
+    (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+}
+
+/// 第 probe 次探测的哈希：0 为名称哈希本身，其后在名称哈希上继续混入探测次数
+fn probe_hash(name: &[u8], probe: u32) -> u32 {
+    let hash = fnv1a(name);
+    if probe == 0 { hash } else { probe.to_le_bytes().iter().fold(hash, fnv1a_step) }
+}
+
+/// 沿用参考 POU：同名变量使用参考中的 var_id/addr_id
+/// 参考中不存在的变量按顺序分配，并避开参考已使用的全部 ID
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceVarIds {
+    ids: HashMap<String, u16>,
+    addrs: HashMap<String, u64>,
+    reserved: HashSet<u16>,
+    fallback: SequentialVarIds,
+}
+
+impl ReferenceVarIds {
+    /// 从参考 POU（通常为目标工程中已存在的同名 POU）收集 ID
+    pub fn from_pou(reference: &UniversalPou) -> Self {
+        let mut this = Self::default();
+        this.collect(&reference.variables);
+        this
+    }
+
+    fn collect(&mut self, nodes: &[VariableNode]) {
+        for node in nodes {
+            match node {
+                VariableNode::Leaf(var) => {
+                    if let Some(id) = var.var_id {
+                        self.ids.entry(var.name.clone()).or_insert(id);
+                        self.reserved.insert(id);
+                    }
+                    if let Some(addr) = var.addr_id {
+                        self.addrs.entry(var.name.clone()).or_insert(addr);
+                    }
+                }
+                VariableNode::Group { children, .. } => self.collect(children),
+            }
+        }
+    }
+}
+
+impl VarIdAllocator for ReferenceVarIds {
+    fn allocate(&self, var: &Variable, pool: &VarIdPool) -> Result<u16> {
+        if let Some(id) = self.ids.get(&var.name)
+            && !pool.is_used(*id)
+        {
+            return Ok(*id);
+        }
+        (self.fallback.start..VAR_ID_LIMIT)
+            .find(|id| !pool.is_used(*id) && !self.reserved.contains(id))
+            .ok_or_else(|| anyhow!("var_id 超出 u16 上限，变量数量过多"))
+    }
+
+    fn addr_id(&self, var: &Variable) -> Option<u64> {
+        self.addrs.get(&var.name).copied()
+    }
+}
@@ -1 +1,2 @@
 pub mod hollysys;
+pub mod plcopen;
@@ -0,0 +1,36 @@
+use anyhow::{Context, Result};
+
+use crate::ast::UniversalPou;
+use crate::ports::backend::PouCodec;
+
+use super::xml_reader::read_pou_xml;
+use super::xml_writer::write_project_xml;
+
+/// PLCopen TC6 XML 编解码器：与 IEC 61131-3 第三方编辑器交换 LD 逻辑
+/// 说明：
+/// - 编码输出完整工程文档（单个 POU）；解码接受工程文档或单独的 `<pou>`；
+/// - TC6 无法表达的 Hollysys 字段（变量 ID、分组、网络号等）写入 addData 私有数据，
+///   本工具读回时还原，第三方编辑器忽略即可。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlcOpenCodec;
+
+impl PlcOpenCodec {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PouCodec for PlcOpenCodec {
+    fn decode(&self, data: &[u8]) -> Result<UniversalPou> {
+        let text = std::str::from_utf8(data).context("PLCopen XML 不是有效的 UTF-8 文本")?;
+        read_pou_xml(text)
+    }
+
+    fn encode(&self, pou: &UniversalPou) -> Result<Vec<u8>> {
+        Ok(write_project_xml(std::slice::from_ref(pou)).into_bytes())
+    }
+
+    fn format_name(&self) -> &'static str {
+        "PLCopen XML"
+    }
+}
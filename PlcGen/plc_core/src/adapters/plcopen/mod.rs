@@ -0,0 +1,17 @@
+/*
+PLCopen 风格的厂商无关输出（文本/交换格式）
+*/
+mod st;
+mod protocol;
+mod xml_reader;
+mod xml_writer;
+mod backend;
+
+// 对外导出：结构化文本打印器
+pub use st::{StPrinter, to_structured_text};
+
+// 对外导出：TC6 XML 读写与编解码器
+pub use protocol::{TC6_NAMESPACE, VENDOR_DATA_NAME};
+pub use xml_reader::{read_pou_xml, read_pous_xml};
+pub use xml_writer::{write_pou_xml, write_project_xml};
+pub use backend::PlcOpenCodec;
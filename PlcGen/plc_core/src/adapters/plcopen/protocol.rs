@@ -0,0 +1,46 @@
+/*
+PLCopen TC6 XML 常量定义
+*/
+
+/// TC6 XML 2.01 命名空间
+pub const TC6_NAMESPACE: &str = "http://www.plcopen.org/xml/tc6_0201";
+
+/// 文档/注释使用的 XHTML 命名空间
+pub const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+/// addData 中本工具私有数据的标识（TC6 要求为 URI）
+/// 用于保存 TC6 无法表达的 Hollysys 字段（变量 ID、分组、网络号等），第三方编辑器可忽略
+pub const VENDOR_DATA_NAME: &str = "urn:plcgen:universal-pou";
+
+/// 文件头中的工具信息
+pub(super) const PRODUCT_NAME: &str = "plc_core";
+pub(super) const COMPANY_NAME: &str = "PlcGen";
+
+/// 固定的创建时间：保证同一 POU 多次导出的文本完全一致，便于比对
+pub(super) const CREATION_DATE_TIME: &str = "1970-01-01T00:00:00";
+
+/// 简易布局参数（TC6 要求每个图形对象都有坐标）
+pub(super) const LAYOUT_COLUMN_WIDTH: i64 = 120;
+pub(super) const LAYOUT_ROW_HEIGHT: i64 = 60;
+pub(super) const LAYOUT_NETWORK_GAP: i64 = 40;
+
+/// TC6 中以空元素表示的基础类型（如 `<BOOL/>`）
+/// 其余类型一律写为 `<derived name="..."/>`
+pub(super) const ELEMENTARY_TYPES: &[&str] = &[
+    "BOOL", "BYTE", "WORD", "DWORD", "LWORD", "SINT", "INT", "DINT", "LINT", "USINT", "UINT",
+    "UDINT", "ULINT", "REAL", "LREAL", "TIME", "DATE", "DT", "TOD",
+];
+
+/// 字符串类型在 TC6 中为小写元素名
+pub(super) const STRING_TYPES: &[(&str, &str)] = &[("STRING", "string"), ("WSTRING", "wstring")];
+
+/// 变量声明所在的区段（读取时全部视为 POU 变量）
+pub(super) const VAR_LIST_TAGS: &[&str] = &[
+    "localVars",
+    "tempVars",
+    "inputVars",
+    "outputVars",
+    "inOutVars",
+    "externalVars",
+    "globalVars",
+];
@@ -0,0 +1,436 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::ast::{
+    BoxPin, ElementType, LdElement, Network, PinDirection, UniversalPou, Variable, VariableNode,
+};
+
+use crate::domain::topology::{Rung, RungNode, network_elements};
+use crate::symbols_config::SymbolConfig;
+
+/// 结构化文本（IEC 61131-3 ST）打印器
+/// 说明：
+/// - 变量表输出为单个 VAR 块，分组信息以注释保留；
+/// - 每个 Network 经 Rung::from_network 还原梯级结构后推导为 ST 语句（Normal 用 connections 图，Safety 用拓扑 Token 流）；
+/// - 样本中缺失名称的功能块引脚（如 Safety 版 MOVE）按功能块库补全，库中也没有时改用位置参数调用；
+/// - 输出仅用于评审/比对，不保证可被第三方编辑器直接编译。
+#[derive(Debug, Clone)]
+pub struct StPrinter {
+    indent: String,
+    /// 补全引脚名用的功能块库；None 时使用内置库
+    symbols: Option<SymbolConfig>,
+}
+
+impl Default for StPrinter {
+    fn default() -> Self {
+        Self {
+            indent: "    ".to_string(),
+            symbols: None,
+        }
+    }
+}
+
+impl StPrinter {
+    /// 默认打印器（4 空格缩进）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 自定义缩进字符串
+    pub fn with_indent(mut self, indent: &str) -> Self {
+        self.indent = indent.to_string();
+        self
+    }
+
+    /// 指定补全引脚名用的功能块库（默认内置库）
+    pub fn with_symbols(mut self, config: &SymbolConfig) -> Self {
+        self.symbols = Some(config.clone());
+        self
+    }
+
+    /// 将 POU 渲染为 ST 文本
+    pub fn print(&self, pou: &UniversalPou) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "PROGRAM {}", pou.name);
+        if !pou.header_strings.is_empty() {
+            let _ = writeln!(out, "(* uses: {} *)", escape_comment(&pou.header_strings.join(", ")));
+        }
+
+        out.push_str("VAR\n");
+        for node in &pou.variables {
+            self.write_variable_node(&mut out, node, "");
+        }
+        out.push_str("END_VAR\n");
+
+        for net in &pou.networks {
+            out.push('\n');
+            self.write_network(&mut out, net);
+        }
+
+        out.push_str("END_PROGRAM\n");
+        out
+    }
+
+    fn write_variable_node(&self, out: &mut String, node: &VariableNode, prefix: &str) {
+        match node {
+            VariableNode::Leaf(var) => self.write_variable(out, var, prefix),
+            VariableNode::Group { name, type_name, children } => {
+                // 虚拟分组（Local Variables / 类型分组）只影响展示，不进入变量路径
+                if is_virtual_group(name, type_name) {
+                    for child in children {
+                        self.write_variable_node(out, child, prefix);
+                    }
+                    return;
+                }
+                let path = join_path(prefix, name);
+                match type_name {
+                    Some(t) => {
+                        let _ = writeln!(out, "{}(* {} : {} *)", self.indent, path, t);
+                    }
+                    None => {
+                        let _ = writeln!(out, "{}(* {} *)", self.indent, path);
+                    }
+                }
+                for child in children {
+                    self.write_variable_node(out, child, &path);
+                }
+            }
+        }
+    }
+
+    fn write_variable(&self, out: &mut String, var: &Variable, prefix: &str) {
+        let _ = write!(out, "{}{} : {}", self.indent, join_path(prefix, &var.name), var.data_type);
+        if !var.init_value.is_empty() {
+            let _ = write!(out, " := {}", var.init_value);
+        }
+        out.push(';');
+        if !var.comment.is_empty() {
+            let _ = write!(out, " (* {} *)", escape_comment(&var.comment));
+        }
+        out.push('\n');
+    }
+
+    fn write_network(&self, out: &mut String, net: &Network) {
+        let mut title = format!("Network {}", net.id);
+        if !net.label.is_empty() {
+            let _ = write!(title, " [{}]", net.label);
+        }
+        if !net.comment.is_empty() {
+            let _ = write!(title, ": {}", net.comment);
+        }
+        let _ = writeln!(out, "(* {} *)", escape_comment(&title));
+
+        let net = self.with_pin_names(net);
+        let net = net.as_ref();
+        for stmt in network_statements(net) {
+            let _ = writeln!(out, "{}", stmt);
+        }
+    }
+
+    /// 补全功能块的空引脚名：同方向第 k 个非 EN/ENO 引脚取库定义中的第 k 个引脚名
+    fn with_pin_names<'a>(&self, net: &'a Network) -> Cow<'a, Network> {
+        let unnamed = |elem: &LdElement| elem.type_code == ElementType::Box && elem.pins.iter().any(|p| p.name.is_empty());
+        if !net.elements.iter().any(unnamed) {
+            return Cow::Borrowed(net);
+        }
+        let symbols = self.symbols.as_ref().unwrap_or_else(|| SymbolConfig::builtin_ref());
+        let mut net = net.clone();
+        for elem in net.elements.iter_mut().filter(|elem| unnamed(elem)) {
+            let Some(fb) = symbols.get(&elem.name) else {
+                continue;
+            };
+            let (mut inputs, mut outputs) = (fb.inputs.iter(), fb.outputs.iter());
+            for pin in elem.pins.iter_mut().filter(|p| !is_enable_pin(p)) {
+                let def = match pin.direction {
+                    PinDirection::Input => inputs.next(),
+                    PinDirection::Output => outputs.next(),
+                };
+                if let Some(def) = def
+                    && pin.name.is_empty()
+                {
+                    pin.name.clone_from(&def.name);
+                }
+            }
+        }
+        Cow::Owned(net)
+    }
+}
+
+/// 快捷入口：使用默认打印器输出 ST 文本
+pub fn to_structured_text(pou: &UniversalPou) -> String {
+    StPrinter::new().print(pou)
+}
+
+/// 能流表达式（仅用于 ST 渲染）
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    True,
+    Var(String),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    fn and(self, other: Expr) -> Expr {
+        match (self, other) {
+            (Expr::True, e) | (e, Expr::True) => e,
+            (Expr::And(mut a), Expr::And(b)) => {
+                a.extend(b);
+                Expr::And(a)
+            }
+            (Expr::And(mut a), e) => {
+                a.push(e);
+                Expr::And(a)
+            }
+            (e, Expr::And(mut b)) => {
+                b.insert(0, e);
+                Expr::And(b)
+            }
+            (a, b) => Expr::And(vec![a, b]),
+        }
+    }
+
+    fn any(items: Vec<Expr>) -> Expr {
+        let mut flat: Vec<Expr> = Vec::new();
+        for item in items {
+            match item {
+                Expr::True => return Expr::True,
+                Expr::Or(inner) => {
+                    for e in inner {
+                        if !flat.contains(&e) {
+                            flat.push(e);
+                        }
+                    }
+                }
+                e => {
+                    if !flat.contains(&e) {
+                        flat.push(e);
+                    }
+                }
+            }
+        }
+        match flat.len() {
+            0 => Expr::True,
+            1 => flat.pop().unwrap_or(Expr::True),
+            _ => Expr::Or(flat),
+        }
+    }
+
+    fn negate(self) -> Expr {
+        match self {
+            Expr::Not(inner) => *inner,
+            e => Expr::Not(Box::new(e)),
+        }
+    }
+
+    /// 渲染时的优先级：OR < AND < NOT/原子
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Or(_) => 1,
+            Expr::And(_) => 2,
+            _ => 3,
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            Expr::True => "TRUE".to_string(),
+            Expr::Var(name) => name.clone(),
+            Expr::Not(inner) => format!("NOT {}", inner.render_operand(3)),
+            Expr::And(items) => items
+                .iter()
+                .map(|e| e.render_operand(2))
+                .collect::<Vec<_>>()
+                .join(" AND "),
+            Expr::Or(items) => items
+                .iter()
+                .map(|e| e.render_operand(1))
+                .collect::<Vec<_>>()
+                .join(" OR "),
+        }
+    }
+
+    /// 子表达式优先级低于父节点时加括号
+    fn render_operand(&self, parent: u8) -> String {
+        if self.precedence() < parent {
+            format!("({})", self.render())
+        } else {
+            self.render()
+        }
+    }
+}
+
+/// 拓扑无法解析时的兜底：按元素声明顺序串联。
+fn network_as_series(net: &Network) -> Vec<String> {
+    let mut stmts = Vec::new();
+    let mut power = Expr::True;
+    for elem in &net.elements {
+        if elem.type_code == ElementType::Contact {
+            power = power.and(contact_expr(elem));
+        } else {
+            push_statement(&mut stmts, elem, power.clone());
+        }
+    }
+    stmts
+}
+
+/// 梯级结构统一由 Rung::from_network 推导（Safety Token 流 / Normal connections 图 / 声明顺序）
+fn network_statements(net: &Network) -> Vec<String> {
+    let rung = match Rung::from_network(net) {
+        Ok(rung) => rung,
+        Err(err) => {
+            let mut stmts = vec![format!("(* {} *)", escape_comment(&err.to_string()))];
+            stmts.extend(network_as_series(net));
+            return stmts;
+        }
+    };
+    let elems = network_elements(net);
+    let by_id: HashMap<i32, &LdElement> = elems.iter().map(|e| (e.id, e)).collect();
+    let mut stmts = Vec::new();
+    eval_series(&rung.series, &by_id, &Expr::True, &mut stmts);
+    stmts
+}
+
+/// 计算串联序列的局部条件，并按“外部能流 AND 局部条件”输出语句。
+fn eval_series(
+    nodes: &[RungNode],
+    by_id: &HashMap<i32, &LdElement>,
+    base: &Expr,
+    stmts: &mut Vec<String>,
+) -> Expr {
+    let mut local = Expr::True;
+    for node in nodes {
+        match node {
+            RungNode::Element(id) => match by_id.get(id) {
+                Some(elem) if elem.type_code == ElementType::Contact => {
+                    local = local.and(contact_expr(elem));
+                }
+                Some(elem) => push_statement(stmts, elem, base.clone().and(local.clone())),
+                None => {
+                    stmts.push(format!("(* topology references missing element id={} *)", id));
+                }
+            },
+            RungNode::Parallel(branches) => {
+                let branch_base = base.clone().and(local.clone());
+                let outs = branches
+                    .iter()
+                    .map(|branch| eval_series(branch, by_id, &branch_base, stmts))
+                    .collect();
+                local = local.and(Expr::any(outs));
+            }
+        }
+    }
+    local
+}
+
+fn contact_expr(elem: &LdElement) -> Expr {
+    let var = Expr::Var(elem.name.clone());
+    if elem.sub_type == 1 { var.negate() } else { var }
+}
+
+fn push_statement(stmts: &mut Vec<String>, elem: &LdElement, power: Expr) {
+    let stmt = match elem.type_code {
+        ElementType::Coil => coil_statement(elem, power),
+        ElementType::Box => box_statement(elem, power),
+        // 触点只贡献条件；Assign/Network 为结构节点，不产生语句
+        _ => return,
+    };
+    if elem.comment.is_empty() {
+        stmts.push(stmt);
+    } else {
+        stmts.push(format!("{} (* {} *)", stmt, escape_comment(&elem.comment)));
+    }
+}
+
+fn coil_statement(elem: &LdElement, power: Expr) -> String {
+    match elem.sub_type {
+        0 => format!("{} := {};", elem.name, power.render()),
+        1 => format!("{} := {};", elem.name, power.negate().render()),
+        other => format!("{} := {}; (* coil sub_type={} *)", elem.name, power.render(), other),
+    }
+}
+
+fn box_statement(elem: &LdElement, power: Expr) -> String {
+    if elem.pins.iter().any(|p| p.name.is_empty() && !is_enable_pin(p) && is_bound(&p.variable)) {
+        return positional_box_statement(elem, power);
+    }
+    let mut args = Vec::new();
+    if power != Expr::True {
+        args.push(format!("EN := {}", power.render()));
+    }
+    for pin in elem.pins.iter().filter(|p| p.direction == PinDirection::Input) {
+        if pin.name == "EN" || !is_bound(&pin.variable) {
+            continue;
+        }
+        args.push(format!("{} := {}", pin.name, pin.variable));
+    }
+    for pin in elem.pins.iter().filter(|p| p.direction == PinDirection::Output) {
+        if pin.name == "ENO" || !is_bound(&pin.variable) {
+            continue;
+        }
+        args.push(format!("{} => {}", pin.name, pin.variable));
+    }
+    let callee = if elem.instance.is_empty() { &elem.name } else { &elem.instance };
+    let call = format!("{}({});", callee, args.join(", "));
+    if elem.instance.is_empty() {
+        call
+    } else {
+        format!("{} (* {} *)", call, elem.name)
+    }
+}
+
+/// 引脚名无法补全时的非形式调用：输入按顺序作位置参数，
+/// 函数的唯一输出写成赋值，其余输出只能以注释保留；EN 能流改用 IF 包裹
+fn positional_box_statement(elem: &LdElement, power: Expr) -> String {
+    let bound = |direction: PinDirection| {
+        elem.pins
+            .iter()
+            .filter(move |p| p.direction == direction && !is_enable_pin(p) && is_bound(&p.variable))
+            .map(|p| p.variable.as_str())
+    };
+    let args: Vec<&str> = bound(PinDirection::Input).collect();
+    let outputs: Vec<&str> = bound(PinDirection::Output).collect();
+    let callee = if elem.instance.is_empty() { &elem.name } else { &elem.instance };
+    let call = format!("{}({})", callee, args.join(", "));
+    let mut stmt = match outputs.as_slice() {
+        [output] if elem.instance.is_empty() => format!("{} := {};", output, call),
+        [] => format!("{};", call),
+        _ => format!("{}; (* outputs: {} *)", call, outputs.join(", ")),
+    };
+    if !elem.instance.is_empty() {
+        stmt = format!("{} (* {} *)", stmt, elem.name);
+    }
+    if power == Expr::True {
+        stmt
+    } else {
+        format!("IF {} THEN {} END_IF;", power.render(), stmt)
+    }
+}
+
+fn is_enable_pin(pin: &BoxPin) -> bool {
+    pin.name == "EN" || pin.name == "ENO"
+}
+
+/// 未连接的引脚在样本中表现为空串或 "???"
+fn is_bound(variable: &str) -> bool {
+    let v = variable.trim();
+    !v.is_empty() && v != "???"
+}
+
+fn is_virtual_group(name: &str, type_name: &Option<String>) -> bool {
+    name == "Local Variables" || type_name.as_deref() == Some(name)
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn escape_comment(text: &str) -> String {
+    text.replace("(*", "( *").replace("*)", "* )")
+}
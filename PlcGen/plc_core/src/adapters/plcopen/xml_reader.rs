@@ -0,0 +1,598 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use roxmltree::{Document, Node};
+
+use crate::ast::{
+    BoxPin, ElementType, LdElement, Network, PinDirection, UniversalPou, Variable, VariableNode,
+};
+
+use super::protocol::{VAR_LIST_TAGS, VENDOR_DATA_NAME};
+
+/// 未连接引脚的默认占位值（与和利时 Normal 样本一致）
+const UNBOUND_PIN: &str = "???";
+
+/// 读取文档中的全部 POU
+/// 说明：既接受完整工程（project/types/pous），也接受单独的 `<pou>` 片段。
+pub fn read_pous_xml(xml: &str) -> Result<Vec<UniversalPou>> {
+    let xml = xml.trim_start_matches('\u{feff}');
+    let doc = Document::parse(xml).context("PLCopen XML 解析失败")?;
+    let pous: Vec<Node> = doc
+        .root_element()
+        .descendants()
+        .filter(|node| is_tag(node, "pou"))
+        .collect();
+    if pous.is_empty() {
+        bail!("PLCopen XML 中未找到 <pou> 元素");
+    }
+    pous.into_iter().map(read_pou_node).collect()
+}
+
+/// 读取文档中的第一个 POU
+pub fn read_pou_xml(xml: &str) -> Result<UniversalPou> {
+    let mut pous = read_pous_xml(xml)?;
+    Ok(pous.remove(0))
+}
+
+fn read_pou_node(node: Node) -> Result<UniversalPou> {
+    let name = node
+        .attribute("name")
+        .context("<pou> 缺少 name 属性")?
+        .to_string();
+
+    let header_strings = vendor_data(node, "headerStrings")
+        .map(|headers| {
+            children(headers, "string")
+                .filter_map(|s| s.attribute("value"))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let variables = match child(node, "interface") {
+        Some(interface) => read_variables(interface),
+        None => Vec::new(),
+    };
+
+    let networks = match child(node, "body") {
+        Some(body) => match child(body, "LD") {
+            Some(ld) => read_ld_body(ld).with_context(|| format!("POU {} 的 LD 体解析失败", name))?,
+            None => bail!("POU {} 的实现语言不是 LD，暂不支持", name),
+        },
+        None => Vec::new(),
+    };
+
+    Ok(UniversalPou {
+        name,
+        header_strings,
+        variables,
+        networks,
+        preserved: None,
+    })
+}
+
+// ===== 变量表 =====
+
+/// 分组按首次出现的顺序还原
+struct GroupSlot {
+    name: String,
+    type_name: Option<String>,
+    children: Vec<VariableNode>,
+}
+
+fn read_variables(interface: Node) -> Vec<VariableNode> {
+    let mut slots: Vec<GroupSlot> = Vec::new();
+
+    for list in interface.children().filter(|n| n.is_element()) {
+        if !VAR_LIST_TAGS.contains(&list.tag_name().name()) {
+            continue;
+        }
+        let list_retain = list.attribute("retain") == Some("true");
+        for var_node in children(list, "variable") {
+            let Some(full_name) = var_node.attribute("name") else {
+                continue;
+            };
+            // 功能块实例：成员还原为以实例名命名的分组；声明本身仅在导出时存在时才还原
+            let instance = vendor_data(var_node, "instance");
+            if let Some(instance) = instance {
+                slots.push(GroupSlot {
+                    name: full_name.to_string(),
+                    type_name: instance.attribute("groupType").map(str::to_string),
+                    children: children(instance, "member").map(|m| VariableNode::Leaf(read_member(m))).collect(),
+                });
+            }
+            let vendor = match instance {
+                Some(instance) => match child(instance, "variable") {
+                    Some(data) => Some(data),
+                    None => continue,
+                },
+                None => vendor_data(var_node, "variable"),
+            };
+
+            let mut var = Variable {
+                init_value: child(var_node, "initialValue")
+                    .and_then(|init| child(init, "simpleValue"))
+                    .and_then(|v| v.attribute("value"))
+                    .unwrap_or_default()
+                    .to_string(),
+                power_down_keep: list_retain,
+                comment: child(var_node, "documentation")
+                    .map(xhtml_text)
+                    .unwrap_or_default(),
+                ..Variable::new(full_name, child(var_node, "type").map(read_type).unwrap_or_default())
+            };
+
+            let mut group: Option<(String, Option<String>)> = None;
+            if let Some(data) = vendor {
+                read_vendor_attrs(&mut var, data);
+                if let Some(name) = data.attribute("group") {
+                    group = Some((name.to_string(), data.attribute("groupType").map(str::to_string)));
+                }
+            }
+
+            let (group_name, type_name) = match group {
+                Some((name, type_name)) => {
+                    // 类型分组不改变变量名；路径分组需去掉 "分组." 前缀
+                    if type_name.as_deref() != Some(name.as_str())
+                        && let Some(rest) = var.name.strip_prefix(&format!("{}.", name))
+                    {
+                        var.name = rest.to_string();
+                    }
+                    (name, type_name)
+                }
+                None => match full_name.split_once('.') {
+                    Some((prefix, suffix)) => {
+                        var.name = suffix.to_string();
+                        (prefix.to_string(), None)
+                    }
+                    None => ("Local Variables".to_string(), None),
+                },
+            };
+
+            let idx = match slots.iter().position(|s| s.name == group_name) {
+                Some(idx) => idx,
+                None => {
+                    slots.push(GroupSlot {
+                        name: group_name,
+                        type_name,
+                        children: Vec::new(),
+                    });
+                    slots.len() - 1
+                }
+            };
+            slots[idx].children.push(VariableNode::Leaf(var));
+        }
+    }
+
+    slots
+        .into_iter()
+        .map(|slot| VariableNode::Group {
+            name: slot.name,
+            type_name: slot.type_name,
+            children: slot.children,
+        })
+        .collect()
+}
+
+/// 实例成员：`<member>` 属性即变量的全部字段
+fn read_member(node: Node) -> Variable {
+    let mut var = Variable {
+        init_value: node.attribute("initialValue").unwrap_or_default().to_string(),
+        comment: node.attribute("comment").unwrap_or_default().to_string(),
+        ..Variable::new(node.attribute("name").unwrap_or_default(), node.attribute("dataType").unwrap_or_default())
+    };
+    read_vendor_attrs(&mut var, node);
+    var
+}
+
+fn read_vendor_attrs(var: &mut Variable, data: Node) {
+    var.soe_enable = data.attribute("soeEnable") == Some("true");
+    var.power_down_keep = data.attribute("powerDownKeep") == Some("true");
+    var.var_id = parse_attr(data, "varId");
+    var.addr_id = parse_attr(data, "addrId");
+    var.mode = parse_attr(data, "mode");
+    var.id2 = parse_attr(data, "id2");
+    var.area_code = parse_attr(data, "areaCode");
+}
+
+/// `<type>` 的唯一子元素决定类型名
+fn read_type(type_node: Node) -> String {
+    let Some(inner) = type_node.children().find(|n| n.is_element()) else {
+        return String::new();
+    };
+    match inner.tag_name().name() {
+        "derived" => inner.attribute("name").unwrap_or_default().to_string(),
+        "array" => {
+            let dims: Vec<String> = children(inner, "dimension")
+                .map(|d| {
+                    format!(
+                        "{}..{}",
+                        d.attribute("lower").unwrap_or("0"),
+                        d.attribute("upper").unwrap_or("0")
+                    )
+                })
+                .collect();
+            let base = child(inner, "baseType").map(read_type).unwrap_or_default();
+            format!("ARRAY[{}] OF {}", dims.join(","), base)
+        }
+        other => other.to_ascii_uppercase(),
+    }
+}
+
+// ===== 梯形图 =====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LdKind {
+    LeftRail,
+    Contact,
+    Coil,
+    Block,
+    Vendor,
+    InVariable,
+    OutVariable,
+    Comment,
+    Other,
+}
+
+impl LdKind {
+    fn of(tag: &str) -> Self {
+        match tag {
+            "leftPowerRail" => LdKind::LeftRail,
+            "contact" => LdKind::Contact,
+            "coil" => LdKind::Coil,
+            "block" => LdKind::Block,
+            "vendorElement" => LdKind::Vendor,
+            "inVariable" | "inOutVariable" => LdKind::InVariable,
+            "outVariable" => LdKind::OutVariable,
+            "comment" => LdKind::Comment,
+            _ => LdKind::Other,
+        }
+    }
+}
+
+/// 并查集：按能流连通性把图元划分为网络
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self { parent: (0..len).collect() }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[rb] = ra;
+        }
+    }
+}
+
+/// LD 体 → Network 列表
+/// 说明：TC6 的 LD 没有“网络”概念，这里把每个与左母线连通的图元集合视为一个网络；
+/// 第三方工具若所有梯级共用一条左母线，则会得到单个网络。
+fn read_ld_body(ld: Node) -> Result<Vec<Network>> {
+    let nodes: Vec<(Node, LdKind)> = ld
+        .children()
+        .filter(|n| n.is_element())
+        .map(|n| (n, LdKind::of(n.tag_name().name())))
+        .collect();
+
+    let mut by_local: HashMap<u64, usize> = HashMap::new();
+    for (idx, (node, kind)) in nodes.iter().enumerate() {
+        if *kind == LdKind::Other || *kind == LdKind::Comment {
+            continue;
+        }
+        let local = local_id(*node)?;
+        if by_local.insert(local, idx).is_some() {
+            bail!("localId 重复: {}", local);
+        }
+    }
+
+    // inVariable 的表达式 / outVariable 绑定的功能块输出
+    let mut expressions: HashMap<usize, String> = HashMap::new();
+    let mut output_bindings: HashMap<(usize, String), String> = HashMap::new();
+    for (idx, (node, kind)) in nodes.iter().enumerate() {
+        let expr = child(*node, "expression").and_then(|e| e.text()).unwrap_or_default();
+        match kind {
+            LdKind::InVariable => {
+                expressions.insert(idx, expr.to_string());
+            }
+            LdKind::OutVariable => {
+                for (src, param) in connections(*node, &by_local) {
+                    let param = param.unwrap_or_default().to_string();
+                    output_bindings.insert((src, param), expr.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 元件与能流前驱
+    let mut elements: HashMap<usize, LdElement> = HashMap::new();
+    let mut preds: Vec<(usize, usize)> = Vec::new();
+    for (idx, (node, kind)) in nodes.iter().enumerate() {
+        let elem = match kind {
+            LdKind::Contact | LdKind::Coil => {
+                for (src, _) in connections(*node, &by_local) {
+                    preds.push((src, idx));
+                }
+                read_contact_or_coil(*node, *kind)?
+            }
+            LdKind::Block => read_block(*node, idx, &by_local, &expressions, &output_bindings, &mut preds)?,
+            LdKind::Vendor => {
+                let Some(elem) = read_vendor_element(*node)? else {
+                    continue;
+                };
+                for var in pin_nodes(*node, "inputVariables") {
+                    for (src, _) in connections(var, &by_local) {
+                        preds.push((src, idx));
+                    }
+                }
+                elem
+            }
+            _ => continue,
+        };
+        elements.insert(idx, elem);
+    }
+
+    // connections 记录“本元件 → 下游元件”；母线与元件只参与网络划分。
+    // 私有数据中带原始连接表的元件以原表为准，不再由连线推导。
+    let fixed: Vec<usize> = nodes
+        .iter()
+        .enumerate()
+        .filter(|(idx, (node, _))| {
+            elements.contains_key(idx)
+                && vendor_data(*node, "element").is_some_and(|d| d.has_attribute("connections"))
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+    let mut sets = DisjointSet::new(nodes.len());
+    for &(src, dst) in &preds {
+        let src_is_power = elements.contains_key(&src) || nodes[src].1 == LdKind::LeftRail;
+        if !src_is_power {
+            continue;
+        }
+        sets.union(src, dst);
+        let dst_id = elements[&dst].id;
+        if let Some(src_elem) = elements.get_mut(&src)
+            && !fixed.contains(&src)
+            && !src_elem.connections.contains(&dst_id)
+        {
+            src_elem.connections.push(dst_id);
+        }
+    }
+
+    let mut networks: Vec<Network> = Vec::new();
+    let mut explicit_ids: Vec<bool> = Vec::new();
+    let mut net_of_root: HashMap<usize, usize> = HashMap::new();
+    let mut pending_comment: Option<String> = None;
+    for (idx, (node, kind)) in nodes.iter().enumerate() {
+        match kind {
+            LdKind::Comment => {
+                let text = child(*node, "content").map(xhtml_text).unwrap_or_default();
+                pending_comment = Some(text);
+                continue;
+            }
+            LdKind::LeftRail => {}
+            _ if elements.contains_key(&idx) => {}
+            _ => continue,
+        }
+        let root = sets.find(idx);
+        let net_idx = *net_of_root.entry(root).or_insert_with(|| {
+            networks.push(Network {
+                id: 0,
+                label: String::new(),
+                comment: String::new(),
+                elements: Vec::new(),
+                safety_topology: Vec::new(),
+            });
+            explicit_ids.push(false);
+            networks.len() - 1
+        });
+        let net = &mut networks[net_idx];
+        if *kind == LdKind::LeftRail {
+            if let Some(comment) = pending_comment.take() {
+                net.comment = comment;
+            }
+            if let Some(data) = vendor_data(*node, "network") {
+                if let Some(id) = parse_attr::<i32>(data, "id") {
+                    net.id = id;
+                    explicit_ids[net_idx] = true;
+                }
+                net.label = data.attribute("label").unwrap_or_default().to_string();
+            }
+        } else if let Some(elem) = elements.remove(&idx) {
+            net.elements.push(elem);
+        }
+    }
+
+    // 未携带网络号的网络按 max_id + 1 递增补齐
+    let mut next_id = networks
+        .iter()
+        .zip(&explicit_ids)
+        .filter(|(_, explicit)| **explicit)
+        .map(|(net, _)| net.id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    for (net, explicit) in networks.iter_mut().zip(&explicit_ids) {
+        if !explicit {
+            net.id = next_id;
+            next_id += 1;
+        }
+    }
+
+    Ok(networks)
+}
+
+fn read_contact_or_coil(node: Node, kind: LdKind) -> Result<LdElement> {
+    let negated = node.attribute("negated") == Some("true");
+    let mut elem = new_element(
+        node,
+        if kind == LdKind::Contact { ElementType::Contact } else { ElementType::Coil },
+    )?;
+    elem.name = child(node, "variable")
+        .and_then(|v| v.text())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if elem.sub_type == 0 && negated {
+        elem.sub_type = 1;
+    }
+    Ok(elem)
+}
+
+fn read_block(
+    node: Node,
+    idx: usize,
+    by_local: &HashMap<u64, usize>,
+    expressions: &HashMap<usize, String>,
+    output_bindings: &HashMap<(usize, String), String>,
+    preds: &mut Vec<(usize, usize)>,
+) -> Result<LdElement> {
+    let mut elem = new_element(node, ElementType::Box)?;
+    elem.name = node.attribute("typeName").context("<block> 缺少 typeName")?.to_string();
+    elem.instance = node.attribute("instanceName").unwrap_or_default().to_string();
+    let unbound = vendor_data(node, "element")
+        .and_then(|d| d.attribute("unboundPin"))
+        .unwrap_or(UNBOUND_PIN);
+
+    for list in ["inputVariables", "inOutVariables"] {
+        for var in pin_nodes(node, list) {
+            let name = var.attribute("formalParameter").unwrap_or_default().to_string();
+            let mut variable = unbound.to_string();
+            for (src, _) in connections(var, by_local) {
+                match expressions.get(&src) {
+                    Some(expr) => variable = expr.clone(),
+                    None => preds.push((src, idx)),
+                }
+            }
+            elem.pins.push(BoxPin::new(name, variable, PinDirection::Input));
+        }
+    }
+    for var in pin_nodes(node, "outputVariables") {
+        let name = var.attribute("formalParameter").unwrap_or_default().to_string();
+        let variable = output_bindings
+            .get(&(idx, name.clone()))
+            .cloned()
+            .unwrap_or_else(|| unbound.to_string());
+        elem.pins.push(BoxPin::new(name, variable, PinDirection::Output));
+    }
+    Ok(elem)
+}
+
+/// 仅识别本工具写出的 vendorElement，其余厂商扩展忽略
+fn read_vendor_element(node: Node) -> Result<Option<LdElement>> {
+    let type_code = match vendor_data(node, "element").and_then(|d| d.attribute("type")) {
+        Some("Assign") => ElementType::Assign,
+        Some("Network") => ElementType::Network,
+        _ => return Ok(None),
+    };
+    let mut elem = new_element(node, type_code)?;
+    elem.name = child(node, "alternativeText").map(xhtml_text).unwrap_or_default();
+    Ok(Some(elem))
+}
+
+/// 公共字段：ID（优先私有数据中的原始 ID）、注释、desc、子类型
+fn new_element(node: Node, type_code: ElementType) -> Result<LdElement> {
+    let data = vendor_data(node, "element");
+    let id = match data.and_then(|d| parse_attr::<i32>(d, "id")) {
+        Some(id) => id,
+        None => i32::try_from(local_id(node)?).context("localId 超出元件 ID 范围")?,
+    };
+    Ok(LdElement {
+        comment: child(node, "documentation").map(xhtml_text).unwrap_or_default(),
+        desc: data
+            .and_then(|d| d.attribute("desc"))
+            .unwrap_or_default()
+            .to_string(),
+        connections: data
+            .and_then(|d| d.attribute("connections"))
+            .map(|list| list.split(',').filter_map(|c| c.trim().parse().ok()).collect())
+            .unwrap_or_default(),
+        sub_type: data.and_then(|d| parse_attr(d, "subType")).unwrap_or(0),
+        ..LdElement::new(id, type_code, "")
+    })
+}
+
+// ===== 通用辅助 =====
+
+fn is_tag(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children().find(|n| is_tag(n, name))
+}
+
+fn children<'a, 'i: 'a>(node: Node<'a, 'i>, name: &'a str) -> impl Iterator<Item = Node<'a, 'i>> {
+    node.children().filter(move |n| is_tag(n, name))
+}
+
+fn pin_nodes<'a, 'i: 'a>(node: Node<'a, 'i>, list: &'a str) -> impl Iterator<Item = Node<'a, 'i>> {
+    child(node, list)
+        .into_iter()
+        .flat_map(|l| children(l, "variable"))
+}
+
+/// 图元的 connectionPointIn 引用：返回 (来源图元下标, formalParameter)
+fn connections<'a>(node: Node<'a, '_>, by_local: &HashMap<u64, usize>) -> Vec<(usize, Option<&'a str>)> {
+    let Some(point) = child(node, "connectionPointIn") else {
+        return Vec::new();
+    };
+    children(point, "connection")
+        .filter_map(|conn| {
+            let local = conn.attribute("refLocalId")?.parse::<u64>().ok()?;
+            let src = *by_local.get(&local)?;
+            Some((src, conn.attribute("formalParameter")))
+        })
+        .collect()
+}
+
+fn local_id(node: Node) -> Result<u64> {
+    let raw = node
+        .attribute("localId")
+        .with_context(|| format!("<{}> 缺少 localId", node.tag_name().name()))?;
+    raw.parse::<u64>()
+        .with_context(|| format!("localId 非法: {}", raw))
+}
+
+/// 取本工具写入的 addData 私有数据（`<data name=VENDOR_DATA_NAME>` 下的指定元素）
+fn vendor_data<'a, 'i>(node: Node<'a, 'i>, tag: &str) -> Option<Node<'a, 'i>> {
+    let add_data = child(node, "addData")?;
+    children(add_data, "data")
+        .filter(|d| d.attribute("name") == Some(VENDOR_DATA_NAME))
+        .find_map(|d| child(d, tag))
+}
+
+fn parse_attr<T: std::str::FromStr>(node: Node, name: &str) -> Option<T> {
+    node.attribute(name)?.parse().ok()
+}
+
+/// formattedText：多个 `<xhtml:p>` 以换行拼接；无子元素时取直接文本
+fn xhtml_text(node: Node) -> String {
+    let paragraphs: Vec<String> = node
+        .children()
+        .filter(|n| n.is_element())
+        .map(|p| {
+            p.descendants()
+                .filter(|n| n.is_text())
+                .filter_map(|n| n.text())
+                .collect::<String>()
+        })
+        .collect();
+    if paragraphs.is_empty() {
+        node.text().unwrap_or_default().trim().to_string()
+    } else {
+        paragraphs.join("\n")
+    }
+}
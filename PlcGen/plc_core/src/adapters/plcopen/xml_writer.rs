@@ -0,0 +1,875 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{
+    ElementType, LdElement, Network, PinDirection, UniversalPou, Variable, VariableNode,
+};
+use crate::domain::topology::{Rung, RungNode, network_elements};
+
+use super::protocol::{
+    COMPANY_NAME, CREATION_DATE_TIME, ELEMENTARY_TYPES, LAYOUT_COLUMN_WIDTH, LAYOUT_NETWORK_GAP,
+    LAYOUT_ROW_HEIGHT, PRODUCT_NAME, STRING_TYPES, TC6_NAMESPACE, VENDOR_DATA_NAME,
+    XHTML_NAMESPACE,
+};
+
+/// 将若干 POU 写为完整的 TC6 工程文档（project/types/pous）
+/// 说明：第三方编辑器（CODESYS/Beremiz 等）只接受完整工程，剪贴板/文件导出应使用该入口。
+pub fn write_project_xml(pous: &[UniversalPou]) -> String {
+    let mut w = XmlWriter::new();
+    w.declaration();
+    w.open("project", &[("xmlns", TC6_NAMESPACE), ("xmlns:xhtml", XHTML_NAMESPACE)]);
+    w.empty(
+        "fileHeader",
+        &[
+            ("companyName", COMPANY_NAME),
+            ("productName", PRODUCT_NAME),
+            ("productVersion", env!("CARGO_PKG_VERSION")),
+            ("creationDateTime", CREATION_DATE_TIME),
+        ],
+    );
+    let project_name = pous.first().map(|p| p.name.as_str()).unwrap_or("Project");
+    w.open("contentHeader", &[("name", project_name)]);
+    w.open("coordinateInfo", &[]);
+    for lang in ["fbd", "ld", "sfc"] {
+        w.open(lang, &[]);
+        w.empty("scaling", &[("x", "1"), ("y", "1")]);
+        w.close(lang);
+    }
+    w.close("coordinateInfo");
+    w.close("contentHeader");
+
+    w.open("types", &[]);
+    w.empty("dataTypes", &[]);
+    w.open("pous", &[]);
+    for pou in pous {
+        write_pou(&mut w, pou, false);
+    }
+    w.close("pous");
+    w.close("types");
+
+    w.open("instances", &[]);
+    w.empty("configurations", &[]);
+    w.close("instances");
+    w.close("project");
+    w.finish()
+}
+
+/// 仅输出单个 `<pou>` 元素（自带命名空间声明），便于嵌入已有工程
+pub fn write_pou_xml(pou: &UniversalPou) -> String {
+    let mut w = XmlWriter::new();
+    write_pou(&mut w, pou, true);
+    w.finish()
+}
+
+fn write_pou(w: &mut XmlWriter, pou: &UniversalPou, standalone: bool) {
+    let mut attrs = vec![("name", pou.name.as_str()), ("pouType", "program")];
+    if standalone {
+        attrs.push(("xmlns", TC6_NAMESPACE));
+        attrs.push(("xmlns:xhtml", XHTML_NAMESPACE));
+    }
+    w.open("pou", &attrs);
+
+    w.open("interface", &[]);
+    let mut flat = Vec::new();
+    flatten_variables(&pou.variables, "", None, &block_instances(pou), &mut flat);
+    attach_declarations(&mut flat);
+    if flat.is_empty() {
+        w.empty("localVars", &[]);
+    } else {
+        w.open("localVars", &[]);
+        for item in &flat {
+            match item {
+                FlatItem::Var(var) => write_variable(w, var),
+                FlatItem::Instance(instance) => write_instance(w, instance),
+            }
+        }
+        w.close("localVars");
+    }
+    w.close("interface");
+
+    w.open("body", &[]);
+    w.open("LD", &[]);
+    let mut ids = LocalIds::default();
+    let mut top = 0i64;
+    for net in &pou.networks {
+        top = write_network(w, net, &mut ids, top);
+    }
+    w.close("LD");
+    w.close("body");
+
+    if !pou.header_strings.is_empty() {
+        open_vendor_data(w);
+        w.open("headerStrings", &[]);
+        for header in &pou.header_strings {
+            w.empty("string", &[("value", header)]);
+        }
+        w.close("headerStrings");
+        close_vendor_data(w);
+    }
+    w.close("pou");
+}
+
+// ===== 变量表 =====
+
+/// 扁平化后的变量表项：TC6 没有分组概念，分组信息写入 addData
+enum FlatItem<'a> {
+    Var(FlatVar<'a>),
+    Instance(FlatInstance<'a>),
+}
+
+struct FlatVar<'a> {
+    var: &'a Variable,
+    path: String,
+    group: Option<(String, Option<String>)>,
+}
+
+/// 功能块实例：TC6 只声明一个 derived 类型变量，成员变量写入 addData
+struct FlatInstance<'a> {
+    path: String,
+    block_type: String,
+    /// 分组原有的 type_name（可能为空）
+    type_name: Option<String>,
+    members: Vec<&'a Variable>,
+    /// 变量表中同名的实例变量（安全型样本会单独声明 `TAG_TP : TP`）
+    declared: Option<FlatVar<'a>>,
+}
+
+/// 梯形图中带实例名的功能块：实例名 → 块类型
+fn block_instances(pou: &UniversalPou) -> HashMap<&str, &str> {
+    pou.networks
+        .iter()
+        .flat_map(|net| &net.elements)
+        .filter(|elem| elem.type_code == ElementType::Box && !elem.instance.is_empty())
+        .map(|elem| (elem.instance.as_str(), elem.name.as_str()))
+        .collect()
+}
+
+fn flatten_variables<'a>(
+    nodes: &'a [VariableNode],
+    prefix: &str,
+    group: Option<&(String, Option<String>)>,
+    instances: &HashMap<&str, &str>,
+    out: &mut Vec<FlatItem<'a>>,
+) {
+    for node in nodes {
+        match node {
+            VariableNode::Leaf(var) => out.push(FlatItem::Var(FlatVar {
+                var,
+                path: join_path(prefix, &var.name),
+                group: group.cloned(),
+            })),
+            VariableNode::Group { name, type_name, children } => {
+                // 实例分组：以梯形图中的实例名识别，或 type_name 为块类型（与分组名不同）
+                let block_type = instances
+                    .get(name.as_str())
+                    .map(|block| block.to_string())
+                    .or_else(|| type_name.clone().filter(|t| t != name));
+                if name == "Local Variables" {
+                    flatten_variables(children, prefix, group, instances, out);
+                } else if let Some(block_type) = block_type {
+                    let mut members = Vec::new();
+                    collect_leaves(children, &mut members);
+                    out.push(FlatItem::Instance(FlatInstance {
+                        path: join_path(prefix, name),
+                        block_type,
+                        type_name: type_name.clone(),
+                        members,
+                        declared: None,
+                    }));
+                } else if type_name.as_deref() == Some(name.as_str()) {
+                    // 类型分组只影响展示，不进入变量路径
+                    let info = (name.clone(), type_name.clone());
+                    flatten_variables(children, prefix, Some(&info), instances, out);
+                } else {
+                    let path = join_path(prefix, name);
+                    let info = (path.clone(), type_name.clone());
+                    flatten_variables(children, &path, Some(&info), instances, out);
+                }
+            }
+        }
+    }
+}
+
+/// 同一路径已声明为块类型的变量并入实例，避免 TC6 中出现重名变量
+fn attach_declarations(flat: &mut Vec<FlatItem>) {
+    let mut idx = 0;
+    while idx < flat.len() {
+        let FlatItem::Instance(instance) = &flat[idx] else {
+            idx += 1;
+            continue;
+        };
+        let declared = flat.iter().position(|item| {
+            matches!(item, FlatItem::Var(v)
+                if v.path == instance.path && v.var.data_type.trim() == instance.block_type.trim())
+        });
+        let Some(pos) = declared else {
+            idx += 1;
+            continue;
+        };
+        let FlatItem::Var(var) = flat.remove(pos) else {
+            unreachable!()
+        };
+        if pos < idx {
+            idx -= 1;
+        }
+        if let FlatItem::Instance(instance) = &mut flat[idx] {
+            instance.declared = Some(var);
+        }
+        idx += 1;
+    }
+}
+
+fn collect_leaves<'a>(nodes: &'a [VariableNode], out: &mut Vec<&'a Variable>) {
+    for node in nodes {
+        match node {
+            VariableNode::Leaf(var) => out.push(var),
+            VariableNode::Group { children, .. } => collect_leaves(children, out),
+        }
+    }
+}
+
+fn write_variable(w: &mut XmlWriter, item: &FlatVar) {
+    let var = item.var;
+    w.open("variable", &[("name", &item.path)]);
+
+    w.open("type", &[]);
+    write_type(w, &var.data_type);
+    w.close("type");
+
+    if !var.init_value.is_empty() {
+        w.open("initialValue", &[]);
+        w.empty("simpleValue", &[("value", &var.init_value)]);
+        w.close("initialValue");
+    }
+
+    open_vendor_data(w);
+    w.empty("variable", &borrow_attrs(&variable_attrs(item)));
+    close_vendor_data(w);
+
+    write_documentation(w, "documentation", &var.comment);
+    w.close("variable");
+}
+
+fn variable_attrs(item: &FlatVar) -> Vec<(&'static str, String)> {
+    let mut attrs = vendor_attrs(item.var);
+    if let Some((group, type_name)) = &item.group {
+        attrs.push(("group", group.clone()));
+        if let Some(t) = type_name {
+            attrs.push(("groupType", t.clone()));
+        }
+    }
+    attrs
+}
+
+/// 实例变量：`<derived name="块类型"/>`，成员（引脚/内部变量）及其厂商属性保存在 addData 中
+fn write_instance(w: &mut XmlWriter, item: &FlatInstance) {
+    w.open("variable", &[("name", &item.path)]);
+    w.open("type", &[]);
+    w.empty("derived", &[("name", item.block_type.trim())]);
+    w.close("type");
+
+    let declared = item.declared.as_ref();
+    if let Some(init) = declared.map(|d| &d.var.init_value).filter(|v| !v.is_empty()) {
+        w.open("initialValue", &[]);
+        w.empty("simpleValue", &[("value", init)]);
+        w.close("initialValue");
+    }
+
+    // data 下只允许一个元素：实例变量本身的厂商属性作为 instance 的首个子元素
+    open_vendor_data(w);
+    let mut attrs = vec![("blockType", item.block_type.as_str())];
+    push_opt(&mut attrs, "groupType", &item.type_name);
+    w.open("instance", &attrs);
+    if let Some(declared) = declared {
+        w.empty("variable", &borrow_attrs(&variable_attrs(declared)));
+    }
+    for member in &item.members {
+        let mut attrs = vec![("name", member.name.clone()), ("dataType", member.data_type.clone())];
+        if !member.init_value.is_empty() {
+            attrs.push(("initialValue", member.init_value.clone()));
+        }
+        if !member.comment.is_empty() {
+            attrs.push(("comment", member.comment.clone()));
+        }
+        attrs.extend(vendor_attrs(member));
+        w.empty("member", &borrow_attrs(&attrs));
+    }
+    w.close("instance");
+    close_vendor_data(w);
+
+    if let Some(declared) = declared {
+        write_documentation(w, "documentation", &declared.var.comment);
+    }
+    w.close("variable");
+}
+
+/// 变量的厂商属性（SOE/掉电保持/ID 与地址）
+fn vendor_attrs(var: &Variable) -> Vec<(&'static str, String)> {
+    let mut attrs = vec![("soeEnable", var.soe_enable.to_string()), ("powerDownKeep", var.power_down_keep.to_string())];
+    let ids = [
+        ("varId", var.var_id.map(|v| v.to_string())),
+        ("addrId", var.addr_id.map(|v| v.to_string())),
+        ("mode", var.mode.map(|v| v.to_string())),
+        ("id2", var.id2.map(|v| v.to_string())),
+        ("areaCode", var.area_code.map(|v| v.to_string())),
+    ];
+    attrs.extend(ids.into_iter().filter_map(|(key, value)| Some((key, value?))));
+    attrs
+}
+
+fn borrow_attrs<'a>(attrs: &'a [(&'a str, String)]) -> Vec<(&'a str, &'a str)> {
+    attrs.iter().map(|(key, value)| (*key, value.as_str())).collect()
+}
+
+fn write_type(w: &mut XmlWriter, data_type: &str) {
+    let upper = data_type.trim().to_ascii_uppercase();
+    if ELEMENTARY_TYPES.contains(&upper.as_str()) {
+        w.empty(&upper, &[]);
+    } else if let Some((_, tag)) = STRING_TYPES.iter().find(|(name, _)| *name == upper) {
+        w.empty(tag, &[]);
+    } else {
+        w.empty("derived", &[("name", data_type.trim())]);
+    }
+}
+
+// ===== 梯形图 =====
+
+#[derive(Default)]
+struct LocalIds {
+    next: u64,
+}
+
+impl LocalIds {
+    fn alloc(&mut self) -> u64 {
+        self.next += 1;
+        self.next
+    }
+}
+
+/// 能流来源：左母线或本网络内的元件（按元件下标）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Source {
+    Rail,
+    Elem(usize),
+}
+
+/// 写出一个 Network，返回下一个网络的起始纵坐标
+fn write_network(w: &mut XmlWriter, net: &Network, ids: &mut LocalIds, top: i64) -> i64 {
+    let elems = network_elements(net);
+    let preds = build_predecessors(net, &elems);
+    let depths = compute_depths(&preds);
+
+    let rail_id = ids.alloc();
+    let local: Vec<u64> = elems.iter().map(|_| ids.alloc()).collect();
+
+    // 网络注释在左母线之前输出，读取时据此关联到随后的网络
+    if !net.comment.is_empty() {
+        let comment_id = ids.alloc().to_string();
+        w.open("comment", &[("localId", &comment_id), ("height", "20"), ("width", "400")]);
+        write_position(w, LAYOUT_COLUMN_WIDTH / 6, top);
+        write_documentation(w, "content", &net.comment);
+        w.close("comment");
+    }
+    let rail_top = top + LAYOUT_ROW_HEIGHT / 2;
+
+    let rail_local = rail_id.to_string();
+    w.open("leftPowerRail", &[("localId", &rail_local), ("height", "40"), ("width", "4")]);
+    write_position(w, 0, rail_top);
+    w.empty("connectionPointOut", &[("formalParameter", "")]);
+    let net_id = net.id.to_string();
+    open_vendor_data(w);
+    w.empty("network", &[("id", &net_id), ("label", &net.label)]);
+    close_vendor_data(w);
+    w.close("leftPowerRail");
+
+    let mut next_y: HashMap<usize, i64> = HashMap::new();
+    let mut bottom = rail_top + LAYOUT_ROW_HEIGHT;
+    let mut has_successor = vec![false; elems.len()];
+    for sources in &preds {
+        for src in sources {
+            if let Source::Elem(i) = src {
+                has_successor[*i] = true;
+            }
+        }
+    }
+
+    for (idx, elem) in elems.iter().enumerate() {
+        let depth = depths[idx];
+        let y = *next_y.entry(depth).or_insert(rail_top);
+        let x = depth as i64 * LAYOUT_COLUMN_WIDTH;
+        let height = element_height(elem);
+        next_y.insert(depth, y + height.max(LAYOUT_ROW_HEIGHT - 20) + 20);
+        bottom = bottom.max(y + height + 20);
+
+        let conns: Vec<(u64, Option<&str>)> = preds[idx]
+            .iter()
+            .map(|src| match src {
+                Source::Rail => (rail_id, None),
+                Source::Elem(i) => (local[*i], power_out_param(&elems[*i])),
+            })
+            .collect();
+        let ctx = ElementCtx { local_id: local[idx], x, y, conns: &conns };
+        match elem.type_code {
+            ElementType::Contact | ElementType::Coil => write_contact_or_coil(w, elem, &ctx),
+            ElementType::Box => write_block(w, elem, &ctx, ids),
+            ElementType::Assign | ElementType::Network => write_vendor_element(w, elem, &ctx),
+        }
+    }
+
+    // 末端线圈接右母线
+    let sinks: Vec<u64> = elems
+        .iter()
+        .enumerate()
+        .filter(|(i, e)| e.type_code == ElementType::Coil && !has_successor[*i])
+        .map(|(i, _)| local[i])
+        .collect();
+    if !sinks.is_empty() {
+        let right_id = ids.alloc().to_string();
+        let max_depth = depths.iter().copied().max().unwrap_or(0) + 1;
+        w.open("rightPowerRail", &[("localId", &right_id), ("height", "40"), ("width", "4")]);
+        write_position(w, max_depth as i64 * LAYOUT_COLUMN_WIDTH, rail_top);
+        w.open("connectionPointIn", &[]);
+        for id in sinks {
+            let id = id.to_string();
+            w.empty("connection", &[("refLocalId", &id)]);
+        }
+        w.close("connectionPointIn");
+        w.close("rightPowerRail");
+    }
+
+    bottom + LAYOUT_NETWORK_GAP
+}
+
+/// 推导每个元件的能流前驱
+/// - Safety Token 流：按串/并联结构连线；
+/// - connections 图：按“本元件 → 下游元件”的有向边反推；
+/// - 两者皆无：触点按声明顺序串联，线圈/功能块挂在当前能流上（与 ST 导出一致）。
+fn build_predecessors(net: &Network, elems: &[LdElement]) -> Vec<Vec<Source>> {
+    let mut index: HashMap<i32, usize> = HashMap::new();
+    for (i, elem) in elems.iter().enumerate() {
+        index.entry(elem.id).or_insert(i);
+    }
+    let mut preds: Vec<Vec<Source>> = vec![Vec::new(); elems.len()];
+
+    let rung = (!net.safety_topology.is_empty())
+        .then(|| Rung::from_tokens(&net.safety_topology).ok())
+        .flatten();
+    if let Some(rung) = rung {
+        wire_series(&rung.series, vec![Source::Rail], &index, elems, &mut preds);
+    } else if elems.iter().any(|e| !e.connections.is_empty()) {
+        for (i, elem) in elems.iter().enumerate() {
+            for dst in &elem.connections {
+                if let Some(&j) = index.get(dst)
+                    && j != i
+                    && !preds[j].contains(&Source::Elem(i))
+                {
+                    preds[j].push(Source::Elem(i));
+                }
+            }
+        }
+    } else {
+        let mut power = Source::Rail;
+        for (i, elem) in elems.iter().enumerate() {
+            preds[i].push(power);
+            if elem.type_code == ElementType::Contact {
+                power = Source::Elem(i);
+            }
+        }
+    }
+
+    for sources in preds.iter_mut() {
+        if sources.is_empty() {
+            sources.push(Source::Rail);
+        }
+    }
+    preds
+}
+
+fn wire_series(
+    nodes: &[RungNode],
+    inputs: Vec<Source>,
+    index: &HashMap<i32, usize>,
+    elems: &[LdElement],
+    preds: &mut [Vec<Source>],
+) -> Vec<Source> {
+    let mut power = inputs;
+    for node in nodes {
+        match node {
+            RungNode::Element(id) => {
+                let Some(&j) = index.get(id) else {
+                    continue;
+                };
+                preds[j] = power.clone();
+                if elems[j].type_code == ElementType::Contact {
+                    power = vec![Source::Elem(j)];
+                }
+            }
+            RungNode::Parallel(branches) => {
+                let mut outs: Vec<Source> = Vec::new();
+                for branch in branches {
+                    for src in wire_series(branch, power.clone(), index, elems, preds) {
+                        if !outs.contains(&src) {
+                            outs.push(src);
+                        }
+                    }
+                }
+                power = outs;
+            }
+        }
+    }
+    power
+}
+
+/// 列号 = 到左母线的最长路径（环路按左母线处理）
+fn compute_depths(preds: &[Vec<Source>]) -> Vec<usize> {
+    fn visit(
+        i: usize,
+        preds: &[Vec<Source>],
+        memo: &mut [Option<usize>],
+        visiting: &mut HashSet<usize>,
+    ) -> usize {
+        if let Some(d) = memo[i] {
+            return d;
+        }
+        if !visiting.insert(i) {
+            return 0;
+        }
+        let mut depth = 1;
+        for src in &preds[i] {
+            if let Source::Elem(p) = src {
+                depth = depth.max(visit(*p, preds, memo, visiting) + 1);
+            }
+        }
+        visiting.remove(&i);
+        memo[i] = Some(depth);
+        depth
+    }
+
+    let mut memo = vec![None; preds.len()];
+    let mut visiting = HashSet::new();
+    (0..preds.len())
+        .map(|i| visit(i, preds, &mut memo, &mut visiting))
+        .collect()
+}
+
+fn element_height(elem: &LdElement) -> i64 {
+    if elem.type_code != ElementType::Box {
+        return 20;
+    }
+    let inputs = elem.pins.iter().filter(|p| p.direction == PinDirection::Input).count();
+    let outputs = elem.pins.len() - inputs;
+    (inputs.max(outputs) as i64 + 1) * 20
+}
+
+/// 元件作为能流来源时被引用的输出脚（功能块优先 ENO）
+fn power_out_param(elem: &LdElement) -> Option<&str> {
+    match elem.type_code {
+        ElementType::Box => elem
+            .pins
+            .iter()
+            .filter(|p| p.direction == PinDirection::Output)
+            .find(|p| p.name.eq_ignore_ascii_case("ENO"))
+            .or_else(|| elem.pins.iter().find(|p| p.direction == PinDirection::Output))
+            .map(|p| p.name.as_str()),
+        ElementType::Assign | ElementType::Network => Some("OUT"),
+        _ => None,
+    }
+}
+
+struct ElementCtx<'a> {
+    local_id: u64,
+    x: i64,
+    y: i64,
+    conns: &'a [(u64, Option<&'a str>)],
+}
+
+fn write_contact_or_coil(w: &mut XmlWriter, elem: &LdElement, ctx: &ElementCtx) {
+    let tag = if elem.type_code == ElementType::Contact { "contact" } else { "coil" };
+    let local = ctx.local_id.to_string();
+    let negated = if elem.sub_type == 1 { "true" } else { "false" };
+    w.open(tag, &[("localId", &local), ("negated", negated), ("height", "20"), ("width", "20")]);
+    write_position(w, ctx.x, ctx.y);
+    write_connection_in(w, ctx.conns);
+    w.empty("connectionPointOut", &[]);
+    w.text("variable", &[], &elem.name);
+    write_element_vendor_data(w, elem);
+    write_documentation(w, "documentation", &elem.comment);
+    w.close(tag);
+}
+
+fn write_block(w: &mut XmlWriter, elem: &LdElement, ctx: &ElementCtx, ids: &mut LocalIds) {
+    let local = ctx.local_id.to_string();
+    let height = element_height(elem).to_string();
+    let mut attrs = vec![
+        ("localId", local.as_str()),
+        ("typeName", elem.name.as_str()),
+        ("height", height.as_str()),
+        ("width", "80"),
+    ];
+    if !elem.instance.is_empty() {
+        attrs.push(("instanceName", elem.instance.as_str()));
+    }
+    w.open("block", &attrs);
+    write_position(w, ctx.x, ctx.y);
+
+    // 绑定变量的引脚转为独立的 inVariable/outVariable，块输出后再统一写出
+    let mut in_vars: Vec<(u64, &str, i64)> = Vec::new();
+    let mut out_vars: Vec<(u64, &str, &str, i64)> = Vec::new();
+
+    w.open("inputVariables", &[]);
+    let mut row = 0i64;
+    for pin in elem.pins.iter().filter(|p| p.direction == PinDirection::Input) {
+        row += 1;
+        w.open("variable", &[("formalParameter", &pin.name)]);
+        if pin.name.eq_ignore_ascii_case("EN") {
+            write_connection_in(w, ctx.conns);
+        } else if is_bound(&pin.variable) {
+            let var_id = ids.alloc();
+            write_connection_in(w, &[(var_id, None)]);
+            in_vars.push((var_id, pin.variable.as_str(), row));
+        } else {
+            w.empty("connectionPointIn", &[]);
+        }
+        w.close("variable");
+    }
+    w.close("inputVariables");
+    w.empty("inOutVariables", &[]);
+
+    w.open("outputVariables", &[]);
+    let mut row = 0i64;
+    for pin in elem.pins.iter().filter(|p| p.direction == PinDirection::Output) {
+        row += 1;
+        w.open("variable", &[("formalParameter", &pin.name)]);
+        w.empty("connectionPointOut", &[]);
+        w.close("variable");
+        if !pin.name.eq_ignore_ascii_case("ENO") && is_bound(&pin.variable) {
+            out_vars.push((ids.alloc(), pin.name.as_str(), pin.variable.as_str(), row));
+        }
+    }
+    w.close("outputVariables");
+
+    write_element_vendor_data(w, elem);
+    write_documentation(w, "documentation", &elem.comment);
+    w.close("block");
+
+    for (var_id, expr, row) in in_vars {
+        let local = var_id.to_string();
+        w.open("inVariable", &[("localId", &local), ("height", "20"), ("width", "80")]);
+        write_position(w, ctx.x - LAYOUT_COLUMN_WIDTH, ctx.y + row * 20);
+        w.empty("connectionPointOut", &[]);
+        w.text("expression", &[], expr);
+        w.close("inVariable");
+    }
+    for (var_id, param, expr, row) in out_vars {
+        let local = var_id.to_string();
+        w.open("outVariable", &[("localId", &local), ("height", "20"), ("width", "80")]);
+        write_position(w, ctx.x + LAYOUT_COLUMN_WIDTH, ctx.y + row * 20);
+        write_connection_in(w, &[(ctx.local_id, Some(param))]);
+        w.text("expression", &[], expr);
+        w.close("outVariable");
+    }
+}
+
+/// Assign/Network 等 TC6 无对应图元的元件，以 vendorElement 保留
+fn write_vendor_element(w: &mut XmlWriter, elem: &LdElement, ctx: &ElementCtx) {
+    let local = ctx.local_id.to_string();
+    w.open("vendorElement", &[("localId", &local), ("height", "20"), ("width", "40")]);
+    write_position(w, ctx.x, ctx.y);
+    write_documentation(w, "alternativeText", &elem.name);
+    w.open("inputVariables", &[]);
+    w.open("variable", &[("formalParameter", "IN")]);
+    write_connection_in(w, ctx.conns);
+    w.close("variable");
+    w.close("inputVariables");
+    w.open("outputVariables", &[]);
+    w.open("variable", &[("formalParameter", "OUT")]);
+    w.empty("connectionPointOut", &[]);
+    w.close("variable");
+    w.close("outputVariables");
+    write_element_vendor_data(w, elem);
+    write_documentation(w, "documentation", &elem.comment);
+    w.close("vendorElement");
+}
+
+/// 元件私有数据：原始 ID 与 connections 必写（连接表可能引用网络外的 ID，TC6 连线无法表达）；
+/// desc/非标准子类型/特殊元件类型按需写出
+fn write_element_vendor_data(w: &mut XmlWriter, elem: &LdElement) {
+    let id = elem.id.to_string();
+    let sub_type = elem.sub_type.to_string();
+    let connections = elem
+        .connections
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut attrs = vec![("id", id.as_str()), ("connections", connections.as_str())];
+    match elem.type_code {
+        ElementType::Assign => attrs.push(("type", "Assign")),
+        ElementType::Network => attrs.push(("type", "Network")),
+        _ => {}
+    }
+    // 触点/线圈的 0/1 已由 negated 表达
+    let sub_type_in_xml = match elem.type_code {
+        ElementType::Contact | ElementType::Coil => elem.sub_type <= 1,
+        _ => elem.sub_type == 0,
+    };
+    if !sub_type_in_xml {
+        attrs.push(("subType", &sub_type));
+    }
+    if !elem.desc.is_empty() {
+        attrs.push(("desc", &elem.desc));
+    }
+    // 未连接引脚的占位值：Normal 样本为 "???"，Safety 样本为空串
+    if let Some(pin) = elem.pins.iter().find(|p| !is_bound(&p.variable) && p.variable != "???") {
+        attrs.push(("unboundPin", &pin.variable));
+    }
+    open_vendor_data(w);
+    w.empty("element", &attrs);
+    close_vendor_data(w);
+}
+
+fn write_position(w: &mut XmlWriter, x: i64, y: i64) {
+    let x = x.to_string();
+    let y = y.to_string();
+    w.empty("position", &[("x", &x), ("y", &y)]);
+}
+
+fn write_connection_in(w: &mut XmlWriter, conns: &[(u64, Option<&str>)]) {
+    if conns.is_empty() {
+        w.empty("connectionPointIn", &[]);
+        return;
+    }
+    w.open("connectionPointIn", &[]);
+    for (id, param) in conns {
+        let id = id.to_string();
+        match param {
+            Some(p) => w.empty("connection", &[("refLocalId", &id), ("formalParameter", p)]),
+            None => w.empty("connection", &[("refLocalId", &id)]),
+        }
+    }
+    w.close("connectionPointIn");
+}
+
+/// 文本以 `<xhtml:p>` 包裹（documentation/content/alternativeText 共用）
+fn write_documentation(w: &mut XmlWriter, tag: &str, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    w.open(tag, &[]);
+    w.text("xhtml:p", &[], text);
+    w.close(tag);
+}
+
+fn open_vendor_data(w: &mut XmlWriter) {
+    w.open("addData", &[]);
+    w.open("data", &[("name", VENDOR_DATA_NAME), ("handleUnknown", "preserve")]);
+}
+
+fn close_vendor_data(w: &mut XmlWriter) {
+    w.close("data");
+    w.close("addData");
+}
+
+fn push_opt<'a>(attrs: &mut Vec<(&'a str, &'a str)>, key: &'a str, value: &'a Option<String>) {
+    if let Some(v) = value {
+        attrs.push((key, v));
+    }
+}
+
+/// 未连接的引脚在样本中表现为空串或 "???"
+fn is_bound(variable: &str) -> bool {
+    let v = variable.trim();
+    !v.is_empty() && v != "???"
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+/// 简易 XML 文本写入器：只负责缩进与转义，结构由调用方保证
+struct XmlWriter {
+    out: String,
+    depth: usize,
+}
+
+impl XmlWriter {
+    fn new() -> Self {
+        Self { out: String::new(), depth: 0 }
+    }
+
+    fn declaration(&mut self) {
+        self.out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    }
+
+    fn start_tag(&mut self, tag: &str, attrs: &[(&str, &str)]) {
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+        self.out.push('<');
+        self.out.push_str(tag);
+        for (key, value) in attrs {
+            self.out.push(' ');
+            self.out.push_str(key);
+            self.out.push_str("=\"");
+            self.out.push_str(&escape(value));
+            self.out.push('"');
+        }
+    }
+
+    fn open(&mut self, tag: &str, attrs: &[(&str, &str)]) {
+        self.start_tag(tag, attrs);
+        self.out.push_str(">\n");
+        self.depth += 1;
+    }
+
+    fn empty(&mut self, tag: &str, attrs: &[(&str, &str)]) {
+        self.start_tag(tag, attrs);
+        self.out.push_str("/>\n");
+    }
+
+    fn text(&mut self, tag: &str, attrs: &[(&str, &str)], text: &str) {
+        self.start_tag(tag, attrs);
+        self.out.push('>');
+        self.out.push_str(&escape(text));
+        self.out.push_str("</");
+        self.out.push_str(tag);
+        self.out.push_str(">\n");
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth = self.depth.saturating_sub(1);
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+        self.out.push_str("</");
+        self.out.push_str(tag);
+        self.out.push_str(">\n");
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            '\t' => out.push_str("&#9;"),
+            // XML 1.0 不允许其余控制字符（引用形式也不行），只能替换
+            c if (c as u32) < 0x20 => out.push(char::REPLACEMENT_CHARACTER),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
@@ -1,3 +1,5 @@
 pub mod service;
+pub mod validator;
 
 pub use service::PouService;
+pub use validator::{PouValidator, ValidationError, ValidationReport};
@@ -2,18 +2,39 @@ use anyhow::{Result, bail};
 
 use crate::ast::UniversalPou;
 use crate::ports::PouCodec;
+use crate::symbols_config::SymbolConfig;
+
+use super::validator::{PouValidator, ValidationError, ValidationReport};
 
 /// Application layer use case wrapper around `PouCodec`.
 /// Keeps orchestration (validation, boundary checks) away from adapters.
 #[derive(Debug, Clone)]
 pub struct PouService<C: PouCodec> {
     codec: C,
+    validator: PouValidator,
 }
 
 impl<C: PouCodec> PouService<C> {
     /// Create a new service with the given codec.
+    /// The default validator checks pins against the builtin block library and applies the
+    /// variant rules of the codec's target (when the codec has a fixed one).
     pub fn new(codec: C) -> Self {
-        Self { codec }
+        let mut validator = PouValidator::new().with_symbols(&SymbolConfig::builtin());
+        if let Some(variant) = codec.variant() {
+            validator = validator.with_variant(variant);
+        }
+        Self { codec, validator }
+    }
+
+    /// Replace the semantic validator used before encoding.
+    pub fn with_validator(mut self, validator: PouValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    /// Run the semantic validator and return every violation.
+    pub fn validate(&self, pou: &UniversalPou) -> ValidationReport {
+        self.validator.validate(pou)
     }
 
     /// Decode clipboard bytes into a POU and run lightweight validation.
@@ -24,8 +45,13 @@ impl<C: PouCodec> PouService<C> {
     }
 
     /// Encode a POU into clipboard bytes after validation.
+    /// Refused with `ValidationError` when the semantic validator reports errors.
     pub fn encode(&self, pou: &UniversalPou) -> Result<Vec<u8>> {
         validate_pou(pou)?;
+        let report = self.validator.validate(pou);
+        if !report.is_valid() {
+            return Err(ValidationError(report).into());
+        }
         self.codec.encode(pou)
     }
 
@@ -0,0 +1,542 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use encoding_rs::GBK;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::adapters::hollysys::PlcVariant;
+use crate::ast::{ElementType, LdElement, Network, UniversalPou, Variable, VariableNode};
+use crate::symbols_config::SymbolConfig;
+
+/// Elementary IEC 61131-3 types accepted by AutoThink variable tables.
+const ELEMENTARY_TYPES: &[&str] = &[
+    "BOOL", "BYTE", "WORD", "DWORD", "LWORD", "SINT", "INT", "DINT", "LINT", "USINT", "UINT", "UDINT", "ULINT",
+    "REAL", "LREAL", "TIME", "DATE", "TIME_OF_DAY", "TOD", "DATE_AND_TIME", "DT", "STRING", "WSTRING",
+];
+
+/// Pins every box carries regardless of its definition.
+const IMPLICIT_PINS: &[&str] = &["EN", "ENO"];
+
+/// Placeholders the editor writes for unconnected pins.
+const UNCONNECTED_PIN: &[&str] = &["", "???"];
+
+/// Name limits enforced before encoding (byte lengths are measured in GBK).
+#[derive(Debug, Clone, Copy)]
+pub struct NameLimits {
+    /// POU, variable and instance names.
+    pub max_identifier_bytes: usize,
+    /// Element/variable comments and network labels/comments.
+    pub max_comment_bytes: usize,
+}
+
+impl Default for NameLimits {
+    fn default() -> Self {
+        Self { max_identifier_bytes: 64, max_comment_bytes: 200 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Severity {
+    /// The payload would be rejected or crash the target software.
+    Error,
+    /// Suspicious but encodable (e.g. a type or block the validator does not know).
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Rule {
+    InvalidName,
+    UnknownVariable,
+    DuplicateElementId,
+    DanglingConnection,
+    PinMismatch,
+    PinTypeMismatch,
+    UnknownFunctionBlock,
+    UnsupportedFunctionBlock,
+    InstanceMismatch,
+    MissingSafetyTopology,
+    UnknownDataType,
+}
+
+/// Where a violation was found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub network_id: Option<i32>,
+    pub element_id: Option<i32>,
+    pub pin: Option<String>,
+    pub variable: Option<String>,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(id) = self.network_id {
+            parts.push(format!("network#{}", id));
+        }
+        if let Some(id) = self.element_id {
+            parts.push(format!("element#{}", id));
+        }
+        if let Some(pin) = &self.pin {
+            parts.push(format!("pin {}", pin));
+        }
+        if let Some(name) = &self.variable {
+            parts.push(format!("variable {}", name));
+        }
+        if parts.is_empty() {
+            return write!(f, "pou");
+        }
+        write!(f, "{}", parts.join("/"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub severity: Severity,
+    pub rule: Rule,
+    pub location: Location,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:?}] {}: {}", self.severity, self.location, self.message)
+    }
+}
+
+/// All violations found in one POU.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// True when there are no `Error` violations (warnings are allowed).
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Violation> {
+        self.violations.iter().filter(|v| v.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Violation> {
+        self.violations.iter().filter(|v| v.severity == Severity::Warning)
+    }
+}
+
+/// Returned (inside `anyhow::Error`) when encoding is refused; downcast to inspect the report.
+#[derive(Debug, Clone, Error)]
+#[error("POU validation failed with {} error(s): {}", .0.errors().count(), first_error(.0))]
+pub struct ValidationError(pub ValidationReport);
+
+fn first_error(report: &ValidationReport) -> String {
+    report.errors().next().map(ToString::to_string).unwrap_or_default()
+}
+
+/// Rule-based semantic validator run before encoding.
+/// Collects every violation instead of stopping at the first one.
+#[derive(Debug, Clone, Default)]
+pub struct PouValidator {
+    /// Function block library (pins, instance kind, allowed variants).
+    symbols: SymbolConfig,
+    /// Target variant; enables variant specific rules (Safety topology).
+    variant: Option<PlcVariant>,
+    /// Extra data types (user structs/enums) accepted besides elementary types and known blocks.
+    known_types: HashSet<String>,
+    /// Variables declared outside the POU (global variable table) that elements may reference.
+    /// `None` when the caller did not supply the table: undeclared references are then only warnings,
+    /// since decoded POUs routinely reference globals that are not part of the clipboard payload.
+    global_variables: Option<HashSet<String>>,
+    limits: NameLimits,
+}
+
+impl PouValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the function block library for pin, instance and data type checks.
+    pub fn with_symbols(mut self, config: &SymbolConfig) -> Self {
+        self.symbols = config.clone();
+        self
+    }
+
+    pub fn with_variant(mut self, variant: PlcVariant) -> Self {
+        self.variant = Some(variant);
+        self
+    }
+
+    pub fn with_known_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.known_types
+            .extend(types.into_iter().map(|name| name.into().to_ascii_uppercase()));
+        self
+    }
+
+    pub fn with_global_variables<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.global_variables.get_or_insert_default().extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_limits(mut self, limits: NameLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn validate(&self, pou: &UniversalPou) -> ValidationReport {
+        let mut ctx = Context { validator: self, violations: Vec::new() };
+        ctx.check_identifier(&pou.name, "POU name", Location::default());
+
+        let mut scope = VariableScope {
+            names: self.global_variables.iter().flatten().map(|name| (name.clone(), String::new())).collect(),
+        };
+        for node in &pou.variables {
+            ctx.collect_variables(node, None, &mut scope);
+        }
+        for network in &pou.networks {
+            ctx.check_network(network, &scope, pou.preserved.is_some());
+        }
+        ValidationReport { violations: ctx.violations }
+    }
+}
+
+/// Names that pins/contacts may reference, with their declared data type (empty when unknown).
+#[derive(Default)]
+struct VariableScope {
+    names: HashMap<String, String>,
+}
+
+impl VariableScope {
+    fn contains(&self, name: &str) -> bool {
+        if self.names.contains_key(name) {
+            return true;
+        }
+        // `inst.member`: the instance itself is declared, members come from its block type
+        name.split_once('.')
+            .is_some_and(|(instance, _)| self.names.contains_key(instance))
+    }
+
+    fn data_type(&self, name: &str) -> Option<&str> {
+        self.names.get(name).map(|ty| ty.trim()).filter(|ty| !ty.is_empty())
+    }
+}
+
+struct Context<'a> {
+    validator: &'a PouValidator,
+    violations: Vec<Violation>,
+}
+
+impl Context<'_> {
+    fn report(&mut self, severity: Severity, rule: Rule, location: Location, message: String) {
+        self.violations.push(Violation { severity, rule, location, message });
+    }
+
+    fn collect_variables(&mut self, node: &VariableNode, group: Option<&str>, scope: &mut VariableScope) {
+        match node {
+            VariableNode::Leaf(var) => {
+                scope.names.insert(var.name.clone(), var.data_type.clone());
+                if let Some(group) = group {
+                    scope.names.insert(format!("{}.{}", group, var.name), var.data_type.clone());
+                }
+                self.check_variable(var);
+            }
+            VariableNode::Group { name, type_name, children } => {
+                scope.names.insert(name.clone(), type_name.clone().unwrap_or_default());
+                for child in children {
+                    self.collect_variables(child, Some(name), scope);
+                }
+            }
+        }
+    }
+
+    fn check_variable(&mut self, var: &Variable) {
+        let location = Location { variable: Some(var.name.clone()), ..Default::default() };
+        // Block members are kept as `instance.member`; each segment must be a valid identifier
+        for segment in var.name.split('.') {
+            self.check_identifier(segment, "variable name", location.clone());
+        }
+        self.check_comment(&var.comment, "variable comment", location.clone());
+
+        let data_type = var.data_type.trim();
+        if data_type.is_empty() {
+            self.report(Severity::Error, Rule::UnknownDataType, location, "data type is empty".to_string());
+        } else if !self.is_known_type(data_type) {
+            self.report(
+                Severity::Warning,
+                Rule::UnknownDataType,
+                location,
+                format!("unknown data type '{}'", data_type),
+            );
+        }
+    }
+
+    /// Undeclared references are errors only when the global variable table is known.
+    fn unknown_variable_severity(&self) -> Severity {
+        if self.validator.global_variables.is_some() { Severity::Error } else { Severity::Warning }
+    }
+
+    /// `preserved`: the POU carries a lossless layout, which re-encodes the original token stream.
+    fn check_network(&mut self, network: &Network, scope: &VariableScope, preserved: bool) {
+        let location = Location { network_id: Some(network.id), ..Default::default() };
+        let tokenless_safety =
+            self.validator.variant == Some(PlcVariant::Safety) && network.safety_topology.is_empty();
+        // Losslessly decoded Safety networks carry no token stream: their connections may point at Or/And
+        // nodes the decoder does not surface as elements, and the layout writes the original stream back.
+        // Without a layout the serializer writes no rung topology at all, so both stay errors.
+        let topology_severity = if tokenless_safety && preserved { Severity::Warning } else { Severity::Error };
+        self.check_comment(&network.label, "network label", location.clone());
+        self.check_comment(&network.comment, "network comment", location.clone());
+
+        let mut ids = HashSet::new();
+        for elem in &network.elements {
+            if !ids.insert(elem.id) {
+                self.report(
+                    Severity::Error,
+                    Rule::DuplicateElementId,
+                    Location { element_id: Some(elem.id), ..location.clone() },
+                    format!("element id {} is used more than once in the network", elem.id),
+                );
+            }
+        }
+        for elem in &network.elements {
+            let location = Location { element_id: Some(elem.id), ..location.clone() };
+            for target in &elem.connections {
+                if *target != network.id && !ids.contains(target) {
+                    self.report(
+                        topology_severity,
+                        Rule::DanglingConnection,
+                        location.clone(),
+                        format!("connection references missing element id {}", target),
+                    );
+                }
+            }
+            self.check_comment(&elem.comment, "element comment", location.clone());
+            self.check_element(elem, scope, location);
+        }
+
+        if tokenless_safety && network.elements.len() > 1 {
+            let message = if preserved {
+                "only the lossless layout describes its rung"
+            } else {
+                "the encoder would write no rung topology"
+            };
+            self.report(
+                topology_severity,
+                Rule::MissingSafetyTopology,
+                location,
+                format!("Safety network with {} elements has no safety_topology; {}", network.elements.len(), message),
+            );
+        }
+    }
+
+    fn check_element(&mut self, elem: &LdElement, scope: &VariableScope, location: Location) {
+        match elem.type_code {
+            ElementType::Contact | ElementType::Coil => {
+                if elem.name.trim().is_empty() {
+                    self.report(
+                        Severity::Error,
+                        Rule::UnknownVariable,
+                        location,
+                        format!("{:?} is not bound to a variable", elem.type_code),
+                    );
+                } else if !scope.contains(&elem.name) && !is_literal(&elem.name) {
+                    self.report(
+                        self.unknown_variable_severity(),
+                        Rule::UnknownVariable,
+                        location,
+                        format!("variable '{}' is not declared", elem.name),
+                    );
+                }
+            }
+            ElementType::Box => self.check_box(elem, scope, location),
+            ElementType::Network | ElementType::Assign => {}
+        }
+    }
+
+    fn check_box(&mut self, elem: &LdElement, scope: &VariableScope, location: Location) {
+        if !elem.instance.is_empty() {
+            self.check_identifier(&elem.instance, "instance name", location.clone());
+        }
+
+        let symbols = &self.validator.symbols;
+        let block = symbols.get(&elem.name);
+        match block {
+            None if !symbols.is_empty() && !is_standard_block(&elem.name) => self.report(
+                Severity::Warning,
+                Rule::UnknownFunctionBlock,
+                location.clone(),
+                format!("function block '{}' is not defined in the symbol config", elem.name),
+            ),
+            None => {}
+            Some(fb) => {
+                if let Some(variant) = self.validator.variant
+                    && !fb.supports(variant)
+                {
+                    self.report(
+                        Severity::Error,
+                        Rule::UnsupportedFunctionBlock,
+                        location.clone(),
+                        format!("function block '{}' is not available on {:?} controllers", fb.name, variant),
+                    );
+                }
+                if fb.is_instance() && elem.instance.trim().is_empty() {
+                    self.report(
+                        Severity::Error,
+                        Rule::InstanceMismatch,
+                        location.clone(),
+                        format!("function block '{}' needs an instance name", fb.name),
+                    );
+                } else if !fb.is_instance() && !elem.instance.is_empty() {
+                    self.report(
+                        Severity::Warning,
+                        Rule::InstanceMismatch,
+                        location.clone(),
+                        format!("'{}' is stateless but has instance '{}'", fb.name, elem.instance),
+                    );
+                }
+            }
+        }
+
+        for pin in &elem.pins {
+            let location = Location { pin: Some(pin.name.clone()), ..location.clone() };
+            let variable = pin.variable.trim();
+            if let Some(fb) = block
+                && !pin.name.is_empty()
+                && !IMPLICIT_PINS.contains(&pin.name.as_str())
+            {
+                match fb.pin(&pin.name) {
+                    Some((_, direction)) if direction != pin.direction => self.report(
+                        Severity::Warning,
+                        Rule::PinMismatch,
+                        location.clone(),
+                        format!("pin '{}' of '{}' is an {:?} pin", pin.name, fb.name, direction),
+                    ),
+                    Some((def, _)) => {
+                        let expected = def.data_type.trim();
+                        if let Some(actual) = scope.data_type(variable)
+                            && !expected.is_empty()
+                            && !expected.to_ascii_uppercase().starts_with("ANY")
+                            && !actual.eq_ignore_ascii_case(expected)
+                        {
+                            self.report(
+                                Severity::Warning,
+                                Rule::PinTypeMismatch,
+                                location.clone(),
+                                format!("pin '{}' expects {} but '{}' is {}", pin.name, expected, variable, actual),
+                            );
+                        }
+                    }
+                    // Legacy definitions only list member names without pin directions
+                    None if fb.member_names().any(|name| name == pin.name) => {}
+                    None => self.report(
+                        Severity::Warning,
+                        Rule::PinMismatch,
+                        location.clone(),
+                        format!("pin '{}' is not a member of '{}'", pin.name, fb.name),
+                    ),
+                }
+            }
+            if !UNCONNECTED_PIN.contains(&variable) && !is_literal(variable) && !scope.contains(variable) {
+                self.report(
+                    self.unknown_variable_severity(),
+                    Rule::UnknownVariable,
+                    location,
+                    format!("variable '{}' is not declared", variable),
+                );
+            }
+        }
+    }
+
+    fn check_identifier(&mut self, name: &str, what: &str, location: Location) {
+        if name.trim().is_empty() {
+            self.report(Severity::Error, Rule::InvalidName, location, format!("{} is empty", what));
+            return;
+        }
+        let max = self.validator.limits.max_identifier_bytes;
+        let len = gbk_len(name);
+        if len > max {
+            self.report(
+                Severity::Error,
+                Rule::InvalidName,
+                location.clone(),
+                format!("{} '{}' is {} bytes, limit is {}", what, name, len, max),
+            );
+        }
+        if !is_identifier(name) {
+            self.report(
+                Severity::Error,
+                Rule::InvalidName,
+                location,
+                format!("{} '{}' must start with a letter or '_' and contain only letters, digits and '_'", what, name),
+            );
+        }
+    }
+
+    fn check_comment(&mut self, text: &str, what: &str, location: Location) {
+        let max = self.validator.limits.max_comment_bytes;
+        let len = gbk_len(text);
+        if len > max {
+            self.report(
+                Severity::Error,
+                Rule::InvalidName,
+                location,
+                format!("{} is {} bytes, limit is {}", what, len, max),
+            );
+        }
+    }
+
+    fn is_known_type(&self, data_type: &str) -> bool {
+        let upper = data_type.to_ascii_uppercase();
+        // STRING(80) / ARRAY[0..9] OF INT -> check the base/element type
+        let base = match upper.split_once(" OF ") {
+            Some((prefix, element)) if prefix.starts_with("ARRAY") => element.trim(),
+            _ => upper.split('(').next().unwrap_or(&upper).trim(),
+        };
+        ELEMENTARY_TYPES.contains(&base)
+            || self.validator.known_types.contains(base)
+            || self.validator.symbols.get(base).is_some()
+            || is_standard_block(base)
+    }
+}
+
+/// Standard IEC blocks available in every AutoThink project.
+fn is_standard_block(name: &str) -> bool {
+    const STANDARD: &[&str] = &[
+        "MOVE", "ADD", "SUB", "MUL", "DIV", "MOD", "AND", "OR", "XOR", "NOT", "EQ", "NE", "GT", "GE", "LT", "LE",
+        "SEL", "MAX", "MIN", "LIMIT", "MUX", "SHL", "SHR", "ROL", "ROR", "TON", "TOF", "TP", "CTU", "CTD", "CTUD",
+        "R_TRIG", "F_TRIG", "SR", "RS",
+    ];
+    STANDARD.iter().any(|block| block.eq_ignore_ascii_case(name))
+}
+
+/// IEC identifier; GBK (non-ASCII) characters are accepted as letters.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    let is_letter = |c: char| c == '_' || c.is_ascii_alphabetic() || !c.is_ascii();
+    is_letter(first) && chars.all(|c| is_letter(c) || c.is_ascii_digit())
+}
+
+/// Constants allowed where a variable is expected: TRUE/FALSE, numbers, typed literals (T#3S, 16#FF), strings.
+fn is_literal(text: &str) -> bool {
+    let text = text.trim();
+    text.eq_ignore_ascii_case("TRUE")
+        || text.eq_ignore_ascii_case("FALSE")
+        || text.starts_with('\'')
+        || text.contains('#')
+        || text.replace('_', "").parse::<f64>().is_ok()
+}
+
+fn gbk_len(text: &str) -> usize {
+    GBK.encode(text).0.len()
+}
@@ -15,7 +15,11 @@ pub struct UniversalPou{
     #[serde(default)]
     pub variables:Vec<VariableNode>,
     ///梯形图逻辑网络列表
-    pub networks:Vec<Network>
+    pub networks:Vec<Network>,
+    /// 无损模式下记录的原始字节布局（仅由无损解码生成）
+    /// 序列化器据此逐段回写，未修改的对象保持字节级一致
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preserved:Option<PreservedLayout>,
 }
 
 #[derive(Serialize, Deserialize, Debug,Clone)]
@@ -46,6 +50,25 @@ pub struct Variable{
     pub area_code:Option<u8>,
 }
 
+impl Variable {
+    /// 只给出名称与类型的变量，其余字段取默认值、ID 类字段留给序列化器分配
+    pub fn new(name: impl Into<String>, data_type: impl Into<String>) -> Self {
+        Variable {
+            name: name.into(),
+            data_type: data_type.into(),
+            init_value: String::new(),
+            soe_enable: false,
+            power_down_keep: false,
+            comment: String::new(),
+            var_id: None,
+            addr_id: None,
+            mode: None,
+            id2: None,
+            area_code: None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug,Clone)]
 #[serde(untagged)]
 pub enum VariableNode{
@@ -94,6 +117,15 @@ pub struct BoxPin{
     /// 默认输入，兼容旧数据（未提供方向时不会反序列化失败）
     #[serde(default)]
     pub direction: PinDirection,
+    /// 无损模式下保留的引脚未知字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preserved: Option<PinExtras>,
+}
+
+impl BoxPin {
+    pub fn new(name: impl Into<String>, variable: impl Into<String>, direction: PinDirection) -> Self {
+        BoxPin { name: name.into(), variable: variable.into(), direction, preserved: None }
+    }
 }
 
 /// 功能块引脚方向
@@ -143,6 +175,172 @@ pub struct LdElement{
     /// 1 = 常闭/取反线圈
     #[serde(default)]
     pub sub_type: u8,
+
+    /// 无损模式下保留的元件未知字段（几何串、版本字段、标志位等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preserved: Option<ElementExtras>,
+}
+
+impl LdElement {
+    /// 无实例、无引脚、无连接的元件，按需再填充其余字段
+    pub fn new(id: i32, type_code: ElementType, name: impl Into<String>) -> Self {
+        LdElement {
+            id,
+            type_code,
+            name: name.into(),
+            comment: String::new(),
+            desc: String::new(),
+            instance: String::new(),
+            pins: Vec::new(),
+            connections: Vec::new(),
+            sub_type: 0,
+            preserved: None,
+        }
+    }
+}
+
+/// 元件中解析器尚未语义化的字段
+/// 仅用于无损回写；为 None 的字段由序列化器填默认值
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ElementExtras {
+    /// 原始 type_id（CLDElement 基础字段）
+    #[serde(default)]
+    pub type_id: Option<u8>,
+    /// Normal 触点/线圈尾部的几何串（含 MFC 长度前缀的原始字节）
+    #[serde(default, with = "hex_bytes_opt", skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<Vec<u8>>,
+    /// Normal Box 的两个版本字段（serialize_version >= 6）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub box_version: Option<[u32; 2]>,
+    /// Box 的实例标志字节
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub box_flag: Option<u8>,
+    /// 线圈的 flag2 / flag3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coil_flag2: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coil_flag3: Option<u8>,
+}
+
+/// Normal 功能块引脚的未知字段
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PinExtras {
+    pub flag0: u8,
+    pub flag1: u8,
+    /// 输入引脚的绑定 ID（serialize_version >= 13）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_id: Option<u32>,
+}
+
+/// 无损解码得到的 POU 字节布局
+/// 整个 POU 被切分为首尾相接的段，拼接全部段即得到原始字节
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PreservedLayout {
+    /// 生成该布局的编码器标识（如 "hollysys-normal"），不匹配时布局作废
+    pub codec: String,
+    pub serialize_version: u32,
+    pub segments: Vec<PreservedSegment>,
+}
+
+/// 布局段
+/// - fingerprint: 解码时对应语义内容的指纹；编码时指纹不变则原样输出 bytes
+/// - body / fields: 段内偏移，用于只重写语义部分、保留对象头
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind")]
+pub enum PreservedSegment {
+    /// 未识别的字节，原样输出
+    Raw {
+        #[serde(with = "hex_bytes")]
+        bytes: Vec<u8>,
+    },
+    /// 末尾零填充：补零直到总长度
+    Padding { total_len: usize },
+    /// 头部中的 POU 名称串
+    Name {
+        fingerprint: String,
+        align4: bool,
+        #[serde(with = "hex_bytes")]
+        bytes: Vec<u8>,
+    },
+    /// 头部 CStringArray
+    HeaderStrings {
+        fingerprint: String,
+        count_width: u8,
+        #[serde(with = "hex_bytes")]
+        bytes: Vec<u8>,
+    },
+    /// 网络对象（CLDNetwork）
+    Network {
+        id: i32,
+        fingerprint: String,
+        body: usize,
+        fields: usize,
+        #[serde(with = "hex_bytes")]
+        bytes: Vec<u8>,
+    },
+    /// 元件对象，network 为解码时所属网络
+    Element {
+        id: i32,
+        network: i32,
+        fingerprint: String,
+        body: usize,
+        #[serde(with = "hex_bytes")]
+        bytes: Vec<u8>,
+    },
+    /// 变量表主体
+    Variables {
+        fingerprint: String,
+        #[serde(with = "hex_bytes")]
+        bytes: Vec<u8>,
+    },
+}
+
+/// 字节数组以十六进制字符串序列化，避免 JSON 中出现超长数字数组
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(d)?;
+        decode(&text).map_err(serde::de::Error::custom)
+    }
+
+    pub(super) fn encode(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    pub(super) fn decode(text: &str) -> Result<Vec<u8>, String> {
+        if !text.is_ascii() || !text.len().is_multiple_of(2) {
+            return Err(format!("非法十六进制串（长度 {}）", text.len()));
+        }
+        (0..text.len())
+            .step_by(2)
+            .map(|i| {
+                u8::from_str_radix(&text[i..i + 2], 16)
+                    .map_err(|e| format!("非法十六进制 @{}: {}", i, e))
+            })
+            .collect()
+    }
+}
+
+mod hex_bytes_opt {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(b) => s.serialize_some(&super::hex_bytes::encode(b)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u8>>, D::Error> {
+        let text = Option::<String>::deserialize(d)?;
+        text.map(|t| super::hex_bytes::decode(&t).map_err(serde::de::Error::custom))
+            .transpose()
+    }
 }
 
 /// Safety 拓扑 Token（递归 Token 流）
@@ -1,3 +1,518 @@
 /*
-通用硬件树 (Rack, Slot, Module)
-*/
\ No newline at end of file
+通用硬件树（厂商无关）
+控制器 → 机架 → 槽位 → 模块 → 通道；通讯从站（Modbus TCP/RTU）挂在控制器下，按 读取命令 → 通道 组织
+- 通道位号沿用点表格式 `机架_槽_类型_通道`（如 `1_2_AI_0`，也接受 `R1-S3-DO-07`）
+- validate 一次列出全部问题：机架/槽位冲突、模块通道数无效、通道超出模块容量、通道类型不符、点位重复绑定、从站参数越界
+- bind_points 把 IO 点位（变量名 + 通道位号）挂到对应通道；模块已组态但通道未列出时自动补齐
+厂商组态格式（如和利时剪贴板）由 adapters 负责与本模型互转
+*/
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 硬件组态问题
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HardwareError {
+    #[error("机架号重复: {0}")]
+    DuplicateRack(u16),
+    #[error("机架 {rack} 槽位 {slot} 超出槽位数 {slot_count}")]
+    SlotOutOfRange { rack: u16, slot: u16, slot_count: u16 },
+    #[error("机架 {rack} 槽位 {slot} 被多个模块占用")]
+    SlotCollision { rack: u16, slot: u16 },
+    #[error("机架 {rack} 槽位 {slot} 的 {module_type} 模块通道数 {channel_count} 无效（IO 模块至少 1 个通道，其余模块没有通道）")]
+    InvalidChannelCount { rack: u16, slot: u16, module_type: ModuleType, channel_count: u16 },
+    #[error("通道 {0} 超出模块通道数")]
+    ChannelOutOfRange(ChannelAddress),
+    #[error("通道 {0} 重复组态")]
+    DuplicateChannel(ChannelAddress),
+    #[error("通道 {address} 所在模块类型为 {module_type}")]
+    ChannelKindMismatch { address: ChannelAddress, module_type: ModuleType },
+    #[error("通道位号无法识别: {0}")]
+    InvalidChannelAddress(String),
+    #[error("通道 {0} 所在槽位没有组态模块")]
+    ModuleNotFound(ChannelAddress),
+    #[error("通道 {address} 已绑定点位 {point}")]
+    ChannelAlreadyBound { address: ChannelAddress, point: String },
+    #[error("点位 {0} 绑定了多个通道")]
+    DuplicatePoint(String),
+    #[error("通讯从站名称重复: {0}")]
+    DuplicateSlave(String),
+    #[error("通讯从站 {slave} 的站号 {id} 超出 1..=247")]
+    InvalidSlaveId { slave: String, id: u8 },
+    #[error("通讯从站 {slave} 的读取命令 {order} 功能码 {code} 不受支持")]
+    InvalidFunctionCode { slave: String, order: String, code: u8 },
+    #[error("通讯从站 {slave} 的读取命令 {order} 长度超出 1..={max}")]
+    InvalidOrderLength { slave: String, order: String, max: u16 },
+    #[error("通讯从站 {slave} 的通道 {channel} 超出读取命令 {order} 的地址范围")]
+    ChannelOutsideOrder { slave: String, order: String, channel: String },
+}
+
+/// IO 通道类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ChannelKind {
+    AI,
+    AO,
+    DI,
+    DO,
+}
+
+impl ChannelKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChannelKind::AI => "AI",
+            ChannelKind::AO => "AO",
+            ChannelKind::DI => "DI",
+            ChannelKind::DO => "DO",
+        }
+    }
+
+    /// 识别类型标识（不区分大小写）
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "AI" => Some(ChannelKind::AI),
+            "AO" => Some(ChannelKind::AO),
+            "DI" => Some(ChannelKind::DI),
+            "DO" => Some(ChannelKind::DO),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ChannelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 模块类型；IO 模块的通道类型与模块类型一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ModuleType {
+    AI,
+    AO,
+    DI,
+    DO,
+    Cpu,
+    Power,
+    Comm,
+    Other,
+}
+
+impl ModuleType {
+    pub fn channel_kind(self) -> Option<ChannelKind> {
+        match self {
+            ModuleType::AI => Some(ChannelKind::AI),
+            ModuleType::AO => Some(ChannelKind::AO),
+            ModuleType::DI => Some(ChannelKind::DI),
+            ModuleType::DO => Some(ChannelKind::DO),
+            _ => None,
+        }
+    }
+
+    /// 常见模块的通道数（模拟量 8、数字量 16，非 IO 模块无通道），仅作 Module::new 的默认值；
+    /// 实际容量随型号而定（4/12/32 通道卡件等），以 Module::channel_count 为准
+    pub fn default_channel_count(self) -> u16 {
+        match self {
+            ModuleType::AI | ModuleType::AO => 8,
+            ModuleType::DI | ModuleType::DO => 16,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for ModuleType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.channel_kind() {
+            Some(kind) => f.write_str(kind.as_str()),
+            None => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// 通道位号：机架 / 槽位 / 类型 / 通道号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChannelAddress {
+    pub rack: u16,
+    pub slot: u16,
+    pub kind: ChannelKind,
+    pub index: u16,
+}
+
+impl fmt::Display for ChannelAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}_{}_{}", self.rack, self.slot, self.kind, self.index)
+    }
+}
+
+impl FromStr for ChannelAddress {
+    type Err = HardwareError;
+
+    /// 解析 `1_2_AI_0`、`R1-S3-DO-07` 等格式
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || HardwareError::InvalidChannelAddress(text.to_string());
+        let parts: Vec<&str> = text.trim().split(['_', '-', '.', ' ']).filter(|s| !s.is_empty()).collect();
+        let [rack, slot, kind, index] = parts[..] else { return Err(invalid()) };
+        let number = |part: &str, prefix: char| {
+            part.strip_prefix([prefix, prefix.to_ascii_lowercase()]).unwrap_or(part).parse::<u16>().ok()
+        };
+        Ok(Self {
+            rack: number(rack, 'R').ok_or_else(invalid)?,
+            slot: number(slot, 'S').ok_or_else(invalid)?,
+            kind: ChannelKind::from_code(kind).ok_or_else(invalid)?,
+            index: number(index, 'C').ok_or_else(invalid)?,
+        })
+    }
+}
+
+/// IO 通道
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Channel {
+    pub index: u16,
+    pub kind: ChannelKind,
+    /// 绑定的点位（变量名）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point: Option<String>,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// 模块
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Module {
+    /// 厂商型号，如 LK411
+    pub model: String,
+    pub module_type: ModuleType,
+    /// 通道容量（由型号决定）；通道号须小于该值
+    pub channel_count: u16,
+    /// 已组态的通道（可少于 channel_count）
+    #[serde(default)]
+    pub channels: Vec<Channel>,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Module {
+    /// 按模块类型的常见通道数创建；型号容量不同时用 with_channel_count 指定
+    pub fn new(model: impl Into<String>, module_type: ModuleType) -> Self {
+        Self {
+            model: model.into(),
+            module_type,
+            channel_count: module_type.default_channel_count(),
+            channels: Vec::new(),
+            description: String::new(),
+        }
+    }
+
+    /// 指定型号的通道容量；IO 模块为 0 或非 IO 模块非 0 时 validate 报 InvalidChannelCount
+    pub fn with_channel_count(mut self, channel_count: u16) -> Self {
+        self.channel_count = channel_count;
+        self
+    }
+}
+
+/// 槽位
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Slot {
+    pub index: u16,
+    pub module: Module,
+}
+
+/// 机架；槽位号从 0 开始，小于 slot_count
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rack {
+    pub index: u16,
+    pub slot_count: u16,
+    #[serde(default)]
+    pub slots: Vec<Slot>,
+}
+
+/// 串口校验
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Parity {
+    None,
+    Odd,
+    Even,
+}
+
+/// 从站链路参数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "protocol")]
+pub enum SlaveLink {
+    ModbusTcp { ip: String, port: u16, unit_id: u8 },
+    ModbusRtu { port: String, baud_rate: u32, parity: Parity, data_bits: u8, stop_bits: u8, slave_id: u8 },
+}
+
+/// 通讯通道数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CommValueType {
+    Bool,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+}
+
+impl CommValueType {
+    /// 占用的寄存器数（位数据按 1 个线圈计）
+    pub fn width(self) -> u16 {
+        match self {
+            CommValueType::Int32 | CommValueType::UInt32 | CommValueType::Float32 => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// 从站通道：读取命令数据区中的一个值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommChannel {
+    pub name: String,
+    /// 相对读取命令起始地址的偏移（寄存器/线圈）
+    pub offset: u16,
+    pub value_type: CommValueType,
+    /// 寄存器内的位号（位取值时使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point: Option<String>,
+}
+
+/// 读取命令（一次批量读取）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommOrder {
+    pub name: String,
+    /// Modbus 读功能码 1~4
+    pub function_code: u8,
+    pub start_address: u16,
+    pub length: u16,
+    pub scan_period_ms: u32,
+    #[serde(default)]
+    pub channels: Vec<CommChannel>,
+}
+
+impl CommOrder {
+    /// 单次读取的最大长度（线圈 2000、寄存器 125）
+    pub fn max_length(&self) -> u16 {
+        if matches!(self.function_code, 1 | 2) { 2000 } else { 125 }
+    }
+}
+
+/// 通讯从站
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommSlave {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub link: SlaveLink,
+    pub timeout_ms: u32,
+    pub retry_count: u32,
+    #[serde(default)]
+    pub orders: Vec<CommOrder>,
+    #[serde(default)]
+    pub description: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 控制器：硬件树的根
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Controller {
+    pub name: String,
+    pub model: String,
+    #[serde(default)]
+    pub racks: Vec<Rack>,
+    #[serde(default)]
+    pub slaves: Vec<CommSlave>,
+}
+
+impl Controller {
+    pub fn module(&self, rack: u16, slot: u16) -> Option<&Module> {
+        let rack = self.racks.iter().find(|r| r.index == rack)?;
+        rack.slots.iter().find(|s| s.index == slot).map(|s| &s.module)
+    }
+
+    pub fn module_mut(&mut self, rack: u16, slot: u16) -> Option<&mut Module> {
+        let rack = self.racks.iter_mut().find(|r| r.index == rack)?;
+        rack.slots.iter_mut().find(|s| s.index == slot).map(|s| &mut s.module)
+    }
+
+    pub fn channel(&self, address: &ChannelAddress) -> Option<&Channel> {
+        let module = self.module(address.rack, address.slot)?;
+        module.channels.iter().find(|c| c.index == address.index && c.kind == address.kind)
+    }
+
+    /// 已绑定点位的通道，按机架/槽位/通道顺序
+    pub fn bound_points(&self) -> Vec<(&str, ChannelAddress)> {
+        let mut bound = Vec::new();
+        for rack in &self.racks {
+            for slot in &rack.slots {
+                for channel in &slot.module.channels {
+                    if let Some(point) = &channel.point {
+                        let address = ChannelAddress {
+                            rack: rack.index,
+                            slot: slot.index,
+                            kind: channel.kind,
+                            index: channel.index,
+                        };
+                        bound.push((point.as_str(), address));
+                    }
+                }
+            }
+        }
+        bound.sort_by_key(|(_, a)| (a.rack, a.slot, a.index));
+        bound
+    }
+
+    /// 按通道位号绑定点位（变量名, 通道位号）；返回未能绑定的点位问题，其余点位照常绑定
+    pub fn bind_points<'a>(&mut self, points: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<HardwareError> {
+        let mut errors = Vec::new();
+        for (point, channel) in points {
+            if let Err(err) = channel.parse().and_then(|address| self.bind_point(point, address)) {
+                errors.push(err);
+            }
+        }
+        errors
+    }
+
+    /// 绑定单个点位；通道未组态时按模块容量补齐
+    pub fn bind_point(&mut self, point: &str, address: ChannelAddress) -> Result<(), HardwareError> {
+        let module = self.module_mut(address.rack, address.slot).ok_or(HardwareError::ModuleNotFound(address))?;
+        if module.module_type.channel_kind() != Some(address.kind) {
+            return Err(HardwareError::ChannelKindMismatch { address, module_type: module.module_type });
+        }
+        if address.index >= module.channel_count {
+            return Err(HardwareError::ChannelOutOfRange(address));
+        }
+        let channel = match module.channels.iter().position(|c| c.index == address.index) {
+            Some(pos) => &mut module.channels[pos],
+            None => {
+                let pos = module.channels.partition_point(|c| c.index < address.index);
+                let channel =
+                    Channel { index: address.index, kind: address.kind, point: None, description: String::new() };
+                module.channels.insert(pos, channel);
+                &mut module.channels[pos]
+            }
+        };
+        match &channel.point {
+            Some(bound) if !bound.eq_ignore_ascii_case(point) => {
+                Err(HardwareError::ChannelAlreadyBound { address, point: bound.clone() })
+            }
+            _ => {
+                channel.point = Some(point.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// 检查整棵硬件树，返回全部问题（空表示通过）
+    pub fn validate(&self) -> Vec<HardwareError> {
+        let mut errors = Vec::new();
+        let mut racks = HashSet::new();
+        let mut points: HashMap<String, usize> = HashMap::new();
+        for rack in &self.racks {
+            if !racks.insert(rack.index) {
+                errors.push(HardwareError::DuplicateRack(rack.index));
+            }
+            let mut slots = HashSet::new();
+            for slot in &rack.slots {
+                if slot.index >= rack.slot_count {
+                    errors.push(HardwareError::SlotOutOfRange {
+                        rack: rack.index,
+                        slot: slot.index,
+                        slot_count: rack.slot_count,
+                    });
+                }
+                if !slots.insert(slot.index) {
+                    errors.push(HardwareError::SlotCollision { rack: rack.index, slot: slot.index });
+                }
+                validate_module(rack.index, slot, &mut errors);
+                for point in slot.module.channels.iter().filter_map(|c| c.point.as_ref()) {
+                    *points.entry(point.to_ascii_uppercase()).or_default() += 1;
+                }
+            }
+        }
+        for slave in &self.slaves {
+            for point in slave.orders.iter().flat_map(|o| &o.channels).filter_map(|c| c.point.as_ref()) {
+                *points.entry(point.to_ascii_uppercase()).or_default() += 1;
+            }
+        }
+        let mut duplicated: Vec<String> = points.into_iter().filter(|(_, n)| *n > 1).map(|(p, _)| p).collect();
+        duplicated.sort();
+        errors.extend(duplicated.into_iter().map(HardwareError::DuplicatePoint));
+        validate_slaves(&self.slaves, &mut errors);
+        errors
+    }
+}
+
+fn validate_module(rack: u16, slot: &Slot, errors: &mut Vec<HardwareError>) {
+    let module = &slot.module;
+    let expected = module.module_type.channel_kind();
+    // 容量取自模块本身；只拒绝不可能的取值
+    if (module.channel_count == 0) == expected.is_some() {
+        errors.push(HardwareError::InvalidChannelCount {
+            rack,
+            slot: slot.index,
+            module_type: module.module_type,
+            channel_count: module.channel_count,
+        });
+    }
+    let mut indices = HashSet::new();
+    for channel in &module.channels {
+        let address = ChannelAddress { rack, slot: slot.index, kind: channel.kind, index: channel.index };
+        if expected != Some(channel.kind) {
+            errors.push(HardwareError::ChannelKindMismatch { address, module_type: module.module_type });
+        } else if channel.index >= module.channel_count {
+            errors.push(HardwareError::ChannelOutOfRange(address));
+        }
+        if !indices.insert(channel.index) {
+            errors.push(HardwareError::DuplicateChannel(address));
+        }
+    }
+}
+
+fn validate_slaves(slaves: &[CommSlave], errors: &mut Vec<HardwareError>) {
+    let mut names = HashSet::new();
+    for slave in slaves {
+        if !names.insert(slave.name.as_str()) {
+            errors.push(HardwareError::DuplicateSlave(slave.name.clone()));
+        }
+        let id = match &slave.link {
+            SlaveLink::ModbusTcp { unit_id, .. } => *unit_id,
+            SlaveLink::ModbusRtu { slave_id, .. } => *slave_id,
+        };
+        if !(1..=247).contains(&id) {
+            errors.push(HardwareError::InvalidSlaveId { slave: slave.name.clone(), id });
+        }
+        for order in &slave.orders {
+            if !(1..=4).contains(&order.function_code) {
+                errors.push(HardwareError::InvalidFunctionCode {
+                    slave: slave.name.clone(),
+                    order: order.name.clone(),
+                    code: order.function_code,
+                });
+            }
+            if order.length == 0 || order.length > order.max_length() {
+                errors.push(HardwareError::InvalidOrderLength {
+                    slave: slave.name.clone(),
+                    order: order.name.clone(),
+                    max: order.max_length(),
+                });
+            }
+            for channel in &order.channels {
+                if channel.offset as u32 + channel.value_type.width() as u32 > order.length as u32 {
+                    errors.push(HardwareError::ChannelOutsideOrder {
+                        slave: slave.name.clone(),
+                        order: order.name.clone(),
+                        channel: channel.name.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
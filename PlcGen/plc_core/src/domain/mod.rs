@@ -1,2 +1,3 @@
 pub mod ast;
-pub mod hardware;
\ No newline at end of file
+pub mod hardware;
+pub mod topology;
@@ -0,0 +1,384 @@
+/*
+梯级拓扑图（厂商无关的串/并联结构）
+- Normal 以元件 connections（“本元件 → 下游元件”的有向边）描述拓扑
+- Safety 以 0x80xx Token 流（BranchOpen/BranchNext/BranchClose/SeriesNext/NetEnd）描述拓扑
+两者都可还原为同一棵串/并联树，再按目标形态输出，实现 Normal ↔ Safety 网络互转
+*/
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::ast::{LdElement, Network, SafetyTopologyToken};
+
+/// 拓扑转换错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TopologyError {
+    #[error("拓扑 Token 引用的元件 ID 超出范围: {0}")]
+    InvalidElementId(u32),
+    #[error("拓扑引用了不存在的元件: id={0}")]
+    UnknownElement(i32),
+    #[error("连接图存在环路: 元件 id={0}")]
+    Cycle(i32),
+    #[error("连接图不是串并联结构（元件 id={0} 无法唯一归入梯级）")]
+    NotSeriesParallel(i32),
+    #[error("拓扑 Token 流分支不匹配（位置 {0}）")]
+    UnbalancedBranch(usize),
+}
+
+/// 梯级节点：单个元件或若干并联分支（每个分支为串联序列）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
+pub enum RungNode {
+    Element(i32),
+    Parallel(Vec<Vec<RungNode>>),
+}
+
+/// 网络拓扑的目标形态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TopologyForm {
+    /// 元件 connections 图（Normal）
+    Connections,
+    /// 0x80xx Token 流（Safety）
+    Tokens,
+}
+
+/// 梯级：从左母线开始的串联序列
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rung {
+    pub series: Vec<RungNode>,
+}
+
+impl Rung {
+    /// 按网络现有的拓扑描述构建：Token 流优先，其次 connections 图，两者皆无时按声明顺序串联
+    pub fn from_network(net: &Network) -> Result<Self, TopologyError> {
+        if !net.safety_topology.is_empty() {
+            Self::from_tokens(&net.safety_topology)
+        } else if has_internal_connections(&net.elements) {
+            Self::from_connections(&net.elements)
+        } else {
+            Ok(Self::in_declaration_order(&net.elements))
+        }
+    }
+
+    /// 元件按声明顺序串联
+    pub fn in_declaration_order(elements: &[LdElement]) -> Self {
+        Self { series: elements.iter().map(|e| RungNode::Element(e.id)).collect() }
+    }
+
+    /// 由 Safety Token 流还原（SeriesNext/Raw 不影响结构，NetEnd 之后的 Token 忽略）
+    pub fn from_tokens(tokens: &[SafetyTopologyToken]) -> Result<Self, TopologyError> {
+        let mut idx = 0usize;
+        let series = parse_series(tokens, &mut idx)?;
+        match tokens.get(idx) {
+            Some(SafetyTopologyToken::BranchNext | SafetyTopologyToken::BranchClose) => {
+                Err(TopologyError::UnbalancedBranch(idx))
+            }
+            _ => Ok(Self { series }),
+        }
+    }
+
+    /// 由 connections 图还原
+    /// 每个元件的能流 = 其所有前驱能流的并联 + 自身；无前驱的元件挂在左母线上，
+    /// 无后继的元件（汇点）并联组成整条梯级，公共前缀提到分支之外
+    pub fn from_connections(elements: &[LdElement]) -> Result<Self, TopologyError> {
+        let ids: HashSet<i32> = elements.iter().map(|e| e.id).collect();
+        let mut preds: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut has_succ: HashSet<i32> = HashSet::new();
+        for elem in elements {
+            for dst in &elem.connections {
+                if *dst == elem.id || !ids.contains(dst) {
+                    continue;
+                }
+                let entry = preds.entry(*dst).or_default();
+                if !entry.contains(&elem.id) {
+                    entry.push(elem.id);
+                }
+                has_succ.insert(elem.id);
+            }
+        }
+
+        let mut memo: HashMap<i32, Vec<RungNode>> = HashMap::new();
+        let mut visiting: HashSet<i32> = HashSet::new();
+        let mut sinks = Vec::new();
+        for elem in elements {
+            if !has_succ.contains(&elem.id) {
+                sinks.push(power_out(elem.id, &preds, &mut memo, &mut visiting)?);
+            }
+        }
+        if sinks.is_empty()
+            && let Some(elem) = elements.first()
+        {
+            return Err(TopologyError::Cycle(elem.id));
+        }
+
+        let rung = Self { series: factor(sinks) };
+        let mut seen = HashSet::new();
+        for id in rung.element_ids() {
+            if !seen.insert(id) {
+                return Err(TopologyError::NotSeriesParallel(id));
+            }
+        }
+        Ok(rung)
+    }
+
+    /// 梯级中出现的元件 ID（从左到右、从上到下）
+    pub fn element_ids(&self) -> Vec<i32> {
+        let mut ids = Vec::new();
+        collect_ids(&self.series, &mut ids);
+        ids
+    }
+
+    /// 输出 connections 图：元件 ID → 下游元件 ID 列表
+    pub fn to_connections(&self) -> HashMap<i32, Vec<i32>> {
+        let mut edges: HashMap<i32, Vec<i32>> = HashMap::new();
+        wire_series(&self.series, Vec::new(), &mut edges);
+        edges
+    }
+
+    /// 输出 Safety Token 流
+    /// 元件以 ElementRef 引用（type_id 取 Safety 类型码），串联项之间插入 SeriesNext，末尾为 NetEnd
+    pub fn to_tokens(&self, elements: &[LdElement]) -> Result<Vec<SafetyTopologyToken>, TopologyError> {
+        let by_id: HashMap<i32, &LdElement> = elements.iter().map(|e| (e.id, e)).collect();
+        let mut tokens = Vec::new();
+        emit_series(&self.series, &by_id, &mut tokens)?;
+        tokens.push(SafetyTopologyToken::NetEnd);
+        Ok(tokens)
+    }
+}
+
+/// 将网络拓扑转换为目标形态：重建 connections 或 Token 流，并清空另一种描述
+/// 单元素网络不需要 Token 流，转换为 Tokens 时保持为空
+pub fn convert_network(net: &Network, form: TopologyForm) -> Result<Network, TopologyError> {
+    let rung = Rung::from_network(net)?;
+    let mut out = net.clone();
+    out.elements = network_elements(net);
+    match form {
+        TopologyForm::Connections => {
+            let mut edges = rung.to_connections();
+            for elem in &mut out.elements {
+                elem.connections = edges.remove(&elem.id).unwrap_or_default();
+            }
+            out.safety_topology.clear();
+        }
+        TopologyForm::Tokens => {
+            for elem in &mut out.elements {
+                elem.connections.clear();
+            }
+            out.safety_topology = if out.elements.len() > 1 {
+                rung.to_tokens(&out.elements)?
+            } else {
+                Vec::new()
+            };
+        }
+    }
+    Ok(out)
+}
+
+/// 网络是否带有拓扑描述（Token 流，或指向本网络其它元件的连接）
+pub fn has_topology(net: &Network) -> bool {
+    !net.safety_topology.is_empty() || has_internal_connections(&net.elements)
+}
+
+fn has_internal_connections(elements: &[LdElement]) -> bool {
+    let ids: HashSet<i32> = elements.iter().map(|e| e.id).collect();
+    elements
+        .iter()
+        .any(|e| e.connections.iter().any(|dst| *dst != e.id && ids.contains(dst)))
+}
+
+/// 网络的全部元件：elements 在前，Token 流中内联且未登记的元件追加在后
+pub fn network_elements(net: &Network) -> Vec<LdElement> {
+    let mut elems = net.elements.clone();
+    let mut seen: HashSet<i32> = elems.iter().map(|e| e.id).collect();
+    for token in &net.safety_topology {
+        if let SafetyTopologyToken::InlineElement(elem) | SafetyTopologyToken::Element(elem) = token
+            && seen.insert(elem.id)
+        {
+            elems.push((**elem).clone());
+        }
+    }
+    elems
+}
+
+fn parse_series(
+    tokens: &[SafetyTopologyToken],
+    idx: &mut usize,
+) -> Result<Vec<RungNode>, TopologyError> {
+    let mut nodes = Vec::new();
+    while *idx < tokens.len() {
+        match &tokens[*idx] {
+            SafetyTopologyToken::BranchNext | SafetyTopologyToken::BranchClose => break,
+            SafetyTopologyToken::NetEnd => {
+                *idx = tokens.len();
+                break;
+            }
+            SafetyTopologyToken::BranchOpen => {
+                *idx += 1;
+                let mut branches = Vec::new();
+                loop {
+                    branches.push(parse_series(tokens, idx)?);
+                    match tokens.get(*idx) {
+                        Some(SafetyTopologyToken::BranchNext) => *idx += 1,
+                        Some(SafetyTopologyToken::BranchClose) => {
+                            *idx += 1;
+                            break;
+                        }
+                        _ => break,
+                    }
+                }
+                nodes.push(RungNode::Parallel(branches));
+                continue;
+            }
+            SafetyTopologyToken::ElementRef { id, .. } => {
+                let id = i32::try_from(*id).map_err(|_| TopologyError::InvalidElementId(*id))?;
+                nodes.push(RungNode::Element(id));
+            }
+            SafetyTopologyToken::InlineElement(elem) | SafetyTopologyToken::Element(elem) => {
+                nodes.push(RungNode::Element(elem.id));
+            }
+            SafetyTopologyToken::SeriesNext | SafetyTopologyToken::Raw(_) => {}
+        }
+        *idx += 1;
+    }
+    Ok(nodes)
+}
+
+/// 元件输出端的能流路径（串联序列，以自身结尾）
+fn power_out(
+    id: i32,
+    preds: &HashMap<i32, Vec<i32>>,
+    memo: &mut HashMap<i32, Vec<RungNode>>,
+    visiting: &mut HashSet<i32>,
+) -> Result<Vec<RungNode>, TopologyError> {
+    if let Some(path) = memo.get(&id) {
+        return Ok(path.clone());
+    }
+    if !visiting.insert(id) {
+        return Err(TopologyError::Cycle(id));
+    }
+    let mut inputs = Vec::new();
+    for pred in preds.get(&id).map(Vec::as_slice).unwrap_or_default() {
+        inputs.push(power_out(*pred, preds, memo, visiting)?);
+    }
+    visiting.remove(&id);
+    let mut path = factor(inputs);
+    path.push(RungNode::Element(id));
+    memo.insert(id, path.clone());
+    Ok(path)
+}
+
+/// 将若干并联路径合并为一个串联序列：相同路径去重，按首节点分组提取公共前缀
+fn factor(branches: Vec<Vec<RungNode>>) -> Vec<RungNode> {
+    let mut unique: Vec<Vec<RungNode>> = Vec::new();
+    for branch in branches {
+        if !unique.contains(&branch) {
+            unique.push(branch);
+        }
+    }
+    if unique.len() <= 1 {
+        return unique.pop().unwrap_or_default();
+    }
+
+    let mut groups: Vec<(Option<RungNode>, Vec<Vec<RungNode>>)> = Vec::new();
+    for mut branch in unique {
+        let head = (!branch.is_empty()).then(|| branch.remove(0));
+        match groups.iter_mut().find(|(key, _)| head.is_some() && *key == head) {
+            Some((_, tails)) => tails.push(branch),
+            None => groups.push((head, vec![branch])),
+        }
+    }
+    let mut merged: Vec<Vec<RungNode>> = groups
+        .into_iter()
+        .map(|(head, tails)| {
+            let mut series: Vec<RungNode> = head.into_iter().collect();
+            series.extend(factor(tails));
+            series
+        })
+        .collect();
+    if merged.len() == 1 {
+        merged.pop().unwrap_or_default()
+    } else {
+        vec![RungNode::Parallel(merged)]
+    }
+}
+
+fn collect_ids(nodes: &[RungNode], ids: &mut Vec<i32>) {
+    for node in nodes {
+        match node {
+            RungNode::Element(id) => ids.push(*id),
+            RungNode::Parallel(branches) => {
+                for branch in branches {
+                    collect_ids(branch, ids);
+                }
+            }
+        }
+    }
+}
+
+/// 沿串联序列连线：inputs 为进入该序列的上游元件（空表示左母线），返回序列末端的元件
+fn wire_series(nodes: &[RungNode], inputs: Vec<i32>, edges: &mut HashMap<i32, Vec<i32>>) -> Vec<i32> {
+    let mut frontier = inputs;
+    for node in nodes {
+        match node {
+            RungNode::Element(id) => {
+                for src in &frontier {
+                    let dsts = edges.entry(*src).or_default();
+                    if !dsts.contains(id) {
+                        dsts.push(*id);
+                    }
+                }
+                frontier = vec![*id];
+            }
+            RungNode::Parallel(branches) => {
+                let mut outs = Vec::new();
+                for branch in branches {
+                    let ends = if branch.is_empty() {
+                        frontier.clone()
+                    } else {
+                        wire_series(branch, frontier.clone(), edges)
+                    };
+                    for end in ends {
+                        if !outs.contains(&end) {
+                            outs.push(end);
+                        }
+                    }
+                }
+                frontier = outs;
+            }
+        }
+    }
+    frontier
+}
+
+fn emit_series(
+    nodes: &[RungNode],
+    by_id: &HashMap<i32, &LdElement>,
+    tokens: &mut Vec<SafetyTopologyToken>,
+) -> Result<(), TopologyError> {
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 {
+            tokens.push(SafetyTopologyToken::SeriesNext);
+        }
+        match node {
+            RungNode::Element(id) => {
+                let elem = by_id.get(id).ok_or(TopologyError::UnknownElement(*id))?;
+                tokens.push(SafetyTopologyToken::ElementRef {
+                    id: *id as u32,
+                    type_id: elem.type_code as u16,
+                });
+            }
+            RungNode::Parallel(branches) => {
+                tokens.push(SafetyTopologyToken::BranchOpen);
+                for (j, branch) in branches.iter().enumerate() {
+                    if j > 0 {
+                        tokens.push(SafetyTopologyToken::BranchNext);
+                    }
+                    emit_series(branch, by_id, tokens)?;
+                }
+                tokens.push(SafetyTopologyToken::BranchClose);
+            }
+        }
+    }
+    Ok(())
+}
@@ -11,5 +11,6 @@ pub use domain::ast;
 pub use application::service::PouService;
 pub use ports::backend::PouCodec;
 pub use adapters::hollysys::{HollysysCodec, HollysysConfig, PlcVariant};
+pub use adapters::plcopen::PlcOpenCodec;
 
 pub mod symbols_config;
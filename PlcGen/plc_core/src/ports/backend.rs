@@ -1,3 +1,4 @@
+use crate::adapters::hollysys::PlcVariant;
 use crate::ast::UniversalPou;
 use anyhow::Result;
 
@@ -12,4 +13,8 @@ pub trait PouCodec{
     /// 获取该品牌在 Windows 剪贴板中注册的格式名称
     /// e.g. "POU_TREE_Clipboard_PLC"
     fn format_name(&self)->&'static str;
+    /// 编码目标的控制器版本；与版本无关或编码前无法确定（如自动识别）时为 None
+    fn variant(&self)->Option<PlcVariant>{
+        None
+    }
 }
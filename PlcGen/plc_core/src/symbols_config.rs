@@ -1,19 +1,126 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 
 use anyhow::{Context, Result};
+use log::warn;
 
+use crate::adapters::hollysys::PlcVariant;
+use crate::ast::PinDirection;
+
+/// 随库编译的默认功能块库
+const BUILTIN_SYMBOLS_JSON: &str = include_str!("../../config/symbols_config.json");
+
+/// 功能块库（symbols_config.json）
+/// 每个功能块记录输入/输出引脚、实例类型、适用版本与内部成员变量；
+/// 解析器据此对变量分组，序列化器据此写实例标志并拒绝版本不支持的块，校验器据此检查引脚
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SymbolConfig {
+    pub function_blocks: Vec<FbDefinition>,
+}
+
+/// 功能块定义
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
-pub struct FbMembers {
+pub struct FbDefinition {
     pub name: String,
-    pub members: HashSet<String>,
+    /// 实例型（FB，需要实例名与实例变量）或无状态（函数，如 MOVE/ADD）
+    #[serde(default)]
+    pub kind: FbKind,
+    /// 可用的控制器版本；为空表示 Normal/Safety 均可用
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<PlcVariant>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<FbPin>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<FbPin>,
+    /// 实例内部成员变量（不含引脚）；兼容旧格式的纯名称列表
+    #[serde(default, deserialize_with = "deserialize_members", skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<FbMember>,
+    /// 占位定义：块名与引脚为约定值，尚未经现场库或样本确认，生成结果需替换后才能下装
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub placeholder: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
-pub struct SymbolConfig {
-    pub function_blocks: Vec<FbMembers>,
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FbKind {
+    #[default]
+    Instance,
+    Stateless,
+}
+
+/// 功能块引脚
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct FbPin {
+    pub name: String,
+    /// 数据类型；为空或 ANY* 表示不限
+    #[serde(default)]
+    pub data_type: String,
+    /// 未连接时的默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// 功能块实例成员变量
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct FbMember {
+    pub name: String,
+    #[serde(default)]
+    pub data_type: String,
+}
+
+/// 成员列表既可以是对象数组，也可以是旧格式的名称数组
+fn deserialize_members<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<FbMember>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MemberSpec {
+        Name(String),
+        Typed(FbMember),
+    }
+    let specs = Vec::<MemberSpec>::deserialize(d)?;
+    Ok(specs
+        .into_iter()
+        .map(|spec| match spec {
+            MemberSpec::Name(name) => FbMember { name, data_type: String::new() },
+            MemberSpec::Typed(member) => member,
+        })
+        .collect())
+}
+
+impl FbDefinition {
+    pub fn is_instance(&self) -> bool {
+        self.kind == FbKind::Instance
+    }
+
+    /// 是否可用于指定版本
+    pub fn supports(&self, variant: PlcVariant) -> bool {
+        self.variants.is_empty() || self.variants.contains(&variant)
+    }
+
+    /// 按名称查找引脚及其方向
+    pub fn pin(&self, name: &str) -> Option<(&FbPin, PinDirection)> {
+        let input = self.inputs.iter().find(|pin| pin.name == name).map(|pin| (pin, PinDirection::Input));
+        input.or_else(|| self.outputs.iter().find(|pin| pin.name == name).map(|pin| (pin, PinDirection::Output)))
+    }
+
+    /// 实例变量可访问的全部成员名：引脚 + 内部成员
+    pub fn member_names(&self) -> impl Iterator<Item = &str> {
+        self.inputs
+            .iter()
+            .chain(&self.outputs)
+            .map(|pin| pin.name.as_str())
+            .chain(self.members.iter().map(|member| member.name.as_str()))
+    }
+
+    /// 成员（引脚或内部成员）的数据类型；未登记类型时返回 None
+    pub fn member_type(&self, name: &str) -> Option<&str> {
+        let pin_type = self.pin(name).map(|(pin, _)| pin.data_type.as_str());
+        pin_type
+            .or_else(|| self.members.iter().find(|m| m.name == name).map(|m| m.data_type.as_str()))
+            .filter(|ty| !ty.is_empty())
+    }
 }
 
 impl SymbolConfig {
@@ -28,10 +135,48 @@ impl SymbolConfig {
         Ok(config)
     }
 
+    /// 内置默认库（编译期嵌入 config/symbols_config.json，与运行目录无关）
+    pub fn builtin() -> Self {
+        Self::builtin_ref().clone()
+    }
+
+    pub(crate) fn builtin_ref() -> &'static Self {
+        static BUILTIN: OnceLock<SymbolConfig> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            serde_json::from_str(BUILTIN_SYMBOLS_JSON).unwrap_or_else(|err| {
+                warn!("内置符号表解析失败: {}", err);
+                Self::default()
+            })
+        })
+    }
+
+    /// 叠加用户库：同名功能块（忽略大小写）以 overlay 为准，新块追加在末尾
+    pub fn merged(mut self, overlay: SymbolConfig) -> Self {
+        for fb in overlay.function_blocks {
+            match self.function_blocks.iter_mut().find(|base| base.name.eq_ignore_ascii_case(&fb.name)) {
+                Some(base) => *base = fb,
+                None => self.function_blocks.push(fb),
+            }
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.function_blocks.is_empty()
+    }
+
+    /// 按名称查找功能块（忽略大小写）
+    pub fn get(&self, name: &str) -> Option<&FbDefinition> {
+        self.function_blocks
+            .iter()
+            .find(|fb| fb.name == name)
+            .or_else(|| self.function_blocks.iter().find(|fb| fb.name.eq_ignore_ascii_case(name)))
+    }
+
     pub fn to_lookup_map(&self) -> HashMap<String, HashSet<String>> {
         self.function_blocks
             .iter()
-            .map(|fb| (fb.name.clone(), fb.members.clone()))
+            .map(|fb| (fb.name.clone(), fb.member_names().map(str::to_string).collect()))
             .collect()
     }
 }
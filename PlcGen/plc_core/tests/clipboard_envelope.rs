@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use plc_core::PouCodec;
+use plc_core::adapters::hollysys::{HollysysCodec, HollysysConfig, TrailingBytes};
+use plc_core::ast::{UniversalPou, Variable, VariableNode};
+
+const BLOCK: usize = 0x2000;
+
+/// 样本对比/测试用例 中的十六进制样本
+fn sample(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../Docs/样本对比/测试用例").join(name);
+    let digits: Vec<u8> = std::fs::read_to_string(path).unwrap().bytes().filter(u8::is_ascii_hexdigit).collect();
+    digits.chunks(2).map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap()).collect()
+}
+
+/// 无损解码的普通型样本（截到一个容器块），编码时未修改的部分按原字节回写
+fn normal_pou() -> UniversalPou {
+    let mut data = sample("普通型样本1.md");
+    assert!(data[BLOCK..].iter().all(|b| *b == 0));
+    data.truncate(BLOCK);
+    HollysysCodec::new(HollysysConfig::normal().with_lossless(true)).decode(&data).unwrap()
+}
+
+fn leaves(nodes: &[VariableNode]) -> usize {
+    nodes
+        .iter()
+        .map(|node| match node {
+            VariableNode::Leaf(_) => 1,
+            VariableNode::Group { children, .. } => leaves(children),
+        })
+        .sum()
+}
+
+fn renamed(pou: &UniversalPou, name: &str) -> UniversalPou {
+    UniversalPou { name: name.to_string(), ..pou.clone() }
+}
+
+#[test]
+fn single_samples_are_one_container() {
+    for name in ["普通型样本1.md", "安全型样本1.md"] {
+        let data = sample(name);
+        let codec = HollysysCodec::auto();
+        let decoded = codec.decode_clipboard(&data).unwrap();
+        assert_eq!(decoded.containers, [0..data.len()], "{name}");
+        assert!(decoded.trailing.is_empty(), "{name}: {:?}", decoded.trailing);
+        assert_eq!(decoded.pous[0].name, codec.decode(&data).unwrap().name);
+    }
+}
+
+#[test]
+fn multi_pou_buffer_roundtrip() {
+    let codec = HollysysCodec::normal();
+    let pou = normal_pou();
+    let data = codec.encode_clipboard(&[pou.clone(), renamed(&pou, "TEST_COPY")]).unwrap();
+    assert_eq!(data.len(), 2 * BLOCK);
+
+    let decoded = codec.decode_clipboard(&data).unwrap();
+    assert_eq!(decoded.containers, [0..BLOCK, BLOCK..2 * BLOCK]);
+    let names: Vec<&str> = decoded.pous.iter().map(|pou| pou.name.as_str()).collect();
+    assert_eq!(names, [pou.name.as_str(), "TEST_COPY"]);
+    assert_eq!(decoded.pous[1].networks.len(), pou.networks.len());
+    // 单 POU 接口只返回第一个
+    assert_eq!(codec.decode(&data).unwrap().name, pou.name);
+}
+
+#[test]
+fn large_pou_grows_container() {
+    let codec = HollysysCodec::normal();
+    let small = normal_pou();
+    let mut large = renamed(&small, "LARGE");
+    for i in 0..200 {
+        large.variables.push(VariableNode::Leaf(Variable {
+            comment: "容器增长测试用的长注释，使 POU 内容超过 8 KiB".to_string(),
+            ..Variable::new(format!("FILLER_{i:03}"), "REAL")
+        }));
+    }
+
+    let encoded = codec.encode(&large).unwrap();
+    assert!(encoded.len() > BLOCK && encoded.len() % BLOCK == 0, "len={:#x}", encoded.len());
+
+    let data = codec.encode_clipboard(&[large.clone(), small.clone()]).unwrap();
+    let decoded = codec.decode_clipboard(&data).unwrap();
+    assert_eq!(decoded.containers, [0..encoded.len(), encoded.len()..data.len()]);
+    assert_eq!(leaves(&decoded.pous[0].variables), leaves(&large.variables));
+    assert_eq!(decoded.pous[1].name, small.name);
+}
+
+#[test]
+fn reports_trailing_bytes() {
+    let mut data = sample("普通型样本1.md");
+    data[0x1F00] = 0xAB;
+    let codec = HollysysCodec::normal();
+    let decoded = codec.decode_clipboard(&data).unwrap();
+    assert_eq!(decoded.trailing, [TrailingBytes { pou_index: 0, range: 0x1F00..0x1F01 }]);
+    assert_eq!(decoded.pous.len(), 1);
+}
@@ -0,0 +1,69 @@
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use plc_core::PlcVariant;
+
+/// Docs/样本对比 下当前可正确解码的 Sxx 样本
+/// 普通型 S05/S08/S12/S13、安全型 S08/S12/S13 尚未还原；安全型 S05_RS 的 POU 名称解析有误，暂不列入
+pub const NORMAL_SAMPLES: &[&str] = &[
+    "S01_TAG1",
+    "S02_NC",
+    "S03_COIL",
+    "S04_MOVE",
+    "S06_TP",
+    "S07_DESC",
+    "S09_VAR_FLAGS",
+    "S10_VAR_COMMENT",
+    "S11_VAR_TIME",
+];
+
+pub const SAFETY_SAMPLES: &[&str] = &[
+    "S01_TAG1",
+    "S02_NC",
+    "S03_COIL",
+    "S04_MOVE",
+    "S06_TP",
+    "S07_DESC",
+    "S09_VAR_FLAGS",
+    "S10_VAR_COMMENT",
+    "S11_VAR_TIME",
+];
+
+/// 按版本列出 (版本, 样本名)
+pub fn samples() -> impl Iterator<Item = (PlcVariant, &'static str)> {
+    let normal = NORMAL_SAMPLES.iter().map(|name| (PlcVariant::Normal, *name));
+    normal.chain(SAFETY_SAMPLES.iter().map(|name| (PlcVariant::Safety, *name)))
+}
+
+fn docs() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../Docs/样本对比")
+}
+
+/// 十六进制文本转字节（忽略空白与换行）
+pub fn hex(text: &str) -> Vec<u8> {
+    let digits: Vec<u8> = text.bytes().filter(u8::is_ascii_hexdigit).collect();
+    digits.chunks(2).map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap()).collect()
+}
+
+/// 按版本读取 Sxx 样本（文件名大小写不统一）
+pub fn sample(variant: PlcVariant, name: &str) -> Vec<u8> {
+    let dir = docs().join(match variant {
+        PlcVariant::Normal => "普通型",
+        PlcVariant::Safety => "安全型",
+    });
+    let path = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            path.file_stem().is_some_and(|stem| stem == name)
+                && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+        })
+        .unwrap_or_else(|| panic!("sample {name} not found in {}", dir.display()));
+    hex(&std::fs::read_to_string(path).unwrap())
+}
+
+/// 测试用例目录下的完整样本（普通型样本1 / 安全型样本1）
+pub fn case(name: &str) -> Vec<u8> {
+    hex(&std::fs::read_to_string(docs().join("测试用例").join(name)).unwrap())
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use plc_core::adapters::hollysys::detect;
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+use common::{case, sample, samples};
+
+#[test]
+fn detects_variant_of_every_sample() {
+    for (variant, name) in samples() {
+        let format = detect(&sample(variant, name));
+        assert_eq!(format.variant, variant, "{name}");
+        assert_eq!(format.serialize_version, 13, "{variant:?} {name}");
+        assert!(format.confidence >= 0.5, "{variant:?} {name}: {}", format.confidence);
+    }
+    for (variant, name) in [(PlcVariant::Normal, "普通型样本1.md"), (PlcVariant::Safety, "安全型样本1.md")] {
+        let format = detect(&case(name));
+        assert_eq!(format.variant, variant, "{name}");
+        assert!(format.confidence >= 0.5, "{name}: {}", format.confidence);
+    }
+}
+
+#[test]
+fn unrecognised_data_has_zero_confidence() {
+    for data in [&[][..], &[0u8; 64][..]] {
+        let format = detect(data);
+        assert_eq!(format.variant, PlcVariant::Normal);
+        assert_eq!(format.confidence, 0.0);
+    }
+}
+
+#[test]
+fn auto_codec_matches_explicit_variant() {
+    let auto = HollysysCodec::auto();
+    for (variant, name) in samples() {
+        let data = sample(variant, name);
+        assert_eq!(auto.resolve_config(&data).variant, variant, "{name}");
+        let explicit = HollysysCodec::new(HollysysConfig::new(variant)).decode(&data).unwrap();
+        let detected = auto.decode(&data).unwrap();
+        assert_eq!(
+            serde_json::to_value(detected).unwrap(),
+            serde_json::to_value(explicit).unwrap(),
+            "{variant:?} {name}"
+        );
+    }
+}
+
+#[test]
+fn auto_codec_reencodes_with_decoded_variant() {
+    // 自动模式编码沿用无损布局记录的版本，Safety 样本不会按默认 Normal 配置编码
+    let auto = HollysysCodec::auto_with_config(HollysysConfig::normal().with_lossless(true));
+    for (variant, name) in samples() {
+        let data = sample(variant, name);
+        let pou = auto.decode(&data).unwrap();
+        assert_eq!(auto.encode(&pou).unwrap(), data, "{variant:?} {name}");
+    }
+}
@@ -0,0 +1,160 @@
+use plc_core::domain::hardware::{
+    Channel, ChannelAddress, ChannelKind, Controller, HardwareError, Module, ModuleType, Rack, Slot,
+};
+
+fn address(text: &str) -> ChannelAddress {
+    text.parse().unwrap()
+}
+
+/// 机架 1：槽 0 CPU、槽 2 AI、槽 3 DI
+fn controller() -> Controller {
+    let slot = |index: u16, module: Module| Slot { index, module };
+    Controller {
+        name: "PLC_1".to_string(),
+        model: "LK".to_string(),
+        racks: vec![Rack {
+            index: 1,
+            slot_count: 8,
+            slots: vec![
+                slot(0, Module::new("LK210", ModuleType::Cpu)),
+                slot(2, Module::new("LK411", ModuleType::AI)),
+                slot(3, Module::new("LK610", ModuleType::DI)),
+            ],
+        }],
+        slaves: Vec::new(),
+    }
+}
+
+#[test]
+fn parses_channel_address_formats() {
+    let expected = ChannelAddress { rack: 1, slot: 3, kind: ChannelKind::DO, index: 7 };
+    for text in ["1_3_DO_7", "R1-S3-DO-07", "r1.s3.do.c7"] {
+        assert_eq!(address(text), expected, "{text}");
+    }
+    assert_eq!(expected.to_string(), "1_3_DO_7");
+    assert!(matches!("1_3_XX_7".parse::<ChannelAddress>(), Err(HardwareError::InvalidChannelAddress(_))));
+    assert!(matches!("1_3_DO".parse::<ChannelAddress>(), Err(HardwareError::InvalidChannelAddress(_))));
+}
+
+#[test]
+fn standard_modules_pass_validation() {
+    let hw = controller();
+    assert_eq!(hw.module(1, 2).unwrap().channel_count, 8);
+    assert_eq!(hw.module(1, 3).unwrap().channel_count, 16);
+    assert_eq!(hw.module(1, 0).unwrap().channel_count, 0);
+    assert!(hw.validate().is_empty(), "{:?}", hw.validate());
+}
+
+/// 通道容量随型号而定：4/12/32 通道卡件均有效，通道号按各自容量检查
+#[test]
+fn channel_capacity_comes_from_module() {
+    let mut hw = controller();
+    hw.racks[0].slots[1].module = Module::new("LK412", ModuleType::AI).with_channel_count(4);
+    hw.racks[0].slots[2].module = Module::new("LK612", ModuleType::DI).with_channel_count(32);
+    hw.racks[0].slots.push(Slot { index: 4, module: Module::new("LK712", ModuleType::DO).with_channel_count(12) });
+    let errors = hw.bind_points([("XS_131", "1_3_DI_31"), ("XV_111", "1_4_DO_11"), ("PT_104", "1_2_AI_4")]);
+    assert_eq!(errors, [HardwareError::ChannelOutOfRange(address("1_2_AI_4"))]);
+    assert!(hw.validate().is_empty(), "{:?}", hw.validate());
+
+    hw.module_mut(1, 2).unwrap().channels.push(Channel {
+        index: 4,
+        kind: ChannelKind::AI,
+        point: None,
+        description: String::new(),
+    });
+    assert_eq!(hw.validate(), [HardwareError::ChannelOutOfRange(address("1_2_AI_4"))]);
+}
+
+#[test]
+fn rejects_impossible_channel_count() {
+    let mut hw = controller();
+    hw.racks[0].slots[1].module = Module::new("LK411", ModuleType::AI).with_channel_count(0);
+    hw.racks[0].slots[0].module = Module::new("LK210", ModuleType::Cpu).with_channel_count(4);
+    assert_eq!(
+        hw.validate(),
+        [
+            HardwareError::InvalidChannelCount { rack: 1, slot: 0, module_type: ModuleType::Cpu, channel_count: 4 },
+            HardwareError::InvalidChannelCount { rack: 1, slot: 2, module_type: ModuleType::AI, channel_count: 0 },
+        ]
+    );
+}
+
+#[test]
+fn lists_every_layout_problem() {
+    let mut hw = controller();
+    hw.racks.push(Rack { index: 1, slot_count: 4, slots: Vec::new() });
+    let rack = &mut hw.racks[0];
+    rack.slots.push(Slot { index: 3, module: Module::new("LK610", ModuleType::DI) });
+    rack.slots.push(Slot { index: 9, module: Module::new("LK710", ModuleType::DO) });
+    let channel = |index: u16, kind: ChannelKind, point: &str| Channel {
+        index,
+        kind,
+        point: Some(point.to_string()),
+        description: String::new(),
+    };
+    rack.slots[1].module.channels = vec![
+        channel(0, ChannelKind::AI, "PT_101"),
+        channel(0, ChannelKind::AI, "PT_102"),
+        channel(8, ChannelKind::AI, "PT_103"),
+    ];
+    rack.slots[2].module.channels = vec![channel(1, ChannelKind::AI, "pt_101")];
+
+    let errors = hw.validate();
+    for expected in [
+        HardwareError::DuplicateRack(1),
+        HardwareError::SlotCollision { rack: 1, slot: 3 },
+        HardwareError::SlotOutOfRange { rack: 1, slot: 9, slot_count: 8 },
+        HardwareError::DuplicateChannel(address("1_2_AI_0")),
+        HardwareError::ChannelOutOfRange(address("1_2_AI_8")),
+        HardwareError::ChannelKindMismatch { address: address("1_3_AI_1"), module_type: ModuleType::DI },
+        HardwareError::DuplicatePoint("PT_101".to_string()),
+    ] {
+        assert!(errors.contains(&expected), "{expected:?} not in {errors:?}");
+    }
+    assert_eq!(errors.len(), 7, "{errors:?}");
+}
+
+#[test]
+fn binds_points_and_fills_missing_channels() {
+    let mut hw = controller();
+    let errors = hw.bind_points([("PT_102", "R1-S2-AI-05"), ("PT_101", "1_2_AI_0"), ("XS_101", "1_3_DI_15")]);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let indices: Vec<u16> = hw.module(1, 2).unwrap().channels.iter().map(|c| c.index).collect();
+    assert_eq!(indices, [0, 5]);
+    assert_eq!(hw.channel(&address("1_2_AI_5")).unwrap().point.as_deref(), Some("PT_102"));
+    assert_eq!(
+        hw.bound_points(),
+        [("PT_101", address("1_2_AI_0")), ("PT_102", address("1_2_AI_5")), ("XS_101", address("1_3_DI_15"))]
+    );
+    // 同一点位重复绑定同一通道（忽略大小写）不算冲突
+    assert_eq!(hw.bind_point("pt_101", address("1_2_AI_0")), Ok(()));
+    assert!(hw.validate().is_empty(), "{:?}", hw.validate());
+}
+
+#[test]
+fn reports_points_that_cannot_be_bound() {
+    let mut hw = controller();
+    hw.bind_point("PT_101", address("1_2_AI_0")).unwrap();
+    let errors = hw.bind_points([
+        ("PT_102", "1_2_AI_0"),
+        ("PT_103", "1_2_AI_8"),
+        ("PT_104", "1_2_DI_1"),
+        ("PT_105", "1_5_AI_0"),
+        ("PT_106", "1_2_AI"),
+        ("PT_107", "1_2_AI_1"),
+    ]);
+    assert_eq!(
+        errors,
+        [
+            HardwareError::ChannelAlreadyBound { address: address("1_2_AI_0"), point: "PT_101".to_string() },
+            HardwareError::ChannelOutOfRange(address("1_2_AI_8")),
+            HardwareError::ChannelKindMismatch { address: address("1_2_DI_1"), module_type: ModuleType::AI },
+            HardwareError::ModuleNotFound(address("1_5_AI_0")),
+            HardwareError::InvalidChannelAddress("1_2_AI".to_string()),
+        ]
+    );
+    // 其余点位照常绑定
+    assert_eq!(hw.channel(&address("1_2_AI_1")).unwrap().point.as_deref(), Some("PT_107"));
+    assert_eq!(hw.bound_points().len(), 2);
+}
@@ -0,0 +1,161 @@
+use plc_core::adapters::hollysys::{
+    DeviceHeader, HardwareCodec, MappingItem, ModbusChannelRecord, ModbusOrderRecord, ModbusSlaveRecord, SlaveObjects,
+    SlaveTrailer,
+};
+use plc_core::domain::hardware::{CommChannel, CommOrder, CommSlave, CommValueType, Parity, SlaveLink};
+
+/// PayloadGenerator 输出的从站载荷（empty_slave.bin 指纹，IP 改为 192.168.1.100）
+const TCP_SLAVE_HEX: &str = "\
+    0000000000000000\
+    0131 0d3139322e3136382e312e313030 07302e302e302e30 0131 03353032 0130\
+    00000000 01000000 ffffffff 00000000 000000ff 00000000 00000000 ffffffff 00000000 ffffffff\
+    00000000 00000000 00000000 00000000 00000000 00000000 00000000 00";
+
+fn hex(text: &str) -> Vec<u8> {
+    let digits: Vec<u8> = text.bytes().filter(u8::is_ascii_hexdigit).collect();
+    digits.chunks(2).map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap()).collect()
+}
+
+fn slave(link: SlaveLink) -> CommSlave {
+    CommSlave {
+        name: "SLAVE_1".to_string(),
+        enabled: true,
+        link,
+        timeout_ms: 1000,
+        retry_count: 3,
+        orders: Vec::new(),
+        description: String::new(),
+    }
+}
+
+#[test]
+fn tcp_slave_sample_roundtrip() {
+    let codec = HardwareCodec::new();
+    let bytes = hex(TCP_SLAVE_HEX);
+    let record = codec.decode_slave(&bytes).unwrap();
+    assert!(record.enabled);
+    assert_eq!((record.ip.as_str(), record.backup_ip.as_str()), ("192.168.1.100", "0.0.0.0"));
+    assert_eq!((record.unit_id, record.port), (1, 502));
+    assert_eq!(record.trailer, SlaveTrailer::default());
+    assert_eq!(codec.encode_slave(&record).unwrap(), bytes);
+
+    let link = SlaveLink::ModbusTcp { ip: "192.168.1.100".to_string(), port: 502, unit_id: 1 };
+    assert_eq!(SlaveObjects::from_comm_slave(&slave(link)).unwrap().slave, record);
+
+    // 剪贴板尾部零填充可接受，其余多余字节报错
+    let mut padded = bytes.clone();
+    padded.extend([0; 16]);
+    assert_eq!(codec.decode_slave(&padded).unwrap(), record);
+    padded.push(1);
+    assert!(codec.decode_slave(&padded).is_err());
+}
+
+#[test]
+fn rtu_slave_is_rejected_until_layout_confirmed() {
+    let link = SlaveLink::ModbusRtu {
+        port: "COM2".to_string(),
+        baud_rate: 9600,
+        parity: Parity::Even,
+        data_bits: 8,
+        stop_bits: 1,
+        slave_id: 17,
+    };
+    // 串口参数无处存放，不能静默生成只带站号的从站对象
+    let err = SlaveObjects::from_comm_slave(&slave(link)).unwrap_err().to_string();
+    assert!(err.contains("SLAVE_1") && err.contains("COM2"), "{err}");
+}
+
+#[test]
+fn tcp_slave_carries_fields_it_cannot_encode() {
+    let mut slave = slave(SlaveLink::ModbusTcp { ip: "10.0.0.5".to_string(), port: 5020, unit_id: 3 });
+    slave.description = "计量撬".to_string();
+    slave.orders = vec![CommOrder {
+        name: "READ_HR".to_string(),
+        function_code: 3,
+        start_address: 100,
+        length: 4,
+        scan_period_ms: 500,
+        channels: vec![CommChannel {
+            name: "PT_101".to_string(),
+            offset: 2,
+            value_type: CommValueType::Float32,
+            bit: None,
+            point: Some("PT_101".to_string()),
+        }],
+    }];
+    let objects = SlaveObjects::from_comm_slave(&slave).unwrap();
+    assert_eq!((objects.slave.ip.as_str(), objects.slave.port, objects.slave.unit_id), ("10.0.0.5", 5020, 3));
+
+    let order = &objects.orders[0];
+    assert_eq!(order.header.name, "READ_HR");
+    assert_eq!((order.function_index, order.start_address, order.length, order.scan_period), (3, 100, 4, 500));
+    assert!(order.channels.is_empty());
+    let codec = HardwareCodec::new();
+    assert_eq!(codec.decode_order(&codec.encode_order(order).unwrap()).unwrap(), *order);
+
+    let carried: Vec<(&str, String)> =
+        objects.unencoded.iter().map(|field| (field.path.as_str(), field.value.to_string())).collect();
+    assert_eq!(
+        carried[..4],
+        [
+            ("name", "\"SLAVE_1\"".to_string()),
+            ("timeout_ms", "1000".to_string()),
+            ("retry_count", "3".to_string()),
+            ("description", "\"计量撬\"".to_string()),
+        ]
+    );
+    assert_eq!(carried[4].0, "orders[0].channels");
+    assert_eq!(objects.unencoded[4].value[0]["name"], "PT_101");
+    assert_eq!(objects.unencoded[4].value[0]["offset"], 2);
+}
+
+#[test]
+fn modified_slave_record_roundtrip() {
+    let codec = HardwareCodec::new();
+    let record = ModbusSlaveRecord {
+        enabled: false,
+        base_strings: ["MODBUSSLAVE_1", "", "", "", "", "", "", ""].map(String::from).to_vec(),
+        unit_id: 17,
+        ..ModbusSlaveRecord::default()
+    };
+    let bytes = codec.encode_slave(&record).unwrap();
+    assert_eq!(codec.decode_slave(&bytes).unwrap(), record);
+}
+
+#[test]
+fn order_with_channels_roundtrip() {
+    let codec = HardwareCodec::new();
+    let channel = |name: &str, offset: u32| ModbusChannelRecord {
+        header: DeviceHeader {
+            name: name.to_string(),
+            id: offset + 10,
+            flag1: 1,
+            flag2: 0,
+            description: "压力".into(),
+        },
+        offset,
+        data_type: 3,
+        bit_offset: 0,
+    };
+    let order = ModbusOrderRecord {
+        // 超过 254 字节的描述走 0xFF + u16 长度前缀
+        header: DeviceHeader { name: "ORDER_1".into(), id: 2, flag1: 1, flag2: 1, description: "读".repeat(200) },
+        function_index: 3,
+        start_address: 100,
+        length: 10,
+        param3: 0,
+        param4: 0,
+        param5: 1,
+        scan_period: 500,
+        param7: 0,
+        mappings: vec![MappingItem { p1: 1, p2: 2, p3: 3, p4: 4, blob: vec![0xAA, 0xBB] }],
+        channels: vec![channel("PT_101", 0), channel("PT_102", 2)],
+    };
+    let bytes = codec.encode_order(&order).unwrap();
+    assert_eq!(&bytes[14..17], &[0xFF, 0x90, 0x01]);
+    assert_eq!(codec.decode_order(&bytes).unwrap(), order);
+
+    let single = codec.encode_channel(&order.channels[1]).unwrap();
+    assert_eq!(codec.decode_channel(&single).unwrap(), order.channels[1]);
+    assert!(codec.decode_order(&bytes[..bytes.len() - 1]).is_err());
+}
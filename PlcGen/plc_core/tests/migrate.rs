@@ -0,0 +1,135 @@
+mod common;
+
+use plc_core::adapters::hollysys::{MigrationSeverity, migrate_pou};
+use plc_core::ast::{Network, UniversalPou, Variable, VariableNode};
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+use common::sample;
+
+fn decode(variant: PlcVariant, name: &str) -> UniversalPou {
+    HollysysCodec::new(HollysysConfig::new(variant)).decode(&sample(variant, name)).unwrap()
+}
+
+fn leaves(nodes: &[VariableNode]) -> Vec<&Variable> {
+    nodes
+        .iter()
+        .flat_map(|node| match node {
+            VariableNode::Leaf(var) => vec![var],
+            VariableNode::Group { children, .. } => leaves(children),
+        })
+        .collect()
+}
+
+fn pou_with(vars: Vec<Variable>) -> UniversalPou {
+    UniversalPou {
+        name: "ADDR_POU".to_string(),
+        header_strings: Vec::new(),
+        variables: vec![VariableNode::Group {
+            name: "Local Variables".to_string(),
+            type_name: None,
+            children: vars.into_iter().map(VariableNode::Leaf).collect(),
+        }],
+        networks: Vec::new(),
+        preserved: None,
+    }
+}
+
+fn addressed(name: &str, addr_id: Option<u64>) -> Variable {
+    Variable { addr_id, ..Variable::new(name, "INT") }
+}
+
+#[test]
+fn normal_addresses_never_become_safety_unbound_marker() {
+    let pou = pou_with(vec![
+        addressed("BOUND", Some(0x1234)),
+        addressed("UNBOUND", Some(0xFFFF_FFFF_FFFF_FFFF)),
+        addressed("WIDE", Some(0x1_0000_0010)),
+        addressed("MARKER", Some(0xFFFF_FFFF)),
+        addressed("WIDE_MARKER", Some(0x2_FFFF_FFFF)),
+        addressed("NONE", None),
+    ]);
+    let (migrated, report) = migrate_pou(&pou, PlcVariant::Normal, PlcVariant::Safety);
+    let addrs: Vec<(&str, Option<u64>)> =
+        leaves(&migrated.variables).iter().map(|var| (var.name.as_str(), var.addr_id)).collect();
+    assert_eq!(
+        addrs,
+        [
+            ("BOUND", Some(0x1234)),
+            ("UNBOUND", None),
+            ("WIDE", Some(0x10)),
+            ("MARKER", None),
+            ("WIDE_MARKER", None),
+            ("NONE", None),
+        ]
+    );
+
+    let issues: Vec<(MigrationSeverity, &str)> =
+        report.issues.iter().map(|issue| (issue.severity, issue.variable.as_deref().unwrap_or_default())).collect();
+    assert_eq!(
+        issues,
+        [
+            (MigrationSeverity::Lossy, "WIDE"),
+            (MigrationSeverity::Unsupported, "MARKER"),
+            (MigrationSeverity::Unsupported, "WIDE_MARKER"),
+        ]
+    );
+    assert!(!report.is_lossless());
+    assert_eq!(report.unsupported().count(), 2);
+}
+
+#[test]
+fn safety_unbound_marker_migrates_to_normal_unbound() {
+    let mut var = addressed("AREA", Some(0x20));
+    var.area_code = Some(0x02);
+    let pou = pou_with(vec![addressed("UNBOUND", Some(0xFFFF_FFFF)), addressed("DEFAULT_AREA", Some(0x10)), var]);
+    let (migrated, report) = migrate_pou(&pou, PlcVariant::Safety, PlcVariant::Normal);
+    let vars = leaves(&migrated.variables);
+    assert_eq!(vars.iter().map(|var| var.addr_id).collect::<Vec<_>>(), [None, Some(0x10), Some(0x20)]);
+    assert!(vars.iter().all(|var| var.area_code.is_none()));
+    assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+    assert_eq!(report.issues[0].severity, MigrationSeverity::Lossy);
+    assert_eq!(report.issues[0].variable.as_deref(), Some("AREA"));
+}
+
+#[test]
+fn migrates_samples_between_topology_forms() {
+    let normal = decode(PlcVariant::Normal, "S09_VAR_FLAGS");
+    let (safety, report) = migrate_pou(&normal, PlcVariant::Normal, PlcVariant::Safety);
+    assert!(safety.preserved.is_none());
+    assert!(report.unsupported().next().is_none(), "{:?}", report.issues);
+    let net = &safety.networks[0];
+    assert!(net.elements.iter().all(|elem| elem.connections.is_empty()));
+    assert!(!net.safety_topology.is_empty());
+    // 样本地址超出 32 位，截断后仍为有效地址
+    assert!(report.issues.iter().any(|issue| issue.message.contains("已截断")));
+    assert!(leaves(&safety.variables).iter().all(|var| var.addr_id != Some(0xFFFF_FFFF)));
+    assert!(!HollysysCodec::safety().encode(&safety).unwrap().is_empty());
+
+    let (back, report) = migrate_pou(&safety, PlcVariant::Safety, PlcVariant::Normal);
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+    let (net, original) = (&back.networks[0], &normal.networks[0]);
+    assert!(net.safety_topology.is_empty());
+    let names = |net: &Network| net.elements.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(net), names(original));
+    assert_eq!(net.elements[0].connections, [net.elements[1].id]);
+    assert!(!HollysysCodec::normal().encode(&back).unwrap().is_empty());
+}
+
+#[test]
+fn same_variant_is_returned_unchanged() {
+    let pou = decode(PlcVariant::Safety, "S06_TP");
+    let (migrated, report) = migrate_pou(&pou, PlcVariant::Safety, PlcVariant::Safety);
+    assert!(report.issues.is_empty());
+    assert_eq!(migrated.name, pou.name);
+    assert!(migrated.preserved.is_some() == pou.preserved.is_some());
+}
+
+#[test]
+fn safety_instances_become_normal_typed_groups() {
+    let pou = decode(PlcVariant::Safety, "S06_TP");
+    let (normal, report) = migrate_pou(&pou, PlcVariant::Safety, PlcVariant::Normal);
+    assert!(normal.header_strings.is_empty());
+    assert!(report.issues.iter().all(|issue| issue.severity != MigrationSeverity::Unsupported), "{:?}", report.issues);
+    let (safety, _) = migrate_pou(&normal, PlcVariant::Normal, PlcVariant::Safety);
+    assert!(safety.header_strings.contains(&"TP".to_string()), "{:?}", safety.header_strings);
+}
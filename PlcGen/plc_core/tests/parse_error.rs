@@ -0,0 +1,87 @@
+mod common;
+
+use plc_core::adapters::hollysys::{ParseError, ParseSection};
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+use common::{sample, samples};
+
+fn parse_error(variant: PlcVariant, data: &[u8]) -> ParseError {
+    let err = HollysysCodec::new(HollysysConfig::new(variant)).decode(data).unwrap_err();
+    err.downcast_ref::<ParseError>().unwrap_or_else(|| panic!("not a ParseError: {err:#}")).clone()
+}
+
+fn find(data: &[u8], needle: &[u8]) -> usize {
+    data.windows(needle.len()).position(|w| w == needle).unwrap()
+}
+
+/// 普通型 S04_MOVE：CLDBox 类名之后第 19 字节为连接数量（u32）的首字节
+#[test]
+fn locates_corrupted_connection_count() {
+    let mut data = sample(PlcVariant::Normal, "S04_MOVE");
+    let count = find(&data, b"CLDBox") + 19;
+    data[count] = 0xEE;
+    let err = parse_error(PlcVariant::Normal, &data);
+    assert!(err.message.contains("连接数量异常"), "{}", err.message);
+    assert_eq!(err.offset, count + 3);
+    assert_eq!(err.path.section, ParseSection::Networks);
+    assert_eq!((err.path.network_index, err.path.element_id, err.path.pin_index), (Some(0), Some(4), None));
+    assert_eq!(err.class_name.as_deref(), Some("CLDBox"));
+    assert_eq!(
+        err.to_string(),
+        format!("{}（偏移 0x{:X}，位置 Networks/network[0]/element#4，类 CLDBox）", err.message, err.offset)
+    );
+}
+
+#[test]
+fn hex_window_marks_failing_byte() {
+    let mut data = sample(PlcVariant::Normal, "S04_MOVE");
+    let count = find(&data, b"CLDBox") + 19;
+    data[count] = 0xEE;
+    let err = parse_error(PlcVariant::Normal, &data);
+    assert_eq!(err.window_start, err.offset - 16);
+    let bytes: Vec<&str> = err.window.split(' ').collect();
+    assert_eq!(bytes.len(), 32);
+    assert_eq!(bytes[16], format!("[{:02X}]", data[err.offset]));
+    let plain: Vec<u8> = bytes.iter().map(|b| u8::from_str_radix(b.trim_matches(['[', ']']), 16).unwrap()).collect();
+    assert_eq!(plain, data[err.window_start..err.window_start + 32]);
+}
+
+#[test]
+fn truncated_pins_report_pin_index() {
+    let data = sample(PlcVariant::Normal, "S04_MOVE");
+    let boxed = find(&data, b"CLDBox");
+    for (cut, pin) in [(36, 0), (50, 1), (71, 2), (85, 3)] {
+        let err = parse_error(PlcVariant::Normal, &data[..boxed + cut]);
+        assert!(err.offset <= boxed + cut, "{err}");
+        assert_eq!(err.path.element_id, Some(4), "{err}");
+        assert_eq!(err.path.pin_index, Some(pin), "{err}");
+        assert_eq!(err.class_name.as_deref(), Some("CLDBox"), "{err}");
+    }
+}
+
+#[test]
+fn reports_section_of_failure() {
+    let data = sample(PlcVariant::Normal, "S04_MOVE");
+    assert_eq!(parse_error(PlcVariant::Normal, &data[..40]).path.section, ParseSection::Header);
+    assert_eq!(parse_error(PlcVariant::Normal, &data[..100]).path.section, ParseSection::Networks);
+    let err = parse_error(PlcVariant::Normal, &data[..find(&data, b"CLDAssign") + 30]);
+    assert_eq!(err.path.section, ParseSection::Variables, "{err}");
+    assert!(err.path.network_index.is_none());
+
+    let err = parse_error(PlcVariant::Normal, &[]);
+    assert_eq!((err.offset, err.path.section, err.window.as_str()), (0, ParseSection::Header, ""));
+}
+
+#[test]
+fn truncated_samples_fail_inside_data() {
+    for (variant, name) in samples() {
+        let data = sample(variant, name);
+        let end = data.iter().rposition(|b| *b != 0).unwrap();
+        for cut in [end / 4, end / 2, end * 3 / 4] {
+            let Err(err) = HollysysCodec::new(HollysysConfig::new(variant)).decode(&data[..cut]) else { continue };
+            let err = err.downcast_ref::<ParseError>().unwrap_or_else(|| panic!("{variant:?} {name} @{cut}: {err:#}"));
+            assert!(err.offset <= cut, "{variant:?} {name} @{cut}: {err}");
+            assert!(err.window_start <= err.offset);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use plc_core::adapters::plcopen::{
+    PlcOpenCodec, TC6_NAMESPACE, VENDOR_DATA_NAME, read_pou_xml, read_pous_xml, write_pou_xml, write_project_xml,
+};
+use plc_core::ast::UniversalPou;
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+use common::{case, sample, samples};
+
+/// 去掉 Hollysys 无损布局后的 AST（TC6 不携带原始字节）
+fn comparable(pou: &UniversalPou) -> serde_json::Value {
+    let mut pou = pou.clone();
+    pou.preserved = None;
+    for elem in pou.networks.iter_mut().flat_map(|net| &mut net.elements) {
+        elem.preserved = None;
+        elem.pins.iter_mut().for_each(|pin| pin.preserved = None);
+    }
+    serde_json::to_value(pou).unwrap()
+}
+
+fn decode(variant: PlcVariant, data: &[u8]) -> UniversalPou {
+    HollysysCodec::new(HollysysConfig::new(variant)).decode(data).unwrap()
+}
+
+#[test]
+fn decoded_samples_roundtrip_through_tc6() {
+    let codec = PlcOpenCodec::new();
+    for (variant, name) in samples() {
+        let pou = decode(variant, &sample(variant, name));
+        let xml = codec.encode(&pou).unwrap();
+        let text = std::str::from_utf8(&xml).unwrap();
+        assert!(text.contains(TC6_NAMESPACE), "{variant:?} {name}");
+        assert!(text.contains(VENDOR_DATA_NAME), "{variant:?} {name}");
+        assert!(text.contains(&format!("<pou name=\"{}\"", pou.name)), "{variant:?} {name}");
+        assert_eq!(comparable(&codec.decode(&xml).unwrap()), comparable(&pou), "{variant:?} {name}");
+        // 固定的文件头时间：同一 POU 导出结果完全一致
+        assert_eq!(codec.encode(&pou).unwrap(), xml, "{variant:?} {name}");
+    }
+}
+
+#[test]
+fn full_cases_roundtrip_through_tc6() {
+    for (variant, name) in [(PlcVariant::Normal, "普通型样本1.md"), (PlcVariant::Safety, "安全型样本1.md")] {
+        let pou = decode(variant, &case(name));
+        let back = read_pou_xml(&write_pou_xml(&pou)).unwrap();
+        assert_eq!(comparable(&back), comparable(&pou), "{name}");
+    }
+}
+
+#[test]
+fn project_keeps_every_pou() {
+    let pous: Vec<UniversalPou> = ["S04_MOVE", "S06_TP"]
+        .iter()
+        .map(|name| decode(PlcVariant::Normal, &sample(PlcVariant::Normal, name)))
+        .collect();
+    let back = read_pous_xml(&write_project_xml(&pous)).unwrap();
+    assert_eq!(back.len(), 2);
+    for (back, pou) in back.iter().zip(&pous) {
+        assert_eq!(comparable(back), comparable(pou));
+    }
+}
+
+#[test]
+fn control_characters_are_replaced() {
+    let mut pou = decode(PlcVariant::Normal, &sample(PlcVariant::Normal, "S07_DESC"));
+    pou.networks[0].comment = "line1\nline2\u{1}end".to_string();
+    let xml = write_pou_xml(&pou);
+    assert!(!xml.contains('\u{1}'));
+    let back = read_pou_xml(&xml).unwrap();
+    assert_eq!(back.networks[0].comment, "line1\nline2\u{FFFD}end");
+}
+
+#[test]
+fn rejects_non_utf8_and_missing_pou() {
+    let codec = PlcOpenCodec::new();
+    assert!(codec.decode(&[0xFF, 0xFE, 0x00]).is_err());
+    assert!(codec.decode(br#"<project xmlns="http://www.plcopen.org/xml/tc6_0201"/>"#).is_err());
+}
+
+/// S06_TP：功能块实例声明为 `TAG_TP : TP`，引脚成员不作为 TC6 变量导出
+#[test]
+fn block_instance_is_declared_as_derived_variable() {
+    for variant in [PlcVariant::Normal, PlcVariant::Safety] {
+        let pou = decode(variant, &sample(variant, "S06_TP"));
+        let xml = write_pou_xml(&pou);
+        let declarations: Vec<&str> = xml.split("<variable name=\"").skip(1).collect();
+        let tag_tp: Vec<&&str> = declarations.iter().filter(|decl| decl.starts_with("TAG_TP\"")).collect();
+        assert_eq!(tag_tp.len(), 1, "{variant:?}");
+        let derived = tag_tp[0].find("<derived name=\"TP\"/>").unwrap();
+        assert!(derived < tag_tp[0].find("</type>").unwrap(), "{variant:?}");
+        assert!(!xml.contains("name=\"TAG_TP."), "{variant:?}");
+        assert_eq!(comparable(&read_pou_xml(&xml).unwrap()), comparable(&pou), "{variant:?}");
+    }
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use plc_core::adapters::hollysys::{ParseSection, PartialDecode};
+use plc_core::ast::UniversalPou;
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+use common::{sample, samples};
+
+fn find(data: &[u8], needle: &[u8]) -> usize {
+    data.windows(needle.len()).position(|w| w == needle).unwrap()
+}
+
+/// 把 offset 处字节改为 0xEE，确认常规解码失败后返回尽力解码结果
+fn corrupt(variant: PlcVariant, data: &mut [u8], offset: usize) -> PartialDecode {
+    data[offset] = 0xEE;
+    let codec = HollysysCodec::new(HollysysConfig::new(variant));
+    assert!(codec.decode(data).is_err());
+    codec.decode_partial(data).unwrap()
+}
+
+/// 网络中各元件的 (id, 名称, 连接)
+fn elements(pou: &UniversalPou) -> Vec<Vec<(i32, String, Vec<i32>)>> {
+    pou.networks
+        .iter()
+        .map(|net| net.elements.iter().map(|elem| (elem.id, elem.name.clone(), elem.connections.clone())).collect())
+        .collect()
+}
+
+#[test]
+fn intact_samples_decode_completely() {
+    for (variant, name) in samples() {
+        let data = sample(variant, name);
+        let codec = HollysysCodec::new(HollysysConfig::new(variant));
+        let partial = codec.decode_partial(&data).unwrap();
+        assert!(partial.is_complete(), "{variant:?} {name}: {:?}", partial.skipped);
+        assert_eq!(
+            serde_json::to_value(&partial.pou).unwrap(),
+            serde_json::to_value(codec.decode(&data).unwrap()).unwrap(),
+            "{variant:?} {name}"
+        );
+    }
+}
+
+/// 普通型 S09_VAR_FLAGS：CLDAssign 类名之后第 12 字节为元件 id 的首字节
+#[test]
+fn skips_corrupted_object_and_keeps_the_rest() {
+    let mut data = sample(PlcVariant::Normal, "S09_VAR_FLAGS");
+    let intact = HollysysCodec::normal().decode(&data).unwrap();
+    let offset = find(&data, b"CLDAssign") + 12;
+    let partial = corrupt(PlcVariant::Normal, &mut data, offset);
+
+    assert_eq!(partial.skipped.len(), 1, "{:?}", partial.skipped);
+    let skipped = &partial.skipped[0];
+    assert!((skipped.start..skipped.end).contains(&offset), "{skipped:?}");
+    assert!(skipped.reason.message.contains("element.id"), "{}", skipped.reason);
+    assert_eq!(skipped.reason.path.section, ParseSection::Networks);
+    assert_eq!(skipped.reason.path.network_index, Some(0));
+    assert_eq!(skipped.reason.class_name.as_deref(), Some("CLDAssign"));
+
+    assert_eq!(elements(&partial.pou), elements(&intact));
+    assert_eq!(serde_json::to_value(&partial.pou.variables).unwrap(), serde_json::to_value(&intact.variables).unwrap());
+}
+
+/// 普通型 S04_MOVE：CLDBox 的连接数量损坏，该元件被丢弃，其后找不到变量表起点
+#[test]
+fn unlocatable_section_is_recorded_as_empty_range() {
+    let mut data = sample(PlcVariant::Normal, "S04_MOVE");
+    let offset = find(&data, b"CLDBox") + 19;
+    let partial = corrupt(PlcVariant::Normal, &mut data, offset);
+    assert!(!partial.is_complete());
+
+    let element = &partial.skipped[0];
+    assert!((element.start..element.end).contains(&offset), "{element:?}");
+    assert!(element.reason.message.contains("连接数量异常"), "{}", element.reason);
+    assert_eq!((element.reason.path.network_index, element.reason.path.element_id), (Some(0), Some(4)));
+    assert_eq!(element.reason.class_name.as_deref(), Some("CLDBox"));
+    assert_eq!(partial.pou.networks.len(), 1);
+    assert!(partial.pou.networks[0].elements.is_empty());
+
+    let variables = partial.skipped.last().unwrap();
+    assert_eq!(variables.reason.path.section, ParseSection::Variables);
+    assert_eq!(variables.start, variables.end);
+    assert!(partial.pou.variables.is_empty());
+}
+
+/// 安全型 S09_VAR_FLAGS：网络对象的连接数量损坏，后续元件仍按类签名重新定位
+#[test]
+fn safety_elements_resync_after_corrupted_network() {
+    let mut data = sample(PlcVariant::Safety, "S09_VAR_FLAGS");
+    let intact = HollysysCodec::safety().decode(&data).unwrap();
+    let offset = find(&data, b"CLD") + 19;
+    let partial = corrupt(PlcVariant::Safety, &mut data, offset);
+
+    let first = &partial.skipped[0];
+    assert!((first.start..first.end).contains(&offset), "{first:?}");
+    assert!(first.reason.message.contains("连接数量异常"), "{}", first.reason);
+    assert_eq!(first.reason.path.section, ParseSection::Networks);
+    assert_eq!(first.reason.class_name.as_deref(), Some("CLDNetwork"));
+    assert!(partial.skipped.windows(2).all(|pair| pair[0].end <= pair[1].start), "{:?}", partial.skipped);
+
+    assert_eq!(elements(&partial.pou), elements(&intact));
+    assert!(partial.pou.preserved.is_none());
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant};
+
+use common::{case, sample, samples};
+
+/// 强制按 AST 重编码也能逐字节复现的样本
+const REENCODABLE: &[&str] = &["S09_VAR_FLAGS", "S10_VAR_COMMENT", "S11_VAR_TIME"];
+
+#[test]
+fn samples_roundtrip_byte_exact() {
+    for (variant, name) in samples() {
+        let data = sample(variant, name);
+        let report = HollysysCodec::new(HollysysConfig::new(variant)).verify_roundtrip(&data).unwrap();
+        assert!(report.is_exact(), "{variant:?} {name}: {:?}", report.first_diff);
+        assert_eq!((report.original_len, report.encoded_len), (data.len(), data.len()), "{variant:?} {name}");
+        assert!(report.reencode_error.is_none(), "{variant:?} {name}: {:?}", report.reencode_error);
+        if REENCODABLE.contains(&name) {
+            assert!(report.reencode_diff.is_none(), "{variant:?} {name}: {:?}", report.reencode_diff);
+        }
+    }
+    for (variant, name) in [(PlcVariant::Normal, "普通型样本1.md"), (PlcVariant::Safety, "安全型样本1.md")] {
+        let report = HollysysCodec::new(HollysysConfig::new(variant)).verify_roundtrip(&case(name)).unwrap();
+        assert!(report.is_exact(), "{name}: {:?}", report.first_diff);
+    }
+}
+
+#[test]
+fn reencode_diff_pinpoints_segment() {
+    let data = sample(PlcVariant::Normal, "S04_MOVE");
+    let report = HollysysCodec::normal().verify_roundtrip(&data).unwrap();
+    assert!(report.is_exact());
+    let diff = report.reencode_diff.expect("MOVE 变量表尚不能按 AST 复现");
+    assert!(diff.offset < data.len());
+    // 段标签形如 "Variables @起点+段内偏移"
+    let (label, position) = diff.segment.split_once(" @").unwrap();
+    assert_eq!(label, "Variables");
+    let (start, within) = position.split_once('+').unwrap();
+    assert_eq!(start.parse::<usize>().unwrap() + within.parse::<usize>().unwrap(), diff.offset);
+    // 十六进制窗口以差异偏移为中心，两侧各 16 字节
+    assert_ne!(diff.expected, diff.actual);
+    assert_eq!(diff.expected.split(' ').count(), 32);
+    let window: Vec<u8> = diff.expected.split(' ').map(|b| u8::from_str_radix(b, 16).unwrap()).collect();
+    assert_eq!(window, data[diff.offset - 16..diff.offset + 16]);
+}
+
+#[test]
+fn nonzero_padding_is_preserved() {
+    let mut data = sample(PlcVariant::Safety, "S02_NC");
+    *data.last_mut().unwrap() = 0x5A;
+    let report = HollysysCodec::safety().verify_roundtrip(&data).unwrap();
+    assert!(report.is_exact(), "{:?}", report.first_diff);
+}
+
+#[test]
+fn auto_codec_verifies_both_variants() {
+    for (variant, name) in samples() {
+        let report = HollysysCodec::auto().verify_roundtrip(&sample(variant, name)).unwrap();
+        assert!(report.is_exact(), "{variant:?} {name}");
+    }
+}
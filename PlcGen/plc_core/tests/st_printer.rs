@@ -0,0 +1,74 @@
+mod common;
+
+use plc_core::adapters::plcopen::{StPrinter, to_structured_text};
+use plc_core::ast::UniversalPou;
+use plc_core::domain::topology::{TopologyForm, convert_network};
+use plc_core::symbols_config::SymbolConfig;
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+use common::{NORMAL_SAMPLES, SAFETY_SAMPLES, sample};
+
+fn decode(variant: PlcVariant, name: &str) -> UniversalPou {
+    HollysysCodec::new(HollysysConfig::new(variant)).decode(&sample(variant, name)).unwrap()
+}
+
+/// 形式参数调用中不应出现空引脚名
+fn assert_named_pins(variant: PlcVariant, name: &str, text: &str) {
+    for pattern in ["( :=", ",  :=", "( =>", ",  =>"] {
+        assert!(!text.contains(pattern), "{variant:?} {name}: {pattern:?} in\n{text}");
+    }
+}
+
+#[test]
+fn normal_samples_print_formal_calls() {
+    for name in NORMAL_SAMPLES {
+        let text = to_structured_text(&decode(PlcVariant::Normal, name));
+        assert_named_pins(PlcVariant::Normal, name, &text);
+    }
+    let text = to_structured_text(&decode(PlcVariant::Normal, "S04_MOVE"));
+    assert!(text.contains("MOVE(IN := INPUT1, OUT => OUTPUT1);"), "{text}");
+    let text = to_structured_text(&decode(PlcVariant::Normal, "S06_TP"));
+    assert!(text.contains("TAG_TP(IN := TP_IN, PT := T#3S, Q => TP_Q, ET => TP_ET); (* TP *)"), "{text}");
+}
+
+#[test]
+fn safety_samples_complete_pin_names_from_library() {
+    for name in SAFETY_SAMPLES {
+        let text = to_structured_text(&decode(PlcVariant::Safety, name));
+        assert_named_pins(PlcVariant::Safety, name, &text);
+    }
+    // Safety 版 MOVE 的引脚名为空串，按功能块库补全
+    let pou = decode(PlcVariant::Safety, "S04_MOVE");
+    let mv = pou.networks.iter().flat_map(|net| &net.elements).find(|elem| elem.name == "MOVE").unwrap();
+    assert!(mv.pins.iter().any(|pin| pin.name.is_empty()));
+    let text = to_structured_text(&pou);
+    assert!(text.contains("MOVE(IN := INPUT1, OUT => OUTPUT1);"), "{text}");
+}
+
+#[test]
+fn unnamed_pins_fall_back_to_positional_call() {
+    let pou = decode(PlcVariant::Safety, "S04_MOVE");
+    let text = StPrinter::new().with_symbols(&SymbolConfig::default()).print(&pou);
+    assert_named_pins(PlcVariant::Safety, "S04_MOVE", &text);
+    assert!(text.contains("OUTPUT1 := MOVE(INPUT1);"), "{text}");
+}
+
+/// connections 图与 Token 流经同一梯级结构输出；指向网络外元件的连接不参与推导
+#[test]
+fn connection_graph_prints_like_tokens() {
+    let tokens = decode(PlcVariant::Safety, "S09_VAR_FLAGS");
+    let mut graph = tokens.clone();
+    for net in &mut graph.networks {
+        *net = convert_network(net, TopologyForm::Connections).unwrap();
+    }
+    let text = to_structured_text(&tokens);
+    assert_eq!(to_structured_text(&graph), text);
+
+    let mut dangling = graph.clone();
+    dangling.networks[0].elements[0].connections.push(999);
+    assert_eq!(to_structured_text(&dangling), text);
+    let mut unconnected = decode(PlcVariant::Normal, "S09_VAR_FLAGS");
+    let series = to_structured_text(&unconnected);
+    unconnected.networks[0].elements[0].connections.push(999);
+    assert_eq!(to_structured_text(&unconnected), series);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use plc_core::adapters::hollysys::{ParseOptions, read_pou_with_options};
+use plc_core::application::PouValidator;
+use plc_core::application::validator::Rule;
+use plc_core::ast::{PinDirection, UniversalPou, VariableNode};
+use plc_core::symbols_config::SymbolConfig;
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+use common::{case, sample, samples};
+
+#[test]
+fn builtin_library_types_standard_blocks() {
+    let symbols = SymbolConfig::builtin();
+    for name in ["TON", "TOF", "TP", "RS", "SR", "CTU", "CTD", "CTUD", "R_TRIG", "F_TRIG"] {
+        let fb = symbols.get(name).unwrap_or_else(|| panic!("{name} missing"));
+        assert!(fb.is_instance(), "{name}");
+        assert!(fb.inputs.iter().chain(&fb.outputs).all(|pin| !pin.data_type.is_empty()), "{name}");
+    }
+    let ton = symbols.get("TON").unwrap();
+    assert_eq!(ton.pin("PT").map(|(pin, dir)| (pin.data_type.as_str(), dir)), Some(("TIME", PinDirection::Input)));
+    assert_eq!(ton.pin("Q").map(|(_, dir)| dir), Some(PinDirection::Output));
+    assert_eq!(ton.member_type("StartTime"), Some("TIME"));
+
+    let sis = symbols.get("AI_ALARM_IO_SIS").unwrap();
+    assert!(sis.supports(PlcVariant::Safety) && !sis.supports(PlcVariant::Normal));
+    let plc = symbols.get("AI_ALARM_IO_PLC").unwrap();
+    assert!(plc.supports(PlcVariant::Normal) && !plc.supports(PlcVariant::Safety));
+}
+
+fn pin_issues(variant: PlcVariant, pou: &UniversalPou) -> Vec<(Rule, Option<String>)> {
+    let report = PouValidator::new().with_symbols(&SymbolConfig::builtin()).with_variant(variant).validate(pou);
+    report
+        .violations
+        .iter()
+        .filter(|v| matches!(v.rule, Rule::PinMismatch | Rule::PinTypeMismatch))
+        .map(|v| (v.rule, v.location.pin.clone()))
+        .collect()
+}
+
+#[test]
+fn decoded_pins_match_library() {
+    let tp = HollysysCodec::normal().decode(&sample(PlcVariant::Normal, "S06_TP")).unwrap();
+    assert_eq!(pin_issues(PlcVariant::Normal, &tp), []);
+    let ai = HollysysCodec::normal().decode(&case("普通型样本1.md")).unwrap();
+    assert_eq!(pin_issues(PlcVariant::Normal, &ai), []);
+
+    // 安全型 S06_TP 把 ET(TIME) 接到了 BOOL 变量 ET_Q
+    let tp = HollysysCodec::safety().decode(&sample(PlcVariant::Safety, "S06_TP")).unwrap();
+    assert_eq!(pin_issues(PlcVariant::Safety, &tp), [(Rule::PinTypeMismatch, Some("ET".to_string()))]);
+
+    // AI_ALARM_IO_PLC 只能用于普通型
+    let report =
+        PouValidator::new().with_symbols(&SymbolConfig::builtin()).with_variant(PlcVariant::Safety).validate(&ai);
+    assert_eq!(report.errors().filter(|v| v.rule == Rule::UnsupportedFunctionBlock).count(), 3);
+}
+
+fn top_level(pou: &UniversalPou) -> Vec<(String, Option<String>, Vec<String>)> {
+    pou.variables
+        .iter()
+        .filter_map(|node| match node {
+            VariableNode::Group { name, type_name, children } => Some((
+                name.clone(),
+                type_name.clone(),
+                children
+                    .iter()
+                    .filter_map(|child| match child {
+                        VariableNode::Leaf(var) => Some(var.name.clone()),
+                        VariableNode::Group { .. } => None,
+                    })
+                    .collect(),
+            )),
+            VariableNode::Leaf(_) => None,
+        })
+        .collect()
+}
+
+/// 安全型 S06_TP 的头部字符串为 ["BOOL"]：注入同名功能块后其引脚变量归入实例分组
+fn bool_block() -> SymbolConfig {
+    serde_json::from_str(
+        r#"{ "function_blocks": [
+            { "name": "BOOL", "inputs": [{ "name": "TP_IN", "data_type": "BOOL" }],
+              "outputs": [{ "name": "TP_Q", "data_type": "BOOL" }] }
+        ] }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn injected_library_groups_decoded_variables() {
+    let data = sample(PlcVariant::Safety, "S06_TP");
+    let builtin = HollysysCodec::safety().decode(&data).unwrap();
+    assert_eq!(builtin.header_strings, ["BOOL"]);
+    assert!(top_level(&builtin).iter().all(|(name, _, _)| name != "BOOL"));
+
+    let grouped = ("BOOL".to_string(), Some("BOOL".to_string()), vec!["TP_IN".to_string(), "TP_Q".to_string()]);
+    let config = HollysysConfig::new(PlcVariant::Safety).with_symbols(bool_block());
+    let codec = HollysysCodec::new(config);
+    let injected = codec.decode(&data).unwrap();
+    assert_eq!(top_level(&injected)[0], grouped);
+    assert!(top_level(&injected).iter().skip(1).all(|(_, _, vars)| !vars.contains(&"TP_IN".to_string())));
+
+    // 尽力解码与叠加到内置库上的 ParseOptions 同样使用注入的库
+    let merged = SymbolConfig::builtin().merged(bool_block());
+    let options = ParseOptions::new(PlcVariant::Safety).with_symbols(merged);
+    assert_eq!(top_level(&read_pou_with_options(&data, &options).unwrap())[0], grouped);
+    assert_eq!(top_level(&codec.decode_partial(&data).unwrap().pou)[0], grouped);
+}
+
+#[test]
+fn empty_library_falls_back_to_builtin() {
+    for (variant, name) in samples() {
+        let data = sample(variant, name);
+        let default = read_pou_with_options(&data, &ParseOptions::new(variant)).unwrap();
+        let builtin =
+            read_pou_with_options(&data, &ParseOptions::new(variant).with_symbols(SymbolConfig::builtin())).unwrap();
+        assert_eq!(
+            serde_json::to_value(default).unwrap(),
+            serde_json::to_value(builtin).unwrap(),
+            "{variant:?} {name}"
+        );
+    }
+}
+
+#[test]
+fn overlay_replaces_blocks_ignoring_case() {
+    let builtin = SymbolConfig::builtin();
+    let overlay: SymbolConfig = serde_json::from_str(
+        r#"{ "function_blocks": [
+            { "name": "ton", "inputs": [{ "name": "IN", "data_type": "BOOL" }] },
+            { "name": "MY_BLOCK", "kind": "stateless" }
+        ] }"#,
+    )
+    .unwrap();
+    let merged = builtin.clone().merged(overlay);
+    assert_eq!(merged.function_blocks.len(), builtin.function_blocks.len() + 1);
+    let ton = merged.get("TON").unwrap();
+    assert_eq!(ton.name, "ton");
+    assert!(ton.pin("PT").is_none());
+    assert_eq!(merged.function_blocks.last().unwrap().name, "MY_BLOCK");
+    assert!(!merged.get("MY_BLOCK").unwrap().is_instance());
+    assert_eq!(merged.get("TP"), builtin.get("TP"));
+}
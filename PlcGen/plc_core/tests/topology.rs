@@ -0,0 +1,120 @@
+mod common;
+
+use plc_core::ast::{Network, SafetyTopologyToken, UniversalPou};
+use plc_core::domain::topology::{Rung, RungNode, TopologyError, TopologyForm, convert_network, has_topology};
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+use common::{case, sample, samples};
+
+fn decode(variant: PlcVariant, data: &[u8]) -> UniversalPou {
+    HollysysCodec::new(HollysysConfig::new(variant)).decode(data).unwrap()
+}
+
+/// Token 流的简写：元件引用写作 id，其余为标记名
+fn shape(tokens: &[SafetyTopologyToken]) -> Vec<String> {
+    tokens
+        .iter()
+        .map(|token| match token {
+            SafetyTopologyToken::ElementRef { id, .. } => id.to_string(),
+            SafetyTopologyToken::BranchOpen => "(".to_string(),
+            SafetyTopologyToken::BranchNext => "|".to_string(),
+            SafetyTopologyToken::BranchClose => ")".to_string(),
+            SafetyTopologyToken::SeriesNext => "-".to_string(),
+            SafetyTopologyToken::NetEnd => ".".to_string(),
+            other => format!("{other:?}"),
+        })
+        .collect()
+}
+
+/// 元件 id → 排序后的下游 id
+fn edges(net: &Network) -> Vec<(i32, Vec<i32>)> {
+    let mut edges: Vec<(i32, Vec<i32>)> = net
+        .elements
+        .iter()
+        .map(|elem| {
+            let mut dsts = elem.connections.clone();
+            dsts.sort();
+            (elem.id, dsts)
+        })
+        .collect();
+    edges.sort();
+    edges
+}
+
+/// 安全型 S09_VAR_FLAGS：赋值元件 3 之后并联线圈 7 与触点 4
+#[test]
+fn safety_connections_become_parallel_tokens() {
+    let pou = decode(PlcVariant::Safety, &sample(PlcVariant::Safety, "S09_VAR_FLAGS"));
+    let net = &pou.networks[0];
+    assert!(net.safety_topology.is_empty());
+    let rung = Rung::from_network(net).unwrap();
+    assert_eq!(
+        rung.series,
+        [RungNode::Element(3), RungNode::Parallel(vec![vec![RungNode::Element(7)], vec![RungNode::Element(4)]])]
+    );
+
+    let tokens = rung.to_tokens(&net.elements).unwrap();
+    assert_eq!(shape(&tokens), ["3", "-", "(", "7", "|", "4", ")", "."]);
+    for token in &tokens {
+        if let SafetyTopologyToken::ElementRef { id, type_id } = *token {
+            let elem = net.elements.iter().find(|elem| elem.id as u32 == id).unwrap();
+            assert_eq!(type_id, elem.type_code as u16);
+        }
+    }
+    assert_eq!(Rung::from_tokens(&tokens).unwrap(), rung);
+}
+
+#[test]
+fn samples_convert_between_forms() {
+    let cases = samples().map(|(variant, name)| (variant, name.to_string(), sample(variant, name))).chain([
+        (PlcVariant::Normal, "普通型样本1.md".to_string(), case("普通型样本1.md")),
+        (PlcVariant::Safety, "安全型样本1.md".to_string(), case("安全型样本1.md")),
+    ]);
+    for (variant, name, data) in cases {
+        for net in decode(variant, &data).networks {
+            let rung = Rung::from_network(&net).unwrap();
+            assert_eq!(rung.element_ids().len(), net.elements.len(), "{variant:?} {name}");
+
+            let tokens = convert_network(&net, TopologyForm::Tokens).unwrap();
+            assert!(tokens.elements.iter().all(|elem| elem.connections.is_empty()), "{variant:?} {name}");
+            assert_eq!(tokens.safety_topology.is_empty(), net.elements.len() <= 1, "{variant:?} {name}");
+            assert_eq!(Rung::from_network(&tokens).unwrap(), rung, "{variant:?} {name}");
+
+            let back = convert_network(&tokens, TopologyForm::Connections).unwrap();
+            assert!(back.safety_topology.is_empty());
+            assert_eq!(Rung::from_network(&back).unwrap(), rung, "{variant:?} {name}");
+            if has_topology(&net) {
+                assert_eq!(edges(&back), edges(&net), "{variant:?} {name}");
+            }
+        }
+    }
+}
+
+/// 普通型样本的元件之间没有连接：按声明顺序串联，转换为 Token 后每个元件之间插入 SeriesNext
+#[test]
+fn unconnected_elements_follow_declaration_order() {
+    let pou = decode(PlcVariant::Normal, &sample(PlcVariant::Normal, "S09_VAR_FLAGS"));
+    let net = &pou.networks[0];
+    assert!(!has_topology(net));
+    let converted = convert_network(net, TopologyForm::Tokens).unwrap();
+    assert_eq!(shape(&converted.safety_topology), ["7", "-", "4", "."]);
+    assert!(has_topology(&converted));
+}
+
+#[test]
+fn rejects_invalid_topology() {
+    let pou = decode(PlcVariant::Safety, &sample(PlcVariant::Safety, "S09_VAR_FLAGS"));
+    let net = &pou.networks[0];
+
+    let mut cyclic = net.clone();
+    cyclic.elements.iter_mut().find(|elem| elem.id == 7).unwrap().connections = vec![3];
+    assert!(matches!(Rung::from_network(&cyclic), Err(TopologyError::Cycle(_))));
+
+    let unbalanced = [SafetyTopologyToken::ElementRef { id: 3, type_id: 0 }, SafetyTopologyToken::BranchClose];
+    assert_eq!(Rung::from_tokens(&unbalanced), Err(TopologyError::UnbalancedBranch(1)));
+    let overflow = [SafetyTopologyToken::ElementRef { id: u32::MAX, type_id: 0 }];
+    assert_eq!(Rung::from_tokens(&overflow), Err(TopologyError::InvalidElementId(u32::MAX)));
+
+    let rung = Rung::from_network(net).unwrap();
+    assert_eq!(rung.to_tokens(&net.elements[..2]).unwrap_err(), TopologyError::UnknownElement(3));
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use plc_core::application::validator::{Rule, Severity};
+use plc_core::application::{PouValidator, ValidationError, ValidationReport};
+use plc_core::ast::{ElementType, LdElement, Network, UniversalPou};
+use plc_core::symbols_config::SymbolConfig;
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec, PouService};
+
+use common::{case, sample, samples};
+
+fn validator(variant: PlcVariant) -> PouValidator {
+    PouValidator::new().with_symbols(&SymbolConfig::builtin()).with_variant(variant)
+}
+
+fn lossless(variant: PlcVariant) -> HollysysCodec {
+    HollysysCodec::new(HollysysConfig::new(variant).with_lossless(true))
+}
+
+#[test]
+fn decoded_samples_pass_validation() {
+    for (variant, name) in samples() {
+        let data = sample(variant, name);
+        let pou = lossless(variant).decode(&data).unwrap();
+        let report = validator(variant).validate(&pou);
+        assert!(report.is_valid(), "{variant:?} {name}: {:?}", report.errors().collect::<Vec<_>>());
+    }
+    for (variant, name) in [(PlcVariant::Normal, "普通型样本1.md"), (PlcVariant::Safety, "安全型样本1.md")] {
+        let pou = lossless(variant).decode(&case(name)).unwrap();
+        let report = validator(variant).validate(&pou);
+        assert!(report.is_valid(), "{name}: {:?}", report.errors().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn service_reencodes_decoded_samples() {
+    for (variant, name) in samples() {
+        let data = sample(variant, name);
+        let service = PouService::new(lossless(variant)).with_validator(validator(variant));
+        let pou = service.decode(&data).unwrap();
+        assert_eq!(service.encode(&pou).unwrap(), data, "{variant:?} {name}");
+    }
+}
+
+#[test]
+fn undeclared_reference_is_error_only_with_global_table() {
+    // S06_TP 的 TP 引脚引用全局变量 TP_IN/TP_Q/TP_ET
+    let pou = HollysysCodec::normal().decode(&sample(PlcVariant::Normal, "S06_TP")).unwrap();
+    let unknown = |report: &ValidationReport| -> Vec<(Severity, Option<String>)> {
+        report
+            .violations
+            .iter()
+            .filter(|v| v.rule == Rule::UnknownVariable)
+            .map(|v| (v.severity, v.location.pin.clone()))
+            .collect()
+    };
+
+    let report = validator(PlcVariant::Normal).validate(&pou);
+    assert!(report.is_valid());
+    assert_eq!(unknown(&report).len(), 3);
+    assert!(unknown(&report).iter().all(|(severity, _)| *severity == Severity::Warning));
+
+    let report = validator(PlcVariant::Normal).with_global_variables(["TP_IN", "TP_Q"]).validate(&pou);
+    assert_eq!(unknown(&report), [(Severity::Error, Some("ET".to_string()))]);
+    let err = PouService::new(HollysysCodec::normal())
+        .with_validator(validator(PlcVariant::Normal).with_global_variables(["TP_IN", "TP_Q"]))
+        .encode(&pou)
+        .unwrap_err();
+    assert!(err.downcast_ref::<ValidationError>().is_some());
+}
+
+fn topology_issues(report: &ValidationReport) -> Vec<(Rule, Severity)> {
+    report
+        .violations
+        .iter()
+        .filter(|v| matches!(v.rule, Rule::MissingSafetyTopology | Rule::DanglingConnection))
+        .map(|v| (v.rule, v.severity))
+        .collect()
+}
+
+#[test]
+fn tokenless_safety_network_needs_lossless_layout() {
+    let contact = LdElement { connections: vec![3], ..LdElement::new(2, ElementType::Contact, "TRUE") };
+    let coil = LdElement::new(3, ElementType::Coil, "OUT_1");
+    let pou = UniversalPou {
+        name: "NO_TOKENS".to_string(),
+        header_strings: Vec::new(),
+        variables: Vec::new(),
+        networks: vec![Network {
+            id: 1,
+            label: String::new(),
+            comment: String::new(),
+            elements: vec![contact, coil],
+            safety_topology: Vec::new(),
+        }],
+        preserved: None,
+    };
+    // 手工构建的 POU 没有无损布局，编码器不会写出梯级拓扑
+    let report = validator(PlcVariant::Safety).validate(&pou);
+    assert_eq!(topology_issues(&report), [(Rule::MissingSafetyTopology, Severity::Error)]);
+    let err = PouService::new(HollysysCodec::safety()).encode(&pou).unwrap_err();
+    assert!(err.downcast_ref::<ValidationError>().is_some());
+    // Normal 不检查 Token 流；未声明的线圈变量在未提供全局表时只是警告
+    assert!(validator(PlcVariant::Normal).validate(&pou).is_valid());
+    assert!(PouService::new(HollysysCodec::normal()).encode(&pou).is_ok());
+}
+
+/// 安全型样本1：赋值元件连接到解码器未输出的 Or/And 节点 12/14
+#[test]
+fn decoded_safety_topology_is_warning_only_with_layout() {
+    let pou = lossless(PlcVariant::Safety).decode(&case("安全型样本1.md")).unwrap();
+    let report = validator(PlcVariant::Safety).validate(&pou);
+    assert!(report.is_valid(), "{:?}", report.errors().collect::<Vec<_>>());
+    let issues = topology_issues(&report);
+    assert!(issues.contains(&(Rule::DanglingConnection, Severity::Warning)), "{issues:?}");
+    assert!(issues.contains(&(Rule::MissingSafetyTopology, Severity::Warning)), "{issues:?}");
+
+    let pou = HollysysCodec::safety().decode(&case("安全型样本1.md")).unwrap();
+    let report = validator(PlcVariant::Safety).validate(&pou);
+    assert!(!report.is_valid());
+    assert!(topology_issues(&report).iter().all(|(_, severity)| *severity == Severity::Error));
+}
@@ -0,0 +1,42 @@
+use plc_core::adapters::hollysys::{StableHashVarIds, VarIdAllocator, VarIdPool};
+use plc_core::ast::Variable;
+
+fn allocate(name: &str, reserved: &[u16], explicit: &[Variable]) -> u16 {
+    StableHashVarIds::default().allocate(&Variable::new(name, "REAL"), &VarIdPool::new(reserved, explicit)).unwrap()
+}
+
+#[test]
+fn stable_hash_id_depends_only_on_name() {
+    let home = allocate("PT_101", &[], &[]);
+    assert_eq!(allocate("PT_101", &[], &[]), home);
+
+    // 其它变量占用的 ID 与首选槽位无关时不影响结果
+    let others: Vec<Variable> = (1..=50u16)
+        .filter(|id| *id != home)
+        .map(|id| Variable { var_id: Some(id), ..Variable::new(format!("V{id}"), "BOOL") })
+        .collect();
+    assert_eq!(allocate("PT_101", &[2, 3, 5], &others), home);
+}
+
+#[test]
+fn collision_probes_are_derived_from_name() {
+    let home = allocate("PT_101", &[], &[]);
+    let second = allocate("PT_101", &[home], &[]);
+    assert_ne!(second, home);
+
+    // 首选槽位附近被占满时仍落在同一个备选槽位，而不是向后顺延
+    let crowded: Vec<u16> =
+        (home.saturating_sub(64).max(1)..=home.saturating_add(64)).filter(|id| *id != second).collect();
+    assert_eq!(allocate("PT_101", &crowded, &[]), second);
+
+    let third = allocate("PT_101", &[home, second], &[]);
+    assert!(third != home && third != second);
+    assert_eq!(allocate("PT_101", &[home, second], &[]), third);
+}
+
+#[test]
+fn explicit_ids_count_as_taken() {
+    let home = allocate("PT_101", &[], &[]);
+    let explicit = Variable { var_id: Some(home), ..Variable::new("PT_102", "REAL") };
+    assert_eq!(allocate("PT_101", &[], &[explicit]), allocate("PT_101", &[home], &[]));
+}
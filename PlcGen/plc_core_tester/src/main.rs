@@ -3,11 +3,16 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use plc_core::adapters::hollysys::HollysysCodec;
+use plc_core::adapters::hollysys::{HollysysCodec, HollysysConfig, ParseError, PlcVariant, detect, migrate_pou};
+use plc_core::adapters::plcopen::{PlcOpenCodec, to_structured_text};
+use plc_core::application::PouValidator;
 use plc_core::ast::UniversalPou;
+use plc_core::symbols_config::SymbolConfig;
 use plc_core::PouCodec;
 
 const DEFAULT_CASE_DIR: &str = "..\\Docs\\样本对比\\测试用例";
+/// 用户可编辑的功能块库，叠加在内置默认库之上
+const SYMBOL_CONFIG_PATH: &str = "../config/symbols_config.json";
 
 fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
@@ -38,6 +43,9 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    let symbols = SymbolConfig::builtin().merged(SymbolConfig::load_from_file(Path::new(SYMBOL_CONFIG_PATH))?);
+    let codec = HollysysCodec::auto_with_config(HollysysConfig::normal().with_symbols(symbols.clone()));
+
     for path in entries {
         let file_name = path.file_name().unwrap_or_default().to_string_lossy();
         let text = fs::read_to_string(&path)
@@ -49,18 +57,36 @@ fn main() -> Result<()> {
 
         let bytes = parse_hex(&text)
             .with_context(|| format!("failed to parse hex in {}", file_name))?;
-        let variants = variants_for_name(&file_name);
-
-        for (label, codec) in variants {
-            match codec.decode(&bytes) {
-                Ok(pou) => {
-                    let out_path = out_dir.join(format!("{}_{}.json", file_name, label));
-                    write_json(&out_path, &pou)?;
-                    print_summary(&file_name, label, &pou, &out_path);
-                }
-                Err(err) => {
-                    println!("[fail] {} {}: {:#}", file_name, label, err);
+        let format = detect(&bytes);
+        let label = match format.variant {
+            PlcVariant::Normal => "normal",
+            PlcVariant::Safety => "safety",
+        };
+        println!(
+            "[detect] {} -> {} v{} (confidence {:.2})",
+            file_name, label, format.serialize_version, format.confidence
+        );
+
+        match codec.decode(&bytes) {
+            Ok(pou) => {
+                let out_path = out_dir.join(format!("{}_{}.json", file_name, label));
+                write_json(&out_path, &pou)?;
+                let st_path = out_dir.join(format!("{}_{}.st", file_name, label));
+                fs::write(&st_path, to_structured_text(&pou))?;
+                let xml_path = out_dir.join(format!("{}_{}.xml", file_name, label));
+                write_plcopen_xml(&xml_path, &pou)?;
+                print_summary(&file_name, label, &pou, &out_path);
+                let validator = PouValidator::new().with_symbols(&symbols).with_variant(format.variant);
+                report_validation(&validator, &pou);
+                report_roundtrip(&codec, &bytes);
+                report_migration(&pou, format.variant);
+            }
+            Err(err) => {
+                println!("[fail] {} {}: {:#}", file_name, label, err);
+                if let Some(parse_err) = err.downcast_ref::<ParseError>() {
+                    println!("  bytes @0x{:X}: {}", parse_err.window_start, parse_err.window);
                 }
+                report_partial(&codec, &bytes);
             }
         }
     }
@@ -68,6 +94,62 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// 语义校验：输出错误/警告数量及明细
+fn report_validation(validator: &PouValidator, pou: &UniversalPou) {
+    let report = validator.validate(pou);
+    println!(
+        "  [validate] errors={} warnings={}",
+        report.errors().count(),
+        report.warnings().count()
+    );
+    for violation in &report.violations {
+        println!("    {}", violation);
+    }
+}
+
+/// 跨版本迁移：迁移到另一版本并尝试编码
+fn report_migration(pou: &UniversalPou, from: PlcVariant) {
+    let to = match from {
+        PlcVariant::Normal => PlcVariant::Safety,
+        PlcVariant::Safety => PlcVariant::Normal,
+    };
+    let (migrated, report) = migrate_pou(pou, from, to);
+    let encoded = HollysysCodec::new(HollysysConfig::new(to)).encode(&migrated);
+    println!(
+        "  [migrate] -> {:?} issues={} lossless={} encode={}",
+        to,
+        report.issues.len(),
+        report.is_lossless(),
+        match &encoded {
+            Ok(bytes) => format!("ok ({} bytes)", bytes.len()),
+            Err(err) => format!("failed: {:#}", err),
+        }
+    );
+    for issue in &report.issues {
+        println!("    {}", issue);
+    }
+}
+
+/// 尽力解码：列出可抢救的内容与跳过的区间
+fn report_partial(codec: &HollysysCodec, bytes: &[u8]) {
+    match codec.decode_partial(bytes) {
+        Ok(partial) => {
+            let elements: usize = partial.pou.networks.iter().map(|net| net.elements.len()).sum();
+            println!(
+                "  [partial] networks={} elements={} variables={} skipped={}",
+                partial.pou.networks.len(),
+                elements,
+                partial.pou.variables.len(),
+                partial.skipped.len()
+            );
+            for range in &partial.skipped {
+                println!("    skip 0x{:X}..0x{:X}: {}", range.start, range.end, range.reason);
+            }
+        }
+        Err(err) => println!("  [partial] failed: {:#}", err),
+    }
+}
+
 fn parse_hex(text: &str) -> Result<Vec<u8>> {
     let mut bytes = Vec::new();
     for (idx, token) in text.split_whitespace().enumerate() {
@@ -81,25 +163,44 @@ fn parse_hex(text: &str) -> Result<Vec<u8>> {
     Ok(bytes)
 }
 
-fn variants_for_name(file_name: &str) -> Vec<(&'static str, HollysysCodec)> {
-    if file_name.contains("安全") {
-        vec![("safety", HollysysCodec::safety())]
-    } else if file_name.contains("普通") {
-        vec![("normal", HollysysCodec::normal())]
-    } else {
-        vec![
-            ("safety", HollysysCodec::safety()),
-            ("normal", HollysysCodec::normal()),
-        ]
-    }
-}
-
 fn write_json(path: &Path, pou: &UniversalPou) -> Result<()> {
     let json = serde_json::to_string_pretty(pou)?;
     fs::write(path, json)?;
     Ok(())
 }
 
+/// 导出 PLCopen XML 并立即读回，校验交换格式不丢信息
+fn write_plcopen_xml(path: &Path, pou: &UniversalPou) -> Result<()> {
+    let codec = PlcOpenCodec::new();
+    let xml = codec.encode(pou)?;
+    fs::write(path, &xml)?;
+    let reloaded = codec.decode(&xml)?;
+    if serde_json::to_value(pou)? != serde_json::to_value(&reloaded)? {
+        println!("  [warn] PLCopen XML round-trip differs: {}", path.display());
+    }
+    Ok(())
+}
+
+/// 无损往返校验：原样回写必须逐字节一致；强制重编码的差异仅作提示
+fn report_roundtrip(codec: &HollysysCodec, bytes: &[u8]) {
+    match codec.verify_roundtrip(bytes) {
+        Ok(report) => {
+            if let Some(diff) = &report.first_diff {
+                println!(
+                    "  [warn] byte round-trip differs at 0x{:X} ({})\n    expected: {}\n    actual:   {}",
+                    diff.offset, diff.segment, diff.expected, diff.actual
+                );
+            }
+            match (&report.reencode_diff, &report.reencode_error) {
+                (_, Some(err)) => println!("  re-encode failed: {}", err),
+                (Some(diff), None) => println!("  re-encode diverges at 0x{:X} ({})", diff.offset, diff.segment),
+                (None, None) => println!("  re-encode exact"),
+            }
+        }
+        Err(err) => println!("  [warn] round-trip check failed: {:#}", err),
+    }
+}
+
 fn print_summary(file_name: &str, label: &str, pou: &UniversalPou, out_path: &Path) {
     let var_count = count_variables(&pou.variables);
     println!(
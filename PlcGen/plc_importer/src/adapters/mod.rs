@@ -0,0 +1 @@
+pub mod xlsx_workbook;
@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use calamine::{Data, Reader, Sheets, open_workbook_auto};
+
+use crate::domain::import_errors::ImportError;
+use crate::ports::workbook_port::WorkbookSource;
+
+/// Excel 工作簿（xlsx/xlsm/xls/ods，按扩展名识别）
+pub struct XlsxWorkbook {
+    sheets: Sheets<BufReader<File>>,
+}
+
+impl XlsxWorkbook {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImportError> {
+        let path = path.as_ref();
+        let sheets =
+            open_workbook_auto(path).map_err(|source| ImportError::Open { path: path.to_path_buf(), source })?;
+        Ok(Self { sheets })
+    }
+}
+
+impl WorkbookSource for XlsxWorkbook {
+    fn sheet_names(&self) -> Vec<String> {
+        self.sheets.sheet_names()
+    }
+
+    fn read_sheet(&mut self, name: &str) -> Result<Vec<Vec<String>>, ImportError> {
+        if !self.sheets.sheet_names().iter().any(|sheet| sheet == name) {
+            return Err(ImportError::SheetNotFound(name.to_string()));
+        }
+        let range = self
+            .sheets
+            .worksheet_range(name)
+            .map_err(|source| ImportError::Sheet { sheet: name.to_string(), source })?;
+        // calamine 的区域从第一个非空单元格开始，补齐前导空行/空列以保持 Excel 行列号
+        let (top, left) = range.start().map_or((0, 0), |(row, col)| (row as usize, col as usize));
+        let mut rows = vec![Vec::new(); top];
+        rows.extend(range.rows().map(|row| {
+            let mut cells = vec![String::new(); left];
+            cells.extend(row.iter().map(cell_text));
+            cells
+        }));
+        Ok(rows)
+    }
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(text) => text.trim().to_string(),
+        Data::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => format!("{}", *value as i64),
+        other => other.to_string().trim().to_string(),
+    }
+}
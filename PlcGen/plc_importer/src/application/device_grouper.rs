@@ -0,0 +1,179 @@
+use plc_core::symbols_config::SymbolConfig;
+use regex::Regex;
+
+use crate::domain::device_group::{DeviceGroup, DeviceMember, IncompleteDevice, PointSource};
+use crate::domain::grouping_rule::GroupingRule;
+use crate::domain::import_errors::ImportError;
+use crate::domain::import_result::{ImportResult, ImportWarning};
+use crate::domain::point::{PlcPoint, PointKind};
+
+/// Groups points into `DeviceGroup`s by tag naming rules (`XV1001_ZIO` → device `XV1001`,
+/// member `ZIO`), for point tables without a device classification sheet.
+///
+/// Rules are tried in order; the first whose pattern matches a point name claims it.
+/// Suffixes are resolved through the rule's aliases and must name a member of the rule's
+/// function block. Devices already listed in the classification sheet (same device number and
+/// template) are left as they are.
+#[derive(Debug, Clone)]
+pub struct DeviceGrouper {
+    rules: Vec<CompiledRule>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    template_name: String,
+    function_block: String,
+    pattern: Regex,
+    /// (suffix, member) with the member in the library's spelling.
+    aliases: Vec<(String, String)>,
+    required: Vec<String>,
+    members: Vec<String>,
+}
+
+impl CompiledRule {
+    fn compile(rule: &GroupingRule, library: &SymbolConfig) -> Result<Self, ImportError> {
+        let template = || rule.template_name.clone();
+        let pattern =
+            Regex::new(&rule.pattern).map_err(|source| ImportError::InvalidPattern { template: template(), source })?;
+        for group in ["device", "suffix"] {
+            if !pattern.capture_names().flatten().any(|name| name == group) {
+                return Err(ImportError::MissingCaptureGroup { template: template(), group });
+            }
+        }
+        let fb = library
+            .get(rule.function_block())
+            .ok_or_else(|| ImportError::UnknownFunctionBlock(rule.function_block().to_string()))?;
+        let members: Vec<String> = fb.member_names().map(str::to_string).collect();
+        let member =
+            |name: &str| {
+                members.iter().find(|member| member.eq_ignore_ascii_case(name)).cloned().ok_or_else(|| {
+                    ImportError::UnknownMember { function_block: fb.name.clone(), member: name.to_string() }
+                })
+            };
+
+        let aliases = rule
+            .aliases
+            .iter()
+            .map(|(suffix, target)| Ok((suffix.clone(), member(target)?)))
+            .collect::<Result<_, ImportError>>()?;
+        let required = rule.required.iter().map(|name| member(name)).collect::<Result<_, ImportError>>()?;
+        Ok(Self {
+            template_name: rule.template_name.clone(),
+            function_block: fb.name.clone(),
+            pattern,
+            aliases,
+            required,
+            members,
+        })
+    }
+
+    /// (device, suffix) of a point name matched by this rule.
+    fn split<'a>(&self, name: &'a str) -> Option<(&'a str, &'a str)> {
+        let caps = self.pattern.captures(name)?;
+        Some((caps.name("device")?.as_str(), caps.name("suffix")?.as_str()))
+    }
+
+    fn member_for(&self, suffix: &str) -> Option<&str> {
+        let alias = self.aliases.iter().find(|(from, _)| from.eq_ignore_ascii_case(suffix)).map(|(_, to)| to);
+        alias.or_else(|| self.members.iter().find(|member| member.eq_ignore_ascii_case(suffix))).map(String::as_str)
+    }
+
+    fn missing_members(&self, group: &DeviceGroup) -> Vec<String> {
+        self.required
+            .iter()
+            .filter(|name| group.member(name).is_none_or(|member| member.point.is_none()))
+            .cloned()
+            .collect()
+    }
+}
+
+impl DeviceGrouper {
+    /// Compile `rules` against the function block library.
+    /// Fails on an invalid pattern, a pattern without the `device`/`suffix` groups, an unknown
+    /// function block, or an alias target / required member the block does not have.
+    pub fn new(rules: &[GroupingRule], library: &SymbolConfig) -> Result<Self, ImportError> {
+        let rules = rules.iter().map(|rule| CompiledRule::compile(rule, library)).collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    /// The built-in rules (`GroupingRule::defaults`) over the built-in function block library.
+    pub fn builtin() -> Result<Self, ImportError> {
+        Self::new(&GroupingRule::defaults(), &SymbolConfig::builtin())
+    }
+
+    /// Append rule-based device groups to `result` (in `point_order`) and report every device
+    /// group, from the classification sheet or from the rules, that lacks a required member.
+    /// Points whose suffix maps to no member, or to a member that is already bound, are
+    /// recorded as warnings and left out.
+    pub fn group(&self, result: &mut ImportResult) -> Vec<IncompleteDevice> {
+        let mut groups: Vec<DeviceGroup> = Vec::new();
+        let mut warnings = Vec::new();
+        for point in result.ordered_points() {
+            let Some((rule, device, suffix)) = self
+                .rules
+                .iter()
+                .find_map(|rule| rule.split(&point.name).map(|(device, suffix)| (rule, device, suffix)))
+            else {
+                continue;
+            };
+            let listed = |group: &&DeviceGroup| {
+                group.device_no.eq_ignore_ascii_case(device) && group.template_name == rule.template_name
+            };
+            if result.device_groups.iter().any(|group| listed(&group)) {
+                continue;
+            }
+            let Some(member) = rule.member_for(suffix) else {
+                let message = format!(
+                    "点位 {} 的后缀 {} 不是功能块 {} 的成员，未归入设备 {}",
+                    point.name, suffix, rule.function_block, device
+                );
+                warnings.push(point_warning(point, message));
+                continue;
+            };
+
+            let group = match groups.iter().position(|group| listed(&group)) {
+                Some(index) => &mut groups[index],
+                None => {
+                    groups.push(DeviceGroup {
+                        device_no: device.to_string(),
+                        template_name: rule.template_name.clone(),
+                        members: Vec::new(),
+                    });
+                    groups.last_mut().expect("group was just pushed")
+                }
+            };
+            if let Some(bound) = group.member(member).and_then(|bound| bound.point.as_deref()) {
+                let message =
+                    format!("设备 {} 的成员 {} 已绑定点位 {}，点位 {} 被忽略", device, member, bound, point.name);
+                warnings.push(point_warning(point, message));
+                continue;
+            }
+            let source = if point.kind == PointKind::Soft { PointSource::Soft } else { PointSource::Hard };
+            group.members.push(DeviceMember {
+                alias: member.to_string(),
+                point: Some(point.name.clone()),
+                source: Some(source),
+            });
+        }
+        result.device_groups.extend(groups);
+        result.warnings.extend(warnings);
+
+        result
+            .device_groups
+            .iter()
+            .filter_map(|group| {
+                let rule = self.rules.iter().find(|rule| rule.template_name == group.template_name)?;
+                let missing = rule.missing_members(group);
+                (!missing.is_empty()).then(|| IncompleteDevice {
+                    device_no: group.device_no.clone(),
+                    template_name: group.template_name.clone(),
+                    missing,
+                })
+            })
+            .collect()
+    }
+}
+
+fn point_warning(point: &PlcPoint, message: String) -> ImportWarning {
+    ImportWarning { sheet: point.sheet.clone(), row: point.row, column: None, message }
+}
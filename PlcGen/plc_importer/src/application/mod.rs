@@ -0,0 +1,3 @@
+pub mod device_grouper;
+pub mod point_table_importer;
+pub(crate) mod sheet;
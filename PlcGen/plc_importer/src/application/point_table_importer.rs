@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::adapters::xlsx_workbook::XlsxWorkbook;
+use crate::domain::column_mapping::{ColumnMapping, LinkedColumns};
+use crate::domain::device_group::{DeviceGroup, DeviceMember, PointSource};
+use crate::domain::import_errors::ImportError;
+use crate::domain::import_result::ImportResult;
+use crate::domain::point::{AlarmLevel, AlarmLimit, LinkedPoint, PlcPoint, PointKind};
+use crate::ports::workbook_port::WorkbookSource;
+
+use super::sheet::{Header, RowReader, find_header};
+
+/// Imports IO point tables and the device classification sheet into an `ImportResult`.
+///
+/// Columns are located by header name (see `ColumnMapping`), so column order and extra
+/// columns do not matter. A sheet-level problem (unreadable sheet, missing required column)
+/// is an error; a bad row or cell is recorded as a warning and skipped.
+#[derive(Debug, Clone, Default)]
+pub struct PointTableImporter {
+    mapping: ColumnMapping,
+}
+
+/// Column indexes of one point sheet.
+struct PointLayout {
+    name: usize,
+    description: Option<usize>,
+    data_type: Option<usize>,
+    unit: Option<usize>,
+    station: Option<usize>,
+    module_name: Option<usize>,
+    module_type: Option<usize>,
+    channel: Option<usize>,
+    save_history: Option<usize>,
+    power_down_keep: Option<usize>,
+    range_low: Option<usize>,
+    range_high: Option<usize>,
+    plc_address: Option<usize>,
+    comm_address: Option<usize>,
+    limits: Vec<LimitLayout>,
+    maintenance_value: Option<usize>,
+    maintenance_setpoint: LinkedLayout,
+    maintenance_enable: LinkedLayout,
+}
+
+struct LimitLayout {
+    level: AlarmLevel,
+    value: Option<usize>,
+    setpoint: LinkedLayout,
+    alarm: LinkedLayout,
+}
+
+struct LinkedLayout {
+    name: Option<usize>,
+    plc_address: Option<usize>,
+    comm_address: Option<usize>,
+}
+
+impl LinkedLayout {
+    fn resolve(header: &Header, columns: &LinkedColumns) -> Self {
+        Self {
+            name: header.find(&columns.name),
+            plc_address: header.find(&columns.plc_address),
+            comm_address: header.find(&columns.comm_address),
+        }
+    }
+}
+
+impl PointTableImporter {
+    pub fn new(mapping: ColumnMapping) -> Self {
+        Self { mapping }
+    }
+
+    pub fn mapping(&self) -> &ColumnMapping {
+        &self.mapping
+    }
+
+    /// Open an Excel workbook and import it.
+    pub fn import_file(&self, path: impl AsRef<Path>) -> Result<ImportResult, ImportError> {
+        let mut workbook = XlsxWorkbook::open(path)?;
+        self.import(&mut workbook)
+    }
+
+    /// Import every point sheet (in workbook order), then the device classification sheet.
+    pub fn import(&self, source: &mut dyn WorkbookSource) -> Result<ImportResult, ImportError> {
+        let names = source.sheet_names();
+        let explicit = !self.mapping.point_sheets.is_empty();
+        let point_sheets: Vec<String> = if explicit {
+            self.mapping.point_sheets.clone()
+        } else {
+            names.iter().filter(|name| **name != self.mapping.device_sheet).cloned().collect()
+        };
+
+        let mut result = ImportResult::default();
+        let mut first_seen = HashMap::new();
+        let mut any_points = false;
+        for sheet in &point_sheets {
+            let rows = source.read_sheet(sheet)?;
+            let columns = &self.mapping.points;
+            let Some(header) = find_header(&rows, &columns.name, self.mapping.header_search_rows) else {
+                if explicit {
+                    return Err(ImportError::MissingColumn { sheet: sheet.clone(), column: columns.name.join("/") });
+                }
+                continue;
+            };
+            // Classification-style sheets carry a name column too; auto-detection leaves them out.
+            if !explicit && header.find(&self.mapping.devices.device_no).is_some() {
+                continue;
+            }
+            any_points = true;
+            self.read_points(sheet, &rows, &header, &mut result, &mut first_seen);
+        }
+        if !any_points {
+            return Err(ImportError::NoPointSheet);
+        }
+
+        if names.contains(&self.mapping.device_sheet) {
+            let sheet = &self.mapping.device_sheet;
+            let rows = source.read_sheet(sheet)?;
+            self.read_devices(sheet, &rows, &mut result)?;
+        }
+        Ok(result)
+    }
+
+    fn read_points(
+        &self,
+        sheet: &str,
+        rows: &[Vec<String>],
+        header: &Header,
+        result: &mut ImportResult,
+        first_seen: &mut HashMap<String, (String, usize)>,
+    ) {
+        let layout = self.point_layout(header);
+        for (index, cells) in rows.iter().enumerate().skip(header.row + 1) {
+            let mut reader = RowReader { sheet, row: index + 1, cells, header, warnings: &mut result.warnings };
+            if reader.is_blank() {
+                continue;
+            }
+            let Some(name) = reader.text(Some(layout.name)) else {
+                reader.warn_at(Some(layout.name), "变量名称为空，已跳过该行");
+                continue;
+            };
+            if let Some((sheet, row)) = first_seen.get(&name) {
+                let message = format!("变量名称 {} 重复（首次出现于 {} 第 {} 行），已跳过该行", name, sheet, row);
+                reader.warn_at(Some(layout.name), message);
+                continue;
+            }
+
+            let point = read_point(&mut reader, &layout, name);
+            first_seen.insert(point.name.clone(), (sheet.to_string(), point.row));
+            result.point_order.push(point.name.clone());
+            result.points.insert(point.name.clone(), point);
+        }
+    }
+
+    fn point_layout(&self, header: &Header) -> PointLayout {
+        let columns = &self.mapping.points;
+        PointLayout {
+            name: header.find(&columns.name).expect("header row is located by the name column"),
+            description: header.find(&columns.description),
+            data_type: header.find(&columns.data_type),
+            unit: header.find(&columns.unit),
+            station: header.find(&columns.station),
+            module_name: header.find(&columns.module_name),
+            module_type: header.find(&columns.module_type),
+            channel: header.find(&columns.channel),
+            save_history: header.find(&columns.save_history),
+            power_down_keep: header.find(&columns.power_down_keep),
+            range_low: header.find(&columns.range_low),
+            range_high: header.find(&columns.range_high),
+            plc_address: header.find(&columns.plc_address),
+            comm_address: header.find(&columns.comm_address),
+            limits: columns
+                .limits
+                .iter()
+                .map(|limit| LimitLayout {
+                    level: limit.level,
+                    value: header.find(&limit.value),
+                    setpoint: LinkedLayout::resolve(header, &limit.setpoint),
+                    alarm: LinkedLayout::resolve(header, &limit.alarm),
+                })
+                .collect(),
+            maintenance_value: header.find(&columns.maintenance_value),
+            maintenance_setpoint: LinkedLayout::resolve(header, &columns.maintenance_setpoint),
+            maintenance_enable: LinkedLayout::resolve(header, &columns.maintenance_enable),
+        }
+    }
+
+    fn read_devices(&self, sheet: &str, rows: &[Vec<String>], result: &mut ImportResult) -> Result<(), ImportError> {
+        let columns = &self.mapping.devices;
+        let missing = |candidates: &[String]| ImportError::MissingColumn {
+            sheet: sheet.to_string(),
+            column: candidates.join("/"),
+        };
+        let header = find_header(rows, &columns.device_no, self.mapping.header_search_rows)
+            .ok_or_else(|| missing(&columns.device_no))?;
+        let device_no = header.find(&columns.device_no).expect("header row is located by the device column");
+        let alias = header.find(&columns.alias).ok_or_else(|| missing(&columns.alias))?;
+        let point = header.find(&columns.point);
+        let source = header.find(&columns.source);
+        let template_name = header.find(&columns.template_name);
+
+        for (index, cells) in rows.iter().enumerate().skip(header.row + 1) {
+            let mut reader =
+                RowReader { sheet, row: index + 1, cells, header: &header, warnings: &mut result.warnings };
+            if reader.is_blank() {
+                continue;
+            }
+            let Some(device) = reader.text(Some(device_no)) else {
+                reader.warn_at(Some(device_no), "设备位号为空，已跳过该行");
+                continue;
+            };
+            let Some(alias_name) = reader.text(Some(alias)) else {
+                reader.warn_at(Some(alias), "别名为空，已跳过该行");
+                continue;
+            };
+            let Some(template) = reader.text(template_name) else {
+                reader.warn_at(template_name, "模板名称为空，已跳过该行");
+                continue;
+            };
+            let point_name = reader.text(point);
+            if let Some(name) = &point_name
+                && !result.points.contains_key(name)
+            {
+                reader.warn_at(point, format!("点位 {} 不在点位表中", name));
+            }
+            let point_source = reader.text(source).and_then(|text| {
+                let parsed = PointSource::parse(&text);
+                if parsed.is_none() {
+                    reader.warn_at(source, format!("无法识别的点位类型: {}", text));
+                }
+                parsed
+            });
+
+            let group = match result
+                .device_groups
+                .iter()
+                .position(|group| group.device_no == device && group.template_name == template)
+            {
+                Some(position) => &mut result.device_groups[position],
+                None => {
+                    result.device_groups.push(DeviceGroup {
+                        device_no: device,
+                        template_name: template,
+                        members: Vec::new(),
+                    });
+                    result.device_groups.last_mut().expect("group was just pushed")
+                }
+            };
+            if group.member(&alias_name).is_some() {
+                let message = format!("设备 {} 的别名 {} 重复，已跳过该行", group.device_no, alias_name);
+                reader.warn_at(Some(alias), message);
+                continue;
+            }
+            group.members.push(DeviceMember { alias: alias_name, point: point_name, source: point_source });
+        }
+        Ok(())
+    }
+}
+
+fn read_point(reader: &mut RowReader<'_>, layout: &PointLayout, name: String) -> PlcPoint {
+    let channel = reader.text(layout.channel);
+    let module_type = reader.text(layout.module_type);
+    let kind = classify(reader, layout, channel.as_deref(), module_type.as_deref());
+    let data_type = reader.text(layout.data_type).unwrap_or_default().to_ascii_uppercase();
+    if kind.is_digital() && !data_type.is_empty() && data_type != "BOOL" {
+        reader.warn_at(layout.data_type, format!("{} 点位的数据类型应为 BOOL，实际为 {}", kind, data_type));
+    } else if kind.is_analog() && data_type == "BOOL" {
+        reader.warn_at(layout.data_type, format!("{} 点位的数据类型不应为 BOOL", kind));
+    }
+
+    let range_low = reader.number(layout.range_low);
+    let range_high = reader.number(layout.range_high);
+    if let (Some(low), Some(high)) = (range_low, range_high)
+        && low > high
+    {
+        reader.warn(format!("量程低限 {} 大于高限 {}", low, high));
+    }
+    let mut alarms = Vec::new();
+    for limit in &layout.limits {
+        let value = reader.number(limit.value);
+        let setpoint = read_linked(reader, &limit.setpoint);
+        let alarm = read_linked(reader, &limit.alarm);
+        if let (Some(value), Some(low), Some(high)) = (value, range_low, range_high)
+            && !(low..=high).contains(&value)
+        {
+            let message = format!("S{} 设定值 {} 超出量程 [{}, {}]", limit.level.code(), value, low, high);
+            reader.warn_at(limit.value, message);
+        }
+        if value.is_some() || setpoint.is_some() || alarm.is_some() {
+            alarms.push(AlarmLimit { level: limit.level, value, setpoint, alarm });
+        }
+    }
+    alarms.sort_by_key(|alarm| alarm.level);
+
+    PlcPoint {
+        name,
+        description: reader.text(layout.description).unwrap_or_default(),
+        data_type,
+        kind,
+        station: reader.text(layout.station),
+        module_name: reader.text(layout.module_name),
+        module_type,
+        channel,
+        unit: reader.text(layout.unit),
+        range_low,
+        range_high,
+        alarms,
+        maintenance_value: reader.number(layout.maintenance_value),
+        maintenance_setpoint: read_linked(reader, &layout.maintenance_setpoint),
+        maintenance_enable: read_linked(reader, &layout.maintenance_enable),
+        plc_address: reader.text(layout.plc_address),
+        comm_address: reader.text(layout.comm_address),
+        save_history: reader.flag(layout.save_history),
+        power_down_keep: reader.flag(layout.power_down_keep),
+        sheet: reader.sheet.to_string(),
+        row: reader.row,
+    }
+}
+
+/// The channel tag decides (`1_2_AI_0`), then the module type column; points without
+/// a channel are soft points.
+fn classify(
+    reader: &mut RowReader<'_>,
+    layout: &PointLayout,
+    channel: Option<&str>,
+    module: Option<&str>,
+) -> PointKind {
+    let from_module = module.and_then(PointKind::from_code);
+    match channel {
+        Some(channel) => PointKind::from_channel(channel).or(from_module).unwrap_or_else(|| {
+            reader.warn_at(layout.channel, format!("无法从通道位号 {} 识别 AI/AO/DI/DO 类型，按软点处理", channel));
+            PointKind::Soft
+        }),
+        None => from_module.unwrap_or(PointKind::Soft),
+    }
+}
+
+fn read_linked(reader: &mut RowReader<'_>, layout: &LinkedLayout) -> Option<LinkedPoint> {
+    let plc_address = reader.text(layout.plc_address);
+    let comm_address = reader.text(layout.comm_address);
+    match reader.text(layout.name) {
+        Some(name) => Some(LinkedPoint { name, plc_address, comm_address }),
+        None => {
+            if plc_address.is_some() || comm_address.is_some() {
+                reader.warn_at(layout.name, "附属点位有地址但缺少点位名称，已忽略");
+            }
+            None
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::domain::import_result::ImportWarning;
+
+/// Header row of a sheet, with normalized names for matching.
+pub(crate) struct Header {
+    /// Index of the header row in the sheet.
+    pub row: usize,
+    titles: Vec<String>,
+    normalized: Vec<String>,
+}
+
+impl Header {
+    fn new(row: usize, cells: &[String]) -> Self {
+        Self { row, titles: cells.to_vec(), normalized: cells.iter().map(|cell| normalize(cell)).collect() }
+    }
+
+    /// Column of the first candidate that matches: exact normalized titles win over titles
+    /// matched with their trailing parenthesized note stripped.
+    pub fn find(&self, candidates: &[String]) -> Option<usize> {
+        let candidates: Vec<String> = candidates.iter().map(|name| normalize(name)).collect();
+        candidates.iter().find_map(|name| self.normalized.iter().position(|title| title == name)).or_else(|| {
+            candidates.iter().find_map(|name| self.normalized.iter().position(|title| strip_note(title) == name))
+        })
+    }
+
+    pub fn title(&self, column: usize) -> &str {
+        &self.titles[column]
+    }
+}
+
+/// Locate the header row: the first of the leading `search_rows` rows holding one of `candidates`.
+pub(crate) fn find_header(rows: &[Vec<String>], candidates: &[String], search_rows: usize) -> Option<Header> {
+    rows.iter()
+        .take(search_rows.max(1))
+        .enumerate()
+        .map(|(index, cells)| Header::new(index, cells))
+        .find(|header| header.find(candidates).is_some())
+}
+
+/// Reads typed cells of one data row and records row-level warnings.
+pub(crate) struct RowReader<'a> {
+    pub sheet: &'a str,
+    /// 1-based (Excel) row number.
+    pub row: usize,
+    pub cells: &'a [String],
+    pub header: &'a Header,
+    pub warnings: &'a mut Vec<ImportWarning>,
+}
+
+impl RowReader<'_> {
+    pub fn is_blank(&self) -> bool {
+        self.cells.iter().all(|cell| cell.is_empty())
+    }
+
+    pub fn text(&self, column: Option<usize>) -> Option<String> {
+        let cell = self.cells.get(column?)?.trim();
+        (!cell.is_empty()).then(|| cell.to_string())
+    }
+
+    pub fn number(&mut self, column: Option<usize>) -> Option<f64> {
+        let text = self.text(column)?;
+        match text.parse::<f64>() {
+            Ok(value) if value.is_finite() => Some(value),
+            _ => {
+                self.warn_at(column, format!("不是有效数值: {}", text));
+                None
+            }
+        }
+    }
+
+    /// 是/否 style flag; empty cells are `false`.
+    pub fn flag(&mut self, column: Option<usize>) -> bool {
+        let Some(text) = self.text(column) else {
+            return false;
+        };
+        match text.to_ascii_uppercase().as_str() {
+            "是" | "Y" | "YES" | "TRUE" | "1" | "√" => true,
+            "否" | "N" | "NO" | "FALSE" | "0" => false,
+            _ => {
+                self.warn_at(column, format!("无法识别的是/否取值: {}", text));
+                false
+            }
+        }
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(ImportWarning {
+            sheet: self.sheet.to_string(),
+            row: self.row,
+            column: None,
+            message: message.into(),
+        });
+    }
+
+    pub fn warn_at(&mut self, column: Option<usize>, message: impl Into<String>) {
+        self.warnings.push(ImportWarning {
+            sheet: self.sheet.to_string(),
+            row: self.row,
+            column: column.map(|column| self.header.title(column).to_string()),
+            message: message.into(),
+        });
+    }
+}
+
+/// Ignore whitespace, full-width parentheses and ASCII case.
+fn normalize(title: &str) -> String {
+    title
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(|ch| match ch {
+            '（' => '(',
+            '）' => ')',
+            other => other.to_ascii_uppercase(),
+        })
+        .collect()
+}
+
+/// `点位类型(硬点,软点)` → `点位类型`
+fn strip_note(title: &str) -> &str {
+    match title.find('(') {
+        Some(start) if start > 0 && title.ends_with(')') => &title[..start],
+        _ => title,
+    }
+}
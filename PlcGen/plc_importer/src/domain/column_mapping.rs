@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use super::point::AlarmLevel;
+
+/// 点表列映射：每个字段给出候选表头（按优先级）
+/// 表头比较忽略空白、全角/半角括号与大小写；精确匹配不到时再忽略表头末尾的括号说明，
+/// 如 `点位类型（硬点，软点，485，TCP）` 可由 `点位类型` 命中
+/// 所有字段带 serde 默认值，配置文件只需写出与默认 FAT 点表不同的部分
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    /// 设备分类表名称；工作表不存在时不生成设备组
+    pub device_sheet: String,
+    /// 点位表名称；为空时自动识别：除设备分类表外、表头含变量名称列且不含设备位号列的全部工作表
+    pub point_sheets: Vec<String>,
+    /// 在每个工作表的前几行内查找表头
+    pub header_search_rows: usize,
+    pub points: PointColumns,
+    pub devices: DeviceColumns,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        Self {
+            device_sheet: "设备分类表".to_string(),
+            point_sheets: Vec::new(),
+            header_search_rows: 5,
+            points: PointColumns::default(),
+            devices: DeviceColumns::default(),
+        }
+    }
+}
+
+/// 点位表各列；name 为必需列，其余缺失时对应字段为空
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PointColumns {
+    pub name: Vec<String>,
+    pub description: Vec<String>,
+    pub data_type: Vec<String>,
+    pub unit: Vec<String>,
+    pub station: Vec<String>,
+    pub module_name: Vec<String>,
+    pub module_type: Vec<String>,
+    pub channel: Vec<String>,
+    pub save_history: Vec<String>,
+    pub power_down_keep: Vec<String>,
+    pub range_low: Vec<String>,
+    pub range_high: Vec<String>,
+    pub plc_address: Vec<String>,
+    pub comm_address: Vec<String>,
+    pub limits: Vec<LimitColumns>,
+    pub maintenance_value: Vec<String>,
+    pub maintenance_setpoint: LinkedColumns,
+    pub maintenance_enable: LinkedColumns,
+}
+
+impl Default for PointColumns {
+    fn default() -> Self {
+        Self {
+            name: headers(&["变量名称(HMI)", "变量名称"]),
+            description: headers(&["变量描述"]),
+            data_type: headers(&["数据类型"]),
+            unit: headers(&["单位"]),
+            station: headers(&["场站名"]),
+            module_name: headers(&["模块名称"]),
+            module_type: headers(&["模块类型"]),
+            channel: headers(&["通道位号"]),
+            save_history: headers(&["保存历史"]),
+            power_down_keep: headers(&["掉电保护"]),
+            range_low: headers(&["量程低限"]),
+            range_high: headers(&["量程高限"]),
+            plc_address: headers(&["PLC绝对地址", "PLC地址"]),
+            comm_address: headers(&["上位机通讯地址", "MODBUS地址", "通讯地址"]),
+            limits: AlarmLevel::ALL.into_iter().map(LimitColumns::fat).collect(),
+            maintenance_value: headers(&["维护值设定"]),
+            maintenance_setpoint: LinkedColumns::fat("维护值设定点位"),
+            maintenance_enable: LinkedColumns::fat("维护使能开关点位"),
+        }
+    }
+}
+
+/// 单个报警等级的列：设定值、设定点位、报警点位
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitColumns {
+    pub level: AlarmLevel,
+    #[serde(default)]
+    pub value: Vec<String>,
+    #[serde(default)]
+    pub setpoint: LinkedColumns,
+    #[serde(default)]
+    pub alarm: LinkedColumns,
+}
+
+impl LimitColumns {
+    /// FAT 点表写法：`SLL设定值` / `SLL设定点位[_PLC地址|_通讯地址]` / `LL报警[_PLC地址|_通讯地址]`
+    pub fn fat(level: AlarmLevel) -> Self {
+        let code = level.code();
+        Self {
+            level,
+            value: vec![format!("S{}设定值", code)],
+            setpoint: LinkedColumns::fat(&format!("S{}设定点位", code)),
+            alarm: LinkedColumns::fat(&format!("{}报警", code)),
+        }
+    }
+}
+
+/// 附属点位的列：点位名 + PLC 地址 + 通讯地址
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkedColumns {
+    pub name: Vec<String>,
+    pub plc_address: Vec<String>,
+    pub comm_address: Vec<String>,
+}
+
+impl LinkedColumns {
+    /// FAT 点表写法：`{prefix}` / `{prefix}_PLC地址` / `{prefix}_通讯地址`
+    pub fn fat(prefix: &str) -> Self {
+        Self {
+            name: vec![prefix.to_string()],
+            plc_address: vec![format!("{}_PLC地址", prefix)],
+            comm_address: vec![format!("{}_通讯地址", prefix)],
+        }
+    }
+}
+
+/// 设备分类表各列；device_no 与 alias 为必需列
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceColumns {
+    pub point: Vec<String>,
+    pub alias: Vec<String>,
+    pub source: Vec<String>,
+    pub device_no: Vec<String>,
+    pub template_name: Vec<String>,
+}
+
+impl Default for DeviceColumns {
+    fn default() -> Self {
+        Self {
+            point: headers(&["变量名称(HMI)", "变量名称"]),
+            alias: headers(&["别名"]),
+            source: headers(&["点位类型"]),
+            device_no: headers(&["设备位号"]),
+            template_name: headers(&["模板名称"]),
+        }
+    }
+}
+
+fn headers(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+/// 设备分类表中的点位类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointSource {
+    /// 硬点（IO 通道）
+    Hard,
+    /// 软点（PLC 内部变量）
+    Soft,
+    /// 485 串口通讯点
+    Rs485,
+    /// TCP 通讯点
+    Tcp,
+}
+
+impl PointSource {
+    /// 识别点表中的写法：硬点 / 软点 / 485 / TCP
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().to_ascii_uppercase();
+        if text.contains('硬') {
+            Some(PointSource::Hard)
+        } else if text.contains('软') {
+            Some(PointSource::Soft)
+        } else if text.contains("485") {
+            Some(PointSource::Rs485)
+        } else if text.contains("TCP") {
+            Some(PointSource::Tcp)
+        } else {
+            None
+        }
+    }
+}
+
+/// 设备成员：模板别名 → 点位
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceMember {
+    /// 模板中的别名（功能块引脚/成员名），如 ZIO
+    pub alias: String,
+    /// 绑定的点位名；点表中留空表示该别名不接点位
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PointSource>,
+}
+
+/// 设备组：同一设备位号 + 模板名称下的全部成员
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceGroup {
+    /// 设备位号，如 MOV101
+    pub device_no: String,
+    pub template_name: String,
+    /// 按点表行序
+    #[serde(default)]
+    pub members: Vec<DeviceMember>,
+}
+
+impl DeviceGroup {
+    pub fn member(&self, alias: &str) -> Option<&DeviceMember> {
+        self.members.iter().find(|member| member.alias.eq_ignore_ascii_case(alias))
+    }
+
+    /// 别名 → 点位名，跳过未接点位的别名
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.members.iter().filter_map(|member| member.point.as_deref().map(|point| (member.alias.as_str(), point)))
+    }
+}
+
+/// 缺少必需成员的设备
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncompleteDevice {
+    pub device_no: String,
+    pub template_name: String,
+    /// 未绑定点位的必需成员（按规则中的顺序）
+    pub missing: Vec<String>,
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// 按位号命名规则分组设备（无设备分类表时使用）
+/// 点位名由 pattern 拆成设备位号与后缀，如 `XV1001_ZIO` → (`XV1001`, `ZIO`)；
+/// 后缀经 aliases 换算后须是功能块的成员，与功能块成员同名的后缀无需列出
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupingRule {
+    /// 生成的设备组模板名称
+    pub template_name: String,
+    /// 功能块库中的块名；为空时同 template_name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_block: Option<String>,
+    /// 点位名正则，须含命名分组 `device` 与 `suffix`
+    pub pattern: String,
+    /// 后缀 → 功能块成员（不区分大小写），如 ZSH → ZIO
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    /// 必须绑定点位的功能块成员；缺少时报告为不完整设备
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+}
+
+impl GroupingRule {
+    pub fn function_block(&self) -> &str {
+        self.function_block.as_deref().unwrap_or(&self.template_name)
+    }
+
+    /// 开关阀：`XV1001_ZIO` / `XV1001_ZSH`（限位开关写法换算为 ZIO/ZIC）
+    pub fn xv_ctrl() -> Self {
+        Self {
+            template_name: "XV_CTRL".to_string(),
+            function_block: None,
+            pattern: r"(?i)^(?P<device>XV[-_]?\d{2,5}[A-Z]?)_(?P<suffix>[A-Z0-9_]+)$".to_string(),
+            aliases: limit_switch_aliases(&[]),
+            required: members(&["ZIO", "ZIC", "XO", "XC"]),
+        }
+    }
+
+    /// 电动阀：`MOV101_ZSH`；运行/故障信号 RL/F 对应 ZIX/ZIA
+    pub fn mov_ctrl() -> Self {
+        Self {
+            template_name: "MOV_CTRL".to_string(),
+            function_block: None,
+            pattern: r"(?i)^(?P<device>MOV[-_]?\d{2,5}[A-Z]?)_(?P<suffix>[A-Z0-9_]+)$".to_string(),
+            aliases: limit_switch_aliases(&[("RL", "ZIX"), ("F", "ZIA")]),
+            required: members(&["ZIO", "ZIC", "XO", "XC"]),
+        }
+    }
+
+    /// 内置规则：XV_CTRL、MOV_CTRL
+    pub fn defaults() -> Vec<Self> {
+        vec![Self::xv_ctrl(), Self::mov_ctrl()]
+    }
+}
+
+fn limit_switch_aliases(extra: &[(&str, &str)]) -> BTreeMap<String, String> {
+    [("ZSH", "ZIO"), ("ZSL", "ZIC")]
+        .iter()
+        .chain(extra)
+        .map(|(suffix, member)| (suffix.to_string(), member.to_string()))
+        .collect()
+}
+
+fn members(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// 点表导入错误（整表级）；行级问题记录为 ImportWarning，不在此报错
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("点表文件打开失败: {path}: {source}")]
+    Open {
+        path: PathBuf,
+        #[source]
+        source: calamine::Error,
+    },
+    #[error("工作表读取失败: {sheet}: {source}")]
+    Sheet {
+        sheet: String,
+        #[source]
+        source: calamine::Error,
+    },
+    #[error("工作表不存在: {0}")]
+    SheetNotFound(String),
+    #[error("工作表 {sheet} 缺少列: {column}")]
+    MissingColumn { sheet: String, column: String },
+    #[error("点表中没有可识别的点位表（缺少变量名称列）")]
+    NoPointSheet,
+    #[error("分组规则 {template} 的正则无效: {source}")]
+    InvalidPattern {
+        template: String,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("分组规则 {template} 的正则缺少命名分组 {group}")]
+    MissingCaptureGroup { template: String, group: &'static str },
+    #[error("功能块库中没有 {0}")]
+    UnknownFunctionBlock(String),
+    #[error("功能块 {function_block} 没有成员 {member}")]
+    UnknownMember { function_block: String, member: String },
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::device_group::DeviceGroup;
+use super::point::{PlcPoint, PointKind};
+
+/// 点表导入结果（plc_importer → plc_templates / plc_logic_gen 的数据契约）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportResult {
+    /// 点位字典，键为变量名称
+    pub points: HashMap<String, PlcPoint>,
+    #[serde(default)]
+    pub device_groups: Vec<DeviceGroup>,
+    /// 点位名按工作表与行序排列，用于确定性生成
+    #[serde(default)]
+    pub point_order: Vec<String>,
+    /// 行级问题（格式错误、重名、引用缺失等）；对应的单元格或行被跳过，不中断导入
+    #[serde(default)]
+    pub warnings: Vec<ImportWarning>,
+}
+
+impl ImportResult {
+    /// 按 point_order 遍历点位
+    pub fn ordered_points(&self) -> impl Iterator<Item = &PlcPoint> {
+        self.point_order.iter().filter_map(|name| self.points.get(name))
+    }
+
+    pub fn points_of(&self, kind: PointKind) -> impl Iterator<Item = &PlcPoint> {
+        self.ordered_points().filter(move |point| point.kind == kind)
+    }
+}
+
+/// 行级警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportWarning {
+    pub sheet: String,
+    /// Excel 行号（1 起）
+    pub row: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ImportWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "{} 第 {} 行 [{}]: {}", self.sheet, self.row, column, self.message),
+            None => write!(f, "{} 第 {} 行: {}", self.sheet, self.row, self.message),
+        }
+    }
+}
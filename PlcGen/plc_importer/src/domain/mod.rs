@@ -0,0 +1,6 @@
+pub mod column_mapping;
+pub mod device_group;
+pub mod grouping_rule;
+pub mod import_errors;
+pub mod import_result;
+pub mod point;
@@ -0,0 +1,155 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// 点位分类：硬点通道只有 AI/AO/DI/DO 四类，无通道的点位（软点表、公共点位）归为 Soft
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PointKind {
+    AI,
+    AO,
+    DI,
+    DO,
+    Soft,
+}
+
+impl PointKind {
+    /// 识别通道类型标识（不区分大小写），如 `AI`、`di`
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "AI" => Some(PointKind::AI),
+            "AO" => Some(PointKind::AO),
+            "DI" => Some(PointKind::DI),
+            "DO" => Some(PointKind::DO),
+            _ => None,
+        }
+    }
+
+    /// 从通道位号中识别类型，如 `1_2_AI_0`、`R1-S3-DO-07`
+    pub fn from_channel(channel: &str) -> Option<Self> {
+        channel.split(['_', '-', '.', ' ']).find_map(Self::from_code)
+    }
+
+    pub fn is_analog(self) -> bool {
+        matches!(self, PointKind::AI | PointKind::AO)
+    }
+
+    pub fn is_digital(self) -> bool {
+        matches!(self, PointKind::DI | PointKind::DO)
+    }
+}
+
+impl fmt::Display for PointKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            PointKind::AI => "AI",
+            PointKind::AO => "AO",
+            PointKind::DI => "DI",
+            PointKind::DO => "DO",
+            PointKind::Soft => "SOFT",
+        };
+        f.write_str(code)
+    }
+}
+
+/// 报警等级；设定值列以 S 前缀区分（SLL/SL/SH/SHH），报警点列不带前缀（LL/L/H/HH）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AlarmLevel {
+    LL,
+    L,
+    H,
+    HH,
+}
+
+impl AlarmLevel {
+    pub const ALL: [AlarmLevel; 4] = [AlarmLevel::LL, AlarmLevel::L, AlarmLevel::H, AlarmLevel::HH];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            AlarmLevel::LL => "LL",
+            AlarmLevel::L => "L",
+            AlarmLevel::H => "H",
+            AlarmLevel::HH => "HH",
+        }
+    }
+}
+
+/// 点表中随主点位一起定义的附属点位（设定点、报警点、维护点）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkedPoint {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plc_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comm_address: Option<String>,
+}
+
+/// 单个等级的报警限值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlarmLimit {
+    pub level: AlarmLevel,
+    /// 设定值（点表中为文本，解析失败时为空并记录警告）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    /// 设定值点位
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setpoint: Option<LinkedPoint>,
+    /// 报警输出点位
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alarm: Option<LinkedPoint>,
+}
+
+/// 统一点位模型（一行点表）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlcPoint {
+    /// 变量名称（HMI），点位字典的键
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// 数据类型（大写），如 BOOL / REAL
+    #[serde(default)]
+    pub data_type: String,
+    pub kind: PointKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub station: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_type: Option<String>,
+    /// 通道位号，如 `1_2_AI_0`；软点为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_low: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_high: Option<f64>,
+    /// 已配置的报警限值，按 LL/L/H/HH 排序
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alarms: Vec<AlarmLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintenance_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintenance_setpoint: Option<LinkedPoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maintenance_enable: Option<LinkedPoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plc_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comm_address: Option<String>,
+    #[serde(default)]
+    pub save_history: bool,
+    #[serde(default)]
+    pub power_down_keep: bool,
+    /// 来源工作表与行号（1 起，与 Excel 行号一致）
+    pub sheet: String,
+    pub row: usize,
+}
+
+impl PlcPoint {
+    pub fn alarm(&self, level: AlarmLevel) -> Option<&AlarmLimit> {
+        self.alarms.iter().find(|alarm| alarm.level == level)
+    }
+}
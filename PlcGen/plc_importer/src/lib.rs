@@ -1,14 +1,23 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! IO point table import.
+//! Responsibilities: read FAT point tables (IO sheets, soft point sheets and the device
+//! classification sheet) into a unified `ImportResult` of points, device groups and point order.
+//! Columns are mapped by header name; malformed rows become warnings instead of failing the import.
+//! Devices missing from the classification sheet can be grouped by tag naming rules checked
+//! against the function block library.
+//! Non-goals: template selection and instantiation (plc_templates / plc_logic_gen).
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod adapters;
+pub mod application;
+pub mod domain;
+pub mod ports;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use adapters::xlsx_workbook::XlsxWorkbook;
+pub use application::device_grouper::DeviceGrouper;
+pub use application::point_table_importer::PointTableImporter;
+pub use domain::column_mapping::{ColumnMapping, DeviceColumns, LimitColumns, LinkedColumns, PointColumns};
+pub use domain::device_group::{DeviceGroup, DeviceMember, IncompleteDevice, PointSource};
+pub use domain::grouping_rule::GroupingRule;
+pub use domain::import_errors::ImportError;
+pub use domain::import_result::{ImportResult, ImportWarning};
+pub use domain::point::{AlarmLevel, AlarmLimit, LinkedPoint, PlcPoint, PointKind};
+pub use ports::workbook_port::WorkbookSource;
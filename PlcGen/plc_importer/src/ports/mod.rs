@@ -0,0 +1 @@
+pub mod workbook_port;
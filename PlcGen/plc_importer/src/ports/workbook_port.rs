@@ -0,0 +1,11 @@
+use crate::domain::import_errors::ImportError;
+
+/// 点表工作簿读取端口
+/// 单元格统一转为去除首尾空白的文本（整数值的浮点数不带小数部分），空单元格为空串
+pub trait WorkbookSource {
+    /// 工作表名称（按工作簿中的顺序）
+    fn sheet_names(&self) -> Vec<String>;
+
+    /// 读取整张工作表（第 1 行对应 Excel 第 1 行）
+    fn read_sheet(&mut self, name: &str) -> Result<Vec<Vec<String>>, ImportError>;
+}
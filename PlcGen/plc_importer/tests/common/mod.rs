@@ -0,0 +1,41 @@
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use plc_importer::{ImportError, WorkbookSource};
+
+/// 仓库自带的 FAT 点表
+pub fn fat_workbook() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../Docs/德州果子里调压站_IO_点表_FAT_20260120160600.xlsx")
+}
+
+/// 内存工作簿：(表名, 行)
+pub struct MemoryWorkbook(Vec<(String, Vec<Vec<String>>)>);
+
+impl MemoryWorkbook {
+    pub fn new(sheets: &[(&str, &[&[&str]])]) -> Self {
+        Self(
+            sheets
+                .iter()
+                .map(|(name, rows)| {
+                    let rows = rows.iter().map(|row| row.iter().map(|cell| cell.to_string()).collect()).collect();
+                    (name.to_string(), rows)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl WorkbookSource for MemoryWorkbook {
+    fn sheet_names(&self) -> Vec<String> {
+        self.0.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    fn read_sheet(&mut self, name: &str) -> Result<Vec<Vec<String>>, ImportError> {
+        self.0
+            .iter()
+            .find(|(sheet, _)| sheet == name)
+            .map(|(_, rows)| rows.clone())
+            .ok_or_else(|| ImportError::SheetNotFound(name.to_string()))
+    }
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::fat_workbook;
+use plc_importer::{AlarmLevel, PointKind, PointSource, PointTableImporter};
+
+#[test]
+fn imports_fat_workbook() {
+    let result = PointTableImporter::default().import_file(fat_workbook()).expect("import FAT workbook");
+    assert!(result.warnings.is_empty(), "unexpected warnings: {:?}", result.warnings);
+    assert_eq!(result.points.len(), result.point_order.len());
+    assert_eq!(result.point_order.first().map(String::as_str), Some("ESD01"));
+
+    let counts = |kind| result.points_of(kind).count();
+    assert_eq!(counts(PointKind::AI), 16);
+    assert_eq!(counts(PointKind::AO), 0);
+    assert_eq!(counts(PointKind::DI) + counts(PointKind::DO), 40);
+
+    let pt = &result.points["PT0101"];
+    assert_eq!(pt.kind, PointKind::AI);
+    assert_eq!(pt.channel.as_deref(), Some("1_2_AI_0"));
+    assert_eq!((pt.range_low, pt.range_high), (Some(0.0), Some(6.0)));
+    assert_eq!(pt.plc_address.as_deref(), Some("%MD320"));
+    assert!(pt.save_history && pt.power_down_keep);
+    let sll = pt.alarm(AlarmLevel::LL).expect("LL limit");
+    assert_eq!(sll.value, Some(1.2));
+    assert_eq!(sll.setpoint.as_ref().map(|point| point.name.as_str()), Some("PT0101_LoLoLimit"));
+    assert_eq!(sll.alarm.as_ref().and_then(|point| point.plc_address.as_deref()), Some("%MX25.0"));
+
+    let pdt = &result.points["PDT101"];
+    assert_eq!(pdt.alarms.iter().map(|alarm| alarm.level).collect::<Vec<_>>(), [AlarmLevel::H, AlarmLevel::HH]);
+
+    // 软点来自模板点位表与公共点位表
+    assert_eq!(result.points["MOV101_A_OPEN"].kind, PointKind::Soft);
+    assert_eq!(result.points["MOV101_A_OPEN"].comm_address.as_deref(), Some("3256"));
+
+    let groups: Vec<_> = result.device_groups.iter().map(|group| group.device_no.as_str()).collect();
+    assert_eq!(groups, ["MOV101", "MOV102"]);
+    let mov = &result.device_groups[0];
+    assert_eq!(mov.template_name, "MOV_CTRL");
+    assert_eq!(mov.members.len(), 17);
+    assert_eq!(mov.member("ZIO").and_then(|member| member.point.as_deref()), Some("MOV101_ZSH"));
+    assert_eq!(mov.member("ZIS").and_then(|member| member.point.as_deref()), None);
+    assert_eq!(mov.member("C_AM").and_then(|member| member.source), Some(PointSource::Soft));
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{MemoryWorkbook, fat_workbook};
+use plc_core::symbols_config::SymbolConfig;
+use plc_importer::{ColumnMapping, DeviceGrouper, GroupingRule, ImportError, IncompleteDevice, PointTableImporter};
+
+#[test]
+fn naming_rules_reproduce_the_classification_sheet() {
+    let listed = PointTableImporter::default().import_file(fat_workbook()).expect("import");
+    let mapping = ColumnMapping { device_sheet: "无设备分类表".to_string(), ..Default::default() };
+    let mut result = PointTableImporter::new(mapping).import_file(fat_workbook()).expect("import");
+    assert!(result.device_groups.is_empty());
+
+    let grouper = DeviceGrouper::builtin().expect("built-in rules");
+    let incomplete = grouper.group(&mut result);
+    assert!(incomplete.is_empty(), "{:?}", incomplete);
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+    assert_eq!(result.device_groups.len(), listed.device_groups.len());
+    for (grouped, expected) in result.device_groups.iter().zip(&listed.device_groups) {
+        assert_eq!((&grouped.device_no, &grouped.template_name), (&expected.device_no, &expected.template_name));
+        let mut actual: Vec<_> = grouped.bindings().collect();
+        let mut wanted: Vec<_> = expected.bindings().collect();
+        actual.sort();
+        wanted.sort();
+        assert_eq!(actual, wanted);
+    }
+
+    // 设备分类表中已有的设备不再按规则重复分组
+    let mut listed = listed;
+    let before = listed.device_groups.clone();
+    assert!(grouper.group(&mut listed).is_empty());
+    assert_eq!(listed.device_groups, before);
+}
+
+#[test]
+fn reports_incomplete_devices_and_unknown_suffixes() {
+    let mut workbook = MemoryWorkbook::new(&[(
+        "IO",
+        &[
+            &["变量名称", "通道位号"],
+            &["XV1001_ZIO", "1_1_DI_0"],
+            &["XV1001_ZIC", "1_1_DI_1"],
+            &["XV1001_XO", "1_1_DO_0"],
+            &["XV1001_FOO", "1_1_DI_2"],
+            &["XV1002_ZSH", "1_1_DI_3"],
+            &["xv1002_zio", "1_1_DI_4"],
+            &["PT1001", "1_2_AI_0"],
+        ],
+    )]);
+    let mut result = PointTableImporter::default().import(&mut workbook).expect("import");
+    let incomplete = DeviceGrouper::builtin().expect("built-in rules").group(&mut result);
+
+    assert_eq!(result.device_groups.len(), 2);
+    assert_eq!(result.device_groups[1].member("ZIO").and_then(|m| m.point.as_deref()), Some("XV1002_ZSH"));
+    assert_eq!(
+        incomplete,
+        [
+            IncompleteDevice {
+                device_no: "XV1001".to_string(),
+                template_name: "XV_CTRL".to_string(),
+                missing: vec!["XC".to_string()],
+            },
+            IncompleteDevice {
+                device_no: "XV1002".to_string(),
+                template_name: "XV_CTRL".to_string(),
+                missing: vec!["ZIC".to_string(), "XO".to_string(), "XC".to_string()],
+            },
+        ]
+    );
+    let messages: Vec<String> = result.warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "IO 第 5 行: 点位 XV1001_FOO 的后缀 FOO 不是功能块 XV_CTRL 的成员，未归入设备 XV1001",
+            "IO 第 7 行: 设备 xv1002 的成员 ZIO 已绑定点位 XV1002_ZSH，点位 xv1002_zio 被忽略",
+        ]
+    );
+}
+
+#[test]
+fn invalid_rules_are_rejected() {
+    let library = SymbolConfig::builtin();
+    let rule = |pattern: &str, fb: &str, required: &str| GroupingRule {
+        template_name: "VALVE".to_string(),
+        function_block: Some(fb.to_string()),
+        pattern: pattern.to_string(),
+        aliases: Default::default(),
+        required: vec![required.to_string()],
+    };
+    let compile = |rule| DeviceGrouper::new(&[rule], &library).map(|_| ()).unwrap_err();
+
+    let ok = r"^(?P<device>V\d+)_(?P<suffix>\w+)$";
+    assert!(matches!(compile(rule("(", "XV_CTRL", "ZIO")), ImportError::InvalidPattern { .. }));
+    assert!(matches!(
+        compile(rule(r"^(?P<device>V\d+)_\w+$", "XV_CTRL", "ZIO")),
+        ImportError::MissingCaptureGroup { group: "suffix", .. }
+    ));
+    assert!(matches!(compile(rule(ok, "NO_SUCH_FB", "ZIO")), ImportError::UnknownFunctionBlock(_)));
+    assert!(matches!(compile(rule(ok, "XV_CTRL", "ZIX")), ImportError::UnknownMember { .. }));
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::MemoryWorkbook;
+use plc_importer::{ColumnMapping, ImportError, PointKind, PointTableImporter};
+
+#[test]
+fn bad_rows_become_warnings() {
+    let mut workbook = MemoryWorkbook::new(&[
+        (
+            "IO",
+            &[
+                &["点表"],
+                &["通道位号", "Tag Name", "数据类型", "量程低限", "量程高限", "SH设定值", "保存历史"],
+                &["1_1_AI_0", "PT1", "REAL", "0", "10", "12", "是"],
+                &["1_1_DI_0", "", "BOOL", "", "", "", ""],
+                &["1_1_DI_1", "PT1", "BOOL", "", "", "", ""],
+                &["1_1_XX_2", "XV1", "BOOL", "", "", "", "maybe"],
+                &["1_1_AO_3", "FV1", "REAL", "low", "100", "", ""],
+                &["", "", "", "", "", "", ""],
+            ],
+        ),
+        (
+            "设备分类表",
+            &[
+                &["变量名称（HMI）", "别名", "点位类型（硬点，软点，485，TCP）", "设备位号", "模板名称"],
+                &["PT1", "PV", "硬点", "PT1", "AI_CONVERT"],
+                &["PT9", "PV", "串口", "PT9", "AI_CONVERT"],
+                &["PT1", "PV", "硬点", "PT1", "AI_CONVERT"],
+                &["PT2", "PV", "硬点", "", "AI_CONVERT"],
+            ],
+        ),
+    ]);
+    let mut mapping = ColumnMapping::default();
+    mapping.points.name.insert(0, "tag name".to_string());
+    let result = PointTableImporter::new(mapping).import(&mut workbook).expect("import");
+
+    assert_eq!(result.point_order, ["PT1", "XV1", "FV1"]);
+    assert_eq!(result.points["PT1"].row, 3);
+    assert_eq!(result.points["XV1"].kind, PointKind::Soft);
+    assert_eq!(result.points["FV1"].kind, PointKind::AO);
+    assert_eq!(result.points["FV1"].range_low, None);
+
+    let messages: Vec<String> = result.warnings.iter().map(ToString::to_string).collect();
+    let expected = [
+        "IO 第 3 行 [SH设定值]: SH 设定值 12 超出量程 [0, 10]",
+        "IO 第 4 行 [Tag Name]: 变量名称为空，已跳过该行",
+        "IO 第 5 行 [Tag Name]: 变量名称 PT1 重复（首次出现于 IO 第 3 行），已跳过该行",
+        "IO 第 6 行 [通道位号]: 无法从通道位号 1_1_XX_2 识别 AI/AO/DI/DO 类型，按软点处理",
+        "IO 第 6 行 [保存历史]: 无法识别的是/否取值: maybe",
+        "IO 第 7 行 [量程低限]: 不是有效数值: low",
+        "设备分类表 第 3 行 [变量名称（HMI）]: 点位 PT9 不在点位表中",
+        "设备分类表 第 3 行 [点位类型（硬点，软点，485，TCP）]: 无法识别的点位类型: 串口",
+        "设备分类表 第 4 行 [别名]: 设备 PT1 的别名 PV 重复，已跳过该行",
+        "设备分类表 第 5 行 [设备位号]: 设备位号为空，已跳过该行",
+    ];
+    assert_eq!(messages, expected);
+    assert_eq!(result.device_groups.len(), 2);
+}
+
+#[test]
+fn explicit_sheet_without_name_column_is_an_error() {
+    let mut workbook = MemoryWorkbook::new(&[("IO", &[&["通道位号", "描述"], &["1_1_AI_0", "压力"]])]);
+    let mapping = ColumnMapping { point_sheets: vec!["IO".to_string()], ..Default::default() };
+    let err = PointTableImporter::new(mapping).import(&mut workbook).unwrap_err();
+    assert!(matches!(err, ImportError::MissingColumn { ref sheet, .. } if sheet == "IO"));
+
+    let err = PointTableImporter::default().import(&mut workbook).unwrap_err();
+    assert!(matches!(err, ImportError::NoPointSheet));
+}
@@ -0,0 +1,296 @@
+/*
+CommIR v1（通讯采集模块 export_comm_ir_v1 导出的统一中间数据）
+- 只声明轮询程序生成用到的字段，其余字段（verification、decisionsSummary 等）忽略
+- 地址统一为 0-based；读取计划不单独导出，按点位 addressSpec 中的 jobStartAddress/jobLength 还原
+*/
+use serde::{Deserialize, Serialize};
+
+use crate::error::CommPollingError;
+
+pub const COMM_IR_SCHEMA_VERSION: u32 = 1;
+pub const COMM_IR_SPEC_VERSION: &str = "v1";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommIrV1 {
+    pub schema_version: u32,
+    pub spec_version: String,
+    pub mapping: CommIrMapping,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommIrMapping {
+    #[serde(default)]
+    pub points: Vec<CommIrPoint>,
+    #[serde(default)]
+    pub profiles: Vec<ConnectionProfile>,
+}
+
+/// 通讯点位
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommIrPoint {
+    pub point_key: String,
+    /// 变量名称（HMI），即生成的 PLC 变量名
+    pub hmi_name: String,
+    pub channel_name: String,
+    pub data_type: CommDataType,
+    /// 32/64 位数据的字节序
+    pub endian: ByteOrder32,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub rw: String,
+    pub address_spec: CommAddressSpec,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// 点位地址（寄存器/线圈单位，0-based）；未生成读取计划的点位缺少 job 字段
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommAddressSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_area: Option<RegisterArea>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub absolute_address: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_length: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_start_address: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_length: Option<u16>,
+}
+
+/// 点位的读取计划：读取区域、点位地址与所属 job 的地址段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadPlan {
+    pub read_area: RegisterArea,
+    pub absolute_address: u16,
+    pub job_start_address: u16,
+    pub job_length: u16,
+}
+
+impl CommAddressSpec {
+    /// 读取计划；缺少区域、地址或 job 字段时为 None（该点位不进入任何读取任务）
+    pub fn read_plan(&self) -> Option<ReadPlan> {
+        Some(ReadPlan {
+            read_area: self.read_area?,
+            absolute_address: self.absolute_address?,
+            job_start_address: self.job_start_address?,
+            job_length: self.job_length?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CommDataType {
+    Bool,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float32,
+    Float64,
+    #[serde(other)]
+    Unknown,
+}
+
+impl CommDataType {
+    /// 对应的 PLC 基本类型
+    pub fn plc_type(self) -> Option<&'static str> {
+        match self {
+            CommDataType::Bool => Some("BOOL"),
+            CommDataType::Int16 => Some("INT"),
+            CommDataType::UInt16 => Some("UINT"),
+            CommDataType::Int32 => Some("DINT"),
+            CommDataType::UInt32 => Some("UDINT"),
+            CommDataType::Int64 => Some("LINT"),
+            CommDataType::UInt64 => Some("ULINT"),
+            CommDataType::Float32 => Some("REAL"),
+            CommDataType::Float64 => Some("LREAL"),
+            CommDataType::Unknown => None,
+        }
+    }
+
+    /// 占用的寄存器数（位数据按 1 个线圈计）；unitLength 缺省时使用
+    pub fn unit_length(self) -> u16 {
+        match self {
+            CommDataType::Int32 | CommDataType::UInt32 | CommDataType::Float32 => 2,
+            CommDataType::Int64 | CommDataType::UInt64 | CommDataType::Float64 => 4,
+            _ => 1,
+        }
+    }
+
+    /// 占用多个寄存器、需要按字节序拼接
+    pub fn is_multi_register(self) -> bool {
+        !matches!(self, CommDataType::Bool | CommDataType::Int16 | CommDataType::UInt16 | CommDataType::Unknown)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ByteOrder32 {
+    ABCD,
+    BADC,
+    CDAB,
+    DCBA,
+    #[serde(other)]
+    Unknown,
+}
+
+impl ByteOrder32 {
+    /// 转换块 ORDER 引脚的取值
+    pub fn code(self) -> Option<u8> {
+        match self {
+            ByteOrder32::ABCD => Some(0),
+            ByteOrder32::BADC => Some(1),
+            ByteOrder32::CDAB => Some(2),
+            ByteOrder32::DCBA => Some(3),
+            ByteOrder32::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RegisterArea {
+    Holding,
+    Input,
+    Coil,
+    Discrete,
+}
+
+impl RegisterArea {
+    /// Modbus 读功能码
+    pub fn function_code(self) -> u8 {
+        match self {
+            RegisterArea::Coil => 1,
+            RegisterArea::Discrete => 2,
+            RegisterArea::Holding => 3,
+            RegisterArea::Input => 4,
+        }
+    }
+
+    pub fn is_bit(self) -> bool {
+        matches!(self, RegisterArea::Coil | RegisterArea::Discrete)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SerialParity {
+    None,
+    Even,
+    Odd,
+}
+
+/// 通讯通道（连接参数）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "protocolType", rename_all_fields = "camelCase")]
+pub enum ConnectionProfile {
+    #[serde(rename = "TCP")]
+    Tcp {
+        channel_name: String,
+        /// UnitId
+        device_id: u8,
+        read_area: RegisterArea,
+        start_address: u16,
+        length: u16,
+        ip: String,
+        port: u16,
+        timeout_ms: u32,
+        retry_count: u32,
+        poll_interval_ms: u32,
+    },
+    #[serde(rename = "485")]
+    Rtu485 {
+        channel_name: String,
+        /// SlaveId
+        device_id: u8,
+        read_area: RegisterArea,
+        start_address: u16,
+        length: u16,
+        serial_port: String,
+        baud_rate: u32,
+        parity: SerialParity,
+        data_bits: u8,
+        stop_bits: u8,
+        timeout_ms: u32,
+        retry_count: u32,
+        poll_interval_ms: u32,
+    },
+}
+
+impl ConnectionProfile {
+    pub fn channel_name(&self) -> &str {
+        match self {
+            ConnectionProfile::Tcp { channel_name, .. } | ConnectionProfile::Rtu485 { channel_name, .. } => {
+                channel_name
+            }
+        }
+    }
+}
+
+/// 一次批量读取：同一通道、同一区域的连续地址段
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadJob<'a> {
+    pub channel_name: &'a str,
+    pub read_area: RegisterArea,
+    pub start_address: u16,
+    pub length: u16,
+    /// 按地址排序（同地址保持点表顺序）
+    pub points: Vec<&'a CommIrPoint>,
+}
+
+impl CommIrV1 {
+    /// 解析并检查版本
+    pub fn from_json(text: &str) -> Result<Self, CommPollingError> {
+        let ir: Self = serde_json::from_str(text)?;
+        if ir.schema_version != COMM_IR_SCHEMA_VERSION || ir.spec_version != COMM_IR_SPEC_VERSION {
+            return Err(CommPollingError::UnsupportedVersion {
+                schema_version: ir.schema_version,
+                spec_version: ir.spec_version,
+            });
+        }
+        Ok(ir)
+    }
+
+    pub fn profile(&self, channel_name: &str) -> Option<&ConnectionProfile> {
+        self.mapping.profiles.iter().find(|profile| profile.channel_name() == channel_name)
+    }
+
+    /// 还原读取计划：按点位首次出现顺序列出各 job；缺少地址或 job 信息的点位不在其中
+    pub fn read_jobs(&self) -> Vec<ReadJob<'_>> {
+        let mut jobs: Vec<ReadJob> = Vec::new();
+        for point in &self.mapping.points {
+            let Some(ReadPlan { read_area, job_start_address: start_address, job_length: length, .. }) =
+                point.address_spec.read_plan()
+            else {
+                continue;
+            };
+            let same_job = |job: &&mut ReadJob| {
+                job.channel_name == point.channel_name
+                    && job.read_area == read_area
+                    && job.start_address == start_address
+                    && job.length == length
+            };
+            match jobs.iter_mut().find(same_job) {
+                Some(job) => job.points.push(point),
+                None => jobs.push(ReadJob {
+                    channel_name: &point.channel_name,
+                    read_area,
+                    start_address,
+                    length,
+                    points: vec![point],
+                }),
+            }
+        }
+        for job in &mut jobs {
+            job.points.sort_by_key(|point| point.address_spec.absolute_address);
+        }
+        jobs
+    }
+}
@@ -0,0 +1,271 @@
+/*
+通讯轮询程序生成（Modbus TCP/RTU）
+CommIR v1 → 每个读取任务一个网络：读取块（MB_TCP_READ / MB_RTU_READ，实例 {通道}_READ{n}）+ 各点位转换块
+- 转换块按数据类型选择 MB_GET_<PLC 类型>，从读取块实例的 DATA 缓冲区按偏移取值；32/64 位数据带 ORDER（字节序）
+- scale ≠ 1 时先转换到 {hmiName}_RAW，再经 MB_SCALE 输出 REAL 到 {hmiName}
+- 读取块与转换块定义在 config/comm_symbols.json（叠加在内置库之上），现场库不同时用 with_symbols 替换
+- 该库目前均为占位定义（placeholder），块名/引脚未经现场库确认；用到占位块时输出警告
+- 转换块 BUF 引脚与读取块 DATA 成员同为 ARRAY[0..124] OF WORD，绑定时按类型校验
+- 单个点位/通道的问题（无读取计划、地址超出读取任务、类型与区域不匹配、名称非法、缺少连接参数）记为警告并跳过
+*/
+use std::collections::{HashMap, HashSet};
+
+use plc_core::PlcVariant;
+use plc_core::ast::UniversalPou;
+use plc_core::symbols_config::SymbolConfig;
+use plc_pou_builder::{BuildError, LOCAL_GROUP, NetworkId, PouBuilder};
+
+use crate::comm_ir::{CommIrPoint, CommIrV1, ConnectionProfile, ReadJob, SerialParity};
+use crate::error::CommPollingError;
+
+/// 随库编译的轮询功能块库
+const COMM_SYMBOLS_JSON: &str = include_str!("../../config/comm_symbols.json");
+
+/// 轮询功能块库（不含内置库）
+pub fn comm_symbols() -> SymbolConfig {
+    serde_json::from_str(COMM_SYMBOLS_JSON).expect("config/comm_symbols.json is a valid symbol config")
+}
+
+/// 生成结果
+#[derive(Debug, Clone)]
+pub struct CommPollingOutput {
+    pub pou: UniversalPou,
+    /// 被跳过的点位/通道，以及用到的占位功能块
+    pub warnings: Vec<String>,
+}
+
+/// 通讯轮询程序生成器
+#[derive(Debug, Clone)]
+pub struct CommPollingGenerator {
+    pou_name: String,
+    variant: PlcVariant,
+    symbols: SymbolConfig,
+}
+
+impl CommPollingGenerator {
+    /// 使用内置库 + 轮询功能块库
+    pub fn new(pou_name: impl Into<String>, variant: PlcVariant) -> Self {
+        Self { pou_name: pou_name.into(), variant, symbols: SymbolConfig::builtin().merged(comm_symbols()) }
+    }
+
+    /// 替换功能块库；需包含读取块与转换块
+    pub fn with_symbols(mut self, symbols: SymbolConfig) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// 编码生成的 POU 时需使用同一功能块库
+    pub fn symbols(&self) -> &SymbolConfig {
+        &self.symbols
+    }
+
+    pub fn generate(&self, ir: &CommIrV1) -> Result<CommPollingOutput, CommPollingError> {
+        let mut builder = PouBuilder::new(&self.pou_name, self.variant).with_symbols(self.symbols.clone());
+        let mut warnings = Vec::new();
+        for point in &ir.mapping.points {
+            if point.address_spec.read_plan().is_none() {
+                warnings.push(format!("点位 {} 没有读取计划（通道 {}），已跳过", point.hmi_name, point.channel_name));
+            }
+        }
+
+        let mut names = HashSet::new();
+        let mut job_counts: HashMap<&str, usize> = HashMap::new();
+        let mut networks = 0;
+        for job in ir.read_jobs() {
+            let Some(profile) = ir.profile(job.channel_name) else {
+                warnings.push(format!("通道 {} 没有连接参数，跳过 {} 个点位", job.channel_name, job.points.len()));
+                continue;
+            };
+            let points: Vec<(&CommIrPoint, u16)> = job
+                .points
+                .iter()
+                .filter_map(|point| accept(point, &job, &mut names, &mut warnings).map(|offset| (*point, offset)))
+                .collect();
+            if points.is_empty() {
+                continue;
+            }
+
+            let count = job_counts.entry(job.channel_name).or_default();
+            *count += 1;
+            let instance = format!("{}_READ{}", identifier(job.channel_name), count);
+            let net = builder.create_network();
+            builder.set_network_label(net, job.channel_name)?;
+            builder.set_network_comment(
+                net,
+                format!("{:?} {}..{}", job.read_area, job.start_address, job.start_address as u32 + job.length as u32),
+            )?;
+            add_read_block(&mut builder, net, &instance, profile, &job)?;
+            for (point, offset) in points {
+                add_conversion(&mut builder, net, &instance, point, offset, &job, &mut warnings)?;
+            }
+            networks += 1;
+        }
+        if networks == 0 {
+            return Err(CommPollingError::NoReadJob);
+        }
+        let pou = builder.build()?;
+        warnings.extend(self.placeholder_warnings(&pou));
+        Ok(CommPollingOutput { pou, warnings })
+    }
+
+    /// 生成结果中用到的占位功能块（按首次出现顺序）
+    fn placeholder_warnings(&self, pou: &UniversalPou) -> Vec<String> {
+        let mut seen = HashSet::new();
+        pou.networks
+            .iter()
+            .flat_map(|net| &net.elements)
+            .filter(|elem| self.symbols.get(&elem.name).is_some_and(|def| def.placeholder))
+            .filter(|elem| seen.insert(elem.name.to_ascii_uppercase()))
+            .map(|elem| format!("功能块 {} 为占位定义，下装前需按现场库确认块名与引脚", elem.name))
+            .collect()
+    }
+}
+
+/// 点位能否生成：名称合法且不重复、数据类型与读取区域匹配、地址落在读取任务内；返回相对 job 起始地址的偏移
+fn accept(point: &CommIrPoint, job: &ReadJob, names: &mut HashSet<String>, warnings: &mut Vec<String>) -> Option<u16> {
+    let spec = &point.address_spec;
+    let offset = spec.absolute_address.and_then(|absolute| absolute.checked_sub(job.start_address));
+    let unit_length = spec.unit_length.unwrap_or_else(|| point.data_type.unit_length());
+    let problem = if offset.is_none() {
+        Some("地址在读取任务起始地址之前")
+    } else if offset.is_some_and(|offset| offset as u32 + unit_length as u32 > job.length as u32) {
+        Some("地址超出读取任务长度")
+    } else if !is_identifier(&point.hmi_name) {
+        Some("变量名不是合法标识符")
+    } else if point.data_type.plc_type().is_none() {
+        Some("数据类型未知")
+    } else if job.read_area.is_bit() != (point.data_type.plc_type() == Some("BOOL")) {
+        Some("数据类型与读取区域不匹配（线圈/离散输入只支持 Bool，寄存器不支持 Bool）")
+    } else if !names.insert(point.hmi_name.to_ascii_uppercase()) {
+        Some("变量名重复")
+    } else {
+        None
+    };
+    if let Some(problem) = problem {
+        warnings.push(format!("点位 {}: {}，已跳过", point.hmi_name, problem));
+        return None;
+    }
+    offset
+}
+
+fn add_read_block(
+    builder: &mut PouBuilder,
+    net: NetworkId,
+    instance: &str,
+    profile: &ConnectionProfile,
+    job: &ReadJob,
+) -> Result<(), BuildError> {
+    let (block, timing, mut pins) = match profile {
+        ConnectionProfile::Tcp { device_id, ip, port, timeout_ms, retry_count, poll_interval_ms, .. } => (
+            "MB_TCP_READ",
+            (*poll_interval_ms, *timeout_ms, *retry_count),
+            vec![("IP", format!("'{}'", ip)), ("PORT", port.to_string()), ("UNIT_ID", device_id.to_string())],
+        ),
+        ConnectionProfile::Rtu485 {
+            device_id,
+            serial_port,
+            baud_rate,
+            parity,
+            data_bits,
+            stop_bits,
+            timeout_ms,
+            retry_count,
+            poll_interval_ms,
+            ..
+        } => {
+            let parity = match parity {
+                SerialParity::None => 0,
+                SerialParity::Odd => 1,
+                SerialParity::Even => 2,
+            };
+            (
+                "MB_RTU_READ",
+                (*poll_interval_ms, *timeout_ms, *retry_count),
+                vec![
+                    ("COM", format!("'{}'", serial_port)),
+                    ("BAUD", baud_rate.to_string()),
+                    ("PARITY", parity.to_string()),
+                    ("DATA_BITS", data_bits.to_string()),
+                    ("STOP_BITS", stop_bits.to_string()),
+                    ("SLAVE", device_id.to_string()),
+                ],
+            )
+        }
+    };
+    let (poll_ms, timeout_ms, retry) = timing;
+    pins.extend([
+        ("REQ", "TRUE".to_string()),
+        ("CYCLE", format!("T#{}MS", poll_ms)),
+        ("FUNC", job.read_area.function_code().to_string()),
+        ("ADDR", job.start_address.to_string()),
+        ("LEN", job.length.to_string()),
+        ("TIMEOUT", format!("T#{}MS", timeout_ms)),
+        ("RETRY", retry.min(u8::MAX as u32).to_string()),
+        ("ERROR", format!("{}_ERR", instance)),
+    ]);
+
+    let elem = builder.add_block(net, block, Some(instance))?;
+    for (pin, value) in &pins {
+        builder.bind_pin(elem, pin, value)?;
+    }
+    Ok(())
+}
+
+fn add_conversion(
+    builder: &mut PouBuilder,
+    net: NetworkId,
+    instance: &str,
+    point: &CommIrPoint,
+    offset: u16,
+    job: &ReadJob,
+    warnings: &mut Vec<String>,
+) -> Result<(), BuildError> {
+    let plc_type = point.data_type.plc_type().unwrap_or_default();
+    let absolute = job.start_address + offset;
+    let scaled = plc_type != "BOOL" && (point.scale - 1.0).abs() > f64::EPSILON;
+    let output_type = if scaled { "REAL" } else { plc_type };
+    builder.add_variable(&[LOCAL_GROUP], &point.hmi_name, output_type)?.comment =
+        format!("{} {:?} {}", point.channel_name, job.read_area, absolute);
+
+    let convert = builder.add_block(net, &format!("MB_GET_{}", plc_type), None)?;
+    builder.bind_pin(convert, "BUF", &format!("{}.DATA", instance))?;
+    builder.bind_pin(convert, "OFFSET", &offset.to_string())?;
+    if point.data_type.is_multi_register() {
+        let order = point.endian.code().unwrap_or_else(|| {
+            warnings.push(format!("点位 {}: 字节序未知，按 ABCD 处理", point.hmi_name));
+            0
+        });
+        builder.bind_pin(convert, "ORDER", &order.to_string())?;
+    }
+    if !scaled {
+        return builder.bind_pin(convert, "OUT", &point.hmi_name);
+    }
+
+    let raw = format!("{}_RAW", point.hmi_name);
+    builder.bind_pin(convert, "OUT", &raw)?;
+    let scale = builder.add_block(net, "MB_SCALE", None)?;
+    builder.bind_pin(scale, "IN", &raw)?;
+    builder.bind_pin(scale, "K", &real_literal(point.scale))?;
+    builder.bind_pin(scale, "OUT", &point.hmi_name)
+}
+
+fn real_literal(value: f64) -> String {
+    let text = value.to_string();
+    if text.contains(['.', 'e', 'E']) { text } else { format!("{}.0", text) }
+}
+
+/// 通道名转为标识符：非字母数字替换为 '_'，数字开头时补 '_'
+fn identifier(name: &str) -> String {
+    let mut ident: String =
+        name.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
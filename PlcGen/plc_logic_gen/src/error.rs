@@ -0,0 +1,36 @@
+use std::io;
+use std::path::PathBuf;
+
+use plc_pou_builder::BuildError;
+use plc_templates::TemplateError;
+use thiserror::Error;
+
+/// 整体生成失败（无法继续的情况）；单个模板的失败记录在 GenerationReport.errors 中
+#[derive(Debug, Error)]
+pub enum GenerationError {
+    #[error("不支持的品牌: {0}")]
+    UnsupportedBrand(String),
+    #[error("模板仓库读取失败: {0}")]
+    Templates(#[from] TemplateError),
+    #[error("产物写入失败: {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("生成报告序列化失败: {0}")]
+    Report(#[from] serde_json::Error),
+}
+
+/// 通讯轮询程序生成失败；单个点位/通道的问题记录为警告
+#[derive(Debug, Error)]
+pub enum CommPollingError {
+    #[error("CommIR 解析失败: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("不支持的 CommIR 版本: schemaVersion={schema_version}, specVersion={spec_version}")]
+    UnsupportedVersion { schema_version: u32, spec_version: String },
+    #[error("没有可生成的读取任务")]
+    NoReadJob,
+    #[error("轮询程序构造失败: {0}")]
+    Build(#[from] BuildError),
+}
@@ -1,14 +1,27 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! Logic generation pipeline.
+//! Responsibilities: turn an importer result into encoded POUs by selecting templates per IO point
+//! class and device template, planning one template instance per point/device, instantiating,
+//! validating and encoding through `PouCodec`, then writing one artifact per template plus a
+//! machine-readable generation report.
+//! Also builds the Modbus RTU/TCP polling POU from the comm-mapping app's CommIR v1 export.
+//! Non-goals: point table parsing (plc_importer), template storage/rendering (plc_templates) and
+//! clipboard delivery.
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod comm_ir;
+pub mod comm_polling;
+pub mod error;
+pub mod pipeline;
+pub mod planner;
+pub mod report;
+pub mod request;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use comm_ir::{
+    ByteOrder32, CommAddressSpec, CommDataType, CommIrMapping, CommIrPoint, CommIrV1, ConnectionProfile, ReadJob,
+    ReadPlan, RegisterArea, SerialParity,
+};
+pub use comm_polling::{CommPollingGenerator, CommPollingOutput, comm_symbols};
+pub use error::{CommPollingError, GenerationError};
+pub use pipeline::{Generation, GenerationPipeline, PouOutput};
+pub use planner::{IMPLICIT_SET, InstanceBinding, InstancePlan, plan_instances};
+pub use report::{ArtifactSource, GeneratedPou, GenerationReport, REPORT_FILE, ReportEntry};
+pub use request::{GenerationRequest, GenerationStrategy};
@@ -0,0 +1,297 @@
+/*
+生成流水线（Docs/plc_code_doc.md 3.4）
+ImportResult → 按 AI/AO/DI/DO 类别与设备模板名选择模板 → 规划实例 → 实例化 → 校验 → PouCodec 编码 → 产物 + 报告
+- 一个模板对应一个产物 POU：同类点位/同模板设备作为该模板的多个实例
+- 单个模板失败（找不到、实例化/校验/编码失败）记录在报告 errors 中，不影响其它模板
+- 点表中的全部点位（含附属点位）视为全局变量参与校验
+*/
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use plc_core::application::validator::PouValidator;
+use plc_core::ast::UniversalPou;
+use plc_core::symbols_config::SymbolConfig;
+use plc_core::{HollysysCodec, HollysysConfig, PouService};
+use plc_importer::{ImportResult, PlcPoint};
+use plc_templates::{FsTemplateStorage, TemplateMeta, TemplateService, TemplateStorage};
+
+use crate::error::GenerationError;
+use crate::planner::{InstanceBinding, plan_instances};
+use crate::report::{ArtifactSource, GeneratedPou, GenerationReport, REPORT_FILE, ReportEntry};
+use crate::request::GenerationRequest;
+
+/// 已编码的产物 POU
+#[derive(Debug, Clone)]
+pub struct PouOutput {
+    pub pou: UniversalPou,
+    pub bytes: Vec<u8>,
+}
+
+/// 内存中的生成结果；outputs 与 report.artifacts 一一对应
+#[derive(Debug, Clone)]
+pub struct Generation {
+    pub report: GenerationReport,
+    pub outputs: Vec<PouOutput>,
+}
+
+/// 生成流水线
+#[derive(Debug, Clone)]
+pub struct GenerationPipeline {
+    request: GenerationRequest,
+    service: TemplateService,
+    /// 校验与编码使用的功能块库
+    symbols: SymbolConfig,
+}
+
+/// 待生成的产物：模板引用 + 实例
+struct Job {
+    reference: String,
+    source: ArtifactSource,
+    bindings: Vec<InstanceBinding>,
+}
+
+impl GenerationPipeline {
+    /// 使用内置功能块库
+    pub fn new(request: GenerationRequest) -> Self {
+        Self { request, service: TemplateService::new(), symbols: SymbolConfig::builtin() }
+    }
+
+    /// 替换功能块库（现场库与内置库不同时）；模板中的功能块都需在库中登记
+    pub fn with_symbols(mut self, symbols: SymbolConfig) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn request(&self) -> &GenerationRequest {
+        &self.request
+    }
+
+    /// 在内存中生成全部产物，不写文件
+    pub fn generate(&self, result: &ImportResult) -> Result<Generation, GenerationError> {
+        let request = &self.request;
+        if !is_hollysys(&request.brand) {
+            return Err(GenerationError::UnsupportedBrand(request.brand.clone()));
+        }
+        let storage = FsTemplateStorage::new(&request.templates_root);
+        let metas: Vec<TemplateMeta> = storage
+            .list()?
+            .into_iter()
+            .filter(|meta| {
+                meta.key.brand == request.brand && meta.key.series == request.series && meta.key.model == request.model
+            })
+            .collect();
+
+        let mut report = GenerationReport {
+            brand: request.brand.clone(),
+            series: request.series.clone(),
+            model: request.model.clone(),
+            ..GenerationReport::default()
+        };
+        report.warnings.extend(result.warnings.iter().map(|warning| {
+            let message = match &warning.column {
+                Some(column) => format!("[{}] {}", column, warning.message),
+                None => warning.message.clone(),
+            };
+            ReportEntry::new(format!("{} 第 {} 行", warning.sheet, warning.row), message)
+        }));
+
+        let globals = global_variables(result);
+        let mut outputs = Vec::new();
+        for job in self.jobs(result, &mut report) {
+            let index = report.artifacts.len() + 1;
+            match self.build(&job, index, &metas, &storage, &globals) {
+                Ok((artifact, output, warnings)) => {
+                    report.warnings.extend(warnings.into_iter().map(|w| ReportEntry::new(&artifact.pou_name, w)));
+                    report.artifacts.push(artifact);
+                    outputs.push(output);
+                }
+                Err(message) => report.errors.push(ReportEntry::new(&job.reference, message)),
+            }
+        }
+        Ok(Generation { report, outputs })
+    }
+
+    /// 生成并写入 output_dir：每个产物一个 `{POU 名}.bin`，外加 generation_report.json
+    pub fn run(&self, result: &ImportResult) -> Result<GenerationReport, GenerationError> {
+        let Generation { mut report, outputs } = self.generate(result)?;
+        let dir = &self.request.output_dir;
+        fs::create_dir_all(dir).map_err(|source| GenerationError::Io { path: dir.clone(), source })?;
+
+        let mut used = HashSet::new();
+        for (artifact, output) in report.artifacts.iter_mut().zip(&outputs) {
+            let file = unique_file_name(&artifact.pou_name, &mut used);
+            write_file(&dir.join(&file), &output.bytes)?;
+            artifact.file = Some(file);
+        }
+        let json = serde_json::to_vec_pretty(&report)?;
+        write_file(&dir.join(REPORT_FILE), &json)?;
+        Ok(report)
+    }
+
+    /// IO 映射按 io_mapping_fixed 顺序，设备按模板名首次出现顺序
+    fn jobs(&self, result: &ImportResult, report: &mut GenerationReport) -> Vec<Job> {
+        let strategy = &self.request.strategy;
+        let mut jobs = Vec::new();
+        for kind in &strategy.io_mapping_fixed {
+            let bindings: Vec<InstanceBinding> = result.points_of(*kind).map(point_binding).collect();
+            if bindings.is_empty() {
+                continue;
+            }
+            match strategy.io_templates.get(kind) {
+                Some(reference) => jobs.push(Job {
+                    reference: reference.clone(),
+                    source: ArtifactSource::Io { point_kind: kind.to_string() },
+                    bindings,
+                }),
+                None => report
+                    .errors
+                    .push(ReportEntry::new(kind.to_string(), format!("未配置 {} 类点位的 IO 映射模板", kind))),
+            }
+        }
+
+        let mut templates: Vec<&str> = Vec::new();
+        for group in &result.device_groups {
+            if !templates.contains(&group.template_name.as_str()) {
+                templates.push(&group.template_name);
+            }
+        }
+        for template_name in templates {
+            let bindings = result
+                .device_groups
+                .iter()
+                .filter(|group| group.template_name == template_name)
+                .map(|group| InstanceBinding {
+                    tag: group.device_no.clone(),
+                    description: String::new(),
+                    pins: group.bindings().map(|(alias, point)| (alias.to_string(), point.to_string())).collect(),
+                })
+                .collect();
+            jobs.push(Job {
+                reference: strategy.device_template(template_name),
+                source: ArtifactSource::Device { template_name: template_name.to_string() },
+                bindings,
+            });
+        }
+        jobs
+    }
+
+    fn build(
+        &self,
+        job: &Job,
+        index: usize,
+        metas: &[TemplateMeta],
+        storage: &FsTemplateStorage,
+        globals: &HashSet<String>,
+    ) -> Result<(GeneratedPou, PouOutput, Vec<String>), String> {
+        let meta = resolve(metas, &job.reference)?;
+        let bundle = storage.load(&meta.key).map_err(|err| err.to_string())?;
+        let pou_name = self.request.strategy.pou_name(&meta.key.name, &meta.key.category_path, index);
+        let plan = plan_instances(&bundle, &pou_name, &job.bindings)?;
+        let mut warnings = plan.warnings;
+
+        let pou = match plan.bundle.script {
+            Some(_) => self.service.instantiate(&plan.bundle, &plan.config),
+            None => self.service.apply(&plan.bundle, &plan.patch),
+        }
+        .map_err(|err| format!("模板实例化失败: {}", err))?;
+
+        let mut config = HollysysConfig::new(meta.variant).with_symbols(self.symbols.clone());
+        config.serialize_version = meta.serialize_version;
+        let validator = PouValidator::new()
+            .with_symbols(&self.symbols)
+            .with_variant(meta.variant)
+            .with_global_variables(globals.iter().cloned());
+        let service = PouService::new(HollysysCodec::new(config)).with_validator(validator);
+        if service.format_name() != meta.format_name {
+            warnings.push(format!(
+                "模板记录的剪贴板格式 {} 与编码器 {} 不一致",
+                meta.format_name,
+                service.format_name()
+            ));
+        }
+
+        let validation = service.validate(&pou);
+        if !validation.is_valid() {
+            let errors: Vec<String> = validation.errors().map(ToString::to_string).collect();
+            return Err(format!("POU {} 校验失败: {}", pou.name, errors.join("; ")));
+        }
+        warnings.extend(validation.warnings().map(ToString::to_string));
+        let bytes = service.encode(&pou).map_err(|err| format!("POU {} 编码失败: {:#}", pou.name, err))?;
+
+        let artifact = GeneratedPou {
+            pou_name: pou.name.clone(),
+            template: meta.key.clone(),
+            template_version: meta.version,
+            source: job.source.clone(),
+            variant: meta.variant,
+            format_name: service.format_name().to_string(),
+            instances: job.bindings.iter().map(|binding| binding.tag.clone()).collect(),
+            file: None,
+            size: bytes.len(),
+        };
+        Ok((artifact, PouOutput { pou, bytes }, warnings))
+    }
+}
+
+fn is_hollysys(brand: &str) -> bool {
+    let brand = brand.trim();
+    brand == "和利时" || brand.eq_ignore_ascii_case("hollysys")
+}
+
+fn point_binding(point: &PlcPoint) -> InstanceBinding {
+    InstanceBinding { tag: point.name.clone(), description: point.description.clone(), pins: Default::default() }
+}
+
+/// 点位名及其附属点位名（设定点、报警点、维护点）
+fn global_variables(result: &ImportResult) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for point in result.points.values() {
+        names.insert(point.name.clone());
+        let linked = point
+            .alarms
+            .iter()
+            .flat_map(|alarm| [&alarm.setpoint, &alarm.alarm])
+            .chain([&point.maintenance_setpoint, &point.maintenance_enable])
+            .flatten();
+        names.extend(linked.map(|linked| linked.name.clone()));
+    }
+    names
+}
+
+/// 模板引用 `分类路径/模板名`；只写模板名时不限定分类路径
+fn resolve<'a>(metas: &'a [TemplateMeta], reference: &str) -> Result<&'a TemplateMeta, String> {
+    let parts: Vec<&str> = reference.split('/').map(str::trim).filter(|part| !part.is_empty()).collect();
+    let Some((name, category)) = parts.split_last() else {
+        return Err("模板引用为空".to_string());
+    };
+    let matches: Vec<&TemplateMeta> = metas
+        .iter()
+        .filter(|meta| meta.key.name == *name && (category.is_empty() || meta.key.category_path == category))
+        .collect();
+    match matches.as_slice() {
+        [meta] => Ok(meta),
+        [] => Err(format!("未找到模板 {}", reference)),
+        _ => {
+            let keys: Vec<String> = matches.iter().map(|meta| meta.key.to_string()).collect();
+            Err(format!("模板 {} 无法确定，匹配到多个: {}", reference, keys.join(", ")))
+        }
+    }
+}
+
+/// 文件名替换 Windows 非法字符，重名时追加序号
+fn unique_file_name(pou_name: &str, used: &mut HashSet<String>) -> PathBuf {
+    let stem: String =
+        pou_name.chars().map(|c| if c.is_control() || "<>:\"/\\|?*".contains(c) { '_' } else { c }).collect();
+    let mut name = format!("{}.bin", stem);
+    let mut n = 2;
+    while !used.insert(name.to_lowercase()) {
+        name = format!("{}_{}.bin", stem, n);
+        n += 1;
+    }
+    PathBuf::from(name)
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), GenerationError> {
+    fs::write(path, bytes).map_err(|source| GenerationError::Io { path: path.to_path_buf(), source })
+}
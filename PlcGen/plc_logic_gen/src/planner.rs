@@ -0,0 +1,265 @@
+/*
+实例规划：把点位/设备映射为模板集合的实例
+- 样例位号：模板字段 prefix 中出现最多的位号（如模板以 PT0101 为样例），各实例把名称中的样例位号替换为自己的位号
+- 设备实例的别名绑定优先：引脚名与设备分类表别名一致的字段直接替换为绑定的点位名
+- 模板未定义集合时，以全部网络 + 名称含样例位号的变量 + 网络中功能块的实例变量作为隐式集合
+- 集合内名称不含样例位号的变量（通常是功能块实例）按 `原名_实例位号` 改名，保证各实例不共用
+- 引脚绑定既不含样例位号、也没有别名绑定时各实例共用同一变量，记为警告
+- 无脚本的模板直接生成 Patch（rename_pou + repeat_template_set）；有脚本的模板以同样的数据作为 cfg 渲染
+*/
+use std::collections::{BTreeMap, HashSet};
+
+use plc_core::ast::{ElementType, VariableNode};
+use plc_core::domain::topology::network_elements;
+use plc_templates::{
+    FieldAnchor, FieldKind, PATCH_SCHEMA_VERSION, Patch, PatchOp, SetInstance, TemplateBundle, TemplateSet,
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// 隐式集合的 id
+pub const IMPLICIT_SET: &str = "__instances";
+
+/// 一个模板实例：IO 点位或设备
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct InstanceBinding {
+    /// 实例位号（点位名或设备位号），替换模板中的样例位号
+    pub tag: String,
+    #[serde(default)]
+    pub description: String,
+    /// 引脚/成员名 → 点位名（设备分类表的别名绑定）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pins: BTreeMap<String, String>,
+}
+
+/// 规划结果
+#[derive(Debug, Clone)]
+pub struct InstancePlan {
+    /// 补上隐式集合后的模板包
+    pub bundle: TemplateBundle,
+    pub patch: Patch,
+    /// 脚本模板的渲染配置：pou_name / sets / instances
+    pub config: Value,
+    pub warnings: Vec<String>,
+}
+
+/// 为 `bindings` 规划模板实例；模板中找不到样例位号且没有别名绑定时无法区分实例，返回错误
+pub fn plan_instances(
+    bundle: &TemplateBundle,
+    pou_name: &str,
+    bindings: &[InstanceBinding],
+) -> Result<InstancePlan, String> {
+    let mut bundle = bundle.clone();
+    let mut warnings = Vec::new();
+    let tag = sample_tag(&bundle);
+    if tag.is_none() && bindings.iter().all(|binding| binding.pins.is_empty()) {
+        return Err("模板字段中没有可识别的样例位号，无法按实例替换名称".to_string());
+    }
+
+    let set = match bundle.spec.template_sets.first() {
+        Some(set) => {
+            if bundle.spec.template_sets.len() > 1 {
+                warnings.push(format!(
+                    "模板定义了 {} 个集合，只按第一个集合 {} 扩展",
+                    bundle.spec.template_sets.len(),
+                    set.id
+                ));
+            }
+            set.clone()
+        }
+        None => {
+            let set = implicit_set(&bundle, tag.as_deref());
+            bundle.spec.template_sets.push(set.clone());
+            set
+        }
+    };
+
+    let instances: Vec<SetInstance> = bindings
+        .iter()
+        .enumerate()
+        .map(|(index, binding)| SetInstance {
+            index: Some(index as u32 + 1),
+            vars: instance_vars(&bundle, &set, tag.as_deref(), index, binding),
+        })
+        .collect();
+    if bindings.len() > 1 {
+        warnings.extend(shared_pin_warnings(&bundle, &set, &instances));
+    }
+
+    let patch = Patch {
+        schema_version: PATCH_SCHEMA_VERSION,
+        ops: vec![
+            PatchOp::RenamePou { name: pou_name.to_string(), expected: None },
+            PatchOp::RepeatTemplateSet { set: set.id.clone(), instances: instances.clone() },
+        ],
+    };
+    let config = json!({
+        "pou_name": pou_name,
+        "sets": { set.id.clone(): instances },
+        "instances": bindings,
+    });
+    Ok(InstancePlan { bundle, patch, config, warnings })
+}
+
+/// 字段 prefix 中出现最多的位号（并列时取先出现者）
+fn sample_tag(bundle: &TemplateBundle) -> Option<String> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for prefix in
+        bundle.spec.fields.iter().filter(|field| field.kind != FieldKind::PouName).filter_map(|f| f.prefix.as_deref())
+    {
+        match counts.iter_mut().find(|(tag, _)| tag.eq_ignore_ascii_case(prefix)) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((prefix, 1)),
+        }
+    }
+    let best = counts.iter().map(|(_, count)| *count).max()?;
+    counts.into_iter().find(|(_, count)| *count == best).map(|(tag, _)| tag.trim_end_matches('_').to_string())
+}
+
+/// 全部网络 + 名称含样例位号的叶子变量/功能块实例变量 + 网络中功能块的实例变量
+fn implicit_set(bundle: &TemplateBundle, tag: Option<&str>) -> TemplateSet {
+    let instances: HashSet<String> = bundle
+        .pou
+        .networks
+        .iter()
+        .flat_map(network_elements)
+        .filter(|elem| elem.type_code == ElementType::Box && !elem.instance.trim().is_empty())
+        .map(|elem| elem.instance.to_ascii_uppercase())
+        .collect();
+    let mut variables = Vec::new();
+    collect_set_variables(&bundle.pou.variables, tag, &instances, &mut Vec::new(), &mut variables);
+    TemplateSet {
+        id: IMPLICIT_SET.to_string(),
+        networks: bundle.pou.networks.iter().map(|net| net.id).collect(),
+        variables,
+    }
+}
+
+/// 名称含样例位号或是功能块实例的变量路径；解码出的实例成员组不带 type_name，按实例名识别
+fn collect_set_variables(
+    nodes: &[VariableNode],
+    tag: Option<&str>,
+    instances: &HashSet<String>,
+    path: &mut Vec<String>,
+    out: &mut Vec<Vec<String>>,
+) {
+    for node in nodes {
+        let name = match node {
+            VariableNode::Leaf(var) => &var.name,
+            VariableNode::Group { name, .. } => name,
+        };
+        let instance = instances.contains(&name.to_ascii_uppercase());
+        match node {
+            VariableNode::Group { type_name: None, children, .. } if !instance => {
+                path.push(name.clone());
+                collect_set_variables(children, tag, instances, path, out);
+                path.pop();
+            }
+            _ if instance || tag.is_some_and(|tag| find_ignore_case(name, tag).is_some()) => {
+                out.push(path.iter().cloned().chain([name.clone()]).collect());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 原名 → 实例名：别名绑定优先，其次替换样例位号；名称不变的不列出
+/// 集合变量不含样例位号时追加实例位号（位号为空时用实例序号）
+fn instance_vars(
+    bundle: &TemplateBundle,
+    set: &TemplateSet,
+    tag: Option<&str>,
+    index: usize,
+    binding: &InstanceBinding,
+) -> BTreeMap<String, String> {
+    let mut vars = BTreeMap::new();
+    let mut add = |original: &str, renamed: String| {
+        if renamed != original {
+            vars.entry(original.to_string()).or_insert(renamed);
+        }
+    };
+    for field in &bundle.spec.fields {
+        if matches!(field.kind, FieldKind::PouName | FieldKind::VariableComment) {
+            continue;
+        }
+        let bound = match (&field.anchor, field.kind) {
+            (FieldAnchor::Element { pin: Some(pin), .. }, FieldKind::PinBinding) => {
+                binding.pins.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(pin)).map(|(_, point)| point.clone())
+            }
+            _ => None,
+        };
+        match (bound, tag) {
+            (Some(point), _) => add(&field.original, point),
+            (None, Some(tag)) => add(&field.original, replace_ignore_case(&field.original, tag, &binding.tag)),
+            (None, None) => {}
+        }
+    }
+    let suffix = match binding.tag.trim() {
+        "" => (index + 1).to_string(),
+        tag => tag.to_string(),
+    };
+    for name in set.variables.iter().filter_map(|path| path.last()) {
+        match tag {
+            Some(tag) if find_ignore_case(name, tag).is_some() => {
+                add(name, replace_ignore_case(name, tag, &binding.tag))
+            }
+            _ => add(name, format!("{}_{}", name, suffix)),
+        }
+    }
+    vars
+}
+
+/// 集合网络中各实例都未改名的引脚绑定（常量除外）：全部实例读写同一变量
+fn shared_pin_warnings(bundle: &TemplateBundle, set: &TemplateSet, instances: &[SetInstance]) -> Vec<String> {
+    let renamed =
+        |name: &str| instances.iter().any(|instance| instance.vars.keys().any(|k| k.eq_ignore_ascii_case(name)));
+    let mut warnings = Vec::new();
+    let elements = bundle.pou.networks.iter().filter(|net| set.networks.contains(&net.id)).flat_map(network_elements);
+    for elem in elements.filter(|elem| elem.type_code == ElementType::Box) {
+        for pin in &elem.pins {
+            let variable = pin.variable.trim();
+            if variable.is_empty() || variable == "???" || is_constant(variable) {
+                continue;
+            }
+            let head = variable.split('.').next().unwrap_or(variable);
+            if renamed(variable) || renamed(head) {
+                continue;
+            }
+            let block = if elem.instance.is_empty() { &elem.name } else { &elem.instance };
+            let message =
+                format!("{} 的引脚 {} 绑定 {}，各实例均未改名，全部实例共用该变量", block, pin.name, variable);
+            if !warnings.contains(&message) {
+                warnings.push(message);
+            }
+        }
+    }
+    warnings
+}
+
+/// 常量绑定（数值、TIME 等带 # 的字面量、TRUE/FALSE、字符串）
+fn is_constant(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '+' | '-' | '\'' | '"'))
+        || value.contains('#')
+        || value.eq_ignore_ascii_case("TRUE")
+        || value.eq_ignore_ascii_case("FALSE")
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    if needle.is_empty() {
+        return None;
+    }
+    haystack.to_ascii_uppercase().find(&needle.to_ascii_uppercase())
+}
+
+/// 替换全部出现（ASCII 不区分大小写）
+fn replace_ignore_case(text: &str, from: &str, to: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(pos) = find_ignore_case(rest, from) {
+        out.push_str(&rest[..pos]);
+        out.push_str(to);
+        rest = &rest[pos + from.len()..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use plc_core::PlcVariant;
+use plc_templates::TemplateKey;
+use serde::{Deserialize, Serialize};
+
+/// 生成报告文件名（写在 output_dir 下）
+pub const REPORT_FILE: &str = "generation_report.json";
+
+/// 生成报告：全部产物 + 警告 + 失败的模板
+/// 单个模板失败不影响其它模板，记录在 errors 中
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationReport {
+    pub brand: String,
+    pub series: String,
+    pub model: String,
+    #[serde(default)]
+    pub artifacts: Vec<GeneratedPou>,
+    #[serde(default)]
+    pub warnings: Vec<ReportEntry>,
+    #[serde(default)]
+    pub errors: Vec<ReportEntry>,
+}
+
+impl GenerationReport {
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// 产物来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArtifactSource {
+    /// IO 映射（按点位类别）
+    Io { point_kind: String },
+    /// 设备模板（按设备分类表的模板名）
+    Device { template_name: String },
+}
+
+/// 单个产物（一个模板对应一个 POU）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedPou {
+    pub pou_name: String,
+    pub template: TemplateKey,
+    pub template_version: u32,
+    pub source: ArtifactSource,
+    pub variant: PlcVariant,
+    pub format_name: String,
+    /// 实例（点位名或设备位号），按生成顺序
+    pub instances: Vec<String>,
+    /// 产物文件（相对 output_dir）；仅在内存中生成时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    pub size: usize,
+}
+
+/// 报告条目：scope 为出处（点表、模板引用或 POU 名）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportEntry {
+    pub scope: String,
+    pub message: String,
+}
+
+impl ReportEntry {
+    pub fn new(scope: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { scope: scope.into(), message: message.into() }
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use plc_importer::PointKind;
+use serde::{Deserialize, Serialize};
+
+/// 生成请求（Docs/plc_code_doc.md 3.4）
+/// brand/series/model 为模板仓库的前三级目录，模板按分类路径 + 模板名在其下查找
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationRequest {
+    pub brand: String,
+    pub series: String,
+    pub model: String,
+    pub templates_root: PathBuf,
+    pub output_dir: PathBuf,
+    #[serde(default)]
+    pub strategy: GenerationStrategy,
+}
+
+/// 生成策略
+/// 模板引用写作 `分类路径/模板名`（如 `IO映射/AI映射/MAPPING_AI`）；只写模板名时在型号目录下按名称查找，
+/// 同名模板出现在多个分类路径下时视为无法确定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationStrategy {
+    /// 需要生成 IO 映射的点位类别（按此顺序输出）
+    pub io_mapping_fixed: Vec<PointKind>,
+    /// 各类别的 IO 映射模板
+    pub io_templates: BTreeMap<PointKind, String>,
+    /// 设备模板的默认分类路径（如 `执行机构/阀门/ESD阀门`）；为空时按模板名查找
+    pub device_category_path: Option<String>,
+    /// 按设备模板名覆盖分类路径
+    pub device_categories: BTreeMap<String, String>,
+    /// 产物 POU 命名规则，可用 `{template_name}`、`{category}`（分类路径末级）、`{index}`（产物序号，从 1 起）
+    pub naming_rule: String,
+}
+
+impl Default for GenerationStrategy {
+    fn default() -> Self {
+        let kinds = [PointKind::AI, PointKind::AO, PointKind::DI, PointKind::DO];
+        Self {
+            io_mapping_fixed: kinds.to_vec(),
+            io_templates: kinds.iter().map(|kind| (*kind, format!("MAPPING_{}", kind))).collect(),
+            device_category_path: None,
+            device_categories: BTreeMap::new(),
+            naming_rule: "{template_name}".to_string(),
+        }
+    }
+}
+
+impl GenerationStrategy {
+    /// 设备模板引用：分类路径（覆盖优先）+ 模板名
+    pub fn device_template(&self, template_name: &str) -> String {
+        match self.device_categories.get(template_name).or(self.device_category_path.as_ref()) {
+            Some(category) if !category.trim().is_empty() => {
+                format!("{}/{}", category.trim_end_matches('/'), template_name)
+            }
+            _ => template_name.to_string(),
+        }
+    }
+
+    /// 按命名规则生成 POU 名
+    pub fn pou_name(&self, template_name: &str, category_path: &[String], index: usize) -> String {
+        self.naming_rule
+            .replace("{template_name}", template_name)
+            .replace("{category}", category_path.last().map_or("", String::as_str))
+            .replace("{index}", &index.to_string())
+    }
+}
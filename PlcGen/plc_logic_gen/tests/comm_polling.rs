@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+
+use plc_core::ast::{UniversalPou, VariableNode};
+use plc_core::symbols_config::SymbolConfig;
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+use plc_logic_gen::{CommIrV1, CommPollingError, CommPollingGenerator, comm_symbols};
+use plc_pou_builder::BuildError;
+
+/// 通讯采集模块导出的 CommIR 样例
+fn sample_ir() -> CommIrV1 {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../Tauri.CommMapping/src-tauri/src/comm/testdata/comm_ir.sample.v1.json");
+    CommIrV1::from_json(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn pin<'a>(pou: &'a UniversalPou, network: usize, element: usize, name: &str) -> &'a str {
+    let elem = &pou.networks[network].elements[element];
+    elem.pins.iter().find(|pin| pin.name == name).map(|pin| pin.variable.as_str()).unwrap()
+}
+
+/// 去掉占位功能块警告后的点位/通道警告
+fn skipped(warnings: &[String]) -> Vec<&String> {
+    warnings.iter().filter(|w| !w.contains("占位定义")).collect()
+}
+
+fn local_type<'a>(pou: &'a UniversalPou, name: &str) -> Option<&'a str> {
+    let VariableNode::Group { children, .. } = &pou.variables[0] else { panic!("Local Variables group") };
+    children.iter().find_map(|node| match node {
+        VariableNode::Leaf(var) if var.name == name => Some(var.data_type.as_str()),
+        _ => None,
+    })
+}
+
+#[test]
+fn one_network_per_read_job() {
+    let generator = CommPollingGenerator::new("COMM_POLL", PlcVariant::Normal);
+    let output = generator.generate(&sample_ir()).unwrap();
+    assert!(skipped(&output.warnings).is_empty(), "{:?}", output.warnings);
+    let pou = &output.pou;
+
+    let layout: Vec<Vec<&str>> =
+        pou.networks.iter().map(|net| net.elements.iter().map(|e| e.name.as_str()).collect()).collect();
+    assert_eq!(
+        layout,
+        [vec!["MB_TCP_READ", "MB_GET_UINT"], vec!["MB_TCP_READ", "MB_GET_BOOL"], vec!["MB_TCP_READ", "MB_GET_REAL"]]
+    );
+    assert_eq!(pou.networks[0].label, "ch_holding");
+    assert_eq!(pou.networks[0].elements[0].instance, "CH_HOLDING_READ1");
+    assert_eq!(pou.networks[2].elements[0].instance, "CH_HOLDING_READ2");
+    assert_eq!(
+        ["IP", "PORT", "FUNC", "ADDR", "LEN", "CYCLE"].map(|name| pin(pou, 0, 0, name)),
+        ["'127.0.0.1'", "502", "3", "100", "1", "T#500MS"]
+    );
+    assert_eq!(pin(pou, 1, 0, "FUNC"), "1");
+
+    // 转换块从读取块缓冲区按偏移取值，32 位数据带字节序
+    assert_eq!(pin(pou, 2, 1, "BUF"), "CH_HOLDING_READ2.DATA");
+    assert_eq!(pin(pou, 2, 1, "OFFSET"), "0");
+    assert_eq!(pin(pou, 2, 1, "ORDER"), "2");
+    assert_eq!(pin(pou, 2, 1, "OUT"), "HOLD_F32_OK");
+    assert_eq!(local_type(pou, "HOLD_U16_OK"), Some("UINT"));
+    assert_eq!(local_type(pou, "COIL_BOOL_TIMEOUT"), Some("BOOL"));
+
+    let codec = HollysysCodec::new(HollysysConfig::normal().with_symbols(generator.symbols().clone()));
+    assert!(!codec.encode(pou).unwrap().is_empty());
+}
+
+const RTU_IR: &str = r#"{
+  "schemaVersion": 1,
+  "specVersion": "v1",
+  "mapping": {
+    "points": [
+      { "pointKey": "k1", "hmiName": "PT_101", "channelName": "rtu-1", "dataType": "Int16", "endian": "ABCD",
+        "scale": 0.1, "rw": "R",
+        "addressSpec": { "readArea": "Holding", "absoluteAddress": 12, "unitLength": 1,
+                         "jobStartAddress": 10, "jobLength": 4, "addressBase": "zero" } },
+      { "pointKey": "k2", "hmiName": "FT_101", "channelName": "rtu-1", "dataType": "UInt32", "endian": "DCBA",
+        "scale": 1.0, "rw": "R",
+        "addressSpec": { "readArea": "Holding", "absoluteAddress": 10, "unitLength": 2,
+                         "jobStartAddress": 10, "jobLength": 4, "addressBase": "zero" } },
+      { "pointKey": "k3", "hmiName": "BAD_BOOL", "channelName": "rtu-1", "dataType": "Bool", "endian": "ABCD",
+        "scale": 1.0, "rw": "R",
+        "addressSpec": { "readArea": "Holding", "absoluteAddress": 13, "unitLength": 1,
+                         "jobStartAddress": 10, "jobLength": 4, "addressBase": "zero" } },
+      { "pointKey": "k4", "hmiName": "UNPLANNED", "channelName": "rtu-1", "dataType": "Int16", "endian": "ABCD",
+        "scale": 1.0, "rw": "R", "addressSpec": { "addressBase": "zero" } }
+    ],
+    "profiles": [
+      { "protocolType": "485", "channelName": "rtu-1", "deviceId": 7, "readArea": "Holding", "startAddress": 0,
+        "length": 20, "serialPort": "COM3", "baudRate": 9600, "parity": "Even", "dataBits": 8, "stopBits": 1,
+        "timeoutMs": 800, "retryCount": 2, "pollIntervalMs": 1000 }
+    ]
+  }
+}"#;
+
+#[test]
+fn rtu_scaling_and_skipped_points() {
+    let output = CommPollingGenerator::new("COMM_RTU", PlcVariant::Normal)
+        .generate(&CommIrV1::from_json(RTU_IR).unwrap())
+        .unwrap();
+    let pou = &output.pou;
+    assert_eq!(skipped(&output.warnings).len(), 2, "{:?}", output.warnings);
+    assert!(output.warnings.iter().any(|w| w.contains("UNPLANNED")));
+    assert!(output.warnings.iter().any(|w| w.contains("BAD_BOOL")));
+
+    // 点位按地址排序；scale ≠ 1 经 _RAW + MB_SCALE 输出 REAL
+    let names: Vec<&str> = pou.networks[0].elements.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["MB_RTU_READ", "MB_GET_UDINT", "MB_GET_INT", "MB_SCALE"]);
+    assert_eq!(
+        ["COM", "BAUD", "PARITY", "SLAVE", "ADDR", "LEN", "RETRY"].map(|name| pin(pou, 0, 0, name)),
+        ["'COM3'", "9600", "2", "7", "10", "4", "2"]
+    );
+    assert_eq!([pin(pou, 0, 1, "OFFSET"), pin(pou, 0, 1, "ORDER")], ["0", "3"]);
+    assert_eq!([pin(pou, 0, 2, "OFFSET"), pin(pou, 0, 2, "OUT")], ["2", "PT_101_RAW"]);
+    assert_eq!([pin(pou, 0, 3, "IN"), pin(pou, 0, 3, "K"), pin(pou, 0, 3, "OUT")], ["PT_101_RAW", "0.1", "PT_101"]);
+    assert_eq!(local_type(pou, "PT_101"), Some("REAL"));
+    assert_eq!(local_type(pou, "PT_101_RAW"), Some("INT"));
+}
+
+#[test]
+fn rejects_other_versions_and_empty_plans() {
+    let v2 = RTU_IR.replace(r#""specVersion": "v1""#, r#""specVersion": "v2""#);
+    assert!(matches!(CommIrV1::from_json(&v2), Err(CommPollingError::UnsupportedVersion { .. })));
+
+    let mut ir = CommIrV1::from_json(RTU_IR).unwrap();
+    ir.mapping.profiles.clear();
+    let err = CommPollingGenerator::new("COMM_RTU", PlcVariant::Normal).generate(&ir).unwrap_err();
+    assert!(matches!(err, CommPollingError::NoReadJob));
+}
+
+#[test]
+fn warns_about_placeholder_blocks() {
+    let output = CommPollingGenerator::new("COMM_RTU", PlcVariant::Normal)
+        .generate(&CommIrV1::from_json(RTU_IR).unwrap())
+        .unwrap();
+    let placeholders: Vec<&String> = output.warnings.iter().filter(|w| w.contains("占位定义")).collect();
+    assert_eq!(placeholders.len(), 4, "{:?}", output.warnings);
+    for block in ["MB_RTU_READ", "MB_GET_UDINT", "MB_GET_INT", "MB_SCALE"] {
+        assert!(placeholders.iter().any(|w| w.contains(&format!("功能块 {} ", block))), "{block}");
+    }
+
+    // 现场库确认后（非占位）不再警告
+    let mut confirmed = comm_symbols();
+    confirmed.function_blocks.iter_mut().for_each(|fb| fb.placeholder = false);
+    let output = CommPollingGenerator::new("COMM_RTU", PlcVariant::Normal)
+        .with_symbols(SymbolConfig::builtin().merged(confirmed))
+        .generate(&CommIrV1::from_json(RTU_IR).unwrap())
+        .unwrap();
+    assert_eq!(output.warnings.len(), 2, "{:?}", output.warnings);
+}
+
+#[test]
+fn buffer_pin_is_type_checked_against_read_block() {
+    let mut symbols = comm_symbols();
+    for fb in symbols.function_blocks.iter_mut().filter(|fb| fb.name.ends_with("_READ")) {
+        fb.members.iter_mut().for_each(|member| member.data_type = "ARRAY[0..63] OF WORD".to_string());
+    }
+    let err = CommPollingGenerator::new("COMM_RTU", PlcVariant::Normal)
+        .with_symbols(SymbolConfig::builtin().merged(symbols))
+        .generate(&CommIrV1::from_json(RTU_IR).unwrap())
+        .unwrap_err();
+    assert!(matches!(err, CommPollingError::Build(BuildError::TypeConflict { .. })), "{err}");
+}
+
+/// 地址在 job 之前、超出 job 长度或缺少 jobLength 的点位逐个警告并跳过，其余点位照常生成
+#[test]
+fn points_outside_their_read_job_are_skipped() {
+    let mut ir = CommIrV1::from_json(RTU_IR).unwrap();
+    let template = ir.mapping.points[1].clone();
+    let point = |name: &str, absolute: u16| {
+        let mut point = template.clone();
+        point.hmi_name = name.to_string();
+        point.address_spec.absolute_address = Some(absolute);
+        point
+    };
+    let mut no_length = point("NO_LENGTH", 10);
+    no_length.address_spec.job_length = None;
+    ir.mapping.points.extend([point("BEFORE_JOB", 8), point("PAST_JOB", 13), no_length]);
+
+    let output = CommPollingGenerator::new("COMM_RTU", PlcVariant::Normal).generate(&ir).unwrap();
+    let warnings = skipped(&output.warnings);
+    assert_eq!(warnings.len(), 5, "{warnings:?}");
+    for (name, problem) in
+        [("BEFORE_JOB", "起始地址之前"), ("PAST_JOB", "超出读取任务长度"), ("NO_LENGTH", "没有读取计划")]
+    {
+        assert!(warnings.iter().any(|w| w.contains(name) && w.contains(problem)), "{name}: {warnings:?}");
+    }
+    let names: Vec<&str> = output.pou.networks[0].elements.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["MB_RTU_READ", "MB_GET_UDINT", "MB_GET_INT", "MB_SCALE"]);
+}
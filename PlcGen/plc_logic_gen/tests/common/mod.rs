@@ -0,0 +1,112 @@
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use plc_core::PlcVariant;
+use plc_core::ast::{BoxPin, ElementType, LdElement, Network, PinDirection, UniversalPou, Variable, VariableNode};
+use plc_importer::{ImportResult, PointTableImporter};
+use plc_templates::{FsTemplateStorage, TemplateBundle, TemplateExtractor, TemplateKey, TemplateMeta, TemplateStorage};
+
+pub const BRAND: &str = "和利时";
+pub const SERIES: &str = "和利时普通型";
+pub const MODEL: &str = "默认";
+
+/// 仓库自带的 FAT 点表导入结果
+pub fn fat_import() -> ImportResult {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../Docs/德州果子里调压站_IO_点表_FAT_20260120160600.xlsx");
+    PointTableImporter::default().import_file(path).expect("import FAT workbook")
+}
+
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("plc_logic_gen_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn leaf(name: &str, data_type: &str) -> VariableNode {
+    VariableNode::Leaf(Variable::new(name, data_type))
+}
+
+fn pin(name: &str, variable: &str, direction: PinDirection) -> BoxPin {
+    BoxPin::new(name, variable, direction)
+}
+
+fn pou(name: &str, variables: Vec<VariableNode>, element: LdElement) -> UniversalPou {
+    UniversalPou {
+        name: name.to_string(),
+        header_strings: Vec::new(),
+        variables: vec![VariableNode::Group {
+            name: "Local Variables".to_string(),
+            type_name: None,
+            children: variables,
+        }],
+        networks: vec![Network {
+            id: 1,
+            label: String::new(),
+            comment: String::new(),
+            elements: vec![element],
+            safety_topology: Vec::new(),
+        }],
+        preserved: None,
+    }
+}
+
+fn box_element(name: &str, instance: &str, pins: Vec<BoxPin>) -> LdElement {
+    LdElement { instance: instance.to_string(), pins, ..LdElement::new(2, ElementType::Box, name) }
+}
+
+/// AI 映射样例：MOVE PT0101 → PT0101_ENG
+pub fn ai_mapping_pou() -> UniversalPou {
+    let element = box_element(
+        "MOVE",
+        "",
+        vec![pin("IN", "PT0101", PinDirection::Input), pin("OUT", "PT0101_ENG", PinDirection::Output)],
+    );
+    pou("MAPPING_AI", vec![leaf("PT0101_ENG", "REAL")], element)
+}
+
+/// AI 报警样例：AI_ALARM_IO_PLC 实例名与输入通道都不含样例位号 PT0101
+pub fn ai_alarm_pou() -> UniversalPou {
+    let element = box_element(
+        "AI_ALARM_IO_PLC",
+        "AI_TEST",
+        vec![pin("IN", "M2_CH_1", PinDirection::Input), pin("OUT", "PT0101_ENG", PinDirection::Output)],
+    );
+    pou("AI_ALARM", vec![leaf("AI_TEST", "AI_ALARM_IO_PLC"), leaf("PT0101_ENG", "REAL")], element)
+}
+
+/// 电动阀样例：MOV_CTRL 实例 MOV101，引脚按设备分类表的别名绑定
+pub fn mov_ctrl_pou() -> UniversalPou {
+    let element = box_element(
+        "MOV_CTRL",
+        "MOV101",
+        vec![
+            pin("ZIO", "MOV101_ZSH", PinDirection::Input),
+            pin("ZIC", "MOV101_ZSL", PinDirection::Input),
+            pin("XO", "MOV101_XO", PinDirection::Output),
+            pin("XC", "MOV101_XC", PinDirection::Output),
+        ],
+    );
+    let instance = VariableNode::Group {
+        name: "MOV101".to_string(),
+        type_name: Some("MOV_CTRL".to_string()),
+        children: Vec::new(),
+    };
+    pou("MOV_CTRL", vec![instance], element)
+}
+
+/// 以抽取的草稿 spec 组成模板包
+pub fn bundle(category_path: &[&str], name: &str, pou: UniversalPou) -> TemplateBundle {
+    let key = TemplateKey::new(BRAND, SERIES, MODEL, category_path.iter().copied(), name);
+    let meta = TemplateMeta::new(key, PlcVariant::Normal, 13, "POU_TREE_Clipboard_PLC");
+    let spec = TemplateExtractor::new().extract(&pou);
+    TemplateBundle::new(meta, spec, pou)
+}
+
+/// 以抽取的草稿 spec 存入模板仓库
+pub fn store_template(root: &Path, category_path: &[&str], name: &str, pou: UniversalPou) -> TemplateKey {
+    let bundle = bundle(category_path, name, pou);
+    FsTemplateStorage::new(root).create(&bundle).expect("create template");
+    bundle.meta.key
+}
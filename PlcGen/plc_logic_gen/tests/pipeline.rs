@@ -0,0 +1,143 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use common::{BRAND, MODEL, SERIES, ai_alarm_pou, ai_mapping_pou, fat_import, mov_ctrl_pou, store_template, temp_dir};
+use plc_core::PlcVariant;
+use plc_core::symbols_config::SymbolConfig;
+use plc_importer::PointKind;
+use plc_logic_gen::{
+    ArtifactSource, GenerationError, GenerationPipeline, GenerationReport, GenerationRequest, GenerationStrategy,
+    InstanceBinding, REPORT_FILE, plan_instances,
+};
+use plc_templates::TemplateService;
+
+fn request(name: &str) -> GenerationRequest {
+    let root = temp_dir(&format!("{}_templates", name));
+    store_template(&root, &["IO映射", "AI映射"], "MAPPING_AI", ai_mapping_pou());
+    store_template(&root, &["执行机构", "阀门"], "MOV_CTRL", mov_ctrl_pou());
+    GenerationRequest {
+        brand: BRAND.to_string(),
+        series: SERIES.to_string(),
+        model: MODEL.to_string(),
+        templates_root: root,
+        output_dir: temp_dir(&format!("{}_out", name)),
+        strategy: GenerationStrategy {
+            io_mapping_fixed: vec![PointKind::AI, PointKind::DI],
+            io_templates: BTreeMap::from([
+                (PointKind::AI, "IO映射/AI映射/MAPPING_AI".to_string()),
+                (PointKind::DI, "MAPPING_DI".to_string()),
+            ]),
+            device_category_path: Some("执行机构/阀门".to_string()),
+            naming_rule: "{template_name}_{index}".to_string(),
+            ..GenerationStrategy::default()
+        },
+    }
+}
+
+#[test]
+fn generates_one_pou_per_template_and_report() {
+    let request = request("run");
+    let output_dir = request.output_dir.clone();
+    let result = fat_import();
+    let pipeline = GenerationPipeline::new(request);
+    let report = pipeline.run(&result).unwrap();
+
+    let names: Vec<&str> = report.artifacts.iter().map(|artifact| artifact.pou_name.as_str()).collect();
+    assert_eq!(names, ["MAPPING_AI_1", "MOV_CTRL_2"]);
+    let ai = &report.artifacts[0];
+    assert_eq!(ai.source, ArtifactSource::Io { point_kind: "AI".to_string() });
+    assert_eq!(ai.instances, result.points_of(PointKind::AI).map(|point| point.name.clone()).collect::<Vec<_>>());
+    let mov = &report.artifacts[1];
+    assert_eq!(mov.source, ArtifactSource::Device { template_name: "MOV_CTRL".to_string() });
+    assert_eq!(mov.template.category_path, ["执行机构", "阀门"]);
+    assert_eq!(mov.instances, ["MOV101", "MOV102"]);
+
+    // DI 模板不存在：记录错误，其它模板照常生成
+    assert!(!report.is_success());
+    assert_eq!(report.errors.len(), 1, "{:?}", report.errors);
+    assert_eq!(report.errors[0].scope, "MAPPING_DI");
+    assert!(report.errors[0].message.contains("未找到模板"));
+
+    // 写出的文件即编码结果；每个实例一套网络
+    let generation = pipeline.generate(&result).unwrap();
+    for (artifact, output) in report.artifacts.iter().zip(&generation.outputs) {
+        assert_eq!(fs::read(output_dir.join(artifact.file.as_ref().unwrap())).unwrap(), output.bytes);
+        assert_eq!(output.bytes.len(), artifact.size);
+    }
+    let pou = &generation.outputs[0].pou;
+    assert_eq!(pou.networks.len(), ai.instances.len());
+    let inputs: Vec<&str> = pou.networks.iter().map(|net| net.elements[0].pins[0].variable.as_str()).collect();
+    assert_eq!(inputs, ai.instances);
+    let outputs: Vec<&str> = pou.networks.iter().map(|net| net.elements[0].pins[1].variable.as_str()).collect();
+    assert_eq!(outputs[..2], ["PT0101_ENG", "PT0102_ENG"]);
+
+    let mov_pou = &generation.outputs[1].pou;
+    assert_eq!(mov.file.as_deref(), Some(std::path::Path::new("MOV_CTRL_2.bin")));
+    let zio: Vec<&str> = mov_pou.networks.iter().map(|net| net.elements[0].pins[0].variable.as_str()).collect();
+    assert_eq!(zio, ["MOV101_ZSH", "MOV102_ZSH"]);
+    let instances: Vec<&str> = mov_pou.networks.iter().map(|net| net.elements[0].instance.as_str()).collect();
+    assert_eq!(instances, ["MOV101", "MOV102"]);
+
+    let saved: GenerationReport = serde_json::from_slice(&fs::read(output_dir.join(REPORT_FILE)).unwrap()).unwrap();
+    assert_eq!(saved, report);
+}
+
+#[test]
+fn ambiguous_template_reference_is_reported() {
+    let mut request = request("ambiguous");
+    store_template(&request.templates_root, &["执行机构", "备用"], "MOV_CTRL", mov_ctrl_pou());
+    request.strategy.device_category_path = None;
+    let generation = GenerationPipeline::new(request).generate(&fat_import()).unwrap();
+
+    assert_eq!(generation.outputs.len(), generation.report.artifacts.len());
+    let scopes: Vec<&str> = generation.report.errors.iter().map(|entry| entry.scope.as_str()).collect();
+    assert_eq!(scopes, ["MAPPING_DI", "MOV_CTRL"]);
+    assert!(generation.report.errors[1].message.contains("匹配到多个"));
+}
+
+#[test]
+fn rejects_unsupported_brand() {
+    let mut request = request("brand");
+    request.brand = "Siemens".to_string();
+    let err = GenerationPipeline::new(request).generate(&fat_import()).unwrap_err();
+    assert!(matches!(err, GenerationError::UnsupportedBrand(brand) if brand == "Siemens"));
+}
+
+#[test]
+fn implicit_set_renames_block_instances_per_instance() {
+    let bundle = common::bundle(&["IO映射", "AI映射"], "AI_ALARM", ai_alarm_pou());
+    let bindings =
+        ["PT0101", "PT0102"].map(|tag| InstanceBinding { tag: tag.to_string(), ..InstanceBinding::default() });
+    let plan = plan_instances(&bundle, "AI_ALARM_1", &bindings).unwrap();
+
+    let set = &plan.bundle.spec.template_sets[0];
+    assert_eq!(set.variables, [["Local Variables", "AI_TEST"], ["Local Variables", "PT0101_ENG"]]);
+    // 输入通道不含样例位号，也没有别名绑定：各实例共用，给出警告
+    assert!(plan.warnings.iter().any(|warning| warning.contains("M2_CH_1")), "{:?}", plan.warnings);
+
+    let pou = TemplateService::new().apply(&plan.bundle, &plan.patch).unwrap();
+    let blocks: Vec<(&str, &str)> = pou
+        .networks
+        .iter()
+        .map(|net| (net.elements[0].instance.as_str(), net.elements[0].pins[1].variable.as_str()))
+        .collect();
+    assert_eq!(blocks, [("AI_TEST_PT0101", "PT0101_ENG"), ("AI_TEST_PT0102", "PT0102_ENG")]);
+}
+
+#[test]
+fn validates_with_injected_symbol_library() {
+    // 现场库中的 MOV_CTRL 只用于安全型控制器
+    let mut symbols = SymbolConfig::builtin();
+    let mov = symbols.function_blocks.iter_mut().find(|fb| fb.name == "MOV_CTRL").unwrap();
+    mov.variants = vec![PlcVariant::Safety];
+    let generation = GenerationPipeline::new(request("symbols")).with_symbols(symbols).generate(&fat_import()).unwrap();
+
+    let names: Vec<&str> = generation.report.artifacts.iter().map(|artifact| artifact.pou_name.as_str()).collect();
+    assert_eq!(names, ["MAPPING_AI_1"]);
+    let errors: Vec<(&str, &str)> =
+        generation.report.errors.iter().map(|entry| (entry.scope.as_str(), entry.message.as_str())).collect();
+    assert_eq!(errors[0].0, "MAPPING_DI");
+    assert!(errors[1].1.contains("not available on Normal"), "{errors:?}");
+}
@@ -0,0 +1,543 @@
+use std::fmt;
+
+use plc_core::PlcVariant;
+use plc_core::application::PouValidator;
+use plc_core::ast::{BoxPin, ElementType, LdElement, Network, PinDirection, UniversalPou, Variable, VariableNode};
+use plc_core::domain::topology::{TopologyForm, convert_network};
+use plc_core::symbols_config::SymbolConfig;
+
+use crate::error::BuildError;
+
+/// 编辑器生成的剪贴板中网络/元件 ID 从 2 开始
+const FIRST_ID: i32 = 2;
+
+/// 自动创建的变量所在分组
+pub const LOCAL_GROUP: &str = "Local Variables";
+
+/// 功能块隐含的使能引脚
+const ENABLE_PINS: [(&str, PinDirection); 2] = [("EN", PinDirection::Input), ("ENO", PinDirection::Output)];
+
+/// 网络句柄
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NetworkId(i32);
+
+impl NetworkId {
+    /// 生成的 Network.id
+    pub fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl fmt::Display for NetworkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "network#{}", self.0)
+    }
+}
+
+/// 元件句柄
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ElementId(i32);
+
+impl ElementId {
+    /// 生成的 LdElement.id
+    pub fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl fmt::Display for ElementId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "element#{}", self.0)
+    }
+}
+
+/// 触点类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContactKind {
+    #[default]
+    NormallyOpen,
+    NormallyClosed,
+}
+
+impl ContactKind {
+    pub fn sub_type(self) -> u8 {
+        match self {
+            ContactKind::NormallyOpen => 0,
+            ContactKind::NormallyClosed => 1,
+        }
+    }
+}
+
+/// 线圈类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoilKind {
+    #[default]
+    Normal,
+    Negated,
+}
+
+impl CoilKind {
+    pub fn sub_type(self) -> u8 {
+        match self {
+            CoilKind::Normal => 0,
+            CoilKind::Negated => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NetworkDraft {
+    id: i32,
+    label: String,
+    comment: String,
+    elements: Vec<LdElement>,
+    /// 能流连线（上游 → 下游）；为空时按插入顺序串联
+    edges: Vec<(i32, i32)>,
+}
+
+/// POU 构造器：从零构建梯形图 POU
+/// - 网络与元件共用一个 ID 序列，由构造器分配
+/// - 触点/线圈/引脚引用的变量未声明时自动创建在 "Local Variables"（或 in_group 指定的分组）中，类型取自元件或功能块库
+/// - build 时按版本生成拓扑（Normal 连接图 / Safety Token 流）并执行语义校验
+#[derive(Debug, Clone)]
+pub struct PouBuilder {
+    name: String,
+    variant: PlcVariant,
+    symbols: SymbolConfig,
+    /// 调用方指定的 Safety 头部依赖项（在推导出的依赖项之前）
+    header_strings: Vec<String>,
+    variables: Vec<VariableNode>,
+    /// 自动声明变量所在的分组路径
+    auto_group: Vec<String>,
+    networks: Vec<NetworkDraft>,
+    next_id: i32,
+}
+
+impl PouBuilder {
+    /// 使用内置功能块库
+    pub fn new(name: impl Into<String>, variant: PlcVariant) -> Self {
+        Self {
+            name: name.into(),
+            variant,
+            symbols: SymbolConfig::builtin(),
+            header_strings: Vec::new(),
+            variables: Vec::new(),
+            auto_group: vec![LOCAL_GROUP.to_string()],
+            networks: Vec::new(),
+            next_id: FIRST_ID,
+        }
+    }
+
+    /// 替换功能块库（通常为内置库叠加用户库）
+    pub fn with_symbols(mut self, symbols: SymbolConfig) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn variant(&self) -> PlcVariant {
+        self.variant
+    }
+
+    pub fn symbols(&self) -> &SymbolConfig {
+        &self.symbols
+    }
+
+    /// 追加 Safety 头部依赖项；实例化功能块的类型会在 build 时自动补上
+    pub fn add_header_string(&mut self, header: impl Into<String>) {
+        let header = header.into();
+        if !self.header_strings.contains(&header) {
+            self.header_strings.push(header);
+        }
+    }
+
+    /// 新建网络（追加在末尾）
+    pub fn create_network(&mut self) -> NetworkId {
+        let id = self.alloc_id();
+        self.networks.push(NetworkDraft {
+            id,
+            label: String::new(),
+            comment: String::new(),
+            elements: Vec::new(),
+            edges: Vec::new(),
+        });
+        NetworkId(id)
+    }
+
+    pub fn set_network_label(&mut self, network: NetworkId, label: impl Into<String>) -> Result<(), BuildError> {
+        self.network_mut(network)?.label = label.into();
+        Ok(())
+    }
+
+    pub fn set_network_comment(&mut self, network: NetworkId, comment: impl Into<String>) -> Result<(), BuildError> {
+        self.network_mut(network)?.comment = comment.into();
+        Ok(())
+    }
+
+    /// 插入触点；变量未声明时按 BOOL 创建
+    pub fn add_contact(
+        &mut self,
+        network: NetworkId,
+        variable: &str,
+        kind: ContactKind,
+    ) -> Result<ElementId, BuildError> {
+        self.network_mut(network)?;
+        self.ensure_variable(variable, "BOOL")?;
+        let mut elem = LdElement::new(self.alloc_id(), ElementType::Contact, variable);
+        elem.sub_type = kind.sub_type();
+        Ok(self.push_element(network, elem))
+    }
+
+    /// 插入线圈；变量未声明时按 BOOL 创建
+    pub fn add_coil(&mut self, network: NetworkId, variable: &str, kind: CoilKind) -> Result<ElementId, BuildError> {
+        self.network_mut(network)?;
+        self.ensure_variable(variable, "BOOL")?;
+        let mut elem = LdElement::new(self.alloc_id(), ElementType::Coil, variable);
+        elem.sub_type = kind.sub_type();
+        Ok(self.push_element(network, elem))
+    }
+
+    /// 插入功能块：引脚按库定义预置为未连接；实例型功能块必须给出实例名，实例变量自动声明
+    pub fn add_block(
+        &mut self,
+        network: NetworkId,
+        block: &str,
+        instance: Option<&str>,
+    ) -> Result<ElementId, BuildError> {
+        self.network_mut(network)?;
+        let fb = self.symbols.get(block).ok_or_else(|| BuildError::UnknownBlock(block.to_string()))?.clone();
+        if !fb.supports(self.variant) {
+            return Err(BuildError::UnsupportedBlock { block: fb.name, variant: self.variant });
+        }
+        let instance = instance.map(str::trim).filter(|name| !name.is_empty());
+        match instance {
+            None if fb.is_instance() => return Err(BuildError::MissingInstance(fb.name)),
+            Some(_) if !fb.is_instance() => return Err(BuildError::UnexpectedInstance(fb.name)),
+            Some(name) => self.ensure_variable(name, &fb.name)?,
+            None => {}
+        }
+
+        let mut elem = LdElement::new(self.alloc_id(), ElementType::Box, &fb.name);
+        elem.instance = instance.unwrap_or_default().to_string();
+        let placeholder = self.unconnected_pin();
+        let inputs = std::iter::once("EN").chain(fb.inputs.iter().map(|pin| pin.name.as_str()));
+        let outputs = std::iter::once("ENO").chain(fb.outputs.iter().map(|pin| pin.name.as_str()));
+        elem.pins = inputs
+            .map(|name| BoxPin::new(name, placeholder, PinDirection::Input))
+            .chain(outputs.map(|name| BoxPin::new(name, placeholder, PinDirection::Output)))
+            .collect();
+        Ok(self.push_element(network, elem))
+    }
+
+    /// 绑定功能块引脚；变量未声明时按引脚类型创建（ANY 类引脚需先声明变量）
+    /// 旧格式库只登记成员名、没有引脚方向的成员不能绑定
+    pub fn bind_pin(&mut self, block: ElementId, pin: &str, variable: &str) -> Result<(), BuildError> {
+        let elem = self.element(block)?;
+        if elem.type_code != ElementType::Box {
+            return Err(BuildError::NotABlock(block));
+        }
+        let unknown_pin = || BuildError::UnknownPin { block: elem.name.clone(), pin: pin.to_string() };
+        let fb = self.symbols.get(&elem.name).ok_or_else(unknown_pin)?;
+        let (data_type, direction) = match (fb.pin(pin), ENABLE_PINS.iter().find(|(name, _)| *name == pin)) {
+            (Some((def, direction)), _) => (def.data_type.clone(), direction),
+            (None, Some((_, direction))) => ("BOOL".to_string(), *direction),
+            (None, None) if fb.member_names().any(|name| name == pin) => {
+                return Err(BuildError::UndirectedPin { block: elem.name.clone(), pin: pin.to_string() });
+            }
+            (None, None) => return Err(unknown_pin()),
+        };
+        self.ensure_variable(variable, &data_type)?;
+
+        let elem = self.element_mut(block)?;
+        match elem.pins.iter_mut().find(|p| p.name == pin) {
+            Some(existing) => existing.variable = variable.to_string(),
+            None => elem.pins.push(BoxPin::new(pin, variable, direction)),
+        }
+        Ok(())
+    }
+
+    /// 能流连线：src 的输出接到 dst 的输入（同一网络内）
+    /// 网络内没有任何连线时，元件按插入顺序串联
+    pub fn connect(&mut self, src: ElementId, dst: ElementId) -> Result<(), BuildError> {
+        if src == dst {
+            return Err(BuildError::SelfConnection(src));
+        }
+        let src_net = self.network_of(src)?;
+        if self.network_of(dst)? != src_net {
+            return Err(BuildError::CrossNetwork { src, dst });
+        }
+        let net = &mut self.networks[src_net];
+        if !net.edges.contains(&(src.0, dst.0)) {
+            net.edges.push((src.0, dst.0));
+        }
+        Ok(())
+    }
+
+    /// 声明变量：group_path 为分组路径（不存在时逐级创建），为空时放在顶层
+    /// 返回新变量以便继续设置初始值、注释等字段
+    pub fn add_variable(
+        &mut self,
+        group_path: &[&str],
+        name: &str,
+        data_type: &str,
+    ) -> Result<&mut Variable, BuildError> {
+        if self.declared_type(name).is_some() {
+            return Err(BuildError::DuplicateVariable(name.to_string()));
+        }
+        let mut nodes = &mut self.variables;
+        for group in group_path {
+            let idx = match nodes
+                .iter()
+                .position(|node| matches!(node, VariableNode::Group { name, .. } if name == group))
+            {
+                Some(idx) => idx,
+                None => {
+                    nodes.push(VariableNode::Group { name: group.to_string(), type_name: None, children: Vec::new() });
+                    nodes.len() - 1
+                }
+            };
+            let VariableNode::Group { children, .. } = &mut nodes[idx] else {
+                unreachable!("position matched a group");
+            };
+            nodes = children;
+        }
+        nodes.push(VariableNode::Leaf(Variable::new(name, data_type)));
+        let Some(VariableNode::Leaf(var)) = nodes.last_mut() else {
+            unreachable!("leaf just pushed");
+        };
+        Ok(var)
+    }
+
+    /// 分组是否存在；Local Variables 根分组在首次自动声明时创建，视为始终存在
+    pub fn has_group(&self, group_path: &[&str]) -> bool {
+        group_path == [LOCAL_GROUP] || group_children(&self.variables, group_path).is_some()
+    }
+
+    /// 在已存在的分组内执行 f：其间引用的变量须声明在该分组下，未声明时创建在该分组中
+    pub fn in_group<R>(
+        &mut self,
+        group_path: &[&str],
+        f: impl FnOnce(&mut Self) -> Result<R, BuildError>,
+    ) -> Result<R, BuildError> {
+        if !self.has_group(group_path) {
+            return Err(BuildError::UnknownGroup(group_path.join("/")));
+        }
+        let group = group_path.iter().map(|name| name.to_string()).collect();
+        let saved = std::mem::replace(&mut self.auto_group, group);
+        let result = f(self);
+        self.auto_group = saved;
+        result
+    }
+
+    pub fn set_element_comment(&mut self, element: ElementId, comment: impl Into<String>) -> Result<(), BuildError> {
+        self.element_mut(element)?.comment = comment.into();
+        Ok(())
+    }
+
+    /// 生成 POU：按版本生成拓扑，并以功能块库 + 目标版本做语义校验（警告不阻断）
+    pub fn build(&self) -> Result<UniversalPou, BuildError> {
+        let form = match self.variant {
+            PlcVariant::Normal => TopologyForm::Connections,
+            PlcVariant::Safety => TopologyForm::Tokens,
+        };
+        let mut networks = Vec::with_capacity(self.networks.len());
+        for draft in &self.networks {
+            let mut elements = draft.elements.clone();
+            for elem in &mut elements {
+                elem.connections =
+                    draft.edges.iter().filter(|(src, _)| *src == elem.id).map(|(_, dst)| *dst).collect();
+            }
+            let net = Network {
+                id: draft.id,
+                label: draft.label.clone(),
+                comment: draft.comment.clone(),
+                elements,
+                safety_topology: Vec::new(),
+            };
+            let net = convert_network(&net, form)
+                .map_err(|source| BuildError::Topology { network: NetworkId(draft.id), source })?;
+            networks.push(net);
+        }
+
+        let pou = UniversalPou {
+            name: self.name.clone(),
+            header_strings: self.header_strings(),
+            variables: self.variables.clone(),
+            networks,
+            preserved: None,
+        };
+        let report = PouValidator::new().with_symbols(&self.symbols).with_variant(self.variant).validate(&pou);
+        if !report.is_valid() {
+            return Err(plc_core::application::ValidationError(report).into());
+        }
+        Ok(pou)
+    }
+
+    /// Safety 头部依赖项：指定项 + 实例化功能块的类型（按首次出现顺序）；Normal 不写该数组
+    fn header_strings(&self) -> Vec<String> {
+        let mut headers: Vec<String> = Vec::new();
+        if self.variant == PlcVariant::Safety {
+            headers.clone_from(&self.header_strings);
+            for elem in self.networks.iter().flat_map(|net| &net.elements) {
+                if !elem.instance.is_empty() && !headers.contains(&elem.name) {
+                    headers.push(elem.name.clone());
+                }
+            }
+        }
+        headers
+    }
+
+    /// 确保变量已声明：字面量与未连接占位不处理；未声明时按 expected 在 auto_group 中创建
+    fn ensure_variable(&mut self, name: &str, expected: &str) -> Result<(), BuildError> {
+        let name = name.trim();
+        if name.is_empty() || name == self.unconnected_pin() || is_literal(name) {
+            return Ok(());
+        }
+        match self.declared_type(name) {
+            Some(declared)
+                if is_concrete(expected) && is_concrete(&declared) && !declared.eq_ignore_ascii_case(expected) =>
+            {
+                Err(BuildError::TypeConflict { name: name.to_string(), declared, expected: expected.to_string() })
+            }
+            Some(_) if !self.in_auto_group(name) => {
+                Err(BuildError::VariableOutsideGroup { name: name.to_string(), group: self.auto_group.join("/") })
+            }
+            Some(_) => Ok(()),
+            None if is_concrete(expected) && !name.contains('.') => {
+                let group = self.auto_group.clone();
+                let path: Vec<&str> = group.iter().map(String::as_str).collect();
+                let var = self.add_variable(&path, name, expected)?;
+                var.init_value = default_init(expected).to_string();
+                Ok(())
+            }
+            None => Err(BuildError::UnresolvedType(name.to_string())),
+        }
+    }
+
+    /// 已声明的普通变量是否直接位于 auto_group 下；默认分组与 `实例.成员` 引用不限制位置
+    fn in_auto_group(&self, name: &str) -> bool {
+        if self.auto_group == [LOCAL_GROUP] || find_leaf(&self.variables, name).is_none() {
+            return true;
+        }
+        let path: Vec<&str> = self.auto_group.iter().map(String::as_str).collect();
+        group_children(&self.variables, &path).is_some_and(|children| {
+            children.iter().any(|node| matches!(node, VariableNode::Leaf(var) if var.name == name))
+        })
+    }
+
+    /// 已声明变量的类型；`实例.成员` 按实例的功能块定义解析（成员类型未登记时为空串）
+    fn declared_type(&self, name: &str) -> Option<String> {
+        if let Some(var) = find_leaf(&self.variables, name) {
+            return Some(var.data_type.clone());
+        }
+        let (prefix, member) = name.split_once('.')?;
+        if let Some(VariableNode::Group { children, .. }) = find_group(&self.variables, prefix)
+            && let Some(var) = find_leaf(children, member)
+        {
+            return Some(var.data_type.clone());
+        }
+        let fb = self.symbols.get(&find_leaf(&self.variables, prefix)?.data_type)?;
+        fb.member_names()
+            .any(|m| m == member)
+            .then(|| fb.member_type(member).unwrap_or_default().to_string())
+    }
+
+    /// 编辑器写入的未连接引脚占位：Normal 为 "???"，Safety 为空串
+    fn unconnected_pin(&self) -> &'static str {
+        match self.variant {
+            PlcVariant::Normal => "???",
+            PlcVariant::Safety => "",
+        }
+    }
+
+    fn alloc_id(&mut self) -> i32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn push_element(&mut self, network: NetworkId, elem: LdElement) -> ElementId {
+        let id = ElementId(elem.id);
+        if let Some(net) = self.networks.iter_mut().find(|net| net.id == network.0) {
+            net.elements.push(elem);
+        }
+        id
+    }
+
+    fn network_mut(&mut self, network: NetworkId) -> Result<&mut NetworkDraft, BuildError> {
+        self.networks
+            .iter_mut()
+            .find(|net| net.id == network.0)
+            .ok_or(BuildError::UnknownNetwork(network))
+    }
+
+    fn network_of(&self, element: ElementId) -> Result<usize, BuildError> {
+        self.networks
+            .iter()
+            .position(|net| net.elements.iter().any(|e| e.id == element.0))
+            .ok_or(BuildError::UnknownElement(element))
+    }
+
+    fn element(&self, element: ElementId) -> Result<&LdElement, BuildError> {
+        let net = self.network_of(element)?;
+        Ok(self.networks[net].elements.iter().find(|e| e.id == element.0).expect("element located above"))
+    }
+
+    fn element_mut(&mut self, element: ElementId) -> Result<&mut LdElement, BuildError> {
+        let net = self.network_of(element)?;
+        Ok(self.networks[net].elements.iter_mut().find(|e| e.id == element.0).expect("element located above"))
+    }
+}
+
+fn find_leaf<'a>(nodes: &'a [VariableNode], name: &str) -> Option<&'a Variable> {
+    nodes.iter().find_map(|node| match node {
+        VariableNode::Leaf(var) => (var.name == name).then_some(var),
+        // 功能块实例成员组（带 type_name）的成员只能以 `实例.成员` 访问
+        VariableNode::Group { type_name: Some(_), .. } => None,
+        VariableNode::Group { children, .. } => find_leaf(children, name),
+    })
+}
+
+/// 按分组路径逐级查找（只匹配直接子分组）
+fn group_children<'a>(nodes: &'a [VariableNode], group_path: &[&str]) -> Option<&'a [VariableNode]> {
+    group_path.iter().try_fold(nodes, |nodes, group| {
+        nodes.iter().find_map(|node| match node {
+            VariableNode::Group { name, children, .. } if name == group => Some(children.as_slice()),
+            _ => None,
+        })
+    })
+}
+
+fn find_group<'a>(nodes: &'a [VariableNode], name: &str) -> Option<&'a VariableNode> {
+    nodes.iter().find_map(|node| match node {
+        VariableNode::Leaf(_) => None,
+        VariableNode::Group { name: group, .. } if group == name => Some(node),
+        VariableNode::Group { children, .. } => find_group(children, name),
+    })
+}
+
+/// 类型是否确定（空串与 ANY* 泛型不能用于创建变量）
+fn is_concrete(data_type: &str) -> bool {
+    let ty = data_type.trim();
+    !ty.is_empty() && !ty.to_ascii_uppercase().starts_with("ANY")
+}
+
+/// 自动创建变量的初始值
+fn default_init(data_type: &str) -> &'static str {
+    match data_type.trim().to_ascii_uppercase().as_str() {
+        "BOOL" => "FALSE",
+        "TIME" => "T#0S",
+        "REAL" | "LREAL" => "0.0",
+        "SINT" | "INT" | "DINT" | "LINT" | "USINT" | "UINT" | "UDINT" | "ULINT" | "BYTE" | "WORD" | "DWORD"
+        | "LWORD" => "0",
+        _ => "",
+    }
+}
+
+/// 可直接写在引脚上的常量：TRUE/FALSE、数字、带类型前缀的字面量（T#3S、16#FF）、字符串
+fn is_literal(text: &str) -> bool {
+    text.eq_ignore_ascii_case("TRUE")
+        || text.eq_ignore_ascii_case("FALSE")
+        || text.starts_with('\'')
+        || text.contains('#')
+        || text.replace('_', "").parse::<f64>().is_ok()
+}
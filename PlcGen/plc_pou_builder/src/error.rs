@@ -0,0 +1,51 @@
+use plc_core::PlcVariant;
+use plc_core::application::ValidationError;
+use plc_core::domain::topology::TopologyError;
+use thiserror::Error;
+
+use crate::builder::{ElementId, NetworkId};
+
+/// 构造错误：每一步原子操作在调用时即检查，build 时再做拓扑与语义校验
+#[derive(Debug, Error)]
+pub enum BuildError {
+    #[error("网络不存在: {0}")]
+    UnknownNetwork(NetworkId),
+    #[error("元件不存在: {0}")]
+    UnknownElement(ElementId),
+    #[error("{0} 不是功能块，不能绑定引脚")]
+    NotABlock(ElementId),
+    #[error("功能块库中没有 {0}")]
+    UnknownBlock(String),
+    #[error("功能块 {block} 不支持 {variant:?} 版本")]
+    UnsupportedBlock { block: String, variant: PlcVariant },
+    #[error("功能块 {0} 需要实例名")]
+    MissingInstance(String),
+    #[error("功能块 {0} 为无状态块，不能带实例名")]
+    UnexpectedInstance(String),
+    #[error("功能块 {block} 没有引脚 {pin}")]
+    UnknownPin { block: String, pin: String },
+    #[error("功能块 {block} 的成员 {pin} 未登记引脚方向，请在功能块库的 inputs/outputs 中声明")]
+    UndirectedPin { block: String, pin: String },
+    #[error("变量分组 {0} 不存在")]
+    UnknownGroup(String),
+    #[error("变量 {name} 未声明在分组 {group} 中")]
+    VariableOutsideGroup { name: String, group: String },
+    #[error("变量 {0} 已存在")]
+    DuplicateVariable(String),
+    #[error("变量 {name} 已声明为 {declared}，此处需要 {expected}")]
+    TypeConflict { name: String, declared: String, expected: String },
+    #[error("无法推断变量 {0} 的类型，请先用 add_variable 声明")]
+    UnresolvedType(String),
+    #[error("{src} 与 {dst} 不在同一网络")]
+    CrossNetwork { src: ElementId, dst: ElementId },
+    #[error("{0} 不能连接到自身")]
+    SelfConnection(ElementId),
+    #[error("{network} 拓扑无法生成: {source}")]
+    Topology {
+        network: NetworkId,
+        #[source]
+        source: TopologyError,
+    },
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+}
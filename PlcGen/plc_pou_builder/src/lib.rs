@@ -0,0 +1,277 @@
+//! Fluent builder for constructing ladder POUs from scratch.
+//! Responsibilities: atomic construction steps (networks, contacts, coils, blocks, pins, variables)
+//! producing a validated `UniversalPou`, and the `BuildPlan` JSON form of those steps.
+//! Non-goals: template rendering, encoding and persistence (handled by plc_templates / plc_core).
+
+mod builder;
+mod error;
+mod plan;
+
+pub use builder::{CoilKind, ContactKind, ElementId, LOCAL_GROUP, NetworkId, PouBuilder};
+pub use error::BuildError;
+pub use plan::{
+    BlockRef, BuildPlan, BuildStep, InitValue, LinkType, PlanError, PlanLibraries, PlanMeta, PouHeader,
+    SCHEMA_VERSION, StepError, VarDef, VarRef,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plc_core::ast::PinDirection;
+    use plc_core::{HollysysCodec, PlcVariant, PouCodec};
+
+    fn sample(variant: PlcVariant) -> PouBuilder {
+        let mut builder = PouBuilder::new("BUILT_POU", variant);
+        let net = builder.create_network();
+        let start = builder.add_contact(net, "START", ContactKind::NormallyOpen).unwrap();
+        let stop = builder.add_contact(net, "STOP", ContactKind::NormallyClosed).unwrap();
+        let motor = builder.add_coil(net, "MOTOR", CoilKind::Normal).unwrap();
+        builder.connect(start, stop).unwrap();
+        builder.connect(stop, motor).unwrap();
+
+        let net = builder.create_network();
+        builder.add_variable(&[LOCAL_GROUP], "SRC", "INT").unwrap().init_value = "0".to_string();
+        builder.add_variable(&[LOCAL_GROUP], "DST", "INT").unwrap();
+        let mv = builder.add_block(net, "MOVE", None).unwrap();
+        builder.bind_pin(mv, "IN", "SRC").unwrap();
+        builder.bind_pin(mv, "OUT", "DST").unwrap();
+        builder
+    }
+
+    fn element<'a>(net: &'a plc_core::ast::Network, name: &str) -> &'a plc_core::ast::LdElement {
+        net.elements.iter().find(|elem| elem.name == name).unwrap()
+    }
+
+    fn leaf_type(pou: &plc_core::ast::UniversalPou, name: &str) -> Option<String> {
+        fn find(nodes: &[plc_core::ast::VariableNode], name: &str) -> Option<String> {
+            nodes.iter().find_map(|node| match node {
+                plc_core::ast::VariableNode::Leaf(var) => (var.name == name).then(|| var.data_type.clone()),
+                plc_core::ast::VariableNode::Group { children, .. } => find(children, name),
+            })
+        }
+        find(&pou.variables, name)
+    }
+
+    #[test]
+    fn builds_and_encodes_both_variants() {
+        for variant in [PlcVariant::Normal, PlcVariant::Safety] {
+            let pou = sample(variant).build().unwrap();
+            assert_eq!(pou.networks.len(), 2);
+            let (rung, block) = (&pou.networks[0], &pou.networks[1]);
+            assert_eq!((rung.elements.len(), block.elements.len()), (3, 1));
+
+            // 触点/线圈引用的变量按 BOOL 自动声明
+            for name in ["START", "STOP", "MOTOR"] {
+                assert_eq!(leaf_type(&pou, name).as_deref(), Some("BOOL"), "{variant:?} {name}");
+            }
+            let (start, stop, motor) = (element(rung, "START"), element(rung, "STOP"), element(rung, "MOTOR"));
+            match variant {
+                PlcVariant::Normal => {
+                    assert_eq!(start.connections, [stop.id]);
+                    assert_eq!(stop.connections, [motor.id]);
+                    assert!(motor.connections.is_empty());
+                    assert!(rung.safety_topology.is_empty());
+                }
+                PlcVariant::Safety => {
+                    assert!(rung.elements.iter().all(|elem| elem.connections.is_empty()));
+                    assert!(!rung.safety_topology.is_empty());
+                }
+            }
+
+            let mv = element(block, "MOVE");
+            let pins: Vec<_> =
+                mv.pins.iter().map(|pin| (pin.name.as_str(), pin.variable.as_str(), pin.direction)).collect();
+            let unbound = match variant {
+                PlcVariant::Normal => "???",
+                PlcVariant::Safety => "",
+            };
+            assert_eq!(
+                pins,
+                [
+                    ("EN", unbound, PinDirection::Input),
+                    ("IN", "SRC", PinDirection::Input),
+                    ("ENO", unbound, PinDirection::Output),
+                    ("OUT", "DST", PinDirection::Output),
+                ]
+            );
+
+            let codec = match variant {
+                PlcVariant::Normal => HollysysCodec::normal(),
+                PlcVariant::Safety => HollysysCodec::safety(),
+            };
+            let encoded = codec.encode(&pou).unwrap();
+            assert_eq!(encoded.len() % 0x2000, 0, "{variant:?}");
+            assert!(encoded.windows(pou.name.len()).any(|w| w == pou.name.as_bytes()), "{variant:?}");
+        }
+    }
+
+    #[test]
+    fn adds_standard_timer_block() {
+        let mut builder = PouBuilder::new("TIMER_POU", PlcVariant::Safety);
+        let net = builder.create_network();
+        let ton = builder.add_block(net, "TON", Some("T1")).unwrap();
+        builder.add_variable(&[LOCAL_GROUP], "DELAY", "TIME").unwrap().init_value = "T#5S".to_string();
+        builder.bind_pin(ton, "IN", "START").unwrap();
+        builder.bind_pin(ton, "PT", "DELAY").unwrap();
+        builder.bind_pin(ton, "Q", "DONE").unwrap();
+        assert!(matches!(builder.bind_pin(ton, "PT", "START"), Err(BuildError::TypeConflict { .. })));
+        // 实例内部成员不是引脚
+        assert!(matches!(builder.bind_pin(ton, "M", "START"), Err(BuildError::UndirectedPin { .. })));
+        assert!(matches!(builder.add_block(net, "TON", None), Err(BuildError::MissingInstance(_))));
+
+        let pou = builder.build().unwrap();
+        assert_eq!(pou.header_strings, ["TON"]);
+        assert_eq!(leaf_type(&pou, "T1").as_deref(), Some("TON"));
+        assert_eq!(leaf_type(&pou, "START").as_deref(), Some("BOOL"));
+        assert_eq!(leaf_type(&pou, "DONE").as_deref(), Some("BOOL"));
+
+        let ton = element(&pou.networks[0], "TON");
+        assert_eq!(ton.instance, "T1");
+        let pins: Vec<_> =
+            ton.pins.iter().map(|pin| (pin.name.as_str(), pin.variable.as_str(), pin.direction)).collect();
+        assert_eq!(
+            pins,
+            [
+                ("EN", "", PinDirection::Input),
+                ("IN", "START", PinDirection::Input),
+                ("PT", "DELAY", PinDirection::Input),
+                ("ENO", "", PinDirection::Output),
+                ("Q", "DONE", PinDirection::Output),
+                ("ET", "", PinDirection::Output),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_members_without_pin_direction() {
+        let mut builder = PouBuilder::new("CTRL_POU", PlcVariant::Normal);
+        let net = builder.create_network();
+        let ctrl = builder.add_block(net, "MOV_CTRL", Some("MOV_1")).unwrap();
+        let err = builder.bind_pin(ctrl, "DA_TIME", "DELAY").unwrap_err();
+        assert!(matches!(err, BuildError::UndirectedPin { ref pin, .. } if pin == "DA_TIME"), "{err}");
+    }
+
+    #[test]
+    fn rejects_untyped_and_unknown_references() {
+        let mut builder = sample(PlcVariant::Normal);
+        let net = builder.create_network();
+        let mv = builder.add_block(net, "MOVE", None).unwrap();
+        assert!(matches!(builder.bind_pin(mv, "IN", "UNDECLARED"), Err(BuildError::UnresolvedType(_))));
+        assert!(matches!(builder.bind_pin(mv, "XX", "SRC"), Err(BuildError::UnknownPin { .. })));
+        assert!(matches!(builder.add_block(net, "MOV_CTRL", None), Err(BuildError::MissingInstance(_))));
+        assert!(matches!(builder.add_contact(net, "SRC", ContactKind::NormallyOpen), Err(BuildError::TypeConflict { .. })));
+    }
+
+    const PLAN: &str = r#"{
+        "schema_version": 1,
+        "meta": { "brand": "和利时", "variant": "Safety", "format_name": "POU_TREE_Clipboard_ITCC" },
+        "pou_header": { "name": "AI_GROUP_1" },
+        "steps": [
+            { "op": "create_network", "id": "net_1", "label": "AI" },
+            { "op": "add_contact", "id": "c1", "network_id": "net_1",
+              "var_ref": { "path": ["Local Variables", "AI_OK"] }, "sub_type": 0 },
+            { "op": "add_block", "id": "b1", "network_id": "net_1", "block_ref": { "name": "MOVE" } },
+            { "op": "bind_pin", "block_id": "b1", "pin_name": "IN", "var_ref": { "path": ["Local Variables", "AI_1"] } },
+            { "op": "bind_pin", "block_id": "b1", "pin_name": "OUT", "var_ref": "AI_OUT" },
+            { "op": "connect", "src_id": "c1", "dst_id": "b1", "link_type": "stmt" },
+            { "op": "add_variable", "group_path": ["Local Variables"],
+              "var_def": { "name": "AI_1", "data_type": "INT", "init_value": { "raw": "0" } } },
+            { "op": "add_variable", "group_path": ["Local Variables"],
+              "var_def": { "name": "AI_OUT", "data_type": "INT" } }
+        ]
+    }"#;
+
+    #[test]
+    fn executes_plan_with_hoisted_declarations() {
+        let pou = BuildPlan::from_json(PLAN).unwrap().execute().unwrap();
+        assert_eq!(pou.name, "AI_GROUP_1");
+        assert_eq!(pou.networks[0].elements.len(), 2);
+        assert!(!pou.networks[0].safety_topology.is_empty());
+        // 声明步骤提前执行：引用在前、声明在后的变量保留计划给出的类型而非按引脚推断
+        assert_eq!(leaf_type(&pou, "AI_1").as_deref(), Some("INT"));
+        assert_eq!(leaf_type(&pou, "AI_OUT").as_deref(), Some("INT"));
+        assert_eq!(leaf_type(&pou, "AI_OK").as_deref(), Some("BOOL"));
+        let mv = element(&pou.networks[0], "MOVE");
+        let bound: Vec<_> = mv.pins.iter().map(|pin| (pin.name.as_str(), pin.variable.as_str())).collect();
+        assert_eq!(bound, [("EN", ""), ("IN", "AI_1"), ("ENO", ""), ("OUT", "AI_OUT")]);
+        let encoded = HollysysCodec::safety().encode(&pou).unwrap();
+        assert!(encoded.windows(pou.name.len()).any(|w| w == pou.name.as_bytes()));
+    }
+
+    /// Docs/plc_code_doc.md 3.6.1 的嵌套路径：前几段为分组，未声明的变量创建在该分组中
+    const NESTED_PLAN: &str = r#"{
+        "schema_version": 1,
+        "meta": { "variant": "Normal" },
+        "pou_header": { "name": "AI_GROUP_1" },
+        "steps": [
+            { "op": "create_network", "id": "net_1", "label": "AI" },
+            { "op": "add_contact", "id": "c1", "network_id": "net_1",
+              "var_ref": { "path": ["Local Variables", "AI", "AI_OK"] }, "sub_type": 0 },
+            { "op": "add_block", "id": "b1", "network_id": "net_1", "block_ref": { "name": "MOVE" } },
+            { "op": "bind_pin", "block_id": "b1", "pin_name": "IN",
+              "var_ref": { "path": ["Local Variables", "AI", "AI_1"] } },
+            { "op": "bind_pin", "block_id": "b1", "pin_name": "OUT", "var_ref": { "path": ["Local Variables", "AI_OUT"] } },
+            { "op": "connect", "src_id": "c1", "dst_id": "b1" },
+            { "op": "add_variable", "group_path": ["Local Variables", "AI"],
+              "var_def": { "name": "AI_1", "data_type": "INT", "init_value": { "raw": "0" } } },
+            { "op": "add_variable", "group_path": ["Local Variables"],
+              "var_def": { "name": "AI_OUT", "data_type": "INT" } }
+        ]
+    }"#;
+
+    #[test]
+    fn resolves_nested_var_ref_paths() {
+        let pou = BuildPlan::from_json(NESTED_PLAN).unwrap().execute().unwrap();
+        let [plc_core::ast::VariableNode::Group { name, children, .. }] = &pou.variables[..] else {
+            panic!("{:?}", pou.variables);
+        };
+        assert_eq!(name, LOCAL_GROUP);
+        let Some(plc_core::ast::VariableNode::Group { name, children: ai, .. }) = children.first() else {
+            panic!("{children:?}");
+        };
+        assert_eq!(name, "AI");
+        let names: Vec<_> = ai
+            .iter()
+            .map(|node| match node {
+                plc_core::ast::VariableNode::Leaf(var) => (var.name.as_str(), var.data_type.as_str()),
+                other => panic!("{other:?}"),
+            })
+            .collect();
+        assert_eq!(names, [("AI_1", "INT"), ("AI_OK", "BOOL")]);
+        let mv = element(&pou.networks[0], "MOVE");
+        assert!(mv.pins.iter().any(|pin| pin.name == "IN" && pin.variable == "AI_1"));
+        assert_eq!(element(&pou.networks[0], "AI_OK").type_code, plc_core::ast::ElementType::Contact);
+    }
+
+    #[test]
+    fn rejects_var_ref_outside_its_group() {
+        let mut plan = BuildPlan::from_json(NESTED_PLAN).unwrap();
+        let BuildStep::AddContact { var_ref, .. } = &mut plan.steps[1] else { unreachable!() };
+        *var_ref = VarRef::Path { path: vec![LOCAL_GROUP.into(), "AO".into(), "AI_OK".into()] };
+        let err = plan.execute().unwrap_err();
+        assert_eq!(err.step_index(), Some(1));
+        assert!(matches!(
+            err,
+            PlanError::Step { source: StepError::UnknownGroup(ref group), .. } if group == "Local Variables/AO"
+        ));
+
+        let mut plan = BuildPlan::from_json(NESTED_PLAN).unwrap();
+        let BuildStep::BindPin { var_ref, .. } = &mut plan.steps[4] else { unreachable!() };
+        *var_ref = VarRef::Path { path: vec![LOCAL_GROUP.into(), "AI".into(), "AI_OUT".into()] };
+        let err = plan.execute().unwrap_err();
+        assert_eq!(err.step_index(), Some(4));
+        assert!(matches!(
+            err,
+            PlanError::Step { source: StepError::Build(BuildError::VariableOutsideGroup { .. }), .. }
+        ));
+    }
+
+    #[test]
+    fn reports_failing_step_index() {
+        let mut plan = BuildPlan::from_json(PLAN).unwrap();
+        plan.steps.insert(2, BuildStep::Connect { src_id: "c1".into(), dst_id: "b9".into(), link_type: LinkType::Stmt });
+        let err = plan.execute().unwrap_err();
+        assert_eq!(err.step_index(), Some(2));
+        assert!(matches!(err, PlanError::Step { source: StepError::UnknownElement(_), .. }));
+    }
+}
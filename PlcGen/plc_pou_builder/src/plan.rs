@@ -0,0 +1,449 @@
+/*
+BuildPlan：构造步骤的序列化形式（Docs/plc_code_doc.md 3.6.1）
+- 前端/第三方工具输出 BuildPlan JSON，由执行器逐步调用 PouBuilder 生成 UniversalPou
+- 步骤中的 id 为计划内的字符串标识，执行器维护其到网络/元件句柄的映射
+- add_variable 步骤先于其它步骤执行（变量声明提升），其余步骤按顺序执行
+- 任一步骤失败时报告该步骤在 steps 中的下标；build 阶段的拓扑/校验错误回溯到产生该网络/元件的步骤
+*/
+use std::collections::HashMap;
+
+use plc_core::PlcVariant;
+use plc_core::ast::UniversalPou;
+use plc_core::symbols_config::SymbolConfig;
+use plc_core::{HollysysCodec, HollysysConfig, PouCodec};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::builder::{CoilKind, ContactKind, ElementId, NetworkId, PouBuilder};
+use crate::error::BuildError;
+
+/// 当前支持的 BuildPlan 结构版本
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 构造计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildPlan {
+    pub schema_version: u32,
+    pub meta: PlanMeta,
+    pub pou_header: PouHeader,
+    /// 库引用（由宿主解析为 SymbolConfig 后传入执行器）
+    #[serde(default)]
+    pub libraries: PlanLibraries,
+    #[serde(default)]
+    pub steps: Vec<BuildStep>,
+}
+
+/// 品牌/系列/型号/版本，用于选择适配器与校验
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanMeta {
+    #[serde(default)]
+    pub brand: String,
+    #[serde(default)]
+    pub series: String,
+    #[serde(default)]
+    pub model: String,
+    pub variant: PlcVariant,
+    /// 剪贴板格式名；非空时必须与 variant 对应的格式一致
+    #[serde(default)]
+    pub format_name: String,
+}
+
+/// 允许编辑的 POU 头字段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PouHeader {
+    pub name: String,
+    #[serde(default)]
+    pub header_strings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanLibraries {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_lib_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variable_lib_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_lib_ref: Option<String>,
+}
+
+/// 原子构造步骤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BuildStep {
+    CreateNetwork {
+        #[serde(default)]
+        id: Option<String>,
+        #[serde(default)]
+        label: String,
+        #[serde(default)]
+        comment: String,
+    },
+    AddContact {
+        id: String,
+        network_id: String,
+        var_ref: VarRef,
+        /// 0 = 常开，1 = 常闭
+        #[serde(default)]
+        sub_type: u8,
+        #[serde(default)]
+        comment: Option<String>,
+    },
+    AddCoil {
+        id: String,
+        network_id: String,
+        var_ref: VarRef,
+        /// 0 = 普通线圈，1 = 取反线圈
+        #[serde(default)]
+        sub_type: u8,
+        #[serde(default)]
+        comment: Option<String>,
+    },
+    AddBlock {
+        id: String,
+        network_id: String,
+        block_ref: BlockRef,
+        #[serde(default)]
+        instance: Option<String>,
+        #[serde(default)]
+        comment: Option<String>,
+    },
+    BindPin {
+        block_id: String,
+        pin_name: String,
+        var_ref: VarRef,
+    },
+    Connect {
+        src_id: String,
+        dst_id: String,
+        #[serde(default)]
+        link_type: LinkType,
+    },
+    AddVariable {
+        #[serde(default)]
+        group_path: Vec<String>,
+        var_def: VarDef,
+    },
+}
+
+impl BuildStep {
+    pub fn op(&self) -> &'static str {
+        match self {
+            BuildStep::CreateNetwork { .. } => "create_network",
+            BuildStep::AddContact { .. } => "add_contact",
+            BuildStep::AddCoil { .. } => "add_coil",
+            BuildStep::AddBlock { .. } => "add_block",
+            BuildStep::BindPin { .. } => "bind_pin",
+            BuildStep::Connect { .. } => "connect",
+            BuildStep::AddVariable { .. } => "add_variable",
+        }
+    }
+}
+
+/// 变量引用：变量树路径（与解析器输出一致，前几段为分组、末段为变量名），或直接给出名称/字面量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VarRef {
+    Path { path: Vec<String> },
+    Name(String),
+}
+
+impl VarRef {
+    /// 元件/引脚上写入的名称
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            VarRef::Path { path } => path.last().map(String::as_str),
+            VarRef::Name(name) => Some(name.as_str()),
+        }
+    }
+
+    /// 变量所在的分组路径；直接给出名称时为空
+    pub fn group_path(&self) -> Vec<&str> {
+        match self {
+            VarRef::Path { path } => path.iter().rev().skip(1).rev().map(String::as_str).collect(),
+            VarRef::Name(_) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockRef {
+    pub name: String,
+    /// 库来源（official / custom），仅作记录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// expr = 表达式树挂接，stmt = 语句链挂接；当前拓扑只区分能流方向，两者都按能流连线处理
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkType {
+    Expr,
+    #[default]
+    Stmt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarDef {
+    pub name: String,
+    pub data_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_value: Option<InitValue>,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub soe_enable: bool,
+    #[serde(default)]
+    pub power_down_keep: bool,
+}
+
+/// 初始值：先保留原始文本，后续按解析字段扩展
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InitValue {
+    pub raw: String,
+}
+
+/// 计划内步骤的错误
+#[derive(Debug, Error)]
+pub enum StepError {
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error("标识 {0} 已被前面的步骤使用")]
+    DuplicateId(String),
+    #[error("未定义的网络标识: {0}")]
+    UnknownNetwork(String),
+    #[error("未定义的元件标识: {0}")]
+    UnknownElement(String),
+    #[error("sub_type 取值无效: {0}（仅支持 0/1）")]
+    InvalidSubType(u8),
+    #[error("var_ref 为空")]
+    EmptyVarRef,
+    #[error("var_ref 的分组 {0} 不存在")]
+    UnknownGroup(String),
+}
+
+/// BuildPlan 执行错误
+#[derive(Debug, Error)]
+pub enum PlanError {
+    #[error("BuildPlan JSON 解析失败: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("不支持的 schema_version: {0}（当前支持 {SCHEMA_VERSION}）")]
+    UnsupportedSchema(u32),
+    #[error("meta.format_name {found} 与 {variant:?} 版本的格式 {expected} 不一致")]
+    FormatMismatch { found: String, expected: &'static str, variant: PlcVariant },
+    #[error("步骤 {index}（{op}）失败: {source}")]
+    Step {
+        index: usize,
+        op: &'static str,
+        #[source]
+        source: StepError,
+    },
+    /// 无法归属到具体步骤的 build 错误（如 POU 名称非法）
+    #[error("生成 POU 失败: {0}")]
+    Build(#[source] BuildError),
+}
+
+impl PlanError {
+    /// 失败步骤在 steps 中的下标
+    pub fn step_index(&self) -> Option<usize> {
+        match self {
+            PlanError::Step { index, .. } => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+impl BuildPlan {
+    pub fn from_json(text: &str) -> Result<Self, PlanError> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, PlanError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// 使用内置功能块库执行
+    pub fn execute(&self) -> Result<UniversalPou, PlanError> {
+        self.execute_with_symbols(SymbolConfig::builtin())
+    }
+
+    /// 使用宿主解析 libraries 得到的功能块库执行
+    pub fn execute_with_symbols(&self, symbols: SymbolConfig) -> Result<UniversalPou, PlanError> {
+        if self.schema_version != SCHEMA_VERSION {
+            return Err(PlanError::UnsupportedSchema(self.schema_version));
+        }
+        let variant = self.meta.variant;
+        let expected = HollysysCodec::new(HollysysConfig::new(variant)).format_name();
+        if !self.meta.format_name.is_empty() && self.meta.format_name != expected {
+            return Err(PlanError::FormatMismatch { found: self.meta.format_name.clone(), expected, variant });
+        }
+
+        let mut exec = Executor {
+            builder: PouBuilder::new(self.pou_header.name.clone(), variant).with_symbols(symbols),
+            networks: HashMap::new(),
+            elements: HashMap::new(),
+            network_steps: HashMap::new(),
+            element_steps: HashMap::new(),
+        };
+        for header in &self.pou_header.header_strings {
+            exec.builder.add_header_string(header.clone());
+        }
+
+        // 变量声明提升：先执行全部 add_variable，再按顺序执行其余步骤
+        let (declarations, others): (Vec<_>, Vec<_>) =
+            self.steps.iter().enumerate().partition(|(_, step)| matches!(step, BuildStep::AddVariable { .. }));
+        for (index, step) in declarations.into_iter().chain(others) {
+            exec.apply(index, step)
+                .map_err(|source| PlanError::Step { index, op: step.op(), source })?;
+        }
+
+        exec.builder.build().map_err(|err| exec.locate(err, &self.steps))
+    }
+}
+
+struct Executor {
+    builder: PouBuilder,
+    networks: HashMap<String, NetworkId>,
+    elements: HashMap<String, ElementId>,
+    /// 网络/元件 → 创建它的步骤下标，用于回溯 build 阶段的错误
+    network_steps: HashMap<i32, usize>,
+    element_steps: HashMap<i32, usize>,
+}
+
+impl Executor {
+    fn apply(&mut self, index: usize, step: &BuildStep) -> Result<(), StepError> {
+        match step {
+            BuildStep::CreateNetwork { id, label, comment } => {
+                if let Some(id) = id
+                    && self.networks.contains_key(id)
+                {
+                    return Err(StepError::DuplicateId(id.clone()));
+                }
+                let network = self.builder.create_network();
+                self.builder.set_network_label(network, label.clone())?;
+                self.builder.set_network_comment(network, comment.clone())?;
+                self.network_steps.insert(network.get(), index);
+                if let Some(id) = id {
+                    self.networks.insert(id.clone(), network);
+                }
+            }
+            BuildStep::AddContact { id, network_id, var_ref, sub_type, comment } => {
+                let kind = match sub_type {
+                    0 => ContactKind::NormallyOpen,
+                    1 => ContactKind::NormallyClosed,
+                    other => return Err(StepError::InvalidSubType(*other)),
+                };
+                self.check_new_element(id)?;
+                let network = self.network(network_id)?;
+                let elem = self.with_var_ref(var_ref, |builder, name| builder.add_contact(network, name, kind))?;
+                self.register_element(id, elem, comment.as_deref(), index)?;
+            }
+            BuildStep::AddCoil { id, network_id, var_ref, sub_type, comment } => {
+                let kind = match sub_type {
+                    0 => CoilKind::Normal,
+                    1 => CoilKind::Negated,
+                    other => return Err(StepError::InvalidSubType(*other)),
+                };
+                self.check_new_element(id)?;
+                let network = self.network(network_id)?;
+                let elem = self.with_var_ref(var_ref, |builder, name| builder.add_coil(network, name, kind))?;
+                self.register_element(id, elem, comment.as_deref(), index)?;
+            }
+            BuildStep::AddBlock { id, network_id, block_ref, instance, comment } => {
+                self.check_new_element(id)?;
+                let network = self.network(network_id)?;
+                let elem = self.builder.add_block(network, &block_ref.name, instance.as_deref())?;
+                self.register_element(id, elem, comment.as_deref(), index)?;
+            }
+            BuildStep::BindPin { block_id, pin_name, var_ref } => {
+                let block = self.element(block_id)?;
+                self.with_var_ref(var_ref, |builder, name| builder.bind_pin(block, pin_name, name))?;
+            }
+            BuildStep::Connect { src_id, dst_id, .. } => {
+                let (src, dst) = (self.element(src_id)?, self.element(dst_id)?);
+                self.builder.connect(src, dst)?;
+            }
+            BuildStep::AddVariable { group_path, var_def } => {
+                let path: Vec<&str> = group_path.iter().map(String::as_str).collect();
+                let var = self.builder.add_variable(&path, &var_def.name, &var_def.data_type)?;
+                if let Some(init) = &var_def.init_value {
+                    var.init_value.clone_from(&init.raw);
+                }
+                var.comment.clone_from(&var_def.comment);
+                var.soe_enable = var_def.soe_enable;
+                var.power_down_keep = var_def.power_down_keep;
+            }
+        }
+        Ok(())
+    }
+
+    /// 路径引用在其分组内查找/自动声明变量；分组须已存在（Local Variables 根分组除外）
+    fn with_var_ref<R>(
+        &mut self,
+        var_ref: &VarRef,
+        f: impl FnOnce(&mut PouBuilder, &str) -> Result<R, BuildError>,
+    ) -> Result<R, StepError> {
+        let name = var_name(var_ref)?;
+        let group = var_ref.group_path();
+        if group.is_empty() {
+            return Ok(f(&mut self.builder, name)?);
+        }
+        if !self.builder.has_group(&group) {
+            return Err(StepError::UnknownGroup(group.join("/")));
+        }
+        Ok(self.builder.in_group(&group, |builder| f(builder, name))?)
+    }
+
+    fn network(&self, id: &str) -> Result<NetworkId, StepError> {
+        self.networks.get(id).copied().ok_or_else(|| StepError::UnknownNetwork(id.to_string()))
+    }
+
+    fn element(&self, id: &str) -> Result<ElementId, StepError> {
+        self.elements.get(id).copied().ok_or_else(|| StepError::UnknownElement(id.to_string()))
+    }
+
+    fn check_new_element(&self, id: &str) -> Result<(), StepError> {
+        if self.elements.contains_key(id) {
+            return Err(StepError::DuplicateId(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn register_element(
+        &mut self,
+        id: &str,
+        elem: ElementId,
+        comment: Option<&str>,
+        index: usize,
+    ) -> Result<(), StepError> {
+        if let Some(comment) = comment {
+            self.builder.set_element_comment(elem, comment)?;
+        }
+        self.elements.insert(id.to_string(), elem);
+        self.element_steps.insert(elem.get(), index);
+        Ok(())
+    }
+
+    /// build 错误回溯到步骤：拓扑错误归属创建网络的步骤，校验错误归属首个错误所在的元件/网络
+    fn locate(&self, err: BuildError, steps: &[BuildStep]) -> PlanError {
+        let index = match &err {
+            BuildError::Topology { network, .. } => self.network_steps.get(&network.get()).copied(),
+            BuildError::Validation(validation) => validation.0.errors().next().and_then(|violation| {
+                let location = &violation.location;
+                location
+                    .element_id
+                    .and_then(|id| self.element_steps.get(&id))
+                    .or_else(|| location.network_id.and_then(|id| self.network_steps.get(&id)))
+                    .copied()
+            }),
+            _ => None,
+        };
+        match index {
+            Some(index) => PlanError::Step { index, op: steps[index].op(), source: err.into() },
+            None => PlanError::Build(err),
+        }
+    }
+}
+
+fn var_name(var_ref: &VarRef) -> Result<&str, StepError> {
+    var_ref.name().filter(|name| !name.trim().is_empty()).ok_or(StepError::EmptyVarRef)
+}
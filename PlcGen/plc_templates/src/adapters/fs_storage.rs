@@ -0,0 +1,215 @@
+/*
+模板包目录结构（运行期仓库）
+{root}/{brand}/{series}/{model}/{category_path...}/{template_name}/
+  pou.json               # 解码后的完整 POU 快照
+  template.spec.json     # 可变字段描述
+  template.meta.json     # 定位/版本/编码参数/时间戳
+  pou.bin                # 原始剪贴板二进制（可选）
+  template.tera          # 规则脚本（可选）
+  .versions/{n}/         # 历史版本（与上面同样的文件集合）
+含 template.meta.json 的目录即为模板目录；以 '.' 开头的目录不参与扫描
+*/
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::domain::template_bundle::{TemplateBundle, TemplateKey, TemplateMeta};
+use crate::domain::template_errors::{TemplateError, TemplateResult};
+use crate::ports::storage_port::TemplateStorage;
+
+const META_FILE: &str = "template.meta.json";
+const SPEC_FILE: &str = "template.spec.json";
+const POU_FILE: &str = "pou.json";
+const RAW_FILE: &str = "pou.bin";
+const SCRIPT_FILE: &str = "template.tera";
+const VERSIONS_DIR: &str = ".versions";
+
+/// 基于文件系统的模板仓库
+#[derive(Debug, Clone)]
+pub struct FsTemplateStorage {
+    root: PathBuf,
+}
+
+impl FsTemplateStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 模板当前版本所在目录
+    pub fn template_dir(&self, key: &TemplateKey) -> TemplateResult<PathBuf> {
+        Ok(self.root.join(key.relative_path()?))
+    }
+
+    fn current_meta(&self, key: &TemplateKey) -> TemplateResult<(PathBuf, TemplateMeta)> {
+        let dir = self.template_dir(key)?;
+        if !dir.join(META_FILE).is_file() {
+            return Err(TemplateError::NotFound(key.to_string()));
+        }
+        let meta = read_json(&dir.join(META_FILE))?;
+        Ok((dir, meta))
+    }
+
+    fn scan(&self, dir: &Path, out: &mut Vec<TemplateMeta>) -> TemplateResult<()> {
+        if dir.join(META_FILE).is_file() {
+            out.push(read_json(&dir.join(META_FILE))?);
+            return Ok(());
+        }
+        let entries = fs::read_dir(dir).map_err(|err| TemplateError::io(dir, err))?;
+        let mut children: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir() && !is_hidden(path))
+            .collect();
+        children.sort();
+        for child in children {
+            self.scan(&child, out)?;
+        }
+        Ok(())
+    }
+}
+
+impl TemplateStorage for FsTemplateStorage {
+    fn create(&self, bundle: &TemplateBundle) -> TemplateResult<TemplateMeta> {
+        let dir = self.template_dir(bundle.key())?;
+        if dir.join(META_FILE).exists() {
+            return Err(TemplateError::AlreadyExists(bundle.key().to_string()));
+        }
+        let now = unix_now();
+        let meta = TemplateMeta { version: 1, created_at: now, updated_at: now, ..bundle.meta.clone() };
+        write_bundle(&dir, bundle, &meta)?;
+        Ok(meta)
+    }
+
+    fn list(&self) -> TemplateResult<Vec<TemplateMeta>> {
+        let mut metas = Vec::new();
+        if self.root.is_dir() {
+            self.scan(&self.root, &mut metas)?;
+        }
+        Ok(metas)
+    }
+
+    fn load(&self, key: &TemplateKey) -> TemplateResult<TemplateBundle> {
+        let (dir, _) = self.current_meta(key)?;
+        read_bundle(&dir)
+    }
+
+    fn save_version(&self, bundle: &TemplateBundle) -> TemplateResult<TemplateMeta> {
+        let (dir, current) = self.current_meta(bundle.key())?;
+        let archive = dir.join(VERSIONS_DIR).join(current.version.to_string());
+        fs::create_dir_all(&archive).map_err(|err| TemplateError::io(&archive, err))?;
+        for file in [META_FILE, SPEC_FILE, POU_FILE, RAW_FILE, SCRIPT_FILE] {
+            let src = dir.join(file);
+            if src.exists() {
+                fs::rename(&src, archive.join(file)).map_err(|err| TemplateError::io(&src, err))?;
+            }
+        }
+
+        let meta = TemplateMeta {
+            version: current.version + 1,
+            created_at: current.created_at,
+            updated_at: unix_now(),
+            ..bundle.meta.clone()
+        };
+        write_bundle(&dir, bundle, &meta)?;
+        Ok(meta)
+    }
+
+    fn versions(&self, key: &TemplateKey) -> TemplateResult<Vec<u32>> {
+        let (dir, current) = self.current_meta(key)?;
+        let mut versions = vec![current.version];
+        let archive = dir.join(VERSIONS_DIR);
+        if archive.is_dir() {
+            let entries = fs::read_dir(&archive).map_err(|err| TemplateError::io(&archive, err))?;
+            versions.extend(
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok()),
+            );
+        }
+        versions.sort_unstable();
+        versions.dedup();
+        Ok(versions)
+    }
+
+    fn load_version(&self, key: &TemplateKey, version: u32) -> TemplateResult<TemplateBundle> {
+        let (dir, current) = self.current_meta(key)?;
+        if version == current.version {
+            return read_bundle(&dir);
+        }
+        let archived = dir.join(VERSIONS_DIR).join(version.to_string());
+        if !archived.join(META_FILE).is_file() {
+            return Err(TemplateError::VersionNotFound { key: key.to_string(), version });
+        }
+        read_bundle(&archived)
+    }
+
+    fn delete(&self, key: &TemplateKey) -> TemplateResult<()> {
+        let (dir, _) = self.current_meta(key)?;
+        fs::remove_dir_all(&dir).map_err(|err| TemplateError::io(&dir, err))
+    }
+}
+
+/// 写入模板包文件；可选文件为空时删除旧文件
+fn write_bundle(dir: &Path, bundle: &TemplateBundle, meta: &TemplateMeta) -> TemplateResult<()> {
+    fs::create_dir_all(dir).map_err(|err| TemplateError::io(dir, err))?;
+    write_json(&dir.join(POU_FILE), &bundle.pou)?;
+    write_json(&dir.join(SPEC_FILE), &bundle.spec)?;
+    write_optional(&dir.join(RAW_FILE), bundle.raw.as_deref())?;
+    write_optional(&dir.join(SCRIPT_FILE), bundle.script.as_deref().map(str::as_bytes))?;
+    // 元数据最后写入：中途失败时目录不会被识别为完整模板
+    write_json(&dir.join(META_FILE), meta)
+}
+
+fn read_bundle(dir: &Path) -> TemplateResult<TemplateBundle> {
+    let raw_path = dir.join(RAW_FILE);
+    let raw = if raw_path.is_file() {
+        Some(fs::read(&raw_path).map_err(|err| TemplateError::io(&raw_path, err))?)
+    } else {
+        None
+    };
+    let script_path = dir.join(SCRIPT_FILE);
+    let script = if script_path.is_file() {
+        Some(fs::read_to_string(&script_path).map_err(|err| TemplateError::io(&script_path, err))?)
+    } else {
+        None
+    };
+    Ok(TemplateBundle {
+        meta: read_json(&dir.join(META_FILE))?,
+        spec: read_json(&dir.join(SPEC_FILE))?,
+        pou: read_json(&dir.join(POU_FILE))?,
+        raw,
+        script,
+    })
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> TemplateResult<T> {
+    let text = fs::read_to_string(path).map_err(|err| TemplateError::io(path, err))?;
+    serde_json::from_str(&text).map_err(|err| TemplateError::json(path, err))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> TemplateResult<()> {
+    let text = serde_json::to_string_pretty(value).map_err(|err| TemplateError::json(path, err))?;
+    fs::write(path, text).map_err(|err| TemplateError::io(path, err))
+}
+
+fn write_optional(path: &Path, bytes: Option<&[u8]>) -> TemplateResult<()> {
+    match bytes {
+        Some(bytes) => fs::write(path, bytes).map_err(|err| TemplateError::io(path, err)),
+        None if path.exists() => fs::remove_file(path).map_err(|err| TemplateError::io(path, err)),
+        None => Ok(()),
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with('.'))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use plc_core::ast::{LdElement, Network, SafetyTopologyToken, UniversalPou, VariableNode};
+use plc_core::domain::topology::Rung;
+
+use crate::domain::patch::SetInstance;
+use crate::domain::template_errors::{PatchError, TemplateError, TemplateResult};
+use crate::domain::template_spec::TemplateSet;
+
+use super::references::{elements_mut, instance_names, is_instance_group, max_id, rename_element_refs};
+
+/// Hands out fresh ids after the POU's current max id (networks and elements share one id space).
+struct IdAllocator(i32);
+
+impl IdAllocator {
+    fn new(pou: &UniversalPou) -> Self {
+        Self(max_id(pou))
+    }
+
+    fn next(&mut self) -> i32 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+/// Clone the networks `source_ids` (kept in POU order) and insert the copies right after the
+/// last source network. Network and element ids continue from the current max id + 1;
+/// `connections` and Safety tokens are rewired to the new ids. `renames` maps an original
+/// name (case-insensitive) to its replacement in the copies.
+/// Returns the ids of the new networks.
+pub fn clone_networks(pou: &mut UniversalPou, source_ids: &[i32], renames: &BTreeMap<String, String>) -> Vec<i32> {
+    let positions = set_positions(pou, source_ids);
+    let Some(&last) = positions.last() else {
+        return Vec::new();
+    };
+
+    let rename = name_map(renames);
+    let mut ids = IdAllocator::new(pou);
+    let clones: Vec<Network> =
+        positions.iter().map(|&pos| clone_network(&pou.networks[pos], &mut ids, &rename)).collect();
+    let new_ids = clones.iter().map(|net| net.id).collect();
+    pou.networks.splice(last + 1..last + 1, clones);
+    new_ids
+}
+
+/// Expand a template set into one copy per instance, merged into the same POU.
+///
+/// - The first instance renames the original set in place (original ids are kept); every
+///   further instance appends a renamed clone of the original set after the previous copy.
+/// - Set variables are re-declared per instance under their original group. A renamed copy
+///   whose name is already declared with the same type is treated as shared and declared once;
+///   a type clash is an error. Clones get fresh `var_id`/`addr_id` (assigned by the serializer).
+/// - References outside the set networks are left untouched.
+pub fn expand_set(pou: &mut UniversalPou, set: &TemplateSet, instances: &[SetInstance]) -> Result<(), PatchError> {
+    let Some((first, rest)) = instances.split_first() else {
+        return Ok(());
+    };
+    if let Some(id) = set.networks.iter().find(|id| !pou.networks.iter().any(|net| net.id == **id)) {
+        return Err(PatchError::NoMatch(format!("网络[id={}]", id)));
+    }
+    let positions = set_positions(pou, &set.networks);
+    let originals: Vec<Network> = positions.iter().map(|&pos| pou.networks[pos].clone()).collect();
+
+    let instance_groups = instance_names(pou);
+    let mut templates = Vec::with_capacity(set.variables.len());
+    for path in &set.variables {
+        templates.push(detach_variable(&mut pou.variables, path, &instance_groups)?);
+    }
+
+    let rename = name_map(&first.vars);
+    for &pos in &positions {
+        for elem in elements_mut(&mut pou.networks[pos]) {
+            rename_element_refs(elem, &rename);
+        }
+    }
+    let mut ids = IdAllocator::new(pou);
+    let mut insert_at = positions.last().map_or(pou.networks.len(), |last| last + 1);
+    for instance in rest {
+        let rename = name_map(&instance.vars);
+        for net in &originals {
+            pou.networks.insert(insert_at, clone_network(net, &mut ids, &rename));
+            insert_at += 1;
+        }
+    }
+
+    // Re-collected: the copied networks now reference each instance's renamed blocks.
+    let instance_groups = instance_names(pou);
+    for (index, instance) in instances.iter().enumerate() {
+        let rename = name_map(&instance.vars);
+        for (parent, node) in &templates {
+            let mut copy = node.clone();
+            rename_node(&mut copy, &rename);
+            if index > 0 {
+                reset_ids(&mut copy);
+            }
+            declare(&mut pou.variables, parent, copy, &instance_groups)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rebuild the Safety token stream of multi-element networks that have none.
+/// JSON snapshots do not carry the token stream, so a Safety template must go through this
+/// before encoding; the rung is derived from `connections`, or declaration order without them.
+pub fn restore_safety_topology(pou: &mut UniversalPou) -> TemplateResult<()> {
+    for net in &mut pou.networks {
+        if net.elements.len() > 1 && net.safety_topology.is_empty() {
+            net.safety_topology = Rung::from_network(net)
+                .and_then(|rung| rung.to_tokens(&net.elements))
+                .map_err(|source| TemplateError::Topology { network: net.id, source })?;
+        }
+    }
+    Ok(())
+}
+
+fn name_map(renames: &BTreeMap<String, String>) -> impl Fn(&str) -> Option<String> + '_ {
+    move |name: &str| renames.iter().find(|(from, _)| from.eq_ignore_ascii_case(name)).map(|(_, to)| to.clone())
+}
+
+/// Indexes of the networks `ids`, in POU order.
+fn set_positions(pou: &UniversalPou, ids: &[i32]) -> Vec<usize> {
+    pou.networks.iter().enumerate().filter(|(_, net)| ids.contains(&net.id)).map(|(i, _)| i).collect()
+}
+
+/// Copy of `source` with fresh ids, rewired topology and renamed references.
+fn clone_network(source: &Network, ids: &mut IdAllocator, rename: &dyn Fn(&str) -> Option<String>) -> Network {
+    let mut net = source.clone();
+    let mut map = HashMap::new();
+    map.insert(net.id, ids.next());
+    for elem in elements_mut(&mut net) {
+        map.entry(elem.id).or_insert_with(|| ids.next());
+    }
+    remap_network(&mut net, &map);
+    for elem in elements_mut(&mut net) {
+        rename_element_refs(elem, rename);
+    }
+    net
+}
+
+/// Apply an old → new id map to a network, its elements, connections and topology tokens.
+fn remap_network(net: &mut Network, ids: &HashMap<i32, i32>) {
+    let map = |id: i32| ids.get(&id).copied().unwrap_or(id);
+    net.id = map(net.id);
+    for elem in &mut net.elements {
+        remap_element(elem, &map);
+    }
+    for token in &mut net.safety_topology {
+        match token {
+            SafetyTopologyToken::InlineElement(elem) | SafetyTopologyToken::Element(elem) => remap_element(elem, &map),
+            SafetyTopologyToken::ElementRef { id, .. } => *id = map(*id as i32) as u32,
+            _ => {}
+        }
+    }
+}
+
+fn remap_element(elem: &mut LdElement, map: &dyn Fn(i32) -> i32) {
+    elem.id = map(elem.id);
+    for dst in &mut elem.connections {
+        *dst = map(*dst);
+    }
+}
+
+/// Remove the variable at `path` (plain groups, then a leaf or instance variable) and return
+/// it together with its parent group path.
+fn detach_variable(
+    nodes: &mut Vec<VariableNode>,
+    path: &[String],
+    instances: &HashSet<String>,
+) -> Result<(Vec<String>, VariableNode), PatchError> {
+    let not_found = || PatchError::NoMatch(format!("变量[path={}]", path.join("/")));
+    let (name, parent) = path.split_last().ok_or_else(not_found)?;
+    let children = group_children(nodes, parent).ok_or_else(not_found)?;
+    let index = children.iter().position(|node| node_name(node).eq_ignore_ascii_case(name)).ok_or_else(not_found)?;
+    if matches!(children[index], VariableNode::Group { .. }) && !is_instance_group(&children[index], instances) {
+        return Err(PatchError::WrongElementType {
+            target: format!("变量[path={}]", path.join("/")),
+            expected: "叶子变量或功能块实例",
+        });
+    }
+    Ok((parent.to_vec(), children.remove(index)))
+}
+
+/// Declare `node` under `parent`, merging it with an existing declaration of the same shape.
+fn declare(
+    nodes: &mut Vec<VariableNode>,
+    parent: &[String],
+    node: VariableNode,
+    instances: &HashSet<String>,
+) -> Result<(), PatchError> {
+    let name = node_name(&node);
+    if let Some(existing) = find_declaration(nodes, &node, instances) {
+        return if same_shape(existing, &node) { Ok(()) } else { Err(PatchError::DuplicateVariable(name.to_string())) };
+    }
+    group_children(nodes, parent).expect("parent group of a detached variable still exists").push(node);
+    Ok(())
+}
+
+fn group_children<'a>(mut nodes: &'a mut Vec<VariableNode>, path: &[String]) -> Option<&'a mut Vec<VariableNode>> {
+    for segment in path {
+        nodes = nodes.iter_mut().find_map(|node| match node {
+            VariableNode::Group { name, type_name: None, children } if name.eq_ignore_ascii_case(segment) => {
+                Some(children)
+            }
+            _ => None,
+        })?;
+    }
+    Some(nodes)
+}
+
+/// Existing declaration with the name and kind (leaf or instance group) of `node`.
+/// The decoder emits a Safety instance both as a leaf typed with the block and as an untyped
+/// member group of the same name; the two are one instance, not a clash.
+fn find_declaration<'a>(
+    nodes: &'a [VariableNode],
+    node: &VariableNode,
+    instances: &HashSet<String>,
+) -> Option<&'a VariableNode> {
+    let is_leaf = matches!(node, VariableNode::Leaf(_));
+    nodes.iter().find_map(|candidate| match candidate {
+        VariableNode::Group { children, .. } if !is_instance_group(candidate, instances) => {
+            find_declaration(children, node, instances)
+        }
+        _ if matches!(candidate, VariableNode::Leaf(_)) == is_leaf
+            && node_name(candidate).eq_ignore_ascii_case(node_name(node)) =>
+        {
+            Some(candidate)
+        }
+        _ => None,
+    })
+}
+
+fn same_shape(a: &VariableNode, b: &VariableNode) -> bool {
+    match (a, b) {
+        (VariableNode::Leaf(a), VariableNode::Leaf(b)) => a.data_type.eq_ignore_ascii_case(&b.data_type),
+        (VariableNode::Group { type_name: Some(a), .. }, VariableNode::Group { type_name: Some(b), .. }) => {
+            a.eq_ignore_ascii_case(b)
+        }
+        // Decoded instance member groups carry no type; compare their members instead.
+        (
+            VariableNode::Group { type_name: None, children: a, .. },
+            VariableNode::Group { type_name: None, children: b, .. },
+        ) => {
+            a.len() == b.len()
+                && a.iter().zip(b).all(|(a, b)| node_name(a).eq_ignore_ascii_case(node_name(b)) && same_shape(a, b))
+        }
+        _ => false,
+    }
+}
+
+fn node_name(node: &VariableNode) -> &str {
+    match node {
+        VariableNode::Leaf(var) => &var.name,
+        VariableNode::Group { name, .. } => name,
+    }
+}
+
+fn rename_node(node: &mut VariableNode, rename: &dyn Fn(&str) -> Option<String>) {
+    let name = match node {
+        VariableNode::Leaf(var) => &mut var.name,
+        VariableNode::Group { name, .. } => name,
+    };
+    if let Some(new) = rename(name) {
+        *name = new;
+    }
+}
+
+fn reset_ids(node: &mut VariableNode) {
+    match node {
+        VariableNode::Leaf(var) => {
+            var.var_id = None;
+            var.addr_id = None;
+        }
+        VariableNode::Group { children, .. } => children.iter_mut().for_each(reset_ids),
+    }
+}
@@ -0,0 +1,6 @@
+pub mod expansion;
+pub mod patch_executor;
+pub(crate) mod references;
+pub mod template_extractor;
+pub mod template_renderer;
+pub mod template_service;
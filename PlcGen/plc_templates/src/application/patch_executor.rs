@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+
+use plc_core::ast::{BoxPin, ElementType, LdElement, UniversalPou, VariableNode};
+use plc_core::domain::topology::network_elements;
+
+use crate::domain::patch::{
+    CommentTarget, ElementLocator, NetworkLocator, PATCH_SCHEMA_VERSION, Patch, PatchOp, PatchVariable, VariableLocator,
+};
+use crate::domain::template_errors::{PatchError, TemplateError, TemplateResult};
+use crate::domain::template_spec::TemplateSet;
+
+use super::expansion::{clone_networks, expand_set};
+use super::references::{
+    elements_mut, instance_names, is_bound, is_declared, is_instance_group, rename_declarations, rename_element_refs,
+};
+
+/// Applies a rendered `Patch` to a template snapshot.
+///
+/// Modification ops run first in patch order, expansion ops (`clone_networks`,
+/// `repeat_template_set`) after them, so copies are made from the corrected template set.
+/// Every locator must match exactly one target; the first failing op aborts the whole patch
+/// and the input POU is left untouched.
+#[derive(Debug, Clone, Default)]
+pub struct TemplatePatchExecutor {
+    sets: Vec<TemplateSet>,
+}
+
+impl TemplatePatchExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Template sets that `repeat_template_set` may refer to (usually `TemplateSpec::template_sets`).
+    pub fn with_sets(mut self, sets: Vec<TemplateSet>) -> Self {
+        self.sets = sets;
+        self
+    }
+
+    /// Apply `patch` to a copy of `pou`. Errors carry the index of the failing op.
+    pub fn apply(&self, pou: &UniversalPou, patch: &Patch) -> TemplateResult<UniversalPou> {
+        if patch.schema_version != PATCH_SCHEMA_VERSION {
+            return Err(TemplateError::UnsupportedPatchSchema(patch.schema_version));
+        }
+        let mut out = pou.clone();
+        let modifications = patch.ops.iter().enumerate().filter(|(_, op)| !op.is_expansion());
+        let expansions = patch.ops.iter().enumerate().filter(|(_, op)| op.is_expansion());
+        for (index, op) in modifications.chain(expansions) {
+            self.apply_op(&mut out, op).map_err(|source| TemplateError::Patch { index, op: op.name(), source })?;
+        }
+        Ok(out)
+    }
+
+    fn apply_op(&self, pou: &mut UniversalPou, op: &PatchOp) -> Result<(), PatchError> {
+        if let PatchOp::RepeatTemplateSet { set, instances } = op {
+            let set = self.sets.iter().find(|s| s.id == *set).ok_or_else(|| PatchError::UnknownSet(set.clone()))?;
+            return expand_set(pou, set, instances);
+        }
+        apply_op(pou, op)
+    }
+}
+
+fn apply_op(pou: &mut UniversalPou, op: &PatchOp) -> Result<(), PatchError> {
+    match op {
+        PatchOp::RenamePou { name, expected } => {
+            check_name(name)?;
+            check_expected(expected.as_deref(), &pou.name)?;
+            pou.name = name.clone();
+        }
+        PatchOp::RenameVariable { target, name } => {
+            let path = locate_variable(pou, target)?;
+            let old = node_name(node_at(&mut pou.variables, &path)).to_string();
+            rename_everywhere(pou, &old, name)?;
+        }
+        PatchOp::RenameElement { target, pin, name } => {
+            let (network_id, element_id) = locate_element(pou, target)?;
+            let elem = element(pou, network_id, element_id);
+            let old = match (elem.type_code, pin) {
+                (ElementType::Box, None) if is_bound(&elem.instance) => elem.instance.clone(),
+                (ElementType::Box, None) => {
+                    return Err(PatchError::WrongElementType {
+                        target: target.to_string(), expected: "实例化功能块"
+                    });
+                }
+                (ElementType::Box, Some(pin)) => {
+                    let bound = &find_pin(elem, pin, target)?.variable;
+                    if !is_bound(bound) {
+                        return Err(PatchError::NoMatch(format!("{}.{} 未绑定", target, pin)));
+                    }
+                    bound.clone()
+                }
+                (ElementType::Contact | ElementType::Coil, None) => elem.name.clone(),
+                _ => {
+                    return Err(PatchError::WrongElementType {
+                        target: target.to_string(),
+                        expected: if pin.is_some() { "功能块" } else { "功能块、触点或线圈" },
+                    });
+                }
+            };
+            rename_everywhere(pou, &old, name)?;
+        }
+        PatchOp::BindPin { target, pin, variable, expected } => {
+            let (network_id, element_id) = locate_element(pou, target)?;
+            let elem = element(pou, network_id, element_id);
+            if elem.type_code != ElementType::Box {
+                return Err(PatchError::WrongElementType { target: target.to_string(), expected: "功能块" });
+            }
+            let found = find_pin(elem, pin, target)?;
+            check_expected(expected.as_deref(), &found.variable)?;
+            let pin_name = found.name.clone();
+            for_element_mut(pou, network_id, element_id, |elem| {
+                for p in elem.pins.iter_mut().filter(|p| p.name == pin_name) {
+                    p.variable = variable.clone();
+                }
+            });
+        }
+        PatchOp::SetComment { target, comment } => match target {
+            CommentTarget::Variable(loc) => {
+                let path = locate_variable(pou, loc)?;
+                match node_at(&mut pou.variables, &path) {
+                    VariableNode::Leaf(var) => var.comment = comment.clone(),
+                    VariableNode::Group { .. } => {
+                        return Err(PatchError::WrongElementType { target: loc.to_string(), expected: "叶子变量" });
+                    }
+                }
+            }
+            CommentTarget::Element(loc) => {
+                let (network_id, element_id) = locate_element(pou, loc)?;
+                for_element_mut(pou, network_id, element_id, |elem| elem.comment = comment.clone());
+            }
+            CommentTarget::Network(loc) => {
+                let index = locate_network(pou, loc)?;
+                pou.networks[index].comment = comment.clone();
+            }
+        },
+        PatchOp::CloneNetworks { networks, renames } => {
+            let mut ids = Vec::with_capacity(networks.len());
+            for loc in networks {
+                let id = pou.networks[locate_network(pou, loc)?].id;
+                if ids.contains(&id) {
+                    return Err(PatchError::AmbiguousMatch { target: loc.to_string(), count: 2 });
+                }
+                ids.push(id);
+            }
+            if renames.values().any(|to| to.trim().is_empty()) {
+                return Err(PatchError::EmptyName);
+            }
+            clone_networks(pou, &ids, renames);
+        }
+        PatchOp::AddVariable { group_path, variable } => add_variable(pou, group_path, variable)?,
+        PatchOp::RemoveVariable { target } => {
+            let path = locate_variable(pou, target)?;
+            let (last, parent) = path.split_last().expect("variable path is never empty");
+            children_at(&mut pou.variables, parent).remove(*last);
+        }
+        PatchOp::RepeatTemplateSet { .. } => unreachable!("handled by TemplatePatchExecutor::apply_op"),
+    }
+    Ok(())
+}
+
+fn check_name(name: &str) -> Result<(), PatchError> {
+    if name.trim().is_empty() { Err(PatchError::EmptyName) } else { Ok(()) }
+}
+
+fn check_expected(expected: Option<&str>, actual: &str) -> Result<(), PatchError> {
+    match expected {
+        Some(expected) if expected != actual => {
+            Err(PatchError::ExpectedMismatch { expected: expected.to_string(), actual: actual.to_string() })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rename `old` in its declarations and every element reference.
+fn rename_everywhere(pou: &mut UniversalPou, old: &str, new: &str) -> Result<(), PatchError> {
+    check_name(new)?;
+    let instances = instance_names(pou);
+    if !old.eq_ignore_ascii_case(new) && is_declared(&pou.variables, new, &instances) {
+        return Err(PatchError::DuplicateVariable(new.to_string()));
+    }
+    rename_declarations(&mut pou.variables, old, new, &instances);
+    let rename = |name: &str| name.eq_ignore_ascii_case(old).then(|| new.to_string());
+    for net in &mut pou.networks {
+        for elem in elements_mut(net) {
+            rename_element_refs(elem, &rename);
+        }
+    }
+    Ok(())
+}
+
+fn matches_name(filter: &Option<String>, value: &str) -> bool {
+    filter.as_ref().is_none_or(|f| f.eq_ignore_ascii_case(value))
+}
+
+/// (network id, element id) of the single element matching `loc`.
+fn locate_element(pou: &UniversalPou, loc: &ElementLocator) -> Result<(i32, i32), PatchError> {
+    let mut hits = Vec::new();
+    for net in &pou.networks {
+        if loc.network_id.is_some_and(|id| id != net.id) {
+            continue;
+        }
+        for elem in network_elements(net) {
+            if loc.element_id.is_none_or(|id| id == elem.id)
+                && loc.type_code.is_none_or(|ty| ty == elem.type_code)
+                && matches_name(&loc.name, &elem.name)
+                && matches_name(&loc.instance, &elem.instance)
+            {
+                hits.push((net.id, elem.id));
+            }
+        }
+    }
+    unique(hits, || loc.to_string())
+}
+
+fn locate_network(pou: &UniversalPou, loc: &NetworkLocator) -> Result<usize, PatchError> {
+    let hits = pou
+        .networks
+        .iter()
+        .enumerate()
+        .filter(|(_, net)| loc.id.is_none_or(|id| id == net.id) && loc.label.as_ref().is_none_or(|l| *l == net.label))
+        .map(|(index, _)| index)
+        .collect();
+    unique(hits, || loc.to_string())
+}
+
+/// Index path of the single variable (leaf or instance variable) matching `loc`.
+fn locate_variable(pou: &UniversalPou, loc: &VariableLocator) -> Result<Vec<usize>, PatchError> {
+    fn walk(
+        nodes: &[VariableNode],
+        loc: &VariableLocator,
+        instances: &HashSet<String>,
+        names: &mut Vec<String>,
+        index: &mut Vec<usize>,
+        hits: &mut Vec<Vec<usize>>,
+    ) {
+        for (i, node) in nodes.iter().enumerate() {
+            names.push(node_name(node).to_string());
+            index.push(i);
+            match node {
+                VariableNode::Group { children, .. } if !is_instance_group(node, instances) => {
+                    walk(children, loc, instances, names, index, hits)
+                }
+                _ => {
+                    let path_ok = loc.path.as_ref().is_none_or(|path| {
+                        path.len() == names.len()
+                            && path.iter().zip(names.iter()).all(|(a, b)| a.eq_ignore_ascii_case(b))
+                    });
+                    if path_ok && matches_name(&loc.name, node_name(node)) {
+                        hits.push(index.clone());
+                    }
+                }
+            }
+            names.pop();
+            index.pop();
+        }
+    }
+
+    let mut hits = Vec::new();
+    walk(&pou.variables, loc, &instance_names(pou), &mut Vec::new(), &mut Vec::new(), &mut hits);
+    unique(hits, || loc.to_string())
+}
+
+fn unique<T>(mut hits: Vec<T>, target: impl FnOnce() -> String) -> Result<T, PatchError> {
+    match hits.len() {
+        0 => Err(PatchError::NoMatch(target())),
+        1 => Ok(hits.remove(0)),
+        count => Err(PatchError::AmbiguousMatch { target: target(), count }),
+    }
+}
+
+fn element(pou: &mut UniversalPou, network_id: i32, element_id: i32) -> &LdElement {
+    let net = pou.networks.iter_mut().find(|net| net.id == network_id).expect("located network exists");
+    elements_mut(net).find(|elem| elem.id == element_id).expect("located element exists")
+}
+
+/// Apply `f` to every copy of the element (element list and inline topology tokens).
+fn for_element_mut(pou: &mut UniversalPou, network_id: i32, element_id: i32, f: impl Fn(&mut LdElement)) {
+    for net in pou.networks.iter_mut().filter(|net| net.id == network_id) {
+        elements_mut(net).filter(|elem| elem.id == element_id).for_each(&f);
+    }
+}
+
+fn find_pin<'a>(elem: &'a LdElement, pin: &str, target: &ElementLocator) -> Result<&'a BoxPin, PatchError> {
+    elem.pins
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(pin))
+        .ok_or_else(|| PatchError::UnknownPin { element: target.to_string(), pin: pin.to_string() })
+}
+
+fn node_name(node: &VariableNode) -> &str {
+    match node {
+        VariableNode::Leaf(var) => &var.name,
+        VariableNode::Group { name, .. } => name,
+    }
+}
+
+fn node_at<'a>(nodes: &'a mut Vec<VariableNode>, path: &[usize]) -> &'a mut VariableNode {
+    let (last, parent) = path.split_last().expect("variable path is never empty");
+    &mut children_at(nodes, parent)[*last]
+}
+
+fn children_at<'a>(mut nodes: &'a mut Vec<VariableNode>, path: &[usize]) -> &'a mut Vec<VariableNode> {
+    for &i in path {
+        nodes = match &mut nodes[i] {
+            VariableNode::Group { children, .. } => children,
+            VariableNode::Leaf(_) => unreachable!("located path only descends through groups"),
+        };
+    }
+    nodes
+}
+
+/// Append a leaf under `group_path`, creating missing (plain) groups.
+fn add_variable(pou: &mut UniversalPou, group_path: &[String], def: &PatchVariable) -> Result<(), PatchError> {
+    check_name(&def.name)?;
+    if is_declared(&pou.variables, &def.name, &instance_names(pou)) {
+        return Err(PatchError::DuplicateVariable(def.name.clone()));
+    }
+    let mut nodes = &mut pou.variables;
+    for segment in group_path {
+        let index = match nodes
+            .iter()
+            .position(|node| matches!(node, VariableNode::Group { name, type_name: None, .. } if name == segment))
+        {
+            Some(index) => index,
+            None => {
+                nodes.push(VariableNode::Group { name: segment.clone(), type_name: None, children: Vec::new() });
+                nodes.len() - 1
+            }
+        };
+        nodes = match &mut nodes[index] {
+            VariableNode::Group { children, .. } => children,
+            VariableNode::Leaf(_) => unreachable!("position matched a group"),
+        };
+    }
+    nodes.push(VariableNode::Leaf(def.into()));
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use plc_core::ast::{ElementType, LdElement, Network, SafetyTopologyToken, UniversalPou, VariableNode};
+use plc_core::domain::topology::network_elements;
+
+/// Unbound pin placeholder written by the Normal editor.
+pub(crate) const UNBOUND_PIN: &str = "???";
+
+pub(crate) fn is_bound(name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty() && name != UNBOUND_PIN
+}
+
+/// Every element of a network, including elements inlined in the Safety token stream.
+/// An element listed in both places is yielded twice so both copies stay in sync.
+pub(crate) fn elements_mut(net: &mut Network) -> impl Iterator<Item = &mut LdElement> {
+    let inline = net.safety_topology.iter_mut().filter_map(|token| match token {
+        SafetyTopologyToken::InlineElement(elem) | SafetyTopologyToken::Element(elem) => Some(&mut **elem),
+        _ => None,
+    });
+    net.elements.iter_mut().chain(inline)
+}
+
+/// Rewrite the variable references held by `elem`: contact/coil names, box instances and pin
+/// bindings. `rename` receives a referenced name and returns its replacement; pin bindings of
+/// the form `inst.member` are also matched on `inst`.
+pub(crate) fn rename_element_refs(elem: &mut LdElement, rename: &dyn Fn(&str) -> Option<String>) {
+    match elem.type_code {
+        ElementType::Contact | ElementType::Coil => {
+            if let Some(new) = rename(&elem.name) {
+                elem.name = new;
+            }
+        }
+        ElementType::Box => {
+            if let Some(new) = rename(&elem.instance) {
+                elem.instance = new;
+            }
+            for pin in &mut elem.pins {
+                if let Some(new) = rename_reference(&pin.variable, rename) {
+                    pin.variable = new;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn rename_reference(reference: &str, rename: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+    if let Some(new) = rename(reference) {
+        return Some(new);
+    }
+    let (head, member) = reference.split_once('.')?;
+    rename(head).map(|new| format!("{}.{}", new, member))
+}
+
+/// Upper-cased instance names of every box element in the POU.
+pub(crate) fn instance_names(pou: &UniversalPou) -> HashSet<String> {
+    pou.networks
+        .iter()
+        .flat_map(network_elements)
+        .filter(|elem| elem.type_code == ElementType::Box && is_bound(&elem.instance))
+        .map(|elem| elem.instance.to_ascii_uppercase())
+        .collect()
+}
+
+/// Whether `node` is the member group of a function block instance rather than a plain folder.
+/// Decoded POUs leave `type_name` empty on instance groups, so a group named after a box
+/// instance (see `instance_names`) counts as one too.
+pub(crate) fn is_instance_group(node: &VariableNode, instances: &HashSet<String>) -> bool {
+    match node {
+        VariableNode::Leaf(_) => false,
+        VariableNode::Group { type_name: Some(_), .. } => true,
+        VariableNode::Group { name, type_name: None, .. } => instances.contains(&name.to_ascii_uppercase()),
+    }
+}
+
+/// Rename the declarations of `old`: leaf variables and function block instance variables.
+/// Members of instance groups are not declarations of their own and are left untouched.
+pub(crate) fn rename_declarations(nodes: &mut [VariableNode], old: &str, new: &str, instances: &HashSet<String>) {
+    for node in nodes {
+        let instance = is_instance_group(node, instances);
+        match node {
+            VariableNode::Leaf(var) if var.name.eq_ignore_ascii_case(old) => var.name = new.to_string(),
+            VariableNode::Group { name, .. } if instance && name.eq_ignore_ascii_case(old) => *name = new.to_string(),
+            VariableNode::Group { children, .. } if !instance => rename_declarations(children, old, new, instances),
+            _ => {}
+        }
+    }
+}
+
+/// Whether `name` is declared (leaf or instance variable) anywhere in the variable tree.
+pub(crate) fn is_declared(nodes: &[VariableNode], name: &str, instances: &HashSet<String>) -> bool {
+    nodes.iter().any(|node| match node {
+        VariableNode::Leaf(var) => var.name.eq_ignore_ascii_case(name),
+        VariableNode::Group { name: group, .. } if is_instance_group(node, instances) => {
+            group.eq_ignore_ascii_case(name)
+        }
+        VariableNode::Group { children, .. } => is_declared(children, name, instances),
+    })
+}
+
+/// Largest network/element id in the POU (they share one id space).
+pub(crate) fn max_id(pou: &UniversalPou) -> i32 {
+    pou.networks
+        .iter()
+        .flat_map(|net| {
+            let inline = net.safety_topology.iter().filter_map(|token| match token {
+                SafetyTopologyToken::InlineElement(elem) | SafetyTopologyToken::Element(elem) => Some(elem.id),
+                _ => None,
+            });
+            std::iter::once(net.id).chain(net.elements.iter().map(|e| e.id)).chain(inline)
+        })
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+
+use plc_core::ast::{ElementType, LdElement, Network, UniversalPou, VariableNode};
+use regex::Regex;
+
+use crate::domain::template_spec::{FieldAnchor, FieldKind, TemplateField, TemplateSpec};
+
+use super::references::is_bound;
+
+/// Default device tag pattern: 1-4 capital letters, an optional `-`/`_`, 2-5 digits and an
+/// optional suffix letter (`XV1001`, `PT_101`, `FV-2001A`). The tag must not be glued to a
+/// preceding letter or digit, so `CONVERT_10` does not yield `VERT_10`.
+pub const DEFAULT_TAG_PATTERN: &str = r"(?:^|[^A-Za-z0-9])([A-Z]{1,4}[-_]?[0-9]{2,5}[A-Z]?)(?:[^0-9]|$)";
+
+/// Proposes a draft `TemplateSpec` for a decoded POU.
+///
+/// Candidates, in network order and then variable-tree order:
+/// - the POU name;
+/// - box instance names (prefix: device tag, else the stem shared with other instances);
+/// - contact and coil variable names;
+/// - box pin bindings that contain a device tag;
+/// - declared variables not referenced above whose name contains a device tag;
+/// - variable comments that contain a device tag.
+///
+/// A name yields one field even if it is referenced several times; the patch executor
+/// renames every occurrence by `original`. The draft is meant to be edited by the user.
+#[derive(Debug, Clone)]
+pub struct TemplateExtractor {
+    tag_pattern: Regex,
+}
+
+impl Default for TemplateExtractor {
+    fn default() -> Self {
+        Self { tag_pattern: Regex::new(DEFAULT_TAG_PATTERN).expect("default tag pattern is valid") }
+    }
+}
+
+impl TemplateExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the device tag pattern. The tag is capture group 1 if present, else the whole match.
+    pub fn with_tag_pattern(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self { tag_pattern: Regex::new(pattern)? })
+    }
+
+    /// First device tag found in `text`.
+    pub fn find_tag(&self, text: &str) -> Option<String> {
+        let caps = self.tag_pattern.captures(text)?;
+        caps.get(1).or_else(|| caps.get(0)).map(|m| m.as_str().to_string())
+    }
+
+    /// Build a draft spec for `pou`.
+    pub fn extract(&self, pou: &UniversalPou) -> TemplateSpec {
+        let mut draft = Draft::default();
+        draft.push(TemplateField {
+            key: "pou_name".to_string(),
+            label: "POU 名称".to_string(),
+            kind: FieldKind::PouName,
+            anchor: FieldAnchor::Pou,
+            original: pou.name.clone(),
+            prefix: self.find_tag(&pou.name),
+            required: true,
+        });
+
+        let stems = shared_stems(&pou.networks);
+        for network in &pou.networks {
+            for element in &network.elements {
+                self.extract_element(network, element, &stems, &mut draft);
+            }
+        }
+
+        let mut path = Vec::new();
+        self.extract_variables(&pou.variables, &mut path, &mut draft);
+
+        TemplateSpec { fields: draft.fields, ..TemplateSpec::default() }
+    }
+
+    fn extract_element(&self, network: &Network, element: &LdElement, stems: &HashSet<String>, draft: &mut Draft) {
+        let anchor = |pin: Option<&str>| FieldAnchor::Element {
+            network_id: network.id,
+            element_id: element.id,
+            pin: pin.map(str::to_string),
+        };
+        match element.type_code {
+            ElementType::Box => {
+                if is_bound(&element.instance) {
+                    let prefix = self
+                        .find_tag(&element.instance)
+                        .or_else(|| stem(&element.instance).filter(|s| stems.contains(s)));
+                    draft.propose("instance", FieldKind::InstanceName, anchor(None), &element.instance, prefix, || {
+                        format!("{} 实例名", element.name)
+                    });
+                }
+                for pin in &element.pins {
+                    if !is_bound(&pin.variable) {
+                        continue;
+                    }
+                    if let Some(tag) = self.find_tag(&pin.variable) {
+                        draft.propose(
+                            "pin",
+                            FieldKind::PinBinding,
+                            anchor(Some(&pin.name)),
+                            &pin.variable,
+                            Some(tag),
+                            || format!("{}.{} 绑定", element.name, pin.name),
+                        );
+                    }
+                }
+            }
+            ElementType::Contact | ElementType::Coil if is_bound(&element.name) => {
+                let (kind, key, label) = if element.type_code == ElementType::Contact {
+                    (FieldKind::ContactName, "contact", "触点")
+                } else {
+                    (FieldKind::CoilName, "coil", "线圈")
+                };
+                draft.propose(key, kind, anchor(None), &element.name, self.find_tag(&element.name), || {
+                    format!("{} {}", label, element.name)
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn extract_variables(&self, nodes: &[VariableNode], path: &mut Vec<String>, draft: &mut Draft) {
+        for node in nodes {
+            match node {
+                VariableNode::Leaf(var) => {
+                    path.push(var.name.clone());
+                    if let Some(tag) = self.find_tag(&var.name) {
+                        let anchor = FieldAnchor::Variable { path: path.clone() };
+                        draft.propose("var", FieldKind::VariableName, anchor, &var.name, Some(tag), || {
+                            format!("变量 {}", var.name)
+                        });
+                    }
+                    if let Some(tag) = self.find_tag(&var.comment) {
+                        let key = draft.unique_key("comment", &var.name);
+                        draft.push(TemplateField {
+                            key,
+                            label: format!("{} 注释", var.name),
+                            kind: FieldKind::VariableComment,
+                            anchor: FieldAnchor::Variable { path: path.clone() },
+                            original: var.comment.clone(),
+                            prefix: Some(tag),
+                            required: false,
+                        });
+                    }
+                    path.pop();
+                }
+                // 功能块实例的成员随实例名一起改名，不单独抽取
+                VariableNode::Group { type_name: Some(_), .. } => {}
+                VariableNode::Group { name, children, .. } => {
+                    path.push(name.clone());
+                    self.extract_variables(children, path, draft);
+                    path.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Fields collected so far, with the keys and names already taken.
+#[derive(Default)]
+struct Draft {
+    fields: Vec<TemplateField>,
+    keys: HashSet<String>,
+    names: HashSet<String>,
+}
+
+impl Draft {
+    /// Propose a name field unless the same name (case-insensitive) is already a field.
+    fn propose(
+        &mut self,
+        key_prefix: &str,
+        kind: FieldKind,
+        anchor: FieldAnchor,
+        name: &str,
+        prefix: Option<String>,
+        label: impl FnOnce() -> String,
+    ) {
+        if !self.names.insert(name.to_ascii_uppercase()) {
+            return;
+        }
+        let key = self.unique_key(key_prefix, name);
+        self.push(TemplateField {
+            key,
+            label: label(),
+            kind,
+            anchor,
+            original: name.to_string(),
+            prefix,
+            required: true,
+        });
+    }
+
+    fn push(&mut self, field: TemplateField) {
+        self.keys.insert(field.key.clone());
+        self.fields.push(field);
+    }
+
+    /// `{prefix}_{identifier}`: ASCII lowercase so the key can be referenced from Tera as `cfg.<key>`.
+    fn unique_key(&mut self, prefix: &str, name: &str) -> String {
+        let mut ident = String::new();
+        for ch in name.chars() {
+            if ch.is_ascii_alphanumeric() {
+                ident.push(ch.to_ascii_lowercase());
+            } else if !ident.is_empty() && !ident.ends_with('_') {
+                ident.push('_');
+            }
+        }
+        let ident = ident.trim_end_matches('_');
+        let base = if ident.is_empty() {
+            format!("{}_{}", prefix, self.fields.len())
+        } else {
+            format!("{}_{}", prefix, ident)
+        };
+        let mut key = base.clone();
+        let mut n = 2;
+        while !self.keys.insert(key.clone()) {
+            key = format!("{}_{}", base, n);
+            n += 1;
+        }
+        key
+    }
+}
+
+/// Name up to and including the first separator: `XV1001_TON` -> `XV1001_`.
+fn stem(name: &str) -> Option<String> {
+    let idx = name.find(['_', '-'])?;
+    (idx > 0).then(|| name[..=idx].to_string())
+}
+
+/// Stems shared by at least two distinct instance names.
+fn shared_stems(networks: &[Network]) -> HashSet<String> {
+    let mut instances: HashMap<String, HashSet<&str>> = HashMap::new();
+    for element in networks.iter().flat_map(|network| &network.elements) {
+        if element.type_code == ElementType::Box
+            && is_bound(&element.instance)
+            && let Some(stem) = stem(&element.instance)
+        {
+            instances.entry(stem).or_default().insert(element.instance.as_str());
+        }
+    }
+    instances.into_iter().filter(|(_, names)| names.len() > 1).map(|(stem, _)| stem).collect()
+}
@@ -0,0 +1,143 @@
+use std::error::Error as _;
+
+use serde::Serialize;
+use serde_json::Value;
+use tera::{Context, Tera};
+
+use crate::domain::patch::{CommentTarget, ElementLocator, PATCH_SCHEMA_VERSION, Patch, VariableLocator};
+use crate::domain::template_bundle::TemplateBundle;
+use crate::domain::template_errors::{TemplateError, TemplateResult};
+use crate::domain::template_spec::{FieldAnchor, FieldKind, TemplateField, TemplateSpec};
+
+/// Renders template scripts (Tera) into a `Patch`.
+///
+/// Script context:
+/// - `cfg`: the user configuration (`template.config.json`), keyed by field key;
+/// - `fields`: the spec fields keyed by field key (`fields.<key>.original` etc.).
+///
+/// Autoescaping is off; scripts should pass values through `json_encode()` when emitting JSON.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRenderer;
+
+impl TemplateRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Render `script` and parse the output as a `Patch`.
+    /// Fails if `config` lacks a required field of `spec`.
+    pub fn render(&self, script: &str, spec: &TemplateSpec, config: &Value) -> TemplateResult<Patch> {
+        for field in spec.fields.iter().filter(|field| field.required) {
+            if config.get(&field.key).is_none_or(Value::is_null) {
+                return Err(TemplateError::MissingConfig(field.key.clone()));
+            }
+        }
+
+        let mut context = Context::new();
+        context.insert("cfg", config);
+        let fields: serde_json::Map<String, Value> = spec
+            .fields
+            .iter()
+            .map(|field| (field.key.clone(), serde_json::to_value(field).expect("field serializes to JSON")))
+            .collect();
+        context.insert("fields", &fields);
+
+        let text = Tera::one_off(script, &context, false).map_err(|err| TemplateError::Render(error_chain(&err)))?;
+        serde_json::from_str(&text).map_err(TemplateError::PatchJson)
+    }
+
+    /// Render the bundle's own script, or the default script derived from its spec.
+    pub fn render_bundle(&self, bundle: &TemplateBundle, config: &Value) -> TemplateResult<Patch> {
+        match &bundle.script {
+            Some(script) => self.render(script, &bundle.spec, config),
+            None => self.render(&Self::default_script(&bundle.spec), &bundle.spec, config),
+        }
+    }
+
+    /// Script that emits one op per spec field present in `cfg`:
+    /// comment fields become `set_comment`, the POU name `rename_pou`, every other field a
+    /// rename of the anchored variable/element. Comment ops come first because renames change
+    /// the variable paths they are anchored by. Each template set is expanded from
+    /// `cfg.sets["<set id>"]`, a list of `{ "index": n, "vars": { original: new } }`.
+    /// Saved as `template.tera` it is the starting point for hand-written rules.
+    pub fn default_script(spec: &TemplateSpec) -> String {
+        let mut script = format!(
+            "{{% set_global sep = \"\" %}}\n{{\n  \"schema_version\": {},\n  \"ops\": [\n",
+            PATCH_SCHEMA_VERSION
+        );
+        let (comments, renames): (Vec<_>, Vec<_>) =
+            spec.fields.iter().partition(|field| field.kind == FieldKind::VariableComment);
+        for field in comments.into_iter().chain(renames) {
+            script.push_str(&format!(
+                "{{% if cfg.{key} is defined %}}    {{{{ sep }}}}{op}\n{{% set_global sep = \",\" %}}{{% endif %}}\n",
+                key = field.key,
+                op = field_op(field),
+            ));
+        }
+        for set in &spec.template_sets {
+            let id = to_json(&set.id);
+            script.push_str(&format!(
+                "{{% if cfg.sets and cfg.sets[{id}] %}}    {{{{ sep }}}}{{\"op\": \"repeat_template_set\", \"set\": {id}, \"instances\": {{{{ cfg.sets[{id}] | json_encode() }}}}}}\n{{% set_global sep = \",\" %}}{{% endif %}}\n",
+            ));
+        }
+        script.push_str("  ]\n}\n");
+        script
+    }
+}
+
+/// Op template for one field; the new value is `cfg.<key>`.
+fn field_op(field: &TemplateField) -> String {
+    let value = format!("{{{{ cfg.{} | json_encode() }}}}", field.key);
+    match (&field.anchor, field.kind) {
+        (FieldAnchor::Pou, _) => {
+            format!(r#"{{"op": "rename_pou", "name": {}, "expected": {}}}"#, value, to_json(&field.original))
+        }
+        (FieldAnchor::Variable { path }, FieldKind::VariableComment) => {
+            let target = CommentTarget::Variable(VariableLocator { path: Some(path.clone()), name: None });
+            format!(r#"{{"op": "set_comment", "target": {}, "comment": {}}}"#, to_json(&target), value)
+        }
+        (FieldAnchor::Variable { path }, _) => {
+            let target = VariableLocator { path: Some(path.clone()), name: None };
+            format!(r#"{{"op": "rename_variable", "target": {}, "name": {}}}"#, to_json(&target), value)
+        }
+        (FieldAnchor::Element { network_id, element_id, pin }, kind) => {
+            let mut target =
+                ElementLocator { network_id: Some(*network_id), element_id: Some(*element_id), ..Default::default() };
+            // The snapshot value takes part in the locator so template drift fails loudly.
+            match kind {
+                FieldKind::VariableComment => {
+                    let target = CommentTarget::Element(target);
+                    return format!(r#"{{"op": "set_comment", "target": {}, "comment": {}}}"#, to_json(&target), value);
+                }
+                FieldKind::InstanceName => target.instance = Some(field.original.clone()),
+                FieldKind::ContactName | FieldKind::CoilName => target.name = Some(field.original.clone()),
+                _ => {}
+            }
+            match pin {
+                Some(pin) => format!(
+                    r#"{{"op": "rename_element", "target": {}, "pin": {}, "name": {}}}"#,
+                    to_json(&target),
+                    to_json(pin),
+                    value
+                ),
+                None => format!(r#"{{"op": "rename_element", "target": {}, "name": {}}}"#, to_json(&target), value),
+            }
+        }
+    }
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).expect("patch values serialize to JSON")
+}
+
+/// Tera reports the useful detail (line, missing variable) in the source chain.
+fn error_chain(err: &tera::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(inner) = source {
+        message.push_str(": ");
+        message.push_str(&inner.to_string());
+        source = inner.source();
+    }
+    message
+}
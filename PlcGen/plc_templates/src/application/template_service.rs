@@ -0,0 +1,40 @@
+use plc_core::PlcVariant;
+use plc_core::ast::UniversalPou;
+use serde_json::Value;
+
+use crate::domain::patch::Patch;
+use crate::domain::template_bundle::TemplateBundle;
+use crate::domain::template_errors::TemplateResult;
+
+use super::expansion::restore_safety_topology;
+use super::patch_executor::TemplatePatchExecutor;
+use super::template_renderer::TemplateRenderer;
+
+/// Template instantiation use case: render the script, apply the patch (including multi-instance
+/// expansion), then restore what JSON snapshots lose so the result can go straight to the codec.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateService {
+    renderer: TemplateRenderer,
+}
+
+impl TemplateService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render `bundle` with `config` and build the resulting POU.
+    pub fn instantiate(&self, bundle: &TemplateBundle, config: &Value) -> TemplateResult<UniversalPou> {
+        let patch = self.renderer.render_bundle(bundle, config)?;
+        self.apply(bundle, &patch)
+    }
+
+    /// Apply an already rendered patch to the bundle snapshot.
+    pub fn apply(&self, bundle: &TemplateBundle, patch: &Patch) -> TemplateResult<UniversalPou> {
+        let executor = TemplatePatchExecutor::new().with_sets(bundle.spec.template_sets.clone());
+        let mut pou = executor.apply(&bundle.pou, patch)?;
+        if bundle.meta.variant == PlcVariant::Safety {
+            restore_safety_topology(&mut pou)?;
+        }
+        Ok(pou)
+    }
+}
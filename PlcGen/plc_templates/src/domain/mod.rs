@@ -0,0 +1,4 @@
+pub mod patch;
+pub mod template_bundle;
+pub mod template_errors;
+pub mod template_spec;
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use plc_core::ast::{ElementType, Variable};
+use serde::{Deserialize, Serialize};
+
+/// 当前 Patch 结构版本
+pub const PATCH_SCHEMA_VERSION: u32 = 1;
+
+/// 模板脚本渲染产物：作用在 POU 快照上的操作序列
+/// 执行顺序不完全等于 ops 顺序：修改类操作先按原顺序执行，扩展类操作（clone_networks /
+/// repeat_template_set）随后按原顺序执行，因此写在扩展之后的修改只作用于原集合，不作用于副本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Patch {
+    #[serde(default = "current_schema")]
+    pub schema_version: u32,
+    #[serde(default)]
+    pub ops: Vec<PatchOp>,
+}
+
+fn current_schema() -> u32 {
+    PATCH_SCHEMA_VERSION
+}
+
+impl Default for Patch {
+    fn default() -> Self {
+        Self { schema_version: PATCH_SCHEMA_VERSION, ops: Vec::new() }
+    }
+}
+
+/// Patch 操作
+/// 改名类操作按名称全局替换（声明 + 触点/线圈/实例/引脚引用，含 `实例.成员` 形式）；
+/// bind_pin 只改单个引脚
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PatchOp {
+    RenamePou {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected: Option<String>,
+    },
+    /// 重命名已声明的变量
+    RenameVariable {
+        target: VariableLocator,
+        name: String,
+    },
+    /// 重命名元件引用的名称：Box 实例名（指定 pin 时为该引脚绑定）、触点/线圈变量名
+    RenameElement {
+        target: ElementLocator,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pin: Option<String>,
+        name: String,
+    },
+    BindPin {
+        target: ElementLocator,
+        pin: String,
+        variable: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected: Option<String>,
+    },
+    SetComment {
+        target: CommentTarget,
+        comment: String,
+    },
+    /// 克隆网络集合并追加在集合最后一个网络之后；renames 按原名精确替换克隆体中的引用
+    CloneNetworks {
+        networks: Vec<NetworkLocator>,
+        #[serde(default)]
+        renames: BTreeMap<String, String>,
+    },
+    AddVariable {
+        /// 所在分组路径，缺失的分组自动创建；为空时加在根层级
+        #[serde(default)]
+        group_path: Vec<String>,
+        variable: PatchVariable,
+    },
+    RemoveVariable {
+        target: VariableLocator,
+    },
+    /// 按 TemplateSpec 中的集合做多实例扩展：第 1 个实例在原集合上改名，其余实例依次追加克隆
+    RepeatTemplateSet {
+        set: String,
+        instances: Vec<SetInstance>,
+    },
+}
+
+/// 多实例扩展中的一个实例
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetInstance {
+    /// 实例序号（仅用于日志与 UI 显示）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
+    /// 原名 → 本实例名称（不区分大小写）；未列出的名称保持不变
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+impl PatchOp {
+    /// 操作名（与 JSON 中的 op 一致），用于错误信息
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchOp::RenamePou { .. } => "rename_pou",
+            PatchOp::RenameVariable { .. } => "rename_variable",
+            PatchOp::RenameElement { .. } => "rename_element",
+            PatchOp::BindPin { .. } => "bind_pin",
+            PatchOp::SetComment { .. } => "set_comment",
+            PatchOp::CloneNetworks { .. } => "clone_networks",
+            PatchOp::AddVariable { .. } => "add_variable",
+            PatchOp::RemoveVariable { .. } => "remove_variable",
+            PatchOp::RepeatTemplateSet { .. } => "repeat_template_set",
+        }
+    }
+
+    /// 扩展类操作在全部修改类操作之后执行（先修正原模板集合，再复制）
+    pub fn is_expansion(&self) -> bool {
+        matches!(self, PatchOp::CloneNetworks { .. } | PatchOp::RepeatTemplateSet { .. })
+    }
+}
+
+/// 元件定位：给出的字段全部匹配（名称不区分大小写），且必须唯一
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementLocator {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_code: Option<ElementType>,
+    /// Box 为指令名，触点/线圈为变量名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+/// 变量定位：path 为完整变量树路径，name 在任意层级按名称匹配
+/// 可命中叶子变量与功能块实例变量，普通分组不参与匹配
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariableLocator {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkLocator {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// 注释目标
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentTarget {
+    Variable(VariableLocator),
+    Element(ElementLocator),
+    Network(NetworkLocator),
+}
+
+/// add_variable 的变量定义；ID 类字段由序列化器分配
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchVariable {
+    pub name: String,
+    pub data_type: String,
+    #[serde(default)]
+    pub init_value: String,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub soe_enable: bool,
+    #[serde(default)]
+    pub power_down_keep: bool,
+}
+
+impl From<&PatchVariable> for Variable {
+    fn from(def: &PatchVariable) -> Self {
+        Variable {
+            init_value: def.init_value.clone(),
+            soe_enable: def.soe_enable,
+            power_down_keep: def.power_down_keep,
+            comment: def.comment.clone(),
+            ..Variable::new(&def.name, &def.data_type)
+        }
+    }
+}
+
+impl fmt::Display for ElementLocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(id) = self.network_id {
+            parts.push(format!("network={}", id));
+        }
+        if let Some(id) = self.element_id {
+            parts.push(format!("element={}", id));
+        }
+        if let Some(ty) = self.type_code {
+            parts.push(format!("type={:?}", ty));
+        }
+        if let Some(name) = &self.name {
+            parts.push(format!("name={}", name));
+        }
+        if let Some(instance) = &self.instance {
+            parts.push(format!("instance={}", instance));
+        }
+        write!(f, "元件[{}]", parts.join(" "))
+    }
+}
+
+impl fmt::Display for VariableLocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(path) = &self.path {
+            parts.push(format!("path={}", path.join("/")));
+        }
+        if let Some(name) = &self.name {
+            parts.push(format!("name={}", name));
+        }
+        write!(f, "变量[{}]", parts.join(" "))
+    }
+}
+
+impl fmt::Display for NetworkLocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(id) = self.id {
+            parts.push(format!("id={}", id));
+        }
+        if let Some(label) = &self.label {
+            parts.push(format!("label={}", label));
+        }
+        write!(f, "网络[{}]", parts.join(" "))
+    }
+}
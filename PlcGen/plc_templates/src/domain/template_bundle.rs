@@ -0,0 +1,132 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use plc_core::PlcVariant;
+use plc_core::ast::UniversalPou;
+use serde::{Deserialize, Serialize};
+
+use super::template_errors::{TemplateError, TemplateResult};
+use super::template_spec::TemplateSpec;
+
+/// 模板定位：品牌 / 系列 / 型号 / 分类路径 / 模板名
+/// 同名模板可存在于不同分类路径下，精确定位必须携带完整路径
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TemplateKey {
+    pub brand: String,
+    pub series: String,
+    pub model: String,
+    #[serde(default)]
+    pub category_path: Vec<String>,
+    pub name: String,
+}
+
+impl TemplateKey {
+    pub fn new(
+        brand: impl Into<String>,
+        series: impl Into<String>,
+        model: impl Into<String>,
+        category_path: impl IntoIterator<Item = impl Into<String>>,
+        name: impl Into<String>,
+    ) -> Self {
+        Self {
+            brand: brand.into(),
+            series: series.into(),
+            model: model.into(),
+            category_path: category_path.into_iter().map(Into::into).collect(),
+            name: name.into(),
+        }
+    }
+
+    /// 按目录层级排列的全部路径段
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        [self.brand.as_str(), self.series.as_str(), self.model.as_str()]
+            .into_iter()
+            .chain(self.category_path.iter().map(String::as_str))
+            .chain(std::iter::once(self.name.as_str()))
+    }
+
+    /// 相对模板仓库根目录的路径；路径段不得为空、不得含分隔符或以 '.' 开头（保留给版本目录）
+    pub fn relative_path(&self) -> TemplateResult<PathBuf> {
+        let mut path = PathBuf::new();
+        for segment in self.segments() {
+            let trimmed = segment.trim();
+            if trimmed.is_empty()
+                || trimmed != segment
+                || segment.starts_with('.')
+                || segment.contains(['/', '\\', ':'])
+            {
+                return Err(TemplateError::InvalidKey(self.to_string()));
+            }
+            path.push(segment);
+        }
+        Ok(path)
+    }
+}
+
+impl fmt::Display for TemplateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.segments().collect::<Vec<_>>().join("/"))
+    }
+}
+
+/// 模板元数据（template.meta.json）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateMeta {
+    pub key: TemplateKey,
+    /// 版本号：创建时为 1，每次保存新版本递增
+    #[serde(default = "first_version")]
+    pub version: u32,
+    /// 快照的编码参数，生成产物时用于一致性校验
+    pub variant: PlcVariant,
+    pub serialize_version: u32,
+    pub format_name: String,
+    /// Unix 时间戳（秒）
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub updated_at: u64,
+    #[serde(default)]
+    pub remark: String,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+impl TemplateMeta {
+    pub fn new(key: TemplateKey, variant: PlcVariant, serialize_version: u32, format_name: impl Into<String>) -> Self {
+        Self {
+            key,
+            version: first_version(),
+            variant,
+            serialize_version,
+            format_name: format_name.into(),
+            created_at: 0,
+            updated_at: 0,
+            remark: String::new(),
+        }
+    }
+}
+
+/// 模板包：同一模板所需的全部文件
+#[derive(Debug, Clone)]
+pub struct TemplateBundle {
+    pub meta: TemplateMeta,
+    pub spec: TemplateSpec,
+    /// 解码后的完整 POU 快照（pou.json）
+    pub pou: UniversalPou,
+    /// 原始剪贴板二进制（pou.bin），可选
+    pub raw: Option<Vec<u8>>,
+    /// Tera 规则脚本（template.tera），可选
+    pub script: Option<String>,
+}
+
+impl TemplateBundle {
+    pub fn new(meta: TemplateMeta, spec: TemplateSpec, pou: UniversalPou) -> Self {
+        Self { meta, spec, pou, raw: None, script: None }
+    }
+
+    pub fn key(&self) -> &TemplateKey {
+        &self.meta.key
+    }
+}
@@ -0,0 +1,85 @@
+use std::io;
+use std::path::PathBuf;
+
+use plc_core::domain::topology::TopologyError;
+use thiserror::Error;
+
+/// 模板管理错误
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("模板包读写失败: {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("模板包文件格式错误: {path}: {source}")]
+    Json {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("模板定位无效: {0}")]
+    InvalidKey(String),
+    #[error("模板不存在: {0}")]
+    NotFound(String),
+    #[error("模板已存在: {0}")]
+    AlreadyExists(String),
+    #[error("模板 {key} 没有版本 {version}")]
+    VersionNotFound { key: String, version: u32 },
+    #[error("配置缺少必填字段: {0}")]
+    MissingConfig(String),
+    #[error("模板脚本渲染失败: {0}")]
+    Render(String),
+    #[error("Patch JSON 格式错误: {0}")]
+    PatchJson(#[source] serde_json::Error),
+    #[error("不支持的 Patch 结构版本: {0}")]
+    UnsupportedPatchSchema(u32),
+    #[error("Safety 拓扑还原失败: 网络 {network}: {source}")]
+    Topology {
+        network: i32,
+        #[source]
+        source: TopologyError,
+    },
+    #[error("Patch 第 {index} 步 ({op}) 执行失败: {source}")]
+    Patch {
+        index: usize,
+        op: &'static str,
+        #[source]
+        source: PatchError,
+    },
+}
+
+/// 单个 Patch 操作的失败原因
+/// 定位器必须恰好命中一处，未命中或命中多处都视为模板漂移，立即报错
+#[derive(Debug, Error)]
+pub enum PatchError {
+    #[error("定位未命中: {0}")]
+    NoMatch(String),
+    #[error("定位命中 {count} 处（必须唯一）: {target}")]
+    AmbiguousMatch { target: String, count: usize },
+    #[error("原值不符: 期望 {expected}，实际 {actual}")]
+    ExpectedMismatch { expected: String, actual: String },
+    #[error("{target} 不是{expected}")]
+    WrongElementType { target: String, expected: &'static str },
+    #[error("{element} 没有引脚 {pin}")]
+    UnknownPin { element: String, pin: String },
+    #[error("变量 {0} 已存在")]
+    DuplicateVariable(String),
+    #[error("名称不能为空")]
+    EmptyName,
+    #[error("模板集合不存在: {0}")]
+    UnknownSet(String),
+}
+
+impl TemplateError {
+    pub(crate) fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Self::Io { path: path.into(), source }
+    }
+
+    pub(crate) fn json(path: impl Into<PathBuf>, source: serde_json::Error) -> Self {
+        Self::Json { path: path.into(), source }
+    }
+}
+
+pub type TemplateResult<T> = Result<T, TemplateError>;
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+/// 当前 TemplateSpec 结构版本
+pub const SPEC_SCHEMA_VERSION: u32 = 1;
+
+/// 模板可变字段描述（template.spec.json）
+/// UI 据此生成配置表单，Tera 脚本以 key 引用字段，Patch 执行器按 anchor 定位
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateSpec {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub fields: Vec<TemplateField>,
+    /// 可整体重复的网络 + 变量集合（多实例扩展）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub template_sets: Vec<TemplateSet>,
+}
+
+impl Default for TemplateSpec {
+    fn default() -> Self {
+        Self { schema_version: SPEC_SCHEMA_VERSION, fields: Vec::new(), template_sets: Vec::new() }
+    }
+}
+
+impl TemplateSpec {
+    pub fn field(&self, key: &str) -> Option<&TemplateField> {
+        self.fields.iter().find(|field| field.key == key)
+    }
+
+    pub fn fields_of(&self, kind: FieldKind) -> impl Iterator<Item = &TemplateField> {
+        self.fields.iter().filter(move |field| field.kind == kind)
+    }
+
+    pub fn template_set(&self, id: &str) -> Option<&TemplateSet> {
+        self.template_sets.iter().find(|set| set.id == id)
+    }
+}
+
+/// 模板集合：每个实例复制一套网络与局部变量
+/// 未列入 variables 的变量（全局点、公共中间量）由各套共享，不复制
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateSet {
+    pub id: String,
+    /// 集合内的网络 id；复制时按 POU 中的顺序
+    pub networks: Vec<i32>,
+    /// 随集合复制的变量（叶子变量或功能块实例变量的变量树路径）
+    #[serde(default)]
+    pub variables: Vec<Vec<String>>,
+}
+
+/// 单个可变字段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateField {
+    /// 字段标识（模板内唯一），供配置与 Tera 脚本引用
+    pub key: String,
+    /// UI 显示名
+    #[serde(default)]
+    pub label: String,
+    pub kind: FieldKind,
+    pub anchor: FieldAnchor,
+    /// 快照中的原值；应用 Patch 时用于防止模板漂移
+    #[serde(default)]
+    pub original: String,
+    /// 原值中可整体替换的公共前缀（设备位号或同组实例共享的前缀），UI 可据此批量改名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// 可变字段类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    PouName,
+    /// 变量名（含功能块实例变量）
+    VariableName,
+    VariableComment,
+    /// Box 元件的实例名
+    InstanceName,
+    ContactName,
+    CoilName,
+    /// 功能块引脚绑定的变量名
+    PinBinding,
+}
+
+/// 字段在 POU 快照中的位置
+/// - 变量：变量树路径（Group 链 + Leaf 名）
+/// - 元件：network.id + element.id，引脚再加引脚名
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FieldAnchor {
+    Pou,
+    Variable {
+        path: Vec<String>,
+    },
+    Element {
+        network_id: i32,
+        element_id: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pin: Option<String>,
+    },
+}
@@ -1,14 +1,24 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! Template management for PLC POUs.
+//! Responsibilities: template packages (POU snapshot + variable field spec + metadata), their storage,
+//! drafting the field spec from a decoded POU, rendering configured templates into POU patches,
+//! and expanding template sets into multi-instance POUs.
+//! Non-goals: POU encoding/decoding (plc_core) and delivery (plc_logic_gen).
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod adapters;
+pub mod application;
+pub mod domain;
+pub mod ports;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use adapters::fs_storage::FsTemplateStorage;
+pub use application::patch_executor::TemplatePatchExecutor;
+pub use application::template_extractor::{DEFAULT_TAG_PATTERN, TemplateExtractor};
+pub use application::template_renderer::TemplateRenderer;
+pub use application::template_service::TemplateService;
+pub use domain::patch::{
+    CommentTarget, ElementLocator, NetworkLocator, PATCH_SCHEMA_VERSION, Patch, PatchOp, PatchVariable, SetInstance,
+    VariableLocator,
+};
+pub use domain::template_bundle::{TemplateBundle, TemplateKey, TemplateMeta};
+pub use domain::template_errors::{PatchError, TemplateError, TemplateResult};
+pub use domain::template_spec::{FieldAnchor, FieldKind, TemplateField, TemplateSet, TemplateSpec};
+pub use ports::storage_port::TemplateStorage;
@@ -0,0 +1 @@
+pub mod storage_port;
@@ -0,0 +1,26 @@
+use crate::domain::template_bundle::{TemplateBundle, TemplateKey, TemplateMeta};
+use crate::domain::template_errors::TemplateResult;
+
+/// 模板包读写端口
+/// 版本规则：create 生成版本 1；save_version 归档当前版本后写入新版本（版本号 +1）；
+/// load 总是读取当前（最新）版本，历史版本经 load_version 读取
+pub trait TemplateStorage {
+    /// 新建模板；同一定位已存在时报错
+    fn create(&self, bundle: &TemplateBundle) -> TemplateResult<TemplateMeta>;
+
+    /// 全部模板的当前版本元数据
+    fn list(&self) -> TemplateResult<Vec<TemplateMeta>>;
+
+    fn load(&self, key: &TemplateKey) -> TemplateResult<TemplateBundle>;
+
+    /// 保存为新版本，返回新版本的元数据
+    fn save_version(&self, bundle: &TemplateBundle) -> TemplateResult<TemplateMeta>;
+
+    /// 已有版本号（升序，含当前版本）
+    fn versions(&self, key: &TemplateKey) -> TemplateResult<Vec<u32>>;
+
+    fn load_version(&self, key: &TemplateKey, version: u32) -> TemplateResult<TemplateBundle>;
+
+    /// 删除模板及其全部历史版本
+    fn delete(&self, key: &TemplateKey) -> TemplateResult<()>;
+}
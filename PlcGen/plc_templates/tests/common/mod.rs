@@ -0,0 +1,92 @@
+#![allow(dead_code)]
+
+use plc_core::ast::{BoxPin, ElementType, LdElement, Network, PinDirection, UniversalPou, Variable, VariableNode};
+use plc_core::{HollysysCodec, HollysysConfig, PlcVariant, PouCodec};
+
+pub fn element(id: i32, type_code: ElementType, name: &str, instance: &str, pins: &[(&str, &str)]) -> LdElement {
+    LdElement {
+        instance: instance.to_string(),
+        pins: pins.iter().map(|(pin, var)| BoxPin::new(*pin, *var, PinDirection::Input)).collect(),
+        ..LdElement::new(id, type_code, name)
+    }
+}
+
+pub fn leaf(name: &str, data_type: &str, comment: &str) -> VariableNode {
+    VariableNode::Leaf(Variable { comment: comment.to_string(), ..Variable::new(name, data_type) })
+}
+
+pub fn sample_pou() -> UniversalPou {
+    let network = |id: i32, elements: Vec<LdElement>| Network {
+        id,
+        label: String::new(),
+        comment: String::new(),
+        elements,
+        safety_topology: Vec::new(),
+    };
+    UniversalPou {
+        name: "XV1001_CTRL".to_string(),
+        header_strings: Vec::new(),
+        variables: vec![VariableNode::Group {
+            name: "Local Variables".to_string(),
+            type_name: None,
+            children: vec![
+                leaf("XV1001_OPEN", "BOOL", "XV1001 开到位"),
+                leaf("XV1001_CMD", "BOOL", ""),
+                leaf("PV_101", "REAL", "出口压力"),
+                leaf("SPARE", "BOOL", "备用"),
+                VariableNode::Group {
+                    name: "VLV_TON".to_string(),
+                    type_name: Some("TON".to_string()),
+                    children: vec![leaf("PT", "TIME", "")],
+                },
+            ],
+        }],
+        networks: vec![
+            network(
+                2,
+                vec![
+                    LdElement { connections: vec![4], ..element(3, ElementType::Contact, "XV1001_OPEN", "", &[]) },
+                    element(4, ElementType::Coil, "xv1001_cmd", "", &[]),
+                ],
+            ),
+            network(
+                5,
+                vec![
+                    LdElement {
+                        connections: vec![7],
+                        ..element(
+                            6,
+                            ElementType::Box,
+                            "TON",
+                            "VLV_TON",
+                            &[("EN", "???"), ("IN", "XV1001_OPEN"), ("PT", "T#5S")],
+                        )
+                    },
+                    LdElement {
+                        connections: vec![8],
+                        ..element(7, ElementType::Box, "TP", "VLV_TP", &[("EN", ""), ("IN", "PV_101")])
+                    },
+                    element(8, ElementType::Box, "MOVE", "", &[("IN", "1")]),
+                ],
+            ),
+        ],
+        preserved: None,
+    }
+}
+
+/// Docs/样本对比 下的 Sxx 样本解码结果（文件名大小写不统一）
+pub fn decoded_sample(variant: PlcVariant, name: &str) -> UniversalPou {
+    let dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../Docs/样本对比").join(match variant {
+        PlcVariant::Normal => "普通型",
+        PlcVariant::Safety => "安全型",
+    });
+    let path = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.file_stem().is_some_and(|stem| stem == name))
+        .unwrap_or_else(|| panic!("sample {name} not found in {}", dir.display()));
+    let digits: Vec<u8> = std::fs::read_to_string(path).unwrap().bytes().filter(u8::is_ascii_hexdigit).collect();
+    let data: Vec<u8> =
+        digits.chunks(2).map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap()).collect();
+    HollysysCodec::new(HollysysConfig::new(variant)).decode(&data).unwrap()
+}
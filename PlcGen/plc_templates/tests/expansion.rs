@@ -0,0 +1,168 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use plc_core::ast::{SafetyTopologyToken, UniversalPou, VariableNode};
+use plc_core::{HollysysCodec, PlcVariant, PouCodec};
+use plc_templates::{
+    Patch, PatchError, PatchOp, SetInstance, TemplateBundle, TemplateError, TemplateKey, TemplateMeta,
+    TemplatePatchExecutor, TemplateService, TemplateSet, TemplateSpec,
+};
+use serde_json::json;
+
+fn bundle(variant: PlcVariant) -> TemplateBundle {
+    let spec = TemplateSpec {
+        template_sets: vec![TemplateSet {
+            id: "VLV_SET".to_string(),
+            networks: vec![2],
+            variables: vec![
+                vec!["Local Variables".to_string(), "XV1001_OPEN".to_string()],
+                vec!["Local Variables".to_string(), "XV1001_CMD".to_string()],
+            ],
+        }],
+        ..TemplateSpec::default()
+    };
+    let key = TemplateKey::new("和利时", "和利时普通型", "默认", ["执行机构"], "XV_CTRL");
+    let meta = TemplateMeta::new(key, variant, 13, "POU_TREE_Clipboard_PLC");
+    TemplateBundle::new(meta, spec, common::sample_pou())
+}
+
+fn local_names(pou: &UniversalPou) -> Vec<String> {
+    let VariableNode::Group { children, .. } = &pou.variables[0] else { panic!("Local Variables group") };
+    children
+        .iter()
+        .map(|node| match node {
+            VariableNode::Leaf(var) => var.name.clone(),
+            VariableNode::Group { name, .. } => name.clone(),
+        })
+        .collect()
+}
+
+fn instance(tag: &str) -> serde_json::Value {
+    json!({ "vars": { "XV1001_OPEN": format!("{}_OPEN", tag), "XV1001_CMD": format!("{}_CMD", tag) } })
+}
+
+#[test]
+fn expands_set_per_instance_and_encodes() {
+    let bundle = bundle(PlcVariant::Normal);
+    let config = json!({
+        "sets": { "VLV_SET": [instance("XV2001"), instance("XV2002"), { "vars": { "XV1001_OPEN": "XV2002_OPEN" } }] }
+    });
+    let pou = TemplateService::new().instantiate(&bundle, &config).unwrap();
+
+    // 第 1 套原位改名，其余套追加在集合之后；ID 从 max_id + 1 继续
+    let layout: Vec<(i32, Vec<i32>)> =
+        pou.networks.iter().map(|net| (net.id, net.elements.iter().map(|e| e.id).collect())).collect();
+    assert_eq!(layout, [(2, vec![3, 4]), (9, vec![10, 11]), (12, vec![13, 14]), (5, vec![6, 7, 8])]);
+    let names: Vec<&str> = pou.networks[..3].iter().flat_map(|n| n.elements.iter().map(|e| e.name.as_str())).collect();
+    assert_eq!(names, ["XV2001_OPEN", "XV2001_CMD", "XV2002_OPEN", "XV2002_CMD", "XV2002_OPEN", "xv1001_cmd"]);
+    assert_eq!(pou.networks[2].elements[0].connections, [14]);
+    // 集合外的引用保持原名
+    assert_eq!(pou.networks[3].elements[0].pins[1].variable, "XV1001_OPEN");
+
+    // 第 3 套的 XV2002_OPEN 与第 2 套共享；未改名的 XV1001_CMD 按原名声明
+    assert_eq!(
+        local_names(&pou),
+        ["PV_101", "SPARE", "VLV_TON", "XV2001_OPEN", "XV2001_CMD", "XV2002_OPEN", "XV2002_CMD", "XV1001_CMD"]
+    );
+
+    let bytes = HollysysCodec::normal().encode(&pou).unwrap();
+    assert!(!bytes.is_empty());
+}
+
+#[test]
+fn safety_snapshot_gets_topology_back() {
+    let bundle = bundle(PlcVariant::Safety);
+    let config = json!({ "sets": { "VLV_SET": [instance("XV2001"), instance("XV2002")] } });
+    let pou = TemplateService::new().instantiate(&bundle, &config).unwrap();
+
+    let refs = |net: usize| -> Vec<u32> {
+        pou.networks[net]
+            .safety_topology
+            .iter()
+            .filter_map(|token| match token {
+                SafetyTopologyToken::ElementRef { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(refs(0), [3, 4]);
+    assert_eq!(refs(1), [10, 11]);
+    assert!(pou.networks.iter().all(|net| net.elements.len() < 2 || !net.safety_topology.is_empty()));
+}
+
+#[test]
+fn type_clash_and_unknown_set_fail() {
+    let bundle = bundle(PlcVariant::Normal);
+    let service = TemplateService::new();
+    let repeat = |set: &str, vars: &[(&str, &str)]| Patch {
+        ops: vec![PatchOp::RepeatTemplateSet {
+            set: set.to_string(),
+            instances: vec![
+                SetInstance::default(),
+                SetInstance {
+                    index: Some(2),
+                    vars: vars.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect::<BTreeMap<_, _>>(),
+                },
+            ],
+        }],
+        ..Patch::default()
+    };
+
+    let clash = repeat("VLV_SET", &[("XV1001_OPEN", "PV_101"), ("XV1001_CMD", "XV2_CMD")]);
+    assert!(matches!(
+        service.apply(&bundle, &clash),
+        Err(TemplateError::Patch { source: PatchError::DuplicateVariable(name), .. }) if name == "PV_101"
+    ));
+    assert!(matches!(
+        service.apply(&bundle, &repeat("NOPE", &[])),
+        Err(TemplateError::Patch { source: PatchError::UnknownSet(_), .. })
+    ));
+}
+
+#[test]
+fn repeats_decoded_instance_group() {
+    for variant in [PlcVariant::Normal, PlcVariant::Safety] {
+        let pou = common::decoded_sample(variant, "S06_TP");
+        let network = pou.networks.iter().find(|net| net.elements.iter().any(|e| e.name == "TP")).unwrap().id;
+        // 实例成员组（不带 type_name）；Safety 另在 Local Variables 下声明实例变量
+        let mut variables = vec![vec!["TAG_TP".to_string()]];
+        if variant == PlcVariant::Safety {
+            variables.push(vec!["Local Variables".to_string(), "TAG_TP".to_string()]);
+        }
+        let set = TemplateSet { id: "TP_SET".to_string(), networks: vec![network], variables };
+        let instances = ["TP_101", "TP_102"]
+            .map(|name| SetInstance { index: None, vars: BTreeMap::from([("TAG_TP".to_string(), name.to_string())]) });
+        let patch = Patch {
+            ops: vec![PatchOp::RepeatTemplateSet { set: "TP_SET".to_string(), instances: instances.to_vec() }],
+            ..Patch::default()
+        };
+        let out = TemplatePatchExecutor::new().with_sets(vec![set]).apply(&pou, &patch).unwrap();
+
+        let blocks: Vec<&str> = out
+            .networks
+            .iter()
+            .flat_map(|net| &net.elements)
+            .filter(|elem| elem.name == "TP")
+            .map(|elem| elem.instance.as_str())
+            .collect();
+        assert_eq!(blocks, ["TP_101", "TP_102"], "{variant:?}");
+        let groups: Vec<(&str, usize)> = out
+            .variables
+            .iter()
+            .filter_map(|node| match node {
+                VariableNode::Group { name, type_name: None, children } if name != "Local Variables" => {
+                    Some((name.as_str(), children.len()))
+                }
+                _ => None,
+            })
+            .collect();
+        let members = if variant == PlcVariant::Safety { 5 } else { 4 };
+        assert_eq!(groups, [("TP_101", members), ("TP_102", members)], "{variant:?}");
+        if variant == PlcVariant::Safety {
+            let names = local_names(&out);
+            assert!(names.iter().all(|name| name != "TAG_TP"), "{names:?}");
+            assert_eq!(names.iter().filter(|name| name.starts_with("TP_10")).count(), 2, "{names:?}");
+        }
+    }
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use plc_templates::{FieldAnchor, FieldKind, TemplateExtractor};
+
+#[test]
+fn proposes_fields_with_anchors_and_prefixes() {
+    let spec = TemplateExtractor::new().extract(&common::sample_pou());
+    let summary: Vec<(&str, FieldKind, &str, Option<&str>)> =
+        spec.fields.iter().map(|f| (f.key.as_str(), f.kind, f.original.as_str(), f.prefix.as_deref())).collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("pou_name", FieldKind::PouName, "XV1001_CTRL", Some("XV1001")),
+            ("contact_xv1001_open", FieldKind::ContactName, "XV1001_OPEN", Some("XV1001")),
+            ("coil_xv1001_cmd", FieldKind::CoilName, "xv1001_cmd", None),
+            ("instance_vlv_ton", FieldKind::InstanceName, "VLV_TON", Some("VLV_")),
+            ("instance_vlv_tp", FieldKind::InstanceName, "VLV_TP", Some("VLV_")),
+            ("pin_pv_101", FieldKind::PinBinding, "PV_101", Some("PV_101")),
+            ("comment_xv1001_open", FieldKind::VariableComment, "XV1001 开到位", Some("XV1001")),
+        ]
+    );
+
+    let pin = spec.field("pin_pv_101").unwrap();
+    assert_eq!(pin.anchor, FieldAnchor::Element { network_id: 5, element_id: 7, pin: Some("IN".to_string()) });
+    let comment = spec.field("comment_xv1001_open").unwrap();
+    assert_eq!(comment.anchor, FieldAnchor::Variable { path: vec!["Local Variables".into(), "XV1001_OPEN".into()] });
+    assert!(!comment.required);
+}
+
+#[test]
+fn custom_tag_pattern_picks_up_declared_variables() {
+    let extractor = TemplateExtractor::with_tag_pattern(r"SPA\w+").unwrap();
+    assert_eq!(extractor.find_tag("1#SPARE"), Some("SPARE".to_string()));
+
+    let spec = extractor.extract(&common::sample_pou());
+    let spare = spec.field("var_spare").unwrap();
+    assert_eq!(spare.kind, FieldKind::VariableName);
+    assert_eq!(spare.anchor, FieldAnchor::Variable { path: vec!["Local Variables".into(), "SPARE".into()] });
+    assert!(TemplateExtractor::with_tag_pattern("(").is_err());
+}
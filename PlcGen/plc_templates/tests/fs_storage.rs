@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::PathBuf;
+
+use plc_core::PlcVariant;
+use plc_core::ast::UniversalPou;
+use plc_templates::{
+    FieldAnchor, FieldKind, FsTemplateStorage, TemplateBundle, TemplateError, TemplateField, TemplateKey, TemplateMeta,
+    TemplateSpec, TemplateStorage,
+};
+
+fn scratch_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("plc_templates_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+fn bundle(key: TemplateKey, pou_name: &str) -> TemplateBundle {
+    let pou = UniversalPou {
+        name: pou_name.to_string(),
+        header_strings: Vec::new(),
+        variables: Vec::new(),
+        networks: Vec::new(),
+        preserved: None,
+    };
+    let spec = TemplateSpec {
+        fields: vec![TemplateField {
+            key: "pou_name".to_string(),
+            label: "POU 名称".to_string(),
+            kind: FieldKind::PouName,
+            anchor: FieldAnchor::Pou,
+            original: pou_name.to_string(),
+            prefix: None,
+            required: true,
+        }],
+        ..TemplateSpec::default()
+    };
+    let meta = TemplateMeta::new(key, PlcVariant::Normal, 13, "POU_TREE_Clipboard_PLC");
+    TemplateBundle { raw: Some(vec![1, 2, 3]), ..TemplateBundle::new(meta, spec, pou) }
+}
+
+#[test]
+fn create_list_version_and_delete() {
+    let root = scratch_root("lifecycle");
+    let storage = FsTemplateStorage::new(&root);
+    let key = TemplateKey::new("和利时", "和利时普通型", "默认", ["IO映射", "AI映射"], "AI_CONVERT");
+
+    let meta = storage.create(&bundle(key.clone(), "AI_V1")).unwrap();
+    assert_eq!(meta.version, 1);
+    assert!(matches!(storage.create(&bundle(key.clone(), "AI_V1")), Err(TemplateError::AlreadyExists(_))));
+
+    let other = TemplateKey::new("和利时", "和利时普通型", "默认", ["执行机构"], "XV_CTRL");
+    storage.create(&bundle(other.clone(), "XV")).unwrap();
+    let listed: Vec<TemplateKey> = storage.list().unwrap().into_iter().map(|meta| meta.key).collect();
+    assert_eq!(listed, vec![key.clone(), other.clone()]);
+
+    let mut next = bundle(key.clone(), "AI_V2");
+    next.raw = None;
+    next.script = Some("{{ cfg.pou_name }}".to_string());
+    assert_eq!(storage.save_version(&next).unwrap().version, 2);
+    assert_eq!(storage.versions(&key).unwrap(), vec![1, 2]);
+
+    let current = storage.load(&key).unwrap();
+    assert_eq!(current.pou.name, "AI_V2");
+    assert!(current.raw.is_none());
+    assert_eq!(current.spec.field("pou_name").map(|f| f.kind), Some(FieldKind::PouName));
+    let first = storage.load_version(&key, 1).unwrap();
+    assert_eq!(first.pou.name, "AI_V1");
+    assert_eq!(first.raw, Some(vec![1, 2, 3]));
+    assert!(matches!(storage.load_version(&key, 7), Err(TemplateError::VersionNotFound { .. })));
+
+    storage.delete(&key).unwrap();
+    assert!(matches!(storage.load(&key), Err(TemplateError::NotFound(_))));
+    assert_eq!(storage.list().unwrap().len(), 1);
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn rejects_unsafe_keys() {
+    let storage = FsTemplateStorage::new(scratch_root("keys"));
+    for name in ["..", ".versions", "a/b", ""] {
+        let key = TemplateKey::new("和利时", "和利时普通型", "默认", Vec::<String>::new(), name);
+        assert!(matches!(storage.create(&bundle(key, "P")), Err(TemplateError::InvalidKey(_))));
+    }
+}
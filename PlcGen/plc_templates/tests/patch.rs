@@ -0,0 +1,175 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use plc_core::PlcVariant;
+use plc_core::ast::{ElementType, UniversalPou, VariableNode};
+use plc_templates::{
+    ElementLocator, NetworkLocator, Patch, PatchError, PatchOp, PatchVariable, TemplateError, TemplateExtractor,
+    TemplatePatchExecutor, TemplateRenderer, VariableLocator,
+};
+use serde_json::json;
+
+fn leaf_names(nodes: &[VariableNode]) -> Vec<String> {
+    nodes
+        .iter()
+        .flat_map(|node| match node {
+            VariableNode::Leaf(var) => vec![var.name.clone()],
+            VariableNode::Group { name, type_name: Some(_), .. } => vec![name.clone()],
+            VariableNode::Group { children, .. } => leaf_names(children),
+        })
+        .collect()
+}
+
+fn patch(ops: Vec<PatchOp>) -> Patch {
+    Patch { ops, ..Patch::default() }
+}
+
+#[test]
+fn default_script_renders_and_applies() {
+    let pou = common::sample_pou();
+    let spec = TemplateExtractor::new().extract(&pou);
+    let config = json!({
+        "pou_name": "XV2001_CTRL",
+        "contact_xv1001_open": "XV2001_OPEN",
+        "coil_xv1001_cmd": "XV2001_CMD",
+        "instance_vlv_ton": "VLV2_TON",
+        "instance_vlv_tp": "VLV2_TP",
+        "pin_pv_101": "PV_201",
+        "comment_xv1001_open": "XV2001 开到位",
+    });
+    let script = TemplateRenderer::default_script(&spec);
+    let rendered = TemplateRenderer::new().render(&script, &spec, &config).unwrap();
+    assert_eq!(rendered.ops.len(), 7);
+
+    let out = TemplatePatchExecutor::new().apply(&pou, &rendered).unwrap();
+    assert_eq!(out.name, "XV2001_CTRL");
+    assert_eq!(leaf_names(&out.variables), ["XV2001_OPEN", "XV2001_CMD", "PV_201", "SPARE", "VLV2_TON"]);
+    let elements = &out.networks[1].elements;
+    assert_eq!(out.networks[0].elements[0].name, "XV2001_OPEN");
+    assert_eq!(out.networks[0].elements[1].name, "XV2001_CMD");
+    assert_eq!((elements[0].instance.as_str(), elements[0].pins[1].variable.as_str()), ("VLV2_TON", "XV2001_OPEN"));
+    assert_eq!((elements[1].instance.as_str(), elements[1].pins[1].variable.as_str()), ("VLV2_TP", "PV_201"));
+
+    // 缺少必填字段时在渲染前报错
+    let partial = json!({ "pou_name": "X" });
+    assert!(matches!(
+        TemplateRenderer::new().render(&script, &spec, &partial),
+        Err(TemplateError::MissingConfig(key)) if key == "contact_xv1001_open"
+    ));
+}
+
+#[test]
+fn locators_must_match_exactly_once() {
+    let pou = common::sample_pou();
+    let executor = TemplatePatchExecutor::new();
+    let boxes = ElementLocator { type_code: Some(ElementType::Box), ..ElementLocator::default() };
+    let cases = [
+        (PatchOp::RenameElement { target: boxes.clone(), pin: None, name: "X".into() }, "ambiguous"),
+        (
+            PatchOp::BindPin {
+                target: ElementLocator { instance: Some("NOPE".into()), ..ElementLocator::default() },
+                pin: "IN".into(),
+                variable: "X".into(),
+                expected: None,
+            },
+            "none",
+        ),
+        (PatchOp::RemoveVariable { target: VariableLocator { path: None, name: Some("PT".into()) } }, "none"),
+        (
+            PatchOp::RenameVariable {
+                target: VariableLocator { path: None, name: Some("SPARE".into()) },
+                name: "XV1001_CMD".into(),
+            },
+            "duplicate",
+        ),
+    ];
+    for (op, expect) in cases {
+        let ops = vec![PatchOp::RenamePou { name: "OK".into(), expected: Some("XV1001_CTRL".into()) }, op];
+        let Err(TemplateError::Patch { index, source, .. }) = executor.apply(&pou, &patch(ops)) else {
+            panic!("patch should fail: {}", expect);
+        };
+        assert_eq!(index, 1);
+        match expect {
+            "ambiguous" => assert!(matches!(source, PatchError::AmbiguousMatch { count: 3, .. })),
+            "none" => assert!(matches!(source, PatchError::NoMatch(_))),
+            _ => assert!(matches!(source, PatchError::DuplicateVariable(_))),
+        }
+    }
+
+    let drift = patch(vec![PatchOp::RenamePou { name: "OK".into(), expected: Some("OTHER".into()) }]);
+    assert!(matches!(
+        executor.apply(&pou, &drift),
+        Err(TemplateError::Patch { source: PatchError::ExpectedMismatch { .. }, .. })
+    ));
+}
+
+#[test]
+fn clone_runs_after_modifications_with_fresh_ids() {
+    let pou = common::sample_pou();
+    let renames = BTreeMap::from([("XV1001_OPEN".to_string(), "XV1002_OPEN".to_string())]);
+    let ops = vec![
+        PatchOp::CloneNetworks { networks: vec![NetworkLocator { id: Some(2), label: None }], renames },
+        PatchOp::BindPin {
+            target: ElementLocator { network_id: Some(5), element_id: Some(7), ..ElementLocator::default() },
+            pin: "EN".into(),
+            variable: "XV1001_OPEN".into(),
+            expected: Some(String::new()),
+        },
+        PatchOp::AddVariable {
+            group_path: vec!["Local Variables".into()],
+            variable: PatchVariable {
+                name: "XV1002_OPEN".into(),
+                data_type: "BOOL".into(),
+                init_value: "FALSE".into(),
+                comment: String::new(),
+                soe_enable: false,
+                power_down_keep: false,
+            },
+        },
+        PatchOp::RemoveVariable { target: VariableLocator { path: None, name: Some("spare".into()) } },
+    ];
+    let out: UniversalPou = TemplatePatchExecutor::new().apply(&pou, &patch(ops)).unwrap();
+
+    let ids: Vec<i32> = out.networks.iter().map(|net| net.id).collect();
+    assert_eq!(ids, [2, 9, 5]);
+    let clone = &out.networks[1].elements;
+    assert_eq!((clone[0].id, clone[0].name.as_str(), clone[0].connections.clone()), (10, "XV1002_OPEN", vec![11]));
+    assert_eq!((clone[1].id, clone[1].name.as_str()), (11, "xv1001_cmd"));
+    assert_eq!(out.networks[2].elements[1].pins[0].variable, "XV1001_OPEN");
+    assert_eq!(leaf_names(&out.variables), ["XV1001_OPEN", "XV1001_CMD", "PV_101", "VLV_TON", "XV1002_OPEN"]);
+    // 原 POU 不受影响
+    assert_eq!(pou.networks.len(), 2);
+}
+
+#[test]
+fn renames_decoded_instance_group() {
+    for variant in [PlcVariant::Normal, PlcVariant::Safety] {
+        let pou = common::decoded_sample(variant, "S06_TP");
+        // 解码出的实例成员组不带 type_name
+        assert!(matches!(&pou.variables[0], VariableNode::Group { name, type_name: None, .. } if name == "TAG_TP"));
+
+        let rename = PatchOp::RenameElement {
+            target: ElementLocator { instance: Some("TAG_TP".into()), ..ElementLocator::default() },
+            pin: None,
+            name: "TP_101".into(),
+        };
+        let out = TemplatePatchExecutor::new().apply(&pou, &patch(vec![rename])).unwrap();
+        let tp = out.networks.iter().flat_map(|net| &net.elements).find(|elem| elem.name == "TP").unwrap();
+        assert_eq!(tp.instance, "TP_101");
+        let VariableNode::Group { name, children, .. } = &out.variables[0] else { panic!("{variant:?}") };
+        assert_eq!(name, "TP_101", "{variant:?}");
+        assert_eq!(leaf_names(&children[..2]), ["IN", "PT"]);
+        // Safety 另有实例变量声明（类型为 TP 的叶子），一并改名
+        let names = leaf_names(&out.variables);
+        assert!(names.iter().all(|name| name != "TAG_TP"), "{variant:?}: {names:?}");
+
+        // 成员不是独立声明：不能按名称定位，也不占用同名
+        let member = VariableLocator { path: None, name: Some("IN".into()) };
+        let ops = vec![PatchOp::RenameVariable { target: member, name: "X".into() }];
+        assert!(matches!(
+            TemplatePatchExecutor::new().apply(&pou, &patch(ops)),
+            Err(TemplateError::Patch { source: PatchError::NoMatch(_), .. })
+        ));
+    }
+}